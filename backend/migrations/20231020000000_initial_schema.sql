@@ -1,39 +0,0 @@
-CREATE TYPE transaction_type AS ENUM ('deposit', 'withdrawal');
-CREATE TYPE transaction_status AS ENUM ('pending', 'completed', 'failed');
-
-CREATE TABLE users (
-    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
-    email VARCHAR(255) UNIQUE NOT NULL,
-    password_hash VARCHAR(255) NOT NULL,
-    phone_number VARCHAR(20),
-    email_verified BOOLEAN DEFAULT FALSE,
-    created_at TIMESTAMPTZ DEFAULT NOW(),
-    updated_at TIMESTAMPTZ DEFAULT NOW()
-);
-
-CREATE TABLE investments (
-    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
-    user_id UUID REFERENCES users(id),
-    amount BIGINT NOT NULL,
-    stablecoin_percentage INTEGER NOT NULL,
-    growing_assets_percentage INTEGER NOT NULL,
-    blockchain_tx_hash VARCHAR(66),
-    created_at TIMESTAMPTZ DEFAULT NOW(),
-    updated_at TIMESTAMPTZ DEFAULT NOW()
-);
-
-CREATE TABLE transactions (
-    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
-    user_id UUID REFERENCES users(id),
-    type transaction_type NOT NULL,
-    amount BIGINT NOT NULL,
-    status transaction_status NOT NULL,
-    mpesa_reference VARCHAR(50),
-    blockchain_tx_hash VARCHAR(66),
-    failure_reason TEXT,
-    created_at TIMESTAMPTZ DEFAULT NOW(),
-    completed_at TIMESTAMPTZ
-);
-
-CREATE INDEX idx_transactions_user_id ON transactions(user_id);
-CREATE INDEX idx_transactions_created_at ON transactions(created_at); 
\ No newline at end of file
@@ -1,11 +0,0 @@
-CREATE TYPE transaction_type AS ENUM ('DEPOSIT', 'WITHDRAWAL');
-CREATE TYPE transaction_status AS ENUM ('PENDING', 'COMPLETED', 'FAILED');
-
-CREATE TABLE transactions (
-    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
-    user_id UUID NOT NULL REFERENCES users(id),
-    amount DECIMAL NOT NULL,
-    type transaction_type NOT NULL,
-    status transaction_status NOT NULL,
-    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
-);
@@ -1,17 +0,0 @@
-CREATE TABLE users (
-    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
-    email VARCHAR(255) UNIQUE NOT NULL,
-    password_hash VARCHAR(255) NOT NULL,
-    phone_number VARCHAR(20) NOT NULL,
-    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
-);
-
-CREATE TYPE transaction_status AS ENUM ('pending', 'completed', 'failed');
-
-CREATE TABLE transactions (
-    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
-    user_id UUID REFERENCES users(id),
-    amount DECIMAL NOT NULL,
-    status transaction_status NOT NULL,
-    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
-);
@@ -1,86 +0,0 @@
-use actix_web::{
-    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
-    error::ErrorUnauthorized,
-    Error, FromRequest, HttpMessage, HttpRequest,
-};
-use futures::future::{ready, LocalBoxFuture, Ready};
-use jsonwebtoken::{decode, DecodingKey, Validation};
-use serde::{Deserialize, Serialize};
-use uuid::Uuid;
-use std::future::Future;
-use std::pin::Pin;
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct Claims {
-    pub sub: Uuid,
-    pub exp: i64,
-}
-
-pub struct Auth;
-
-impl<S, B> Transform<S, ServiceRequest> for Auth
-where
-    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
-    S::Future: 'static,
-    B: 'static,
-{
-    type Response = ServiceResponse<B>;
-    type Error = Error;
-    type InitError = ();
-    type Transform = AuthMiddleware<S>;
-    type Future = Ready<Result<Self::Transform, Self::InitError>>;
-
-    fn new_transform(&self, service: S) -> Self::Future {
-        ready(Ok(AuthMiddleware { service }))
-    }
-}
-
-pub struct AuthMiddleware<S> {
-    service: S,
-}
-
-impl<S, B> Service<ServiceRequest> for AuthMiddleware<S>
-where
-    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
-    S::Future: 'static,
-    B: 'static,
-{
-    type Response = ServiceResponse<B>;
-    type Error = Error;
-    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
-
-    forward_ready!(service);
-
-    fn call(&self, req: ServiceRequest) -> Self::Future {
-        let auth_header = req.headers().get("Authorization");
-        
-        if let Some(auth_header) = auth_header {
-            if let Ok(auth_str) = auth_header.to_str() {
-                if auth_str.starts_with("Bearer ") {
-                    let token = auth_str.trim_start_matches("Bearer ");
-                    let jwt_secret = std::env::var("JWT_SECRET").unwrap_or_default();
-                    
-                    match decode::<Claims>(
-                        token,
-                        &DecodingKey::from_secret(jwt_secret.as_bytes()),
-                        &Validation::default(),
-                    ) {
-                        Ok(token_data) => {
-                            req.extensions_mut().insert(token_data.claims);
-                            let fut = self.service.call(req);
-                            return Box::pin(async move {
-                                let res = fut.await?;
-                                Ok(res)
-                            });
-                        }
-                        Err(_) => {
-                            return Box::pin(ready(Err(ErrorUnauthorized("Invalid token"))));
-                        }
-                    }
-                }
-            }
-        }
-        
-        Box::pin(ready(Err(ErrorUnauthorized("Missing authorization"))))
-    }
-}
\ No newline at end of file
@@ -1,67 +0,0 @@
-pub mod auth;
-pub mod metrics;
-
-use actix_web::dev::{Service, ServiceRequest, ServiceResponse, Transform};
-use futures::future::LocalBoxFuture;
-use std::future::{ready, Ready};
-
-pub struct MetricsMiddleware;
-
-impl<S, B> Transform<S, ServiceRequest> for MetricsMiddleware
-where
-    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error>,
-    S::Future: 'static,
-    B: 'static,
-{
-    type Response = ServiceResponse<B>;
-    type Error = actix_web::Error;
-    type InitError = ();
-    type Transform = MetricsMiddlewareService<S>;
-    type Future = Ready<Result<Self::Transform, Self::InitError>>;
-
-    fn new_transform(&self, service: S) -> Self::Future {
-        ready(Ok(MetricsMiddlewareService { service }))
-    }
-}
-
-pub struct MetricsMiddlewareService<S> {
-    service: S,
-}
-
-impl<S, B> Service<ServiceRequest> for MetricsMiddlewareService<S>
-where
-    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error>,
-    S::Future: 'static,
-    B: 'static,
-{
-    type Response = ServiceResponse<B>;
-    type Error = actix_web::Error;
-    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
-
-    fn poll_ready(
-        &self,
-        ctx: &mut core::task::Context<'_>,
-    ) -> std::task::Poll<Result<(), Self::Error>> {
-        self.service.poll_ready(ctx)
-    }
-
-    fn call(&self, req: ServiceRequest) -> Self::Future {
-        let start = std::time::Instant::now();
-        let method = req.method().to_string();
-        let path = req.path().to_string();
-
-        let fut = self.service.call(req);
-        Box::pin(async move {
-            let res = fut.await?;
-            let duration = start.elapsed().as_secs_f64();
-            let status = res.status().as_u16().to_string();
-
-            // Record metrics
-            crate::metrics::HTTP_REQUEST_DURATION
-                .with_label_values(&[&method, &path, &status])
-                .observe(duration);
-
-            Ok(res)
-        })
-    }
-} 
\ No newline at end of file
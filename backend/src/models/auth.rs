@@ -1,22 +0,0 @@
-use serde::{Deserialize, Serialize};
-use validator::Validate;
-
-#[derive(Debug, Serialize, Deserialize, Validate)]
-pub struct LoginRequest {
-    #[validate(email)]
-    pub email: String,
-    #[validate(length(min = 8))]
-    pub password: String,
-}
-
-#[derive(Debug, Serialize)]
-pub struct AuthResponse {
-    pub token: String,
-    pub user: UserResponse,
-}
-
-#[derive(Debug, Serialize)]
-pub struct UserResponse {
-    pub id: String,
-    pub email: String,
-} 
\ No newline at end of file
@@ -1,26 +0,0 @@
-use serde::{Deserialize, Serialize};
-use uuid::Uuid;
-use chrono::{DateTime, Utc};
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct Transaction {
-    pub id: Uuid,
-    pub user_id: Uuid,
-    pub amount: Decimal,
-    pub transaction_type: TransactionType,
-    pub status: TransactionStatus,
-    pub created_at: DateTime<Utc>,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-pub enum TransactionType {
-    Deposit,
-    Withdrawal
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-pub enum TransactionStatus {
-    Pending,
-    Completed,
-    Failed
-}
@@ -1,7 +0,0 @@
-mod auth;
-mod investment;
-mod transaction;
-
-pub use auth::AuthService;
-pub use investment::InvestmentService;
-pub use transaction::TransactionService;
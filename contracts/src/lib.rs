@@ -4,26 +4,58 @@ use ink_lang as ink;
 
 #[ink::contract]
 mod pension_fund {
+    use ink_prelude::vec::Vec;
     use ink_storage::{
         collections::HashMap,
         traits::{PackedLayout, SpreadLayout},
     };
 
+    // Basis points, so allocations can be expressed to 0.01%
+    pub const BPS_DENOMINATOR: u16 = 10_000;
+
+    macro_rules! ensure {
+        ($condition:expr, $error:expr) => {
+            if !$condition {
+                return Err($error);
+            }
+        };
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode, SpreadLayout, PackedLayout)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum Asset {
+        Usdc,
+        Btc,
+        Xlm,
+        Tbill,  // Tokenized US Treasury bills
+        KesMmf, // Kenya shilling money-market fund
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum Error {
+        InvestmentTooLow,
+        InvalidAllocation,
+        NoInvestment,
+        InsufficientFunds,
+        InsufficientLiquidity,
+        TransferFailed,
+    }
+
     #[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode, SpreadLayout, PackedLayout)]
     #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
     pub struct Investment {
         amount: Balance,
         timestamp: Timestamp,
-        stablecoin_percentage: u8,
-        growing_assets_percentage: u8,
+        holdings: Vec<(Asset, Balance)>,
+        allocation: Vec<(Asset, u16)>, // Basis points per asset, summing to 10,000
     }
 
     #[ink(storage)]
     pub struct PensionFund {
         investments: HashMap<AccountId, Investment>,
         total_funds: Balance,
-        stablecoin_pool: Balance,
-        growing_assets_pool: Balance,
+        pools: HashMap<Asset, Balance>,
         owner: AccountId,
         minimum_investment: Balance,
     }
@@ -34,34 +66,43 @@ mod pension_fund {
             Self {
                 investments: HashMap::new(),
                 total_funds: 0,
-                stablecoin_pool: 0,
-                growing_assets_pool: 0,
+                pools: HashMap::new(),
                 owner: Self::env().caller(),
                 minimum_investment,
             }
         }
 
+        // Invests the transferred value across the given allocation, which
+        // also becomes the caller's allocation for what they already hold
         #[ink(message)]
-        pub fn invest(&mut self, stablecoin_percentage: u8) -> Result<(), Error> {
+        pub fn invest(&mut self, allocation: Vec<(Asset, u16)>) -> Result<(), Error> {
             let caller = self.env().caller();
             let value = self.env().transferred_value();
-            
-            ensure!(value >= self.minimum_investment, Error::InvestmentTooLow);
-            ensure!(stablecoin_percentage <= 100, Error::InvalidPercentage);
-
-            let growing_assets_percentage = 100 - stablecoin_percentage;
-            let stablecoin_amount = (value * stablecoin_percentage as u128) / 100;
-            let growing_amount = value - stablecoin_amount;
 
-            self.stablecoin_pool += stablecoin_amount;
-            self.growing_assets_pool += growing_amount;
+            ensure!(value >= self.minimum_investment, Error::InvestmentTooLow);
+            let allocation = validate_allocation(allocation)?;
+
+            let amount = match self.investments.take(&caller) {
+                Some(existing) => {
+                    for (asset, held) in existing.holdings.iter() {
+                        *self.pools.entry(*asset).or_insert(0) -= held;
+                    }
+                    existing.amount + value
+                }
+                None => value,
+            };
+
+            let holdings = split(amount, &allocation);
+            for (asset, part) in holdings.iter() {
+                *self.pools.entry(*asset).or_insert(0) += part;
+            }
             self.total_funds += value;
 
             self.investments.insert(caller, Investment {
-                amount: value,
+                amount,
                 timestamp: self.env().block_timestamp(),
-                stablecoin_percentage,
-                growing_assets_percentage,
+                holdings,
+                allocation,
             });
 
             Ok(())
@@ -75,22 +116,36 @@ mod pension_fund {
 
             ensure!(amount <= investment.amount, Error::InsufficientFunds);
 
-            let stablecoin_amount = (amount * investment.stablecoin_percentage as u128) / 100;
-            let growing_amount = amount - stablecoin_amount;
-
-            ensure!(stablecoin_amount <= self.stablecoin_pool, Error::InsufficientLiquidity);
-            ensure!(growing_amount <= self.growing_assets_pool, Error::InsufficientLiquidity);
+            // Draw from every asset in proportion to what the member holds
+            let parts = draw(amount, &investment.holdings);
+            for (asset, part) in parts.iter() {
+                let pool = self.pools.get(asset).copied().unwrap_or(0);
+                ensure!(*part <= pool, Error::InsufficientLiquidity);
+            }
 
-            self.stablecoin_pool -= stablecoin_amount;
-            self.growing_assets_pool -= growing_amount;
+            for (asset, part) in parts.iter() {
+                *self.pools.entry(*asset).or_insert(0) -= part;
+            }
             self.total_funds -= amount;
 
             if amount == investment.amount {
                 self.investments.remove(&caller);
             } else {
+                let holdings = investment.holdings.iter()
+                    .map(|(asset, held)| {
+                        let part = parts.iter()
+                            .find(|(a, _)| a == asset)
+                            .map_or(0, |(_, part)| *part);
+                        (*asset, held - part)
+                    })
+                    .filter(|(_, held)| *held > 0)
+                    .collect();
+                let allocation = investment.allocation.clone();
                 self.investments.insert(caller, Investment {
                     amount: investment.amount - amount,
-                    ..*investment
+                    timestamp: investment.timestamp,
+                    holdings,
+                    allocation,
                 });
             }
 
@@ -98,4 +153,54 @@ mod pension_fund {
             Ok(())
         }
     }
-} 
\ No newline at end of file
+
+    // Drops zero weights; what is left must name each asset once and cover
+    // exactly 100%
+    fn validate_allocation(allocation: Vec<(Asset, u16)>) -> Result<Vec<(Asset, u16)>, Error> {
+        let mut kept: Vec<(Asset, u16)> = Vec::new();
+        let mut total: u32 = 0;
+        for (asset, bps) in allocation.into_iter().filter(|(_, bps)| *bps > 0) {
+            ensure!(!kept.iter().any(|(a, _)| *a == asset), Error::InvalidAllocation);
+            total += bps as u32;
+            kept.push((asset, bps));
+        }
+        ensure!(total == BPS_DENOMINATOR as u32, Error::InvalidAllocation);
+
+        Ok(kept)
+    }
+
+    // Splits an amount by allocation. Rounding leftovers go to the asset with
+    // the largest weight so the parts always add back up to the amount.
+    fn split(amount: Balance, allocation: &[(Asset, u16)]) -> Vec<(Asset, Balance)> {
+        let mut parts: Vec<(Asset, Balance)> = allocation.iter()
+            .map(|(asset, bps)| (*asset, amount * *bps as u128 / BPS_DENOMINATOR as u128))
+            .collect();
+        let assigned: Balance = parts.iter().map(|(_, part)| part).sum();
+
+        if let Some(largest) = (0..allocation.len()).max_by_key(|&i| allocation[i].1) {
+            parts[largest].1 += amount - assigned;
+        }
+        parts.retain(|(_, part)| *part > 0);
+        parts
+    }
+
+    // Takes an amount out of the holdings pro rata. Rounding leftovers come
+    // out of the largest holding.
+    fn draw(amount: Balance, holdings: &[(Asset, Balance)]) -> Vec<(Asset, Balance)> {
+        let balance: Balance = holdings.iter().map(|(_, held)| held).sum();
+        if balance == 0 {
+            return Vec::new();
+        }
+
+        let mut parts: Vec<(Asset, Balance)> = holdings.iter()
+            .map(|(asset, held)| (*asset, amount * held / balance))
+            .collect();
+        let drawn: Balance = parts.iter().map(|(_, part)| part).sum();
+
+        if let Some(largest) = (0..holdings.len()).max_by_key(|&i| holdings[i].1) {
+            parts[largest].1 += amount - drawn;
+        }
+        parts.retain(|(_, part)| *part > 0);
+        parts
+    }
+}
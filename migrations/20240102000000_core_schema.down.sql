@@ -0,0 +1,35 @@
+DROP INDEX idx_transactions_user_id;
+DROP INDEX idx_transactions_fund_type;
+DROP INDEX idx_wallets_user_id;
+
+DROP TABLE investments;
+DROP TABLE portfolio_recommendations;
+DROP TABLE portfolio_allocations;
+DROP TABLE user_risk_profiles;
+
+-- Internal postings have no member to fall back to
+DELETE FROM transactions WHERE user_id IS NULL;
+ALTER TABLE transactions
+    DROP COLUMN fund_id,
+    DROP COLUMN phone_number,
+    DROP COLUMN mpesa_reference,
+    DROP COLUMN failure_reason,
+    ALTER COLUMN user_id SET NOT NULL,
+    ALTER COLUMN transaction_type TYPE VARCHAR(20),
+    ALTER COLUMN amount TYPE BIGINT USING ROUND(amount)::BIGINT,
+    ALTER COLUMN created_at DROP NOT NULL;
+
+DROP TABLE pension_funds;
+
+ALTER TABLE wallets
+    DROP CONSTRAINT wallets_address_key,
+    ALTER COLUMN created_at DROP NOT NULL;
+
+ALTER TABLE users
+    DROP CONSTRAINT users_phone_number_key,
+    DROP COLUMN username,
+    DROP COLUMN wallet_address,
+    DROP COLUMN kyc_status,
+    ALTER COLUMN email_verified DROP NOT NULL,
+    ALTER COLUMN created_at DROP NOT NULL,
+    ALTER COLUMN updated_at DROP NOT NULL;
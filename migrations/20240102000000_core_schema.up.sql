@@ -0,0 +1,110 @@
+-- Brings the tables from 20240101000000_initial up to what the services
+-- use. That migration stays as first shipped so databases that already
+-- applied it keep passing checksum validation.
+
+ALTER TABLE users
+    ADD COLUMN username VARCHAR(100),
+    ADD COLUMN wallet_address VARCHAR(255),
+    ADD COLUMN kyc_status VARCHAR(20) NOT NULL DEFAULT 'UNVERIFIED';
+
+UPDATE users u SET
+    username = split_part(u.email, '@', 1),
+    wallet_address = COALESCE(
+        (SELECT w.address FROM wallets w WHERE w.user_id = u.id ORDER BY w.created_at LIMIT 1),
+        ''
+    ),
+    email_verified = COALESCE(u.email_verified, FALSE),
+    created_at = COALESCE(u.created_at, NOW()),
+    updated_at = COALESCE(u.updated_at, NOW());
+
+ALTER TABLE users
+    ALTER COLUMN username SET NOT NULL,
+    ALTER COLUMN wallet_address SET NOT NULL,
+    ALTER COLUMN email_verified SET NOT NULL,
+    ALTER COLUMN created_at SET NOT NULL,
+    ALTER COLUMN updated_at SET NOT NULL,
+    ADD CONSTRAINT users_phone_number_key UNIQUE (phone_number);
+
+UPDATE wallets SET created_at = NOW() WHERE created_at IS NULL;
+ALTER TABLE wallets
+    ALTER COLUMN created_at SET NOT NULL,
+    ADD CONSTRAINT wallets_address_key UNIQUE (address);
+
+CREATE TABLE pension_funds (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id),
+    investment_plan VARCHAR(20) NOT NULL, -- CONSERVATIVE, MODERATE or AGGRESSIVE
+    balance DECIMAL(20,8) NOT NULL DEFAULT 0,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Every movement of money, keyed on the fund it affects. user_id is
+-- denormalized for history queries and left NULL by internal postings.
+-- Members with earlier transactions get a fund on the moderate plan to
+-- hold them.
+INSERT INTO pension_funds (user_id, investment_plan)
+SELECT DISTINCT user_id, 'MODERATE' FROM transactions;
+
+ALTER TABLE transactions
+    ADD COLUMN fund_id UUID REFERENCES pension_funds(id),
+    ADD COLUMN phone_number VARCHAR(20),
+    ADD COLUMN mpesa_reference VARCHAR(50),
+    ADD COLUMN failure_reason TEXT,
+    ALTER COLUMN user_id DROP NOT NULL,
+    ALTER COLUMN transaction_type TYPE VARCHAR(30),
+    ALTER COLUMN amount TYPE DECIMAL(20,8);
+
+UPDATE transactions t SET
+    fund_id = (SELECT f.id FROM pension_funds f WHERE f.user_id = t.user_id),
+    created_at = COALESCE(t.created_at, NOW());
+
+ALTER TABLE transactions
+    ALTER COLUMN fund_id SET NOT NULL,
+    ALTER COLUMN created_at SET NOT NULL;
+
+CREATE TABLE user_risk_profiles (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID UNIQUE NOT NULL REFERENCES users(id),
+    age SMALLINT NOT NULL,
+    income DECIMAL(20,2) NOT NULL,
+    risk_tolerance VARCHAR(20) NOT NULL,
+    investment_horizon SMALLINT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE portfolio_allocations (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id),
+    stablecoin DECIMAL(5,2) NOT NULL,
+    growing_assets DECIMAL(5,2) NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE portfolio_recommendations (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id),
+    stablecoin DECIMAL(5,2) NOT NULL,
+    growing_assets DECIMAL(5,2) NOT NULL,
+    status VARCHAR(20) NOT NULL DEFAULT 'PENDING',
+    applied_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE investments (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id),
+    amount DECIMAL(20,8) NOT NULL CHECK (amount > 0),
+    status VARCHAR(20) NOT NULL DEFAULT 'PENDING',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_wallets_user_id ON wallets(user_id);
+CREATE INDEX idx_pension_funds_user_id ON pension_funds(user_id);
+CREATE INDEX idx_transactions_fund_type ON transactions(fund_id, transaction_type);
+CREATE INDEX idx_transactions_user_id ON transactions(user_id);
+CREATE INDEX idx_portfolio_allocations_user_id ON portfolio_allocations(user_id, created_at);
+CREATE INDEX idx_portfolio_recommendations_user_id ON portfolio_recommendations(user_id);
+CREATE INDEX idx_investments_user_id ON investments(user_id);
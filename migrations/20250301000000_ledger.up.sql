@@ -0,0 +1,26 @@
+CREATE TABLE ledger_accounts (
+    id UUID PRIMARY KEY,
+    code VARCHAR(64) UNIQUE NOT NULL,
+    account_type VARCHAR(20) NOT NULL,
+    fund_id UUID REFERENCES pension_funds(id),
+    created_at TIMESTAMPTZ DEFAULT NOW()
+);
+
+CREATE TABLE journal_entries (
+    id UUID PRIMARY KEY,
+    description TEXT NOT NULL,
+    transaction_id UUID REFERENCES transactions(id),
+    created_at TIMESTAMPTZ DEFAULT NOW()
+);
+
+CREATE TABLE ledger_postings (
+    id UUID PRIMARY KEY,
+    entry_id UUID NOT NULL REFERENCES journal_entries(id),
+    account_id UUID NOT NULL REFERENCES ledger_accounts(id),
+    amount DECIMAL(20,8) NOT NULL,
+    created_at TIMESTAMPTZ DEFAULT NOW()
+);
+
+CREATE INDEX idx_ledger_accounts_fund_id ON ledger_accounts(fund_id);
+CREATE INDEX idx_ledger_postings_account_id ON ledger_postings(account_id);
+CREATE INDEX idx_ledger_postings_entry_id ON ledger_postings(entry_id);
@@ -0,0 +1,3 @@
+DROP INDEX IF EXISTS idx_transactions_checkout_request_id;
+
+ALTER TABLE transactions DROP COLUMN IF EXISTS checkout_request_id;
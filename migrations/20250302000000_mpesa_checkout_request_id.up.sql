@@ -0,0 +1,5 @@
+ALTER TABLE transactions
+    ADD COLUMN IF NOT EXISTS checkout_request_id VARCHAR(100);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_transactions_checkout_request_id
+ON transactions(checkout_request_id);
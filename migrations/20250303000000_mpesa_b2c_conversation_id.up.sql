@@ -0,0 +1,5 @@
+ALTER TABLE transactions
+    ADD COLUMN IF NOT EXISTS conversation_id VARCHAR(100);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_transactions_conversation_id
+ON transactions(conversation_id);
@@ -0,0 +1,6 @@
+DROP TABLE bpt_transfers;
+DROP TABLE bpt_collateral;
+DROP TABLE bpt_stakes;
+DROP TABLE bpt_tokenizations;
+
+ALTER TABLE pension_funds DROP COLUMN tokenized_balance;
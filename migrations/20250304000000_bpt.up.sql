@@ -0,0 +1,53 @@
+-- Portion of each fund's balance currently represented by minted BPT
+ALTER TABLE pension_funds ADD COLUMN tokenized_balance DECIMAL(20,8) NOT NULL DEFAULT 0;
+
+CREATE TABLE bpt_tokenizations (
+    id UUID PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users(id),
+    fund_id UUID NOT NULL REFERENCES pension_funds(id),
+    direction VARCHAR(10) NOT NULL, -- MINT or REDEEM
+    amount DECIMAL(20,8) NOT NULL CHECK (amount > 0),
+    status VARCHAR(20) NOT NULL,
+    tx_hash VARCHAR(64),
+    failure_reason TEXT,
+    created_at TIMESTAMPTZ DEFAULT NOW(),
+    completed_at TIMESTAMPTZ
+);
+
+CREATE TABLE bpt_stakes (
+    id UUID PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users(id),
+    amount DECIMAL(20,8) NOT NULL CHECK (amount > 0),
+    lockup_days INTEGER NOT NULL,
+    status VARCHAR(20) NOT NULL,
+    locked_until TIMESTAMPTZ NOT NULL,
+    stake_tx_hash VARCHAR(64),
+    unstake_tx_hash VARCHAR(64),
+    created_at TIMESTAMPTZ DEFAULT NOW(),
+    unstaked_at TIMESTAMPTZ
+);
+
+CREATE TABLE bpt_collateral (
+    id UUID PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users(id),
+    amount DECIMAL(20,8) NOT NULL CHECK (amount > 0),
+    status VARCHAR(20) NOT NULL,
+    lock_tx_hash VARCHAR(64),
+    release_tx_hash VARCHAR(64),
+    created_at TIMESTAMPTZ DEFAULT NOW(),
+    released_at TIMESTAMPTZ
+);
+
+CREATE TABLE bpt_transfers (
+    id UUID PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users(id),
+    to_address VARCHAR(255) NOT NULL,
+    amount DECIMAL(20,8) NOT NULL CHECK (amount > 0),
+    tx_hash VARCHAR(64) NOT NULL,
+    created_at TIMESTAMPTZ DEFAULT NOW()
+);
+
+CREATE INDEX idx_bpt_tokenizations_fund_id ON bpt_tokenizations(fund_id);
+CREATE INDEX idx_bpt_stakes_user_id ON bpt_stakes(user_id);
+CREATE INDEX idx_bpt_collateral_user_id ON bpt_collateral(user_id);
+CREATE INDEX idx_bpt_transfers_user_id ON bpt_transfers(user_id);
@@ -0,0 +1,5 @@
+DROP INDEX IF EXISTS idx_transactions_loan_id;
+ALTER TABLE transactions DROP COLUMN IF EXISTS loan_id;
+
+DROP TABLE loan_installments;
+DROP TABLE loans;
@@ -0,0 +1,39 @@
+CREATE TABLE loans (
+    id UUID PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users(id),
+    fund_id UUID NOT NULL REFERENCES pension_funds(id),
+    collateral_id UUID NOT NULL REFERENCES bpt_collateral(id),
+    principal DECIMAL(20,8) NOT NULL CHECK (principal > 0),
+    annual_interest_rate DECIMAL(8,6) NOT NULL,
+    loan_to_value DECIMAL(8,6) NOT NULL,
+    term_months INTEGER NOT NULL,
+    outstanding_principal DECIMAL(20,8) NOT NULL,
+    accrued_interest DECIMAL(20,8) NOT NULL DEFAULT 0,
+    status VARCHAR(20) NOT NULL,
+    last_accrued_at TIMESTAMPTZ,
+    disbursed_at TIMESTAMPTZ,
+    closed_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ DEFAULT NOW()
+);
+
+CREATE TABLE loan_installments (
+    id UUID PRIMARY KEY,
+    loan_id UUID NOT NULL REFERENCES loans(id),
+    seq INTEGER NOT NULL,
+    due_date TIMESTAMPTZ NOT NULL,
+    principal_due DECIMAL(20,8) NOT NULL,
+    interest_due DECIMAL(20,8) NOT NULL,
+    amount_paid DECIMAL(20,8) NOT NULL DEFAULT 0,
+    status VARCHAR(20) NOT NULL DEFAULT 'DUE',
+    last_requested_at TIMESTAMPTZ,
+    paid_at TIMESTAMPTZ,
+    UNIQUE (loan_id, seq)
+);
+
+-- Disbursements, repayments and liquidations show up in the member's history
+ALTER TABLE transactions ADD COLUMN IF NOT EXISTS loan_id UUID REFERENCES loans(id);
+
+CREATE INDEX idx_loans_user_id ON loans(user_id);
+CREATE INDEX idx_loans_status ON loans(status);
+CREATE INDEX idx_loan_installments_due ON loan_installments(status, due_date);
+CREATE INDEX idx_transactions_loan_id ON transactions(loan_id);
@@ -0,0 +1,12 @@
+DROP TABLE otp_codes;
+
+ALTER TABLE users
+    DROP COLUMN locked_until,
+    DROP COLUMN failed_pin_attempts,
+    DROP COLUMN phone_verified,
+    DROP COLUMN pin_hash;
+
+-- Fails while phone-only members exist rather than deleting them
+ALTER TABLE users ALTER COLUMN password_hash SET NOT NULL;
+ALTER TABLE users ALTER COLUMN wallet_address SET NOT NULL;
+ALTER TABLE users ALTER COLUMN email SET NOT NULL;
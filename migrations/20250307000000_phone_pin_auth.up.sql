@@ -0,0 +1,26 @@
+-- Feature-phone members sign up with their M-Pesa number and a PIN, so
+-- email, wallet and password become optional.
+ALTER TABLE users ALTER COLUMN email DROP NOT NULL;
+ALTER TABLE users ALTER COLUMN wallet_address DROP NOT NULL;
+ALTER TABLE users ALTER COLUMN password_hash DROP NOT NULL;
+
+ALTER TABLE users
+    ADD COLUMN pin_hash VARCHAR(255),
+    ADD COLUMN phone_verified BOOLEAN NOT NULL DEFAULT FALSE,
+    ADD COLUMN failed_pin_attempts INTEGER NOT NULL DEFAULT 0,
+    ADD COLUMN locked_until TIMESTAMPTZ;
+
+-- One-time codes sent by SMS, stored only as an argon2 hash. Issuing a new
+-- code for the same number and purpose consumes the previous one.
+CREATE TABLE otp_codes (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    phone_number VARCHAR(20) NOT NULL,
+    purpose VARCHAR(20) NOT NULL, -- REGISTRATION or PIN_RESET
+    code_hash VARCHAR(255) NOT NULL,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    expires_at TIMESTAMPTZ NOT NULL,
+    consumed_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_otp_codes_phone_purpose ON otp_codes(phone_number, purpose);
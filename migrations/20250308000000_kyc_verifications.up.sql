@@ -0,0 +1,22 @@
+-- One row per identity check sent to a KYC provider. The row id doubles as
+-- the provider job id, so callbacks and polls find it directly.
+-- users.kyc_status mirrors the latest verification that reached the
+-- provider and is what withdrawals and investments are gated on.
+CREATE TABLE kyc_verifications (
+    id UUID PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users(id),
+    provider VARCHAR(20) NOT NULL,
+    id_type VARCHAR(30) NOT NULL,
+    country VARCHAR(2) NOT NULL,
+    id_number_last4 VARCHAR(4) NOT NULL, -- The full number stays with the provider
+    status VARCHAR(20) NOT NULL DEFAULT 'SUBMITTED', -- SUBMITTED, PENDING, APPROVED, REJECTED or NEEDS_REVIEW
+    result_code VARCHAR(20),
+    result_text TEXT,
+    provider_reference VARCHAR(100),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    completed_at TIMESTAMPTZ
+);
+
+CREATE INDEX idx_kyc_verifications_user_id ON kyc_verifications(user_id);
+CREATE INDEX idx_kyc_verifications_status ON kyc_verifications(status);
@@ -0,0 +1,3 @@
+DROP TABLE withdrawal_decisions;
+
+ALTER TABLE transactions DROP COLUMN penalty_amount;
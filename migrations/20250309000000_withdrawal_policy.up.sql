@@ -0,0 +1,22 @@
+-- Penalty kept back from a withdrawal; the member is paid amount - penalty_amount
+ALTER TABLE transactions ADD COLUMN penalty_amount DECIMAL(20,8) NOT NULL DEFAULT 0;
+
+-- Every withdrawal request and the rule that allowed or denied it
+CREATE TABLE withdrawal_decisions (
+    id UUID PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users(id),
+    transaction_id UUID REFERENCES transactions(id),
+    requested_kind VARCHAR(20) NOT NULL,
+    applied_kind VARCHAR(20) NOT NULL,
+    reason TEXT,
+    rule VARCHAR(40) NOT NULL,
+    allowed BOOLEAN NOT NULL,
+    requested_amount DECIMAL(20,8) NOT NULL,
+    accessible_amount DECIMAL(20,8) NOT NULL,
+    penalty_amount DECIMAL(20,8) NOT NULL,
+    member_age SMALLINT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_withdrawal_decisions_user_id ON withdrawal_decisions(user_id, created_at);
+CREATE INDEX idx_withdrawal_decisions_transaction_id ON withdrawal_decisions(transaction_id);
@@ -0,0 +1,26 @@
+CREATE TABLE contribution_schedules (
+    id UUID PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users(id),
+    fund_id UUID NOT NULL REFERENCES pension_funds(id),
+    amount DECIMAL(20,2) NOT NULL CHECK (amount > 0),
+    frequency VARCHAR(10) NOT NULL,
+    day_of_week SMALLINT CHECK (day_of_week BETWEEN 1 AND 7), -- ISO weekday, weekly schedules only
+    preferred_time TIME NOT NULL, -- East Africa Time
+    phone_number VARCHAR(20) NOT NULL,
+    status VARCHAR(20) NOT NULL DEFAULT 'ACTIVE',
+    next_run_at TIMESTAMPTZ NOT NULL,
+    last_run_at TIMESTAMPTZ,
+    reminded_for TIMESTAMPTZ, -- The run the member was last reminded about
+    success_streak INTEGER NOT NULL DEFAULT 0,
+    failure_streak INTEGER NOT NULL DEFAULT 0,
+    paused_reason TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Deposits prompted by a schedule feed its success and failure streaks
+ALTER TABLE transactions ADD COLUMN schedule_id UUID REFERENCES contribution_schedules(id);
+
+CREATE INDEX idx_contribution_schedules_user_id ON contribution_schedules(user_id);
+CREATE INDEX idx_contribution_schedules_due ON contribution_schedules(status, next_run_at);
+CREATE INDEX idx_transactions_schedule_id ON transactions(schedule_id);
@@ -0,0 +1,8 @@
+DROP INDEX IF EXISTS idx_transactions_payroll_batch_id;
+ALTER TABLE transactions DROP COLUMN IF EXISTS payroll_batch_id;
+
+DROP TABLE payroll_batch_lines;
+DROP TABLE payroll_batches;
+DROP TABLE employer_members;
+DROP TABLE employer_admins;
+DROP TABLE employers;
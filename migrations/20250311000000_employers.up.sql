@@ -0,0 +1,62 @@
+CREATE TABLE employers (
+    id UUID PRIMARY KEY,
+    name VARCHAR(255) NOT NULL,
+    registration_number VARCHAR(64) UNIQUE NOT NULL, -- KRA PIN or company number
+    employer_rate DECIMAL(8,6) NOT NULL DEFAULT 0,   -- Share of pensionable pay the employer adds
+    employee_rate DECIMAL(8,6) NOT NULL DEFAULT 0,   -- Share of pensionable pay deducted from staff
+    created_by UUID NOT NULL REFERENCES users(id),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE employer_admins (
+    employer_id UUID NOT NULL REFERENCES employers(id),
+    user_id UUID NOT NULL REFERENCES users(id),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (employer_id, user_id)
+);
+
+-- The employer's roster: which member each staff number pays into
+CREATE TABLE employer_members (
+    id UUID PRIMARY KEY,
+    employer_id UUID NOT NULL REFERENCES employers(id),
+    user_id UUID NOT NULL REFERENCES users(id),
+    staff_number VARCHAR(64) NOT NULL,
+    status VARCHAR(20) NOT NULL DEFAULT 'ACTIVE',
+    joined_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    left_at TIMESTAMPTZ,
+    UNIQUE (employer_id, staff_number),
+    UNIQUE (employer_id, user_id)
+);
+
+CREATE TABLE payroll_batches (
+    id UUID PRIMARY KEY,
+    employer_id UUID NOT NULL REFERENCES employers(id),
+    reference VARCHAR(64) NOT NULL,
+    format VARCHAR(10) NOT NULL,
+    uploaded_by UUID NOT NULL REFERENCES users(id),
+    total_lines INTEGER NOT NULL,
+    accepted_lines INTEGER NOT NULL,
+    rejected_lines INTEGER NOT NULL,
+    employer_total DECIMAL(20,2) NOT NULL,
+    employee_total DECIMAL(20,2) NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (employer_id, reference)
+);
+
+CREATE TABLE payroll_batch_lines (
+    id UUID PRIMARY KEY,
+    batch_id UUID NOT NULL REFERENCES payroll_batches(id),
+    line_number INTEGER NOT NULL,
+    staff_number VARCHAR(64),
+    user_id UUID REFERENCES users(id),
+    employer_amount DECIMAL(20,2),
+    employee_amount DECIMAL(20,2),
+    status VARCHAR(20) NOT NULL,
+    rejection_reason TEXT
+);
+
+ALTER TABLE transactions ADD COLUMN payroll_batch_id UUID REFERENCES payroll_batches(id);
+
+CREATE INDEX idx_employer_members_user_id ON employer_members(user_id);
+CREATE INDEX idx_payroll_batch_lines_batch_id ON payroll_batch_lines(batch_id, line_number);
+CREATE INDEX idx_transactions_payroll_batch_id ON transactions(payroll_batch_id);
@@ -0,0 +1,2 @@
+DROP TABLE price_candles;
+DROP TABLE price_history;
@@ -0,0 +1,26 @@
+-- Every observed price, in USD per unit: live feed ticks and imported history
+CREATE TABLE price_history (
+    id BIGSERIAL PRIMARY KEY,
+    symbol VARCHAR(10) NOT NULL,
+    price NUMERIC(30,12) NOT NULL CHECK (price > 0),
+    volume_24h NUMERIC(30,2),
+    sources TEXT[] NOT NULL DEFAULT '{}', -- Providers that agreed on the price
+    origin VARCHAR(10) NOT NULL, -- FEED or IMPORT
+    recorded_at TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (symbol, recorded_at)
+);
+
+-- OHLC rollups of price_history, rebuilt for a bucket whenever it gains ticks
+CREATE TABLE price_candles (
+    symbol VARCHAR(10) NOT NULL,
+    interval VARCHAR(5) NOT NULL,
+    bucket_start TIMESTAMPTZ NOT NULL,
+    open NUMERIC(30,12) NOT NULL,
+    high NUMERIC(30,12) NOT NULL,
+    low NUMERIC(30,12) NOT NULL,
+    close NUMERIC(30,12) NOT NULL,
+    tick_count INTEGER NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (symbol, interval, bucket_start)
+);
@@ -0,0 +1,42 @@
+-- Collapses each allocation back onto two buckets: defensive assets
+-- (USDC, TBILL, KES_MMF) count as stablecoin, the rest as growth
+ALTER TABLE portfolio_allocations
+    ADD COLUMN stablecoin DECIMAL(5,2) NOT NULL DEFAULT 0,
+    ADD COLUMN growing_assets DECIMAL(5,2) NOT NULL DEFAULT 0;
+
+ALTER TABLE portfolio_recommendations
+    ADD COLUMN stablecoin DECIMAL(5,2) NOT NULL DEFAULT 0,
+    ADD COLUMN growing_assets DECIMAL(5,2) NOT NULL DEFAULT 0;
+
+UPDATE portfolio_allocations a SET
+    stablecoin = w.defensive,
+    growing_assets = 100 - w.defensive
+FROM (
+    SELECT allocation_id,
+        COALESCE(SUM(weight) FILTER (WHERE asset IN ('USDC', 'TBILL', 'KES_MMF')), 0) AS defensive
+    FROM portfolio_allocation_weights
+    GROUP BY allocation_id
+) w
+WHERE w.allocation_id = a.id;
+
+UPDATE portfolio_recommendations r SET
+    stablecoin = w.defensive,
+    growing_assets = 100 - w.defensive
+FROM (
+    SELECT recommendation_id,
+        COALESCE(SUM(weight) FILTER (WHERE asset IN ('USDC', 'TBILL', 'KES_MMF')), 0) AS defensive
+    FROM portfolio_recommendation_weights
+    GROUP BY recommendation_id
+) w
+WHERE w.recommendation_id = r.id;
+
+ALTER TABLE portfolio_allocations
+    ALTER COLUMN stablecoin DROP DEFAULT,
+    ALTER COLUMN growing_assets DROP DEFAULT;
+
+ALTER TABLE portfolio_recommendations
+    ALTER COLUMN stablecoin DROP DEFAULT,
+    ALTER COLUMN growing_assets DROP DEFAULT;
+
+DROP TABLE portfolio_recommendation_weights;
+DROP TABLE portfolio_allocation_weights;
@@ -0,0 +1,34 @@
+-- Allocations are keyed by asset instead of a stablecoin/growth pair.
+-- Weights are percentages; each allocation's weights add up to 100.
+CREATE TABLE portfolio_allocation_weights (
+    allocation_id UUID NOT NULL REFERENCES portfolio_allocations(id) ON DELETE CASCADE,
+    asset VARCHAR(16) NOT NULL, -- USDC, BTC, XLM, TBILL, KES_MMF
+    weight DECIMAL(5,2) NOT NULL CHECK (weight > 0 AND weight <= 100),
+    PRIMARY KEY (allocation_id, asset)
+);
+
+CREATE TABLE portfolio_recommendation_weights (
+    recommendation_id UUID NOT NULL REFERENCES portfolio_recommendations(id) ON DELETE CASCADE,
+    asset VARCHAR(16) NOT NULL,
+    weight DECIMAL(5,2) NOT NULL CHECK (weight > 0 AND weight <= 100),
+    PRIMARY KEY (recommendation_id, asset)
+);
+
+-- The stablecoin bucket was held as USDC and the growth bucket as BTC
+INSERT INTO portfolio_allocation_weights (allocation_id, asset, weight)
+SELECT id, 'USDC', stablecoin FROM portfolio_allocations WHERE stablecoin > 0
+UNION ALL
+SELECT id, 'BTC', growing_assets FROM portfolio_allocations WHERE growing_assets > 0;
+
+INSERT INTO portfolio_recommendation_weights (recommendation_id, asset, weight)
+SELECT id, 'USDC', stablecoin FROM portfolio_recommendations WHERE stablecoin > 0
+UNION ALL
+SELECT id, 'BTC', growing_assets FROM portfolio_recommendations WHERE growing_assets > 0;
+
+ALTER TABLE portfolio_allocations
+    DROP COLUMN stablecoin,
+    DROP COLUMN growing_assets;
+
+ALTER TABLE portfolio_recommendations
+    DROP COLUMN stablecoin,
+    DROP COLUMN growing_assets;
@@ -0,0 +1,11 @@
+DROP INDEX idx_portfolio_recommendations_pending;
+DROP TABLE rebalance_trades;
+
+ALTER TABLE portfolio_recommendations
+    DROP COLUMN portfolio_value,
+    DROP COLUMN estimated_cost,
+    DROP COLUMN failure_reason;
+
+ALTER TABLE user_risk_profiles DROP COLUMN auto_rebalance;
+
+DROP TABLE portfolio_holdings;
@@ -0,0 +1,41 @@
+-- Units of each asset a member holds, valued at the venue's quotes
+CREATE TABLE portfolio_holdings (
+    user_id UUID NOT NULL REFERENCES users(id),
+    asset VARCHAR(16) NOT NULL,
+    quantity NUMERIC(30,12) NOT NULL CHECK (quantity >= 0),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (user_id, asset)
+);
+
+-- Members who let recommendations execute without accepting each one
+ALTER TABLE user_risk_profiles ADD COLUMN auto_rebalance BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Recommendations move PENDING -> EXECUTING -> APPLIED or FAILED, or end
+-- as REJECTED by the member or SUPERSEDED by a newer recommendation
+ALTER TABLE portfolio_recommendations
+    ADD COLUMN portfolio_value NUMERIC(30,8) NOT NULL DEFAULT 0,
+    ADD COLUMN estimated_cost NUMERIC(30,8) NOT NULL DEFAULT 0,
+    ADD COLUMN failure_reason TEXT;
+
+CREATE TABLE rebalance_trades (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    recommendation_id UUID NOT NULL REFERENCES portfolio_recommendations(id) ON DELETE CASCADE,
+    seq SMALLINT NOT NULL, -- Execution order; sells come first to fund the buys
+    asset VARCHAR(16) NOT NULL,
+    side VARCHAR(4) NOT NULL CHECK (side IN ('BUY', 'SELL')),
+    quantity NUMERIC(30,12) NOT NULL CHECK (quantity > 0),
+    quoted_price NUMERIC(30,12) NOT NULL,
+    notional NUMERIC(30,8) NOT NULL,       -- USD; what a buy spends, fees included
+    estimated_cost NUMERIC(30,8) NOT NULL, -- USD lost to fees and slippage
+    status VARCHAR(20) NOT NULL DEFAULT 'PENDING', -- PENDING, FILLED or FAILED
+    venue VARCHAR(32),
+    venue_order_id VARCHAR(64),
+    filled_quantity NUMERIC(30,12),
+    fill_price NUMERIC(30,12),
+    fee NUMERIC(30,8),
+    executed_at TIMESTAMPTZ,
+    UNIQUE (recommendation_id, seq)
+);
+
+CREATE INDEX idx_portfolio_recommendations_pending
+    ON portfolio_recommendations(user_id) WHERE status = 'PENDING';
@@ -0,0 +1,3 @@
+ALTER TABLE user_risk_profiles
+    DROP COLUMN allocation_strategy,
+    DROP COLUMN answered_at;
@@ -0,0 +1,10 @@
+-- RISK_BASED members target their risk tolerance plan; TARGET_DATE members
+-- follow the glide path towards their retirement date
+ALTER TABLE user_risk_profiles
+    ADD COLUMN allocation_strategy VARCHAR(20) NOT NULL DEFAULT 'RISK_BASED'
+        CHECK (allocation_strategy IN ('RISK_BASED', 'TARGET_DATE'));
+
+-- When age and horizon were last stated. Members are aged from here, so it
+-- only moves when the questionnaire is answered, unlike updated_at.
+ALTER TABLE user_risk_profiles ADD COLUMN answered_at TIMESTAMPTZ NOT NULL DEFAULT NOW();
+UPDATE user_risk_profiles SET answered_at = updated_at;
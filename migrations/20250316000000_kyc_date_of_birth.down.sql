@@ -0,0 +1 @@
+ALTER TABLE kyc_verifications DROP COLUMN date_of_birth;
@@ -0,0 +1,4 @@
+-- The date of birth submitted with the ID document. It is checked against
+-- the ID registry, so once the verification is approved it is the member's
+-- age of record for retirement withdrawals.
+ALTER TABLE kyc_verifications ADD COLUMN date_of_birth DATE;
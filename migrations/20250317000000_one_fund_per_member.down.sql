@@ -0,0 +1 @@
+ALTER TABLE pension_funds DROP CONSTRAINT pension_funds_user_id_key;
@@ -0,0 +1,4 @@
+-- Balances, withdrawals, tokenization and payroll all find a member's fund
+-- by user_id, so a member holds exactly one. Fails if any member already
+-- has two, which must be merged by hand first.
+ALTER TABLE pension_funds ADD CONSTRAINT pension_funds_user_id_key UNIQUE (user_id);
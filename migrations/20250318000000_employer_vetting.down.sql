@@ -0,0 +1,14 @@
+DROP INDEX IF EXISTS idx_employers_status;
+
+ALTER TABLE payroll_batches
+    DROP COLUMN settled_at,
+    DROP COLUMN settled_by,
+    DROP COLUMN remittance_reference,
+    DROP COLUMN status;
+
+ALTER TABLE employers
+    DROP COLUMN approved_at,
+    DROP COLUMN approved_by,
+    DROP COLUMN status;
+
+ALTER TABLE users DROP COLUMN is_admin;
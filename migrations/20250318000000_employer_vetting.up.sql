@@ -0,0 +1,21 @@
+-- Platform staff who vet employers and confirm their remittances
+ALTER TABLE users ADD COLUMN is_admin BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Employers start PENDING and can only manage a roster or upload payroll
+-- once an administrator has APPROVED them. Existing employers were never
+-- vetted, so they start pending too.
+ALTER TABLE employers
+    ADD COLUMN status VARCHAR(20) NOT NULL DEFAULT 'PENDING',
+    ADD COLUMN approved_by UUID REFERENCES users(id),
+    ADD COLUMN approved_at TIMESTAMPTZ;
+
+-- A batch's contributions stay PENDING until the employer's remittance for
+-- it is confirmed. Batches uploaded before this were credited on upload.
+ALTER TABLE payroll_batches
+    ADD COLUMN status VARCHAR(20) NOT NULL DEFAULT 'SETTLED',
+    ADD COLUMN remittance_reference VARCHAR(64),
+    ADD COLUMN settled_by UUID REFERENCES users(id),
+    ADD COLUMN settled_at TIMESTAMPTZ;
+ALTER TABLE payroll_batches ALTER COLUMN status SET DEFAULT 'AWAITING_REMITTANCE';
+
+CREATE INDEX idx_employers_status ON employers(status);
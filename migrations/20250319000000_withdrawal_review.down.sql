@@ -0,0 +1,5 @@
+DROP INDEX IF EXISTS idx_transactions_pending_review;
+
+ALTER TABLE transactions
+    DROP COLUMN reviewed_at,
+    DROP COLUMN reviewed_by;
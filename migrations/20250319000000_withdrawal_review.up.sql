@@ -0,0 +1,8 @@
+-- Hardship withdrawals wait in PENDING_REVIEW, with the amount held, until
+-- an administrator approves or rejects the claim
+ALTER TABLE transactions
+    ADD COLUMN reviewed_by UUID REFERENCES users(id),
+    ADD COLUMN reviewed_at TIMESTAMPTZ;
+
+CREATE INDEX idx_transactions_pending_review ON transactions(created_at)
+    WHERE status = 'PENDING_REVIEW';
@@ -0,0 +1,3 @@
+UPDATE loans SET status = 'LIQUIDATING' WHERE status = 'SEIZING';
+
+ALTER TABLE loans DROP COLUMN seizure_amount;
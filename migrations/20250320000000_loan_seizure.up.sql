@@ -0,0 +1,6 @@
+-- Liquidation commits SEIZING, with the amount it is about to claw back,
+-- before the collateral is seized on chain, so the loan monitor can finish
+-- or retry a liquidation that was interrupted
+ALTER TABLE loans ADD COLUMN seizure_amount DECIMAL(20,8);
+
+UPDATE loans SET status = 'SEIZING' WHERE status = 'LIQUIDATING';
@@ -0,0 +1,348 @@
+use chrono::{DateTime, Datelike, Months, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::Serialize;
+
+use crate::ai::investment_strategy::{
+    AllocationError, AssetAllocation, RiskProfile, RiskTolerance,
+};
+use crate::config::glide_path::{GlidePathConfig, GlidePoint};
+
+#[derive(Debug, PartialEq, thiserror::Error)]
+pub enum GlidePathError {
+    #[error("Glide path needs at least one point")]
+    Empty,
+    #[error("Glide path growth weight {0}% is outside 0-100%")]
+    GrowthOutOfRange(Decimal),
+    #[error("Glide path has two points {0} years from retirement")]
+    DuplicatePoint(u32),
+}
+
+// Target-date strategy: the share of savings in growth assets falls as
+// retirement approaches, shifted up or down by the member's risk tolerance
+#[derive(Debug, Clone)]
+pub struct GlidePath {
+    retirement_age: u8,
+    points: Vec<GlidePoint>, // Furthest from retirement first
+    tolerance_shift: Decimal,
+}
+
+// The target a member moves onto on one anniversary of their profile
+#[derive(Debug, Clone, Serialize)]
+pub struct GlideStep {
+    pub effective_on: NaiveDate,
+    pub years_to_retirement: u32,
+    pub growth_weight: Decimal,
+    pub allocation: AssetAllocation,
+}
+
+impl GlidePath {
+    // The retirement age is the withdrawal policy's, so savings finish
+    // de-risking when they become payable
+    pub fn new(config: GlidePathConfig, retirement_age: u8) -> Result<Self, GlidePathError> {
+        let mut points = config.points;
+        if points.is_empty() {
+            return Err(GlidePathError::Empty);
+        }
+        if let Some(point) = points
+            .iter()
+            .find(|p| p.growth < Decimal::ZERO || p.growth > Decimal::ONE_HUNDRED)
+        {
+            return Err(GlidePathError::GrowthOutOfRange(point.growth));
+        }
+
+        points.sort_by(|a, b| b.years_to_retirement.cmp(&a.years_to_retirement));
+        if let Some(pair) = points
+            .windows(2)
+            .find(|pair| pair[0].years_to_retirement == pair[1].years_to_retirement)
+        {
+            return Err(GlidePathError::DuplicatePoint(pair[0].years_to_retirement));
+        }
+
+        Ok(Self {
+            retirement_age,
+            points,
+            tolerance_shift: config.tolerance_shift,
+        })
+    }
+
+    // The earlier of the member's own horizon and the retirement age
+    pub fn years_to_retirement(&self, profile: &RiskProfile) -> u32 {
+        let to_retirement = self.retirement_age.saturating_sub(profile.age);
+        u32::from(to_retirement.min(profile.investment_horizon))
+    }
+
+    pub fn growth_weight(&self, years_to_retirement: u32) -> Decimal {
+        let furthest = self.points[0];
+        let nearest = self.points[self.points.len() - 1];
+        if years_to_retirement >= furthest.years_to_retirement {
+            return furthest.growth;
+        }
+        if years_to_retirement <= nearest.years_to_retirement {
+            return nearest.growth;
+        }
+
+        let pair = self
+            .points
+            .windows(2)
+            .find(|pair| years_to_retirement >= pair[1].years_to_retirement)
+            .expect("years fall between the first and last points");
+        let (far, near) = (pair[0], pair[1]);
+
+        let progress = Decimal::from(far.years_to_retirement - years_to_retirement)
+            / Decimal::from(far.years_to_retirement - near.years_to_retirement);
+        (far.growth + (near.growth - far.growth) * progress).round_dp(2)
+    }
+
+    pub fn blended_growth(&self, years_to_retirement: u32, tolerance: RiskTolerance) -> Decimal {
+        let shift = match tolerance {
+            RiskTolerance::Conservative => -self.tolerance_shift,
+            RiskTolerance::Moderate => Decimal::ZERO,
+            RiskTolerance::Aggressive => self.tolerance_shift,
+        };
+        (self.growth_weight(years_to_retirement) + shift).clamp(Decimal::ZERO, Decimal::ONE_HUNDRED)
+    }
+
+    // The glide path sizes the growth and defensive sleeves; the member's
+    // risk tolerance plan sets the mix of assets within each
+    pub fn allocation(&self, profile: &RiskProfile) -> Result<AssetAllocation, AllocationError> {
+        let growth = self.blended_growth(self.years_to_retirement(profile), profile.risk_tolerance);
+        AssetAllocation::from_risk_tolerance(&profile.risk_tolerance).with_growth_weight(growth)
+    }
+
+    // The member's target on each anniversary from `from` until retirement,
+    // listing only the years in which it moves. The rebalancer picks each
+    // shift up as the member's profile ages into it.
+    pub fn schedule(
+        &self,
+        profile: &RiskProfile,
+        from: NaiveDate,
+    ) -> Result<Vec<GlideStep>, AllocationError> {
+        let mut steps: Vec<GlideStep> = Vec::new();
+
+        for year in 0..=self.years_to_retirement(profile) {
+            let aged = aged_profile(profile, year);
+            let years_to_retirement = self.years_to_retirement(&aged);
+            let growth_weight = self.blended_growth(years_to_retirement, aged.risk_tolerance);
+            if steps
+                .last()
+                .is_some_and(|step| step.growth_weight == growth_weight)
+            {
+                continue;
+            }
+
+            let Some(effective_on) = from.checked_add_months(Months::new(12 * year)) else {
+                break;
+            };
+            steps.push(GlideStep {
+                effective_on,
+                years_to_retirement,
+                growth_weight,
+                allocation: self.allocation(&aged)?,
+            });
+        }
+
+        Ok(steps)
+    }
+}
+
+// The profile as it stands `years` after it was answered
+pub fn aged_profile(profile: &RiskProfile, years: u32) -> RiskProfile {
+    let years = u8::try_from(years).unwrap_or(u8::MAX);
+
+    RiskProfile {
+        age: profile.age.saturating_add(years),
+        investment_horizon: profile.investment_horizon.saturating_sub(years),
+        ..profile.clone()
+    }
+}
+
+// Whole years from `from` to `to`, counting one on each anniversary
+pub fn whole_years_between(from: DateTime<Utc>, to: DateTime<Utc>) -> u32 {
+    let (from, to) = (from.date_naive(), to.date_naive());
+
+    let mut years = to.year() - from.year();
+    if (to.month(), to.day()) < (from.month(), from.day()) {
+        years -= 1;
+    }
+    years.max(0) as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::investment_strategy::{AllocationStrategy, Asset};
+    use chrono::TimeZone;
+
+    const RETIREMENT_AGE: u8 = 60;
+
+    fn dec(value: &str) -> Decimal {
+        value.parse().unwrap()
+    }
+
+    fn glide_path() -> GlidePath {
+        GlidePath::new(GlidePathConfig::default(), RETIREMENT_AGE).unwrap()
+    }
+
+    fn member(age: u8, investment_horizon: u8, risk_tolerance: RiskTolerance) -> RiskProfile {
+        RiskProfile {
+            age,
+            income: 60_000.0,
+            risk_tolerance,
+            investment_horizon,
+            strategy: AllocationStrategy::TargetDate,
+        }
+    }
+
+    #[test]
+    fn test_growth_weight_interpolates_between_points() {
+        let path = glide_path();
+
+        assert_eq!(path.growth_weight(50), dec("85"));
+        assert_eq!(path.growth_weight(40), dec("85"));
+        assert_eq!(path.growth_weight(30), dec("78.33"));
+        assert_eq!(path.growth_weight(5), dec("37.5"));
+        assert_eq!(path.growth_weight(0), dec("25"));
+    }
+
+    #[test]
+    fn test_years_to_retirement_uses_earlier_of_horizon_and_retirement_age() {
+        let path = glide_path();
+
+        assert_eq!(
+            path.years_to_retirement(&member(30, 40, RiskTolerance::Moderate)),
+            30
+        );
+        assert_eq!(
+            path.years_to_retirement(&member(30, 12, RiskTolerance::Moderate)),
+            12
+        );
+        assert_eq!(
+            path.years_to_retirement(&member(65, 5, RiskTolerance::Moderate)),
+            0
+        );
+    }
+
+    #[test]
+    fn test_risk_tolerance_shifts_the_path() {
+        let path = glide_path();
+
+        assert_eq!(path.blended_growth(0, RiskTolerance::Aggressive), dec("35"));
+        assert_eq!(
+            path.blended_growth(40, RiskTolerance::Conservative),
+            dec("75")
+        );
+
+        let steep = GlidePath::new(
+            GlidePathConfig {
+                tolerance_shift: dec("30"),
+                ..Default::default()
+            },
+            RETIREMENT_AGE,
+        )
+        .unwrap();
+        assert_eq!(
+            steep.blended_growth(0, RiskTolerance::Conservative),
+            dec("0")
+        );
+        assert_eq!(
+            steep.blended_growth(40, RiskTolerance::Aggressive),
+            dec("100")
+        );
+    }
+
+    #[test]
+    fn test_allocation_sizes_sleeves_from_the_path() {
+        let allocation = glide_path()
+            .allocation(&member(55, 20, RiskTolerance::Moderate))
+            .unwrap();
+
+        assert_eq!(allocation.growth_weight(), dec("37.5"));
+        assert_eq!(allocation.weight(Asset::Btc), dec("30"));
+        assert_eq!(allocation.weight(Asset::Usdc), dec("25"));
+        assert!(allocation.validate().is_ok());
+    }
+
+    #[test]
+    fn test_schedule_shifts_yearly_towards_retirement() {
+        let from = NaiveDate::from_ymd_opt(2025, 3, 15).unwrap();
+        let steps = glide_path()
+            .schedule(&member(55, 20, RiskTolerance::Moderate), from)
+            .unwrap();
+
+        assert_eq!(steps.len(), 6);
+        assert_eq!(steps[0].effective_on, from);
+        assert_eq!(
+            steps[1].effective_on,
+            NaiveDate::from_ymd_opt(2026, 3, 15).unwrap()
+        );
+        assert_eq!(steps[5].years_to_retirement, 0);
+        assert_eq!(steps[5].growth_weight, dec("25"));
+        assert!(steps
+            .windows(2)
+            .all(|pair| pair[1].growth_weight < pair[0].growth_weight));
+    }
+
+    #[test]
+    fn test_schedule_skips_years_on_a_flat_stretch() {
+        let from = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+        let steps = glide_path()
+            .schedule(&member(18, 45, RiskTolerance::Moderate), from)
+            .unwrap();
+
+        // Flat at 85% until 40 years out, so the first shift comes two years on
+        assert_eq!(steps[0].growth_weight, dec("85"));
+        assert_eq!(
+            steps[1].effective_on,
+            NaiveDate::from_ymd_opt(2027, 1, 1).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_rejects_invalid_paths() {
+        let invalid = |points: Vec<GlidePoint>| {
+            GlidePath::new(
+                GlidePathConfig {
+                    points,
+                    ..Default::default()
+                },
+                RETIREMENT_AGE,
+            )
+            .unwrap_err()
+        };
+        let point = |years_to_retirement, growth: &str| GlidePoint {
+            years_to_retirement,
+            growth: dec(growth),
+        };
+
+        assert_eq!(invalid(vec![]), GlidePathError::Empty);
+        assert_eq!(
+            invalid(vec![point(10, "50"), point(10, "40")]),
+            GlidePathError::DuplicatePoint(10)
+        );
+        assert_eq!(
+            invalid(vec![point(10, "120")]),
+            GlidePathError::GrowthOutOfRange(dec("120"))
+        );
+    }
+
+    #[test]
+    fn test_whole_years_count_on_anniversaries() {
+        let answered = Utc.with_ymd_and_hms(2020, 3, 15, 12, 0, 0).unwrap();
+
+        assert_eq!(
+            whole_years_between(
+                answered,
+                Utc.with_ymd_and_hms(2021, 3, 14, 23, 0, 0).unwrap()
+            ),
+            0
+        );
+        assert_eq!(
+            whole_years_between(
+                answered,
+                Utc.with_ymd_and_hms(2021, 3, 15, 0, 0, 0).unwrap()
+            ),
+            1
+        );
+        assert_eq!(whole_years_between(answered, answered), 0);
+    }
+}
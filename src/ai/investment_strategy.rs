@@ -1,75 +1,357 @@
 use anyhow::Result;
-use ndarray::{Array1, Array2};
+use rust_decimal::{prelude::ToPrimitive, Decimal};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::RwLock;
+use std::fmt;
 use uuid::Uuid;
-use tch::{Device, Tensor, nn};
-use chrono::{DateTime, Utc};
+use chrono::{Duration, Utc};
+use crate::ai::glide_path::GlidePath;
+use crate::ai::portfolio_optimizer::{
+    self, EfficientFrontier, MarketHistory, ReturnModel, WeightBounds, DAILY_PERIODS_PER_YEAR,
+    FRONTIER_STEPS,
+};
 use crate::services::price_feed::PriceFeedService;
+use crate::services::price_history::PriceHistoryService;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RiskProfile {
-    age: u8,
-    income: f64,
-    risk_tolerance: RiskTolerance,
-    investment_horizon: u8, // years
+    pub age: u8,
+    pub income: f64,
+    pub risk_tolerance: RiskTolerance,
+    pub investment_horizon: u8, // years
+    #[serde(default)]
+    pub strategy: AllocationStrategy,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum RiskTolerance {
     Conservative,
     Moderate,
     Aggressive,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+impl RiskTolerance {
+    // Stored form in user_risk_profiles.risk_tolerance
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RiskTolerance::Conservative => "CONSERVATIVE",
+            RiskTolerance::Moderate => "MODERATE",
+            RiskTolerance::Aggressive => "AGGRESSIVE",
+        }
+    }
+}
+
+impl std::str::FromStr for RiskTolerance {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "CONSERVATIVE" => Ok(RiskTolerance::Conservative),
+            "MODERATE" => Ok(RiskTolerance::Moderate),
+            "AGGRESSIVE" => Ok(RiskTolerance::Aggressive),
+            other => Err(anyhow::anyhow!("Unknown risk tolerance: {}", other)),
+        }
+    }
+}
+
+// How a member's target allocation is set: from their risk tolerance
+// alone, or along the glide path towards their retirement date
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AllocationStrategy {
+    #[default]
+    RiskBased,
+    TargetDate,
+}
+
+impl AllocationStrategy {
+    // Stored form in user_risk_profiles.allocation_strategy
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AllocationStrategy::RiskBased => "RISK_BASED",
+            AllocationStrategy::TargetDate => "TARGET_DATE",
+        }
+    }
+}
+
+impl std::str::FromStr for AllocationStrategy {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "RISK_BASED" => Ok(AllocationStrategy::RiskBased),
+            "TARGET_DATE" => Ok(AllocationStrategy::TargetDate),
+            other => Err(anyhow::anyhow!("Unknown allocation strategy: {}", other)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum Asset {
+    Usdc,
+    Btc,
+    Xlm,
+    Tbill,  // Tokenized US Treasury bills
+    KesMmf, // Kenya shilling money-market fund
+}
+
+impl Asset {
+    pub const ALL: [Asset; 5] = [
+        Asset::Usdc,
+        Asset::Btc,
+        Asset::Xlm,
+        Asset::Tbill,
+        Asset::KesMmf,
+    ];
+
+    // Stored form in the *_weights tables and the pension contract
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Asset::Usdc => "USDC",
+            Asset::Btc => "BTC",
+            Asset::Xlm => "XLM",
+            Asset::Tbill => "TBILL",
+            Asset::KesMmf => "KES_MMF",
+        }
+    }
+
+    // Held for capital preservation rather than growth
+    pub fn is_defensive(&self) -> bool {
+        matches!(self, Asset::Usdc | Asset::Tbill | Asset::KesMmf)
+    }
+}
+
+impl fmt::Display for Asset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for Asset {
+    type Err = AllocationError;
+
+    fn from_str(s: &str) -> Result<Self, AllocationError> {
+        Asset::ALL
+            .into_iter()
+            .find(|asset| asset.as_str() == s)
+            .ok_or_else(|| AllocationError::UnknownAsset(s.to_string()))
+    }
+}
+
+#[derive(Debug, PartialEq, thiserror::Error)]
+pub enum AllocationError {
+    #[error("Unknown asset: {0}")]
+    UnknownAsset(String),
+    #[error("Weight for {asset} must be between 0.01% and 100%, got {weight}%")]
+    InvalidWeight { asset: Asset, weight: Decimal },
+    #[error("Weights must add up to 100%, got {0}%")]
+    InvalidTotal(Decimal),
+}
+
+// Percentage weight per asset. Assets with no weight are left out.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct AssetAllocation {
-    pub stablecoin: f64,    // Percentage in stablecoins (e.g., USDC)
-    pub growing_assets: f64, // Percentage in growing assets (e.g., Bitcoin)
+    pub weights: BTreeMap<Asset, Decimal>,
 }
 
 impl AssetAllocation {
+    pub fn new(weights: BTreeMap<Asset, Decimal>) -> Result<Self, AllocationError> {
+        let allocation = Self {
+            weights: weights
+                .into_iter()
+                .filter(|(_, weight)| !weight.is_zero())
+                .collect(),
+        };
+        allocation.validate()?;
+        Ok(allocation)
+    }
+
     pub fn from_risk_tolerance(risk_tolerance: &RiskTolerance) -> Self {
-        match risk_tolerance {
-            RiskTolerance::Conservative => Self {
-                stablecoin: 80.0,
-                growing_assets: 20.0,
-            },
-            RiskTolerance::Moderate => Self {
-                stablecoin: 50.0,
-                growing_assets: 50.0,
-            },
-            RiskTolerance::Aggressive => Self {
-                stablecoin: 20.0,
-                growing_assets: 80.0,
-            },
+        let weights: &[(Asset, i64)] = match risk_tolerance {
+            RiskTolerance::Conservative => &[
+                (Asset::Usdc, 30),
+                (Asset::Tbill, 35),
+                (Asset::KesMmf, 15),
+                (Asset::Btc, 15),
+                (Asset::Xlm, 5),
+            ],
+            RiskTolerance::Moderate => &[
+                (Asset::Usdc, 20),
+                (Asset::Tbill, 20),
+                (Asset::KesMmf, 10),
+                (Asset::Btc, 40),
+                (Asset::Xlm, 10),
+            ],
+            RiskTolerance::Aggressive => &[
+                (Asset::Usdc, 10),
+                (Asset::Tbill, 10),
+                (Asset::Btc, 65),
+                (Asset::Xlm, 15),
+            ],
+        };
+
+        Self {
+            weights: weights
+                .iter()
+                .map(|(asset, weight)| (*asset, Decimal::from(*weight)))
+                .collect(),
+        }
+    }
+
+    // Rounds optimizer output to whole basis points, handing the rounding
+    // leftovers to the largest remainders so the weights still total 100%
+    pub fn from_fractions(assets: &[String], fractions: &[f64]) -> Result<Self, AllocationError> {
+        let assets = assets
+            .iter()
+            .map(|asset| asset.parse::<Asset>())
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let total: f64 = fractions.iter().map(|f| f.max(0.0)).sum();
+        if assets.len() != fractions.len() || !total.is_finite() || total <= 0.0 {
+            return Err(AllocationError::InvalidTotal(Decimal::ZERO));
+        }
+
+        let scaled: Vec<f64> = fractions
+            .iter()
+            .map(|f| f.max(0.0) / total * 10_000.0)
+            .collect();
+        let mut units: Vec<i64> = scaled.iter().map(|s| s.floor() as i64).collect();
+
+        let mut order: Vec<usize> = (0..scaled.len()).collect();
+        order.sort_by(|&a, &b| {
+            let rem_a = scaled[a] - scaled[a].floor();
+            let rem_b = scaled[b] - scaled[b].floor();
+            rem_b.total_cmp(&rem_a)
+        });
+        let left = 10_000 - units.iter().sum::<i64>();
+        for &i in order.iter().cycle().take(left.max(0) as usize) {
+            units[i] += 1;
+        }
+
+        let mut weights = BTreeMap::new();
+        for (asset, units) in assets.into_iter().zip(units) {
+            *weights.entry(asset).or_insert(Decimal::ZERO) += Decimal::new(units, 2);
+        }
+        Self::new(weights)
+    }
+
+    pub fn validate(&self) -> Result<(), AllocationError> {
+        for (asset, weight) in &self.weights {
+            if *weight <= Decimal::ZERO
+                || *weight > Decimal::ONE_HUNDRED
+                || weight.round_dp(2) != *weight
+            {
+                return Err(AllocationError::InvalidWeight {
+                    asset: *asset,
+                    weight: *weight,
+                });
+            }
         }
+
+        let total: Decimal = self.weights.values().sum();
+        if total != Decimal::ONE_HUNDRED {
+            return Err(AllocationError::InvalidTotal(total));
+        }
+        Ok(())
     }
 
-    pub fn validate(&self) -> bool {
-        (self.stablecoin + self.growing_assets - 100.0).abs() < 0.01 // Check if total is 100%
+    pub fn weight(&self, asset: Asset) -> Decimal {
+        self.weights.get(&asset).copied().unwrap_or(Decimal::ZERO)
+    }
+
+    pub fn defensive_weight(&self) -> Decimal {
+        self.weights
+            .iter()
+            .filter(|(asset, _)| asset.is_defensive())
+            .map(|(_, weight)| *weight)
+            .sum()
+    }
+
+    pub fn growth_weight(&self) -> Decimal {
+        Decimal::ONE_HUNDRED - self.defensive_weight()
+    }
+
+    // Resizes the growth sleeve to `growth` percent, keeping the mix within
+    // each sleeve. An empty sleeve that has to grow is filled with USDC or BTC.
+    pub fn with_growth_weight(&self, growth: Decimal) -> Result<Self, AllocationError> {
+        let growth = growth.clamp(Decimal::ZERO, Decimal::ONE_HUNDRED);
+        let defensive = Decimal::ONE_HUNDRED - growth;
+        let (held_defensive, held_growth) = (self.defensive_weight(), self.growth_weight());
+
+        let mut assets = Vec::new();
+        let mut fractions = Vec::new();
+        for (asset, weight) in &self.weights {
+            let (held, wanted) = if asset.is_defensive() {
+                (held_defensive, defensive)
+            } else {
+                (held_growth, growth)
+            };
+            assets.push(asset.as_str().to_string());
+            fractions.push((*weight / held * wanted).to_f64().unwrap_or_default());
+        }
+        if held_defensive.is_zero() && !defensive.is_zero() {
+            assets.push(Asset::Usdc.as_str().to_string());
+            fractions.push(defensive.to_f64().unwrap_or_default());
+        }
+        if held_growth.is_zero() && !growth.is_zero() {
+            assets.push(Asset::Btc.as_str().to_string());
+            fractions.push(growth.to_f64().unwrap_or_default());
+        }
+
+        Self::from_fractions(&assets, &fractions)
+    }
+
+    // Largest gap, in percentage points, between any asset's weights
+    pub fn max_drift(&self, other: &AssetAllocation) -> Decimal {
+        Asset::ALL
+            .into_iter()
+            .map(|asset| (self.weight(asset) - other.weight(asset)).abs())
+            .max()
+            .unwrap_or(Decimal::ZERO)
     }
 }
 
+// Days of daily closes the optimizer is fitted on, and the fewest it will
+// trust; with less the fixed splits are used instead
+const HISTORY_WINDOW_DAYS: i64 = 365;
+const MIN_HISTORY_DAYS: usize = 30;
+
+#[derive(Default)]
 pub struct InvestmentAI {
-    model: tch::CModule,
-    market_data: HashMap<String, f64>,
+    market_data: RwLock<HashMap<String, f64>>, // Latest snapshot from the price feed
     price_feed: Option<PriceFeedService>,
+    price_history: Option<PriceHistoryService>,
+    market_history: RwLock<Option<MarketHistory>>, // Daily closes the optimizer works from
+    glide_path: Option<GlidePath>,
 }
 
 impl InvestmentAI {
-    pub fn new() -> Result<Self> {
-        // Load the PyTorch model (you'll need to train this separately)
-        let model = tch::CModule::load("models/investment_model.pt")?;
-        
-        Ok(Self {
-            model,
-            market_data: HashMap::new(),
-            price_feed: None,
-        })
-    }
-
-    pub async fn update_market_data(&mut self) -> Result<()> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_price_feed(mut self, price_feed: PriceFeedService) -> Self {
+        self.price_feed = Some(price_feed);
+        self
+    }
+
+    pub fn with_price_history(mut self, price_history: PriceHistoryService) -> Self {
+        self.price_history = Some(price_history);
+        self
+    }
+
+    pub fn with_market_history(self, market_history: MarketHistory) -> Self {
+        *self.market_history.write().unwrap() = Some(market_history);
+        self
+    }
+
+    pub fn with_glide_path(mut self, glide_path: GlidePath) -> Self {
+        self.glide_path = Some(glide_path);
+        self
+    }
+
+    pub async fn update_market_data(&self) -> Result<()> {
         let price_feed = self.price_feed.as_ref()
             .ok_or_else(|| anyhow::anyhow!("Price feed not initialized"))?;
 
@@ -78,7 +360,7 @@ impl InvestmentAI {
         let usdc_data = price_feed.get_price("USDC").await?;
 
         // Update market data
-        self.market_data = HashMap::from([
+        *self.market_data.write().unwrap() = HashMap::from([
             ("btc_price".to_string(), btc_data.price),
             ("btc_volume".to_string(), btc_data.volume_24h),
             ("btc_change".to_string(), btc_data.percent_change_24h),
@@ -86,62 +368,137 @@ impl InvestmentAI {
             ("usdc_volume".to_string(), usdc_data.volume_24h),
         ]);
 
+        if let Some(price_history) = &self.price_history {
+            self.refresh_market_history(price_history).await;
+        }
+
         Ok(())
     }
 
-    pub fn generate_allocation(&self, profile: &RiskProfile) -> Result<AssetAllocation> {
-        // Instead of using ML model, we'll use the predefined allocations
-        Ok(AssetAllocation::from_risk_tolerance(&profile.risk_tolerance))
-    }
-
-    fn prepare_input(&self, profile: &RiskProfile) -> Result<tch::Tensor> {
-        // Combine risk profile and market data into a single input tensor
-        let mut features = Vec::new();
-        
-        // Add risk profile features
-        features.push(profile.age as f64 / 100.0); // Normalize age
-        features.push(profile.income / 100000.0);  // Normalize income
-        features.push(match profile.risk_tolerance {
-            RiskTolerance::Conservative => 0.0,
-            RiskTolerance::Moderate => 0.5,
-            RiskTolerance::Aggressive => 1.0,
-        });
-        features.push(profile.investment_horizon as f64 / 40.0); // Normalize horizon
+    // Reloads the optimizer's history from the daily candles of every asset
+    // the price history records. A failed load keeps the previous history.
+    async fn refresh_market_history(&self, price_history: &PriceHistoryService) {
+        let symbols: Vec<String> = price_history
+            .config()
+            .market_symbols
+            .iter()
+            .filter(|symbol| symbol.parse::<Asset>().is_ok())
+            .cloned()
+            .collect();
+        let now = Utc::now();
+
+        match price_history
+            .market_history(&symbols, now - Duration::days(HISTORY_WINDOW_DAYS), now)
+            .await
+        {
+            Ok(history) if history.prices.nrows() >= MIN_HISTORY_DAYS => {
+                *self.market_history.write().unwrap() = Some(history);
+            }
+            Ok(history) => tracing::info!(
+                "Only {} days of price history; keeping fixed allocations",
+                history.prices.nrows()
+            ),
+            Err(e) => tracing::warn!("Failed to load market history: {}", e),
+        }
+    }
 
-        // Add market data features
-        for key in ["stock_market_index", "bond_yields", "real_estate_index", "crypto_index"] {
-            features.push(self.market_data.get(key).unwrap_or(&0.0) / 1000.0);
+    // Target-date members follow the glide path. Otherwise optimizes over
+    // the market history when there is one; the fixed splits are only the
+    // fallback before any history is loaded
+    pub fn generate_allocation(&self, profile: &RiskProfile) -> Result<AssetAllocation> {
+        if let (AllocationStrategy::TargetDate, Some(glide_path)) =
+            (profile.strategy, &self.glide_path)
+        {
+            return Ok(glide_path.allocation(profile)?);
         }
 
-        Ok(tch::Tensor::from_slice(&features))
+        let market_history = self.market_history.read().unwrap();
+        let history = match market_history.as_ref() {
+            Some(history) => history,
+            None => return Ok(AssetAllocation::from_risk_tolerance(&profile.risk_tolerance)),
+        };
+
+        let model = ReturnModel::from_history(history, DAILY_PERIODS_PER_YEAR)?;
+        let target = portfolio_optimizer::optimize(
+            &model,
+            &WeightBounds::long_only(model.assets.len()),
+            profile,
+        )?;
+
+        Ok(AssetAllocation::from_fractions(&model.assets, &target.weights.to_vec())?)
+    }
+
+    pub fn calculate_efficient_frontier(
+        &self,
+        history: &MarketHistory,
+    ) -> Result<EfficientFrontier> {
+        let model = ReturnModel::from_history(history, DAILY_PERIODS_PER_YEAR)?;
+        let frontier = portfolio_optimizer::efficient_frontier(
+            &model,
+            &WeightBounds::long_only(model.assets.len()),
+            FRONTIER_STEPS,
+        )?;
+        Ok(frontier)
+    }
+}
+
+// The price clients have no Debug impl, so only the configuration shows
+impl fmt::Debug for InvestmentAI {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("InvestmentAI")
+            .field("price_feed", &self.price_feed.is_some())
+            .field("price_history", &self.price_history.is_some())
+            .field(
+                "market_history",
+                &self.market_history.read().unwrap().is_some(),
+            )
+            .field("glide_path", &self.glide_path)
+            .finish_non_exhaustive()
     }
 }
 
 #[derive(Debug)]
 pub struct PortfolioRebalancer {
     ai: InvestmentAI,
-    rebalance_threshold: f64, // Percentage difference that triggers rebalancing
+    rebalance_threshold: Decimal, // Drift in percentage points that triggers rebalancing
 }
 
 impl PortfolioRebalancer {
-    pub fn new(rebalance_threshold: f64) -> Result<Self> {
-        Ok(Self {
-            ai: InvestmentAI::new()?,
+    pub fn new(rebalance_threshold: Decimal) -> Self {
+        Self {
+            ai: InvestmentAI::new(),
             rebalance_threshold,
-        })
+        }
+    }
+
+    pub fn with_glide_path(mut self, glide_path: GlidePath) -> Self {
+        self.ai = self.ai.with_glide_path(glide_path);
+        self
+    }
+
+    pub fn with_price_feed(mut self, price_feed: PriceFeedService) -> Self {
+        self.ai = self.ai.with_price_feed(price_feed);
+        self
+    }
+
+    pub fn with_price_history(mut self, price_history: PriceHistoryService) -> Self {
+        self.ai = self.ai.with_price_history(price_history);
+        self
+    }
+
+    // The allocation the member's profile calls for at today's market data
+    pub async fn target_allocation(&self, risk_profile: &RiskProfile) -> Result<AssetAllocation> {
+        self.ai.update_market_data().await?;
+        self.ai.generate_allocation(risk_profile)
     }
 
     pub async fn check_and_rebalance(
-        &mut self,
-        portfolio_id: Uuid,
+        &self,
+        _portfolio_id: Uuid,
         current_allocation: &AssetAllocation,
         risk_profile: &RiskProfile,
     ) -> Result<Option<AssetAllocation>> {
-        // Update market data
-        self.ai.update_market_data().await?;
-
-        // Generate target allocation
-        let target = self.ai.generate_allocation(risk_profile)?;
+        let target = self.target_allocation(risk_profile).await?;
 
         // Check if rebalancing is needed
         if self.needs_rebalancing(current_allocation, &target) {
@@ -156,11 +513,7 @@ impl PortfolioRebalancer {
         current: &AssetAllocation,
         target: &AssetAllocation,
     ) -> bool {
-        let diff_stocks = (current.stablecoin - target.stablecoin).abs();
-        let diff_growing_assets = (current.growing_assets - target.growing_assets).abs();
-
-        diff_stocks > self.rebalance_threshold
-            || diff_growing_assets > self.rebalance_threshold
+        current.max_drift(target) > self.rebalance_threshold
     }
 } 
 
@@ -173,167 +526,96 @@ pub enum InvestmentError {
     ModelError(String)
 }
 
-#[derive(Debug, Deserialize)]
-pub struct InvestmentConfig {
-    model_path: String,
-    market_indices: Vec<String>,
-    normalization_factors: HashMap<String, f64>
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct UserProfile {
-    pub age: u8,
-    pub income: f64,
-    pub risk_tolerance: RiskTolerance,
-    pub investment_horizon: u8,
-    pub employment_status: EmploymentStatus,
-    pub dependents: u8,
-    pub financial_goals: Vec<FinancialGoal>,
-    pub market_knowledge: MarketKnowledge,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct MarketConditions {
-    pub btc_volatility: f64,
-    pub stablecoin_liquidity: f64,
-    pub market_trend: MarketTrend,
-    pub economic_indicators: EconomicIndicators,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct InvestmentRecommendation {
-    pub stablecoin_allocation: f64,
-    pub growing_assets_allocation: f64,
-    pub rebalancing_frequency: RebalancingFrequency,
-    pub risk_assessment: RiskAssessment,
-    pub expected_returns: ExpectedReturns,
-    pub confidence_score: f64,
-}
-
-impl InvestmentAI {
-    pub async fn generate_recommendation(
-        &self,
-        user_profile: &UserProfile,
-        market_conditions: &MarketConditions,
-        historical_performance: &[PortfolioPerformance],
-    ) -> Result<InvestmentRecommendation> {
-        // Prepare input features
-        let features = self.prepare_features(user_profile, market_conditions, historical_performance)?;
-        
-        // Run prediction through both models
-        let allocation_prediction = self.allocation_model.forward(&features)?;
-        let risk_prediction = self.risk_model.forward(&features)?;
-        
-        // Process predictions
-        let (stablecoin, growing) = self.process_allocation_prediction(&allocation_prediction)?;
-        let risk_assessment = self.process_risk_prediction(&risk_prediction)?;
-        
-        // Calculate confidence score based on market conditions and prediction certainty
-        let confidence = self.calculate_confidence_score(
-            &allocation_prediction,
-            &risk_prediction,
-            market_conditions,
-        )?;
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        // Generate expected returns
-        let expected_returns = self.calculate_expected_returns(
-            stablecoin,
-            growing,
-            market_conditions,
-            &risk_assessment,
-        )?;
+    fn pct(value: &str) -> Decimal {
+        value.parse().unwrap()
+    }
 
-        Ok(InvestmentRecommendation {
-            stablecoin_allocation: stablecoin,
-            growing_assets_allocation: growing,
-            rebalancing_frequency: self.determine_rebalancing_frequency(
-                &risk_assessment,
-                market_conditions,
-            ),
-            risk_assessment,
-            expected_returns,
-            confidence_score: confidence,
-        })
-    }
-
-    async fn detect_market_anomalies(&self) -> Result<Vec<MarketAnomaly>> {
-        // Implement anomaly detection using isolation forest algorithm
-        let recent_data = self.fetch_recent_market_data().await?;
-        let anomalies = self.anomaly_detector.detect(&recent_data)?;
-        Ok(anomalies)
-    }
-
-    async fn optimize_portfolio(&self, current_allocation: &PortfolioAllocation) -> Result<PortfolioAdjustment> {
-        // Implement portfolio optimization using modern portfolio theory
-        let market_data = self.fetch_market_data().await?;
-        let efficient_frontier = self.calculate_efficient_frontier(&market_data)?;
-        let optimal_point = self.find_optimal_allocation(
-            &efficient_frontier,
-            current_allocation,
-            &self.risk_constraints,
-        )?;
-        
-        Ok(PortfolioAdjustment {
-            target_allocation: optimal_point,
-            suggested_trades: self.generate_trade_suggestions(
-                current_allocation,
-                &optimal_point,
-            )?,
-            expected_improvement: self.calculate_improvement_metrics(
-                current_allocation,
-                &optimal_point,
-            )?,
-        })
-    }
-
-    async fn predict_market_trends(&self) -> Result<MarketPrediction> {
-        // Implement time series forecasting using LSTM
-        let historical_data = self.fetch_historical_data().await?;
-        let preprocessed_data = self.preprocess_time_series(&historical_data)?;
-        let prediction = self.time_series_model.predict(&preprocessed_data)?;
-        
-        Ok(MarketPrediction {
-            trend: self.interpret_trend_prediction(&prediction)?,
-            confidence_intervals: self.calculate_confidence_intervals(&prediction)?,
-            risk_factors: self.identify_risk_factors(&prediction)?,
-        })
+    #[test]
+    fn test_asset_allocation_validation() {
+        let allocation = AssetAllocation::new(BTreeMap::from([
+            (Asset::Usdc, pct("35.5")),
+            (Asset::Btc, pct("40")),
+            (Asset::KesMmf, pct("24.5")),
+            (Asset::Xlm, pct("0")),
+        ]))
+        .unwrap();
+        assert_eq!(allocation.weights.len(), 3);
+        assert_eq!(allocation.defensive_weight(), pct("60"));
+
+        assert_eq!(
+            AssetAllocation::new(BTreeMap::from([
+                (Asset::Usdc, pct("60")),
+                (Asset::Btc, pct("30"))
+            ])),
+            Err(AllocationError::InvalidTotal(pct("90")))
+        );
+        assert_eq!(
+            AssetAllocation::new(BTreeMap::from([
+                (Asset::Usdc, pct("100.005")),
+                (Asset::Btc, pct("-0.005")),
+            ])),
+            Err(AllocationError::InvalidWeight {
+                asset: Asset::Usdc,
+                weight: pct("100.005")
+            })
+        );
     }
-}
 
-// Helper structs for sophisticated market analysis
-#[derive(Debug, Serialize, Deserialize)]
-pub struct MarketAnomaly {
-    pub timestamp: DateTime<Utc>,
-    pub anomaly_type: AnomalyType,
-    pub severity: f64,
-    pub affected_metrics: Vec<String>,
-    pub suggested_actions: Vec<Action>,
-}
+    #[test]
+    fn test_risk_tolerance_plans_are_valid() {
+        for tolerance in [
+            RiskTolerance::Conservative,
+            RiskTolerance::Moderate,
+            RiskTolerance::Aggressive,
+        ] {
+            assert!(AssetAllocation::from_risk_tolerance(&tolerance)
+                .validate()
+                .is_ok());
+        }
+    }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct PortfolioAdjustment {
-    pub target_allocation: PortfolioAllocation,
-    pub suggested_trades: Vec<Trade>,
-    pub expected_improvement: ImprovementMetrics,
-}
+    #[test]
+    fn test_from_fractions_rounds_to_a_full_allocation() {
+        let assets = ["USDC", "BTC", "XLM"].map(String::from);
+        let allocation = AssetAllocation::from_fractions(&assets, &[1.0 / 3.0; 3]).unwrap();
+
+        assert_eq!(allocation.weights.values().sum::<Decimal>(), pct("100"));
+        assert_eq!(allocation.weight(Asset::Usdc), pct("33.34"));
+        assert_eq!(allocation.weight(Asset::Btc), pct("33.33"));
+
+        assert_eq!(
+            AssetAllocation::from_fractions(&["DOGE".to_string()], &[1.0]),
+            Err(AllocationError::UnknownAsset("DOGE".to_string()))
+        );
+    }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct MarketPrediction {
-    pub trend: MarketTrend,
-    pub confidence_intervals: ConfidenceIntervals,
-    pub risk_factors: Vec<RiskFactor>,
-}
+    #[test]
+    fn test_with_growth_weight_keeps_the_mix_within_each_sleeve() {
+        let moderate = AssetAllocation::from_risk_tolerance(&RiskTolerance::Moderate);
+
+        let resized = moderate.with_growth_weight(pct("37.5")).unwrap();
+        assert_eq!(resized.growth_weight(), pct("37.5"));
+        assert_eq!(resized.weight(Asset::Btc), pct("30"));
+        assert_eq!(resized.weight(Asset::Xlm), pct("7.5"));
+        assert_eq!(resized.weight(Asset::Usdc), pct("25"));
+        assert_eq!(resized.weight(Asset::KesMmf), pct("12.5"));
+
+        let all_growth = AssetAllocation::new(BTreeMap::from([(Asset::Btc, pct("100"))])).unwrap();
+        let resized = all_growth.with_growth_weight(pct("60")).unwrap();
+        assert_eq!(resized.weight(Asset::Usdc), pct("40"));
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    
     #[test]
-    fn test_asset_allocation_validation() {
-        let allocation = AssetAllocation {
-            stablecoin: 60.0,
-            growing_assets: 40.0,
-        };
-        assert!(allocation.validate());
+    fn test_max_drift_covers_assets_missing_on_either_side() {
+        let current = AssetAllocation::from_risk_tolerance(&RiskTolerance::Moderate);
+        let target = AssetAllocation::from_risk_tolerance(&RiskTolerance::Aggressive);
+
+        // BTC moves 40 -> 65; KES_MMF drops out entirely (10 -> 0)
+        assert_eq!(current.max_drift(&target), pct("25"));
+        assert_eq!(target.max_drift(&current), pct("25"));
     }
 }
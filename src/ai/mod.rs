@@ -0,0 +1,3 @@
+pub mod glide_path;
+pub mod investment_strategy;
+pub mod portfolio_optimizer;
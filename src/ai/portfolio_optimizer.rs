@@ -0,0 +1,484 @@
+use ndarray::{Array1, Array2, Axis};
+use serde::Serialize;
+
+use crate::ai::investment_strategy::{RiskProfile, RiskTolerance};
+
+// Crypto markets trade every day of the year
+pub const DAILY_PERIODS_PER_YEAR: f64 = 365.0;
+
+// Portfolios traced along the frontier
+pub const FRONTIER_STEPS: usize = 60;
+
+// Risk aversions the frontier is traced over, log-spaced from "return at
+// any cost" to "minimum variance"
+const MIN_RISK_AVERSION_EXP: f64 = -2.0;
+const MAX_RISK_AVERSION_EXP: f64 = 4.0;
+const MAX_ITERATIONS: usize = 20_000;
+const TOLERANCE: f64 = 1e-12;
+
+#[derive(Debug, PartialEq, thiserror::Error)]
+pub enum OptimizerError {
+    #[error("Need at least {needed} price observations per asset, got {got}")]
+    NotEnoughHistory { needed: usize, got: usize },
+    #[error("Expected {expected} assets, got {got}")]
+    ShapeMismatch { expected: usize, got: usize },
+    #[error("Prices must be positive")]
+    NonPositivePrice,
+    #[error("Weight bounds cannot sum to a fully invested portfolio")]
+    InfeasibleBounds,
+}
+
+// Price observations for a set of assets: one row per period, one column
+// per asset, oldest first
+#[derive(Debug, Clone)]
+pub struct MarketHistory {
+    pub assets: Vec<String>,
+    pub prices: Array2<f64>,
+}
+
+// Annualized expected returns and covariance of returns
+#[derive(Debug, Clone)]
+pub struct ReturnModel {
+    pub assets: Vec<String>,
+    pub mean: Array1<f64>,
+    pub covariance: Array2<f64>,
+}
+
+impl ReturnModel {
+    pub fn new(
+        assets: Vec<String>,
+        mean: Array1<f64>,
+        covariance: Array2<f64>,
+    ) -> Result<Self, OptimizerError> {
+        let n = assets.len();
+        if mean.len() != n {
+            return Err(OptimizerError::ShapeMismatch {
+                expected: n,
+                got: mean.len(),
+            });
+        }
+        if covariance.dim() != (n, n) {
+            return Err(OptimizerError::ShapeMismatch {
+                expected: n,
+                got: covariance.nrows(),
+            });
+        }
+
+        Ok(Self {
+            assets,
+            mean,
+            covariance,
+        })
+    }
+
+    pub fn from_history(
+        history: &MarketHistory,
+        periods_per_year: f64,
+    ) -> Result<Self, OptimizerError> {
+        let returns = period_returns(&history.prices)?;
+        if history.assets.len() != returns.ncols() {
+            return Err(OptimizerError::ShapeMismatch {
+                expected: history.assets.len(),
+                got: returns.ncols(),
+            });
+        }
+
+        Self::new(
+            history.assets.clone(),
+            mean_returns(&returns) * periods_per_year,
+            covariance(&returns) * periods_per_year,
+        )
+    }
+
+    pub fn expected_return(&self, weights: &Array1<f64>) -> f64 {
+        self.mean.dot(weights)
+    }
+
+    pub fn volatility(&self, weights: &Array1<f64>) -> f64 {
+        weights.dot(&self.covariance.dot(weights)).max(0.0).sqrt()
+    }
+}
+
+// Per-asset weight limits; the defaults allow anything from 0 to 100%
+#[derive(Debug, Clone)]
+pub struct WeightBounds {
+    pub min: Array1<f64>,
+    pub max: Array1<f64>,
+}
+
+impl WeightBounds {
+    pub fn long_only(n: usize) -> Self {
+        Self {
+            min: Array1::zeros(n),
+            max: Array1::ones(n),
+        }
+    }
+
+    fn check(&self, n: usize) -> Result<(), OptimizerError> {
+        if self.min.len() != n || self.max.len() != n {
+            return Err(OptimizerError::ShapeMismatch {
+                expected: n,
+                got: self.min.len().min(self.max.len()),
+            });
+        }
+        let infeasible = self.min.iter().zip(self.max.iter()).any(|(lo, hi)| lo > hi)
+            || self.min.sum() > 1.0 + 1e-9
+            || self.max.sum() < 1.0 - 1e-9;
+        if infeasible {
+            return Err(OptimizerError::InfeasibleBounds);
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FrontierPoint {
+    pub weights: Array1<f64>,
+    pub expected_return: f64,
+    pub volatility: f64,
+}
+
+// Fully invested portfolios that no other portfolio beats on both return
+// and risk, from least to most volatile
+#[derive(Debug, Clone, Serialize)]
+pub struct EfficientFrontier {
+    pub assets: Vec<String>,
+    pub points: Vec<FrontierPoint>,
+}
+
+impl EfficientFrontier {
+    // The highest returning portfolio within the volatility budget, or the
+    // least volatile one if nothing fits
+    pub fn select(&self, max_volatility: f64) -> &FrontierPoint {
+        self.points
+            .iter()
+            .rev()
+            .find(|p| p.volatility <= max_volatility)
+            .unwrap_or(&self.points[0])
+    }
+}
+
+// Simple returns between consecutive observations
+pub fn period_returns(prices: &Array2<f64>) -> Result<Array2<f64>, OptimizerError> {
+    if prices.nrows() < 3 {
+        return Err(OptimizerError::NotEnoughHistory {
+            needed: 3,
+            got: prices.nrows(),
+        });
+    }
+    if prices.iter().any(|p| !p.is_finite() || *p <= 0.0) {
+        return Err(OptimizerError::NonPositivePrice);
+    }
+
+    let earlier = prices.slice(ndarray::s![..-1, ..]);
+    let later = prices.slice(ndarray::s![1.., ..]);
+    Ok(&later / &earlier - 1.0)
+}
+
+pub fn mean_returns(returns: &Array2<f64>) -> Array1<f64> {
+    returns
+        .mean_axis(Axis(0))
+        .unwrap_or_else(|| Array1::zeros(returns.ncols()))
+}
+
+// Sample covariance (n - 1 denominator) of the columns
+pub fn covariance(returns: &Array2<f64>) -> Array2<f64> {
+    let n = returns.nrows();
+    if n < 2 {
+        return Array2::zeros((returns.ncols(), returns.ncols()));
+    }
+
+    let centered = returns - &mean_returns(returns);
+    centered.t().dot(&centered) / (n - 1) as f64
+}
+
+// Traces the frontier by maximising `return - aversion / 2 * variance` over
+// a range of risk aversions, keeping only the undominated portfolios
+pub fn efficient_frontier(
+    model: &ReturnModel,
+    bounds: &WeightBounds,
+    steps: usize,
+) -> Result<EfficientFrontier, OptimizerError> {
+    let n = model.assets.len();
+    bounds.check(n)?;
+
+    let steps = steps.max(2);
+    let mut points: Vec<FrontierPoint> = (0..steps)
+        .map(|k| {
+            let exponent = MIN_RISK_AVERSION_EXP
+                + (MAX_RISK_AVERSION_EXP - MIN_RISK_AVERSION_EXP) * k as f64 / (steps - 1) as f64;
+            let weights = maximise_utility(model, bounds, 10f64.powf(exponent));
+
+            FrontierPoint {
+                expected_return: model.expected_return(&weights),
+                volatility: model.volatility(&weights),
+                weights,
+            }
+        })
+        .collect();
+
+    points.sort_by(|a, b| a.volatility.total_cmp(&b.volatility));
+
+    let mut frontier: Vec<FrontierPoint> = Vec::with_capacity(points.len());
+    for point in points {
+        match frontier.last() {
+            Some(last) if point.expected_return <= last.expected_return + 1e-9 => {}
+            _ => frontier.push(point),
+        }
+    }
+
+    Ok(EfficientFrontier {
+        assets: model.assets.clone(),
+        points: frontier,
+    })
+}
+
+// Annualized volatility a member's portfolio may carry. Short horizons and
+// members close to retirement get less room.
+pub fn risk_budget(profile: &RiskProfile) -> f64 {
+    let base = match profile.risk_tolerance {
+        RiskTolerance::Conservative => 0.10,
+        RiskTolerance::Moderate => 0.25,
+        RiskTolerance::Aggressive => 0.45,
+    };
+    let horizon_factor = (profile.investment_horizon as f64 / 15.0).clamp(0.5, 1.0);
+    let budget = base * horizon_factor;
+
+    if profile.age >= 55 {
+        budget.min(0.10)
+    } else {
+        budget
+    }
+}
+
+pub fn optimize(
+    model: &ReturnModel,
+    bounds: &WeightBounds,
+    profile: &RiskProfile,
+) -> Result<FrontierPoint, OptimizerError> {
+    let frontier = efficient_frontier(model, bounds, FRONTIER_STEPS)?;
+    Ok(frontier.select(risk_budget(profile)).clone())
+}
+
+// Projected gradient ascent on a concave quadratic. The step is 1/L with
+// L bounded by the trace of the scaled covariance, so it always converges.
+fn maximise_utility(model: &ReturnModel, bounds: &WeightBounds, aversion: f64) -> Array1<f64> {
+    let n = model.assets.len();
+    let lipschitz = aversion * model.covariance.diag().sum();
+    let step = if lipschitz > 0.0 {
+        1.0 / lipschitz
+    } else {
+        1.0
+    };
+
+    let mut weights = project(&Array1::from_elem(n, 1.0 / n as f64), bounds);
+    for _ in 0..MAX_ITERATIONS {
+        let gradient = &model.mean - &(model.covariance.dot(&weights) * aversion);
+        let next = project(&(&weights + &(gradient * step)), bounds);
+
+        let change = (&next - &weights).mapv(f64::abs).sum();
+        weights = next;
+        if change < TOLERANCE {
+            break;
+        }
+    }
+
+    weights
+}
+
+// Euclidean projection onto { w : sum(w) = 1, min <= w <= max }. The
+// projection is clamp(v - tau) for the tau that makes the weights sum to
+// one, found by bisection.
+fn project(v: &Array1<f64>, bounds: &WeightBounds) -> Array1<f64> {
+    let clamped = |tau: f64| -> Array1<f64> {
+        ndarray::Zip::from(v)
+            .and(&bounds.min)
+            .and(&bounds.max)
+            .map_collect(|x, lo, hi| (x - tau).clamp(*lo, *hi))
+    };
+
+    let mut low = v
+        .iter()
+        .zip(bounds.max.iter())
+        .map(|(x, hi)| x - hi)
+        .fold(f64::INFINITY, f64::min);
+    let mut high = v
+        .iter()
+        .zip(bounds.min.iter())
+        .map(|(x, lo)| x - lo)
+        .fold(f64::NEG_INFINITY, f64::max);
+
+    for _ in 0..100 {
+        let mid = (low + high) / 2.0;
+        if clamped(mid).sum() > 1.0 {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+
+    clamped((low + high) / 2.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::array;
+    use crate::ai::investment_strategy::AllocationStrategy;
+
+    fn assets(names: &[&str]) -> Vec<String> {
+        names.iter().map(|s| s.to_string()).collect()
+    }
+
+    fn profile(risk_tolerance: RiskTolerance) -> RiskProfile {
+        RiskProfile {
+            age: 30,
+            income: 50_000.0,
+            risk_tolerance,
+            investment_horizon: 30,
+            strategy: AllocationStrategy::RiskBased,
+        }
+    }
+
+    // Stablecoin, a volatile coin and something in between, with mildly
+    // correlated returns
+    fn fixture_model() -> ReturnModel {
+        ReturnModel::new(
+            assets(&["USDC", "BTC", "XLM"]),
+            array![0.05, 0.60, 0.30],
+            array![
+                [0.0001, 0.0000, 0.0000],
+                [0.0000, 0.3600, 0.1200],
+                [0.0000, 0.1200, 0.1600],
+            ],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_return_statistics() {
+        let prices = array![[1.0, 100.0], [1.0, 110.0], [1.0, 99.0], [1.0, 108.9]];
+        let returns = period_returns(&prices).unwrap();
+
+        let expected = [0.10, -0.10, 0.10];
+        for (got, want) in returns.column(1).iter().zip(expected) {
+            assert!((got - want).abs() < 1e-12);
+        }
+
+        let mean = mean_returns(&returns);
+        assert!((mean[1] - 0.1 / 3.0).abs() < 1e-12);
+
+        // Sample variance of (0.1, -0.1, 0.1) is 0.04 / 3
+        let cov = covariance(&returns);
+        assert!((cov[[1, 1]] - 0.04 / 3.0).abs() < 1e-12);
+        assert_eq!(cov[[0, 0]], 0.0);
+        assert_eq!(cov[[0, 1]], 0.0);
+    }
+
+    #[test]
+    fn test_history_validation() {
+        assert_eq!(
+            period_returns(&array![[1.0], [2.0]]),
+            Err(OptimizerError::NotEnoughHistory { needed: 3, got: 2 })
+        );
+        assert_eq!(
+            period_returns(&array![[1.0], [0.0], [2.0]]),
+            Err(OptimizerError::NonPositivePrice)
+        );
+    }
+
+    #[test]
+    fn test_projection_respects_bounds() {
+        let bounds = WeightBounds {
+            min: array![0.1, 0.0, 0.0],
+            max: array![1.0, 0.5, 0.3],
+        };
+
+        let w = project(&array![-2.0, 3.0, 1.0], &bounds);
+
+        assert!((w.sum() - 1.0).abs() < 1e-9);
+        assert!((w[1] - 0.5).abs() < 1e-9);
+        assert!((w[2] - 0.3).abs() < 1e-9);
+        assert!((w[0] - 0.2).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_minimum_variance_matches_closed_form() {
+        // Uncorrelated assets: the least volatile mix weights each asset by
+        // the inverse of its variance, 0.01 / (0.04 + 0.01) = 0.2
+        let model = ReturnModel::new(
+            assets(&["A", "B"]),
+            array![0.10, 0.05],
+            array![[0.04, 0.0], [0.0, 0.01]],
+        )
+        .unwrap();
+
+        let frontier = efficient_frontier(&model, &WeightBounds::long_only(2), 40).unwrap();
+        let least_volatile = &frontier.points[0];
+
+        assert!((least_volatile.weights[0] - 0.2).abs() < 1e-3);
+        assert!((least_volatile.volatility - 0.008f64.sqrt()).abs() < 1e-4);
+
+        // And the riskiest end is all in the higher returning asset
+        let riskiest = frontier.points.last().unwrap();
+        assert!((riskiest.weights[0] - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_frontier_is_efficient() {
+        let frontier =
+            efficient_frontier(&fixture_model(), &WeightBounds::long_only(3), 60).unwrap();
+
+        assert!(frontier.points.len() > 5);
+        for pair in frontier.points.windows(2) {
+            assert!(pair[1].volatility >= pair[0].volatility);
+            assert!(pair[1].expected_return > pair[0].expected_return);
+        }
+        for point in &frontier.points {
+            assert!((point.weights.sum() - 1.0).abs() < 1e-9);
+            assert!(point.weights.iter().all(|w| *w >= -1e-12));
+        }
+    }
+
+    #[test]
+    fn test_allocation_follows_risk_tolerance() {
+        let model = fixture_model();
+        let bounds = WeightBounds::long_only(3);
+
+        let conservative =
+            optimize(&model, &bounds, &profile(RiskTolerance::Conservative)).unwrap();
+        let moderate = optimize(&model, &bounds, &profile(RiskTolerance::Moderate)).unwrap();
+        let aggressive = optimize(&model, &bounds, &profile(RiskTolerance::Aggressive)).unwrap();
+
+        assert!(conservative.volatility <= 0.10 + 1e-9);
+        assert!(moderate.volatility <= 0.25 + 1e-9);
+        assert!(conservative.expected_return < moderate.expected_return);
+        assert!(moderate.expected_return < aggressive.expected_return);
+        assert!(conservative.weights[0] > aggressive.weights[0]);
+    }
+
+    #[test]
+    fn test_risk_budget_shrinks_near_retirement() {
+        let mut member = profile(RiskTolerance::Aggressive);
+        assert_eq!(risk_budget(&member), 0.45);
+
+        member.investment_horizon = 5;
+        assert!((risk_budget(&member) - 0.225).abs() < 1e-12);
+
+        member.age = 58;
+        assert_eq!(risk_budget(&member), 0.10);
+    }
+
+    #[test]
+    fn test_infeasible_bounds() {
+        let bounds = WeightBounds {
+            min: array![0.0, 0.0, 0.0],
+            max: array![0.3, 0.3, 0.3],
+        };
+
+        assert_eq!(
+            efficient_frontier(&fixture_model(), &bounds, 10).unwrap_err(),
+            OptimizerError::InfeasibleBounds
+        );
+    }
+}
@@ -1 +1,191 @@
- 
\ No newline at end of file
+use axum::{extract::State, http::StatusCode, Json};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    auth::AuthUser,
+    error::Error,
+    services::{auth_service::TokenPair, user_service::UserService, AuthService, PhoneAuthService},
+};
+
+#[derive(Deserialize)]
+pub struct RegisterRequest {
+    username: String,
+    email: String,
+    wallet_address: String,
+    password: String,
+}
+
+// Smartphone members sign in with email and password, feature-phone
+// members with their M-Pesa number and PIN.
+#[derive(Deserialize)]
+#[serde(untagged)]
+pub enum LoginRequest {
+    Email { email: String, password: String },
+    Phone { phone_number: String, pin: String },
+}
+
+#[derive(Serialize)]
+pub struct LoginResponse {
+    token: String,
+    refresh_token: String,
+    expires_in: i64, // Seconds until `token` expires
+    user_id: Uuid,
+}
+
+impl From<TokenPair> for LoginResponse {
+    fn from(tokens: TokenPair) -> Self {
+        Self {
+            token: tokens.access_token,
+            refresh_token: tokens.refresh_token,
+            expires_in: tokens.expires_in,
+            user_id: tokens.user_id,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct RefreshRequest {
+    refresh_token: String,
+}
+
+#[derive(Deserialize)]
+pub struct OtpRequest {
+    phone_number: String,
+}
+
+#[derive(Deserialize)]
+pub struct PhoneRegisterRequest {
+    phone_number: String,
+    otp: String,
+    pin: String,
+    username: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct PinResetRequest {
+    phone_number: String,
+    otp: String,
+    new_pin: String,
+}
+
+pub async fn register(
+    State(auth_service): State<AuthService>,
+    State(user_service): State<UserService>,
+    Json(payload): Json<RegisterRequest>,
+) -> Result<Json<LoginResponse>, Error> {
+    let user_id = user_service
+        .create_user(
+            payload.username,
+            payload.email,
+            payload.wallet_address,
+            payload.password,
+        )
+        .await?;
+
+    let tokens = auth_service.start_session(user_id).await?;
+
+    Ok(Json(tokens.into()))
+}
+
+pub async fn login(
+    State(auth_service): State<AuthService>,
+    State((user_service, phone_auth_service)): State<(UserService, PhoneAuthService)>,
+    Json(payload): Json<LoginRequest>,
+) -> Result<Json<LoginResponse>, Error> {
+    let user_id = match payload {
+        LoginRequest::Email { email, password } => {
+            user_service
+                .verify_password(&email, &password)
+                .await?
+                .ok_or(Error::Unauthorized)?
+                .id
+        }
+        LoginRequest::Phone { phone_number, pin } => {
+            phone_auth_service.login(&phone_number, &pin).await?
+        }
+    };
+
+    let tokens = auth_service.start_session(user_id).await?;
+
+    Ok(Json(tokens.into()))
+}
+
+// Step one of phone registration: text a code to the number
+pub async fn request_registration_otp(
+    State(phone_auth_service): State<PhoneAuthService>,
+    Json(payload): Json<OtpRequest>,
+) -> Result<StatusCode, Error> {
+    phone_auth_service
+        .request_registration_otp(&payload.phone_number)
+        .await?;
+
+    Ok(StatusCode::ACCEPTED)
+}
+
+pub async fn register_phone(
+    State(auth_service): State<AuthService>,
+    State(phone_auth_service): State<PhoneAuthService>,
+    Json(payload): Json<PhoneRegisterRequest>,
+) -> Result<Json<LoginResponse>, Error> {
+    let user_id = phone_auth_service
+        .register(
+            &payload.phone_number,
+            &payload.otp,
+            &payload.pin,
+            payload.username,
+        )
+        .await?;
+
+    let tokens = auth_service.start_session(user_id).await?;
+
+    Ok(Json(tokens.into()))
+}
+
+pub async fn request_pin_reset_otp(
+    State(phone_auth_service): State<PhoneAuthService>,
+    Json(payload): Json<OtpRequest>,
+) -> Result<StatusCode, Error> {
+    phone_auth_service
+        .request_pin_reset_otp(&payload.phone_number)
+        .await?;
+
+    Ok(StatusCode::ACCEPTED)
+}
+
+pub async fn reset_pin(
+    State(phone_auth_service): State<PhoneAuthService>,
+    Json(payload): Json<PinResetRequest>,
+) -> Result<StatusCode, Error> {
+    phone_auth_service
+        .reset_pin(&payload.phone_number, &payload.otp, &payload.new_pin)
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// Rotates the refresh token; the one presented is spent
+pub async fn refresh(
+    State(auth_service): State<AuthService>,
+    Json(payload): Json<RefreshRequest>,
+) -> Result<Json<LoginResponse>, Error> {
+    let tokens = auth_service.refresh(&payload.refresh_token).await?;
+    Ok(Json(tokens.into()))
+}
+
+pub async fn logout(
+    State(auth_service): State<AuthService>,
+    Json(payload): Json<RefreshRequest>,
+) -> Result<StatusCode, Error> {
+    auth_service.logout(&payload.refresh_token).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// Signs the member out on every device
+pub async fn logout_all(
+    auth_user: AuthUser,
+    State(auth_service): State<AuthService>,
+) -> Result<StatusCode, Error> {
+    auth_service.logout_all(auth_user.user_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,116 @@
+use axum::{extract::State, Json};
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::{
+    auth::AuthUser,
+    error::Error,
+    services::bpt_manager::{
+        BPTManager, BptBalance, BptTransfer, CollateralPledge, Stake, Tokenization,
+    },
+};
+
+#[derive(Deserialize)]
+pub struct AmountRequest {
+    amount: Decimal,
+}
+
+#[derive(Deserialize)]
+pub struct StakeRequest {
+    amount: Decimal,
+    lockup_days: u32,
+}
+
+#[derive(Deserialize)]
+pub struct UnstakeRequest {
+    stake_id: Uuid,
+}
+
+#[derive(Deserialize)]
+pub struct TransferRequest {
+    to_address: String,
+    amount: Decimal,
+}
+
+pub async fn get_balance(
+    auth_user: AuthUser,
+    State(bpt_manager): State<BPTManager>,
+) -> Result<Json<BptBalance>, Error> {
+    let balance = bpt_manager.get_user_bpt_balance(auth_user.user_id).await?;
+    Ok(Json(balance))
+}
+
+pub async fn tokenize_savings(
+    auth_user: AuthUser,
+    State(bpt_manager): State<BPTManager>,
+    Json(payload): Json<AmountRequest>,
+) -> Result<Json<Tokenization>, Error> {
+    let tokenization = bpt_manager
+        .tokenize_savings(auth_user.user_id, payload.amount)
+        .await?;
+    Ok(Json(tokenization))
+}
+
+pub async fn redeem_tokens(
+    auth_user: AuthUser,
+    State(bpt_manager): State<BPTManager>,
+    Json(payload): Json<AmountRequest>,
+) -> Result<Json<Tokenization>, Error> {
+    let redemption = bpt_manager
+        .redeem_tokens(auth_user.user_id, payload.amount)
+        .await?;
+    Ok(Json(redemption))
+}
+
+pub async fn stake_tokens(
+    auth_user: AuthUser,
+    State(bpt_manager): State<BPTManager>,
+    Json(payload): Json<StakeRequest>,
+) -> Result<Json<Stake>, Error> {
+    let stake = bpt_manager
+        .stake_tokens(auth_user.user_id, payload.amount, payload.lockup_days)
+        .await?;
+    Ok(Json(stake))
+}
+
+pub async fn unstake_tokens(
+    auth_user: AuthUser,
+    State(bpt_manager): State<BPTManager>,
+    Json(payload): Json<UnstakeRequest>,
+) -> Result<Json<Stake>, Error> {
+    let stake = bpt_manager
+        .unstake_tokens(auth_user.user_id, payload.stake_id)
+        .await?;
+    Ok(Json(stake))
+}
+
+pub async fn get_stakes(
+    auth_user: AuthUser,
+    State(bpt_manager): State<BPTManager>,
+) -> Result<Json<Vec<Stake>>, Error> {
+    let stakes = bpt_manager.get_user_stakes(auth_user.user_id).await?;
+    Ok(Json(stakes))
+}
+
+pub async fn transfer_tokens(
+    auth_user: AuthUser,
+    State(bpt_manager): State<BPTManager>,
+    Json(payload): Json<TransferRequest>,
+) -> Result<Json<BptTransfer>, Error> {
+    let transfer = bpt_manager
+        .transfer_tokens(auth_user.user_id, &payload.to_address, payload.amount)
+        .await?;
+    Ok(Json(transfer))
+}
+
+pub async fn create_collateral(
+    auth_user: AuthUser,
+    State(bpt_manager): State<BPTManager>,
+    Json(payload): Json<AmountRequest>,
+) -> Result<Json<CollateralPledge>, Error> {
+    let pledge = bpt_manager
+        .pledge_collateral(auth_user.user_id, payload.amount)
+        .await?;
+    Ok(Json(pledge))
+}
@@ -0,0 +1,71 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use uuid::Uuid;
+
+use crate::{
+    auth::AuthUser,
+    error::Error,
+    services::contribution_service::{ContributionSchedule, ContributionService, NewSchedule},
+};
+
+pub async fn create_schedule(
+    auth_user: AuthUser,
+    State(contribution_service): State<ContributionService>,
+    Json(payload): Json<NewSchedule>,
+) -> Result<(StatusCode, Json<ContributionSchedule>), Error> {
+    let schedule = contribution_service
+        .create_schedule(auth_user.user_id, payload)
+        .await?;
+
+    Ok((StatusCode::CREATED, Json(schedule)))
+}
+
+pub async fn get_schedules(
+    auth_user: AuthUser,
+    State(contribution_service): State<ContributionService>,
+) -> Result<Json<Vec<ContributionSchedule>>, Error> {
+    let schedules = contribution_service
+        .get_schedules(auth_user.user_id)
+        .await?;
+
+    Ok(Json(schedules))
+}
+
+pub async fn pause_schedule(
+    auth_user: AuthUser,
+    State(contribution_service): State<ContributionService>,
+    Path(schedule_id): Path<Uuid>,
+) -> Result<StatusCode, Error> {
+    contribution_service
+        .pause_schedule(auth_user.user_id, schedule_id)
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn resume_schedule(
+    auth_user: AuthUser,
+    State(contribution_service): State<ContributionService>,
+    Path(schedule_id): Path<Uuid>,
+) -> Result<StatusCode, Error> {
+    contribution_service
+        .resume_schedule(auth_user.user_id, schedule_id)
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn cancel_schedule(
+    auth_user: AuthUser,
+    State(contribution_service): State<ContributionService>,
+    Path(schedule_id): Path<Uuid>,
+) -> Result<StatusCode, Error> {
+    contribution_service
+        .cancel_schedule(auth_user.user_id, schedule_id)
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -1,11 +1,16 @@
 use axum::{extract::State, Json};
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 use crate::{
-    auth::AuthUser,
+    auth::{AuthUser, MPesaCallback},
     error::Error,
-    services::{fund_service::FundService, mpesa_service::MPesaService},
+    services::{
+        fund_service::FundService,
+        loan_service::LoanService,
+        mpesa_service::{MPesaService, STKCallbackEnvelope},
+    },
 };
 
 #[derive(Deserialize)]
@@ -27,18 +32,30 @@ pub async fn initiate_deposit(
     State((fund_service, mpesa_service)): State<(FundService, MPesaService)>,
     Json(payload): Json<DepositRequest>,
 ) -> Result<Json<DepositResponse>, Error> {
-    // Validate amount
-    if payload.amount <= 0.0 {
+    // Validate amount; M-Pesa only moves whole shillings
+    let amount = Decimal::try_from(payload.amount).map_err(|_| Error::InvalidAmount)?;
+    if amount <= Decimal::ZERO || !amount.fract().is_zero() {
         return Err(Error::InvalidAmount);
     }
 
+    // Check the member has a fund to credit and is within their deposit
+    // limits before prompting them to pay
+    fund_service.require_fund(auth_user.user_id).await?;
+    fund_service
+        .validate_deposit(auth_user.user_id, amount)
+        .await?;
+
     // Generate account reference
     let account_ref = format!("PEN{}", auth_user.user_id);
 
     // Initiate M-Pesa payment
     let stk_response = mpesa_service
         .initiate_payment(&payload.phone_number, payload.amount, &account_ref)
-        .await?;
+        .await
+        .map_err(|e| match e.downcast::<Error>() {
+            Ok(err) => err,
+            Err(e) => Error::MPesa(e.to_string()),
+        })?;
 
     // Record pending deposit
     fund_service
@@ -46,6 +63,7 @@ pub async fn initiate_deposit(
             auth_user.user_id,
             payload.amount,
             &stk_response.checkout_request_id,
+            None,
         )
         .await?;
 
@@ -58,34 +76,62 @@ pub async fn initiate_deposit(
 }
 
 // M-Pesa callback handler
-#[derive(Deserialize)]
-pub struct MPesaCallback {
-    checkout_request_id: String,
+#[derive(Serialize)]
+pub struct CallbackAck {
+    #[serde(rename = "ResultCode")]
     result_code: i32,
+    #[serde(rename = "ResultDesc")]
     result_desc: String,
-    amount: f64,
-    mpesa_receipt_number: Option<String>,
 }
 
 pub async fn mpesa_callback(
-    State(fund_service): State<FundService>,
-    Json(callback): Json<MPesaCallback>,
-) -> Result<(), Error> {
-    if callback.result_code == 0 {
+    _callback: MPesaCallback,
+    State((fund_service, loan_service)): State<(FundService, LoanService)>,
+    Json(envelope): Json<STKCallbackEnvelope>,
+) -> Result<Json<CallbackAck>, Error> {
+    let callback = envelope.body.stk_callback;
+
+    // Loan repayments are collected through the same STK callback URL
+    if loan_service
+        .is_repayment(&callback.checkout_request_id)
+        .await?
+    {
+        match callback.payment_details()? {
+            Some(payment) => {
+                loan_service
+                    .complete_repayment(&callback.checkout_request_id, &payment)
+                    .await?
+            }
+            None => {
+                loan_service
+                    .fail_repayment(&callback.checkout_request_id, &callback.result_desc)
+                    .await?
+            }
+        }
+
+        return Ok(Json(CallbackAck {
+            result_code: 0,
+            result_desc: "Accepted".to_string(),
+        }));
+    }
+
+    match callback.payment_details()? {
         // Payment successful
-        fund_service
-            .complete_deposit(
-                &callback.checkout_request_id,
-                callback.amount,
-                callback.mpesa_receipt_number.unwrap_or_default(),
-            )
-            .await?;
-    } else {
-        // Payment failed
-        fund_service
-            .fail_deposit(&callback.checkout_request_id, &callback.result_desc)
-            .await?;
+        Some(payment) => {
+            fund_service
+                .complete_deposit(&callback.checkout_request_id, &payment)
+                .await?;
+        }
+        // Payment failed or was cancelled by the member
+        None => {
+            fund_service
+                .fail_deposit(&callback.checkout_request_id, &callback.result_desc)
+                .await?;
+        }
     }
 
-    Ok(())
-} 
\ No newline at end of file
+    Ok(Json(CallbackAck {
+        result_code: 0,
+        result_desc: "Accepted".to_string(),
+    }))
+}
@@ -0,0 +1,181 @@
+use axum::{
+    body::Bytes,
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    Json,
+};
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::{
+    auth::{AdminUser, AuthUser},
+    error::Error,
+    services::employer_service::{
+        Employer, EmployerService, NewEmployer, PayrollBatchReport, PayrollFormat, RosterMember,
+    },
+};
+
+#[derive(Deserialize)]
+pub struct AddMemberRequest {
+    staff_number: String,
+    phone_number: String,
+}
+
+#[derive(Deserialize)]
+pub struct PayrollUploadQuery {
+    reference: String,
+}
+
+#[derive(Deserialize)]
+pub struct RemittanceRequest {
+    amount: Decimal,
+    reference: String,
+}
+
+pub async fn create_employer(
+    auth_user: AuthUser,
+    State(employer_service): State<EmployerService>,
+    Json(payload): Json<NewEmployer>,
+) -> Result<(StatusCode, Json<Employer>), Error> {
+    let employer = employer_service
+        .create_employer(auth_user.user_id, payload)
+        .await?;
+
+    Ok((StatusCode::CREATED, Json(employer)))
+}
+
+pub async fn get_employer(
+    auth_user: AuthUser,
+    State(employer_service): State<EmployerService>,
+    Path(employer_id): Path<Uuid>,
+) -> Result<Json<Employer>, Error> {
+    let employer = employer_service
+        .get_employer(employer_id, auth_user.user_id)
+        .await?;
+
+    Ok(Json(employer))
+}
+
+pub async fn get_members(
+    auth_user: AuthUser,
+    State(employer_service): State<EmployerService>,
+    Path(employer_id): Path<Uuid>,
+) -> Result<Json<Vec<RosterMember>>, Error> {
+    let members = employer_service
+        .get_members(employer_id, auth_user.user_id)
+        .await?;
+
+    Ok(Json(members))
+}
+
+pub async fn add_member(
+    auth_user: AuthUser,
+    State(employer_service): State<EmployerService>,
+    Path(employer_id): Path<Uuid>,
+    Json(payload): Json<AddMemberRequest>,
+) -> Result<Json<RosterMember>, Error> {
+    let member = employer_service
+        .add_member(
+            employer_id,
+            auth_user.user_id,
+            &payload.staff_number,
+            &payload.phone_number,
+        )
+        .await?;
+
+    Ok(Json(member))
+}
+
+pub async fn remove_member(
+    auth_user: AuthUser,
+    State(employer_service): State<EmployerService>,
+    Path((employer_id, staff_number)): Path<(Uuid, String)>,
+) -> Result<StatusCode, Error> {
+    employer_service
+        .remove_member(employer_id, auth_user.user_id, &staff_number)
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// The payroll is the request body, as text/csv or a JSON array of lines
+pub async fn upload_payroll(
+    auth_user: AuthUser,
+    State(employer_service): State<EmployerService>,
+    Path(employer_id): Path<Uuid>,
+    Query(query): Query<PayrollUploadQuery>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<(StatusCode, Json<PayrollBatchReport>), Error> {
+    let content_type = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default();
+
+    let format = if content_type.starts_with("text/csv") {
+        PayrollFormat::Csv
+    } else if content_type.starts_with("application/json") {
+        PayrollFormat::Json
+    } else {
+        return Err(Error::InvalidPayroll(
+            "Upload the payroll as text/csv or application/json".to_string(),
+        ));
+    };
+
+    let report = employer_service
+        .import_payroll(
+            employer_id,
+            auth_user.user_id,
+            &query.reference,
+            format,
+            &body,
+        )
+        .await?;
+
+    Ok((StatusCode::CREATED, Json(report)))
+}
+
+pub async fn get_payroll_batch(
+    auth_user: AuthUser,
+    State(employer_service): State<EmployerService>,
+    Path((employer_id, batch_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<PayrollBatchReport>, Error> {
+    let report = employer_service
+        .get_batch_report(employer_id, auth_user.user_id, batch_id)
+        .await?;
+
+    Ok(Json(report))
+}
+
+pub async fn approve_employer(
+    admin: AdminUser,
+    State(employer_service): State<EmployerService>,
+    Path(employer_id): Path<Uuid>,
+) -> Result<Json<Employer>, Error> {
+    let employer = employer_service
+        .approve_employer(employer_id, admin.user_id)
+        .await?;
+
+    Ok(Json(employer))
+}
+
+// Records the employer's remittance for a batch and credits its contributions
+pub async fn settle_payroll_batch(
+    admin: AdminUser,
+    State(employer_service): State<EmployerService>,
+    Path((employer_id, batch_id)): Path<(Uuid, Uuid)>,
+    Json(payload): Json<RemittanceRequest>,
+) -> Result<Json<PayrollBatchReport>, Error> {
+    let report = employer_service
+        .settle_payroll(
+            employer_id,
+            batch_id,
+            admin.user_id,
+            payload.amount,
+            &payload.reference,
+        )
+        .await?;
+
+    Ok(Json(report))
+}
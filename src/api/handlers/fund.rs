@@ -1,22 +1,26 @@
-use axum::{extract::Path, Json};
+use axum::{
+    extract::{Path, State},
+    Json,
+};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 use rust_decimal::Decimal;
 
+use crate::{
+    auth::AuthUser,
+    error::Error,
+    services::fund_service::{FundService, PensionFund},
+};
+
+// Funds open empty; money only arrives through an M-Pesa deposit
 #[derive(Deserialize)]
 pub struct CreateFundRequest {
-    user_id: Uuid,
     investment_plan: InvestmentPlan,
-    initial_deposit: Decimal,
-}
-
-#[derive(Deserialize)]
-pub struct TransactionRequest {
-    amount: Decimal,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, sqlx::Type)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[sqlx(type_name = "VARCHAR", rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum InvestmentPlan {
     Conservative,
     Moderate,
@@ -32,54 +36,41 @@ pub struct FundResponse {
     created_at: chrono::DateTime<chrono::Utc>,
 }
 
+impl From<PensionFund> for FundResponse {
+    fn from(fund: PensionFund) -> Self {
+        Self {
+            id: fund.id,
+            user_id: fund.user_id,
+            investment_plan: fund.investment_plan,
+            balance: fund.balance,
+            created_at: fund.created_at,
+        }
+    }
+}
+
 pub async fn create_fund(
+    auth_user: AuthUser,
+    State(fund_service): State<FundService>,
     Json(payload): Json<CreateFundRequest>,
-) -> Json<FundResponse> {
-    // TODO: Implement fund creation logic with service layer
-    Json(FundResponse {
-        id: Uuid::new_v4(),
-        user_id: payload.user_id,
-        investment_plan: payload.investment_plan,
-        balance: payload.initial_deposit,
-        created_at: chrono::Utc::now(),
-    })
-}
+) -> Result<Json<FundResponse>, Error> {
+    let fund_id = fund_service
+        .create_fund(auth_user.user_id, payload.investment_plan, Decimal::ZERO)
+        .await?;
 
-pub async fn get_fund(Path(fund_id): Path<Uuid>) -> Json<FundResponse> {
-    // TODO: Implement fund retrieval logic
-    Json(FundResponse {
-        id: fund_id,
-        user_id: Uuid::new_v4(),
-        investment_plan: InvestmentPlan::Moderate,
-        balance: Decimal::new(1000, 2), // 10.00
-        created_at: chrono::Utc::now(),
-    })
+    get_fund(auth_user, State(fund_service), Path(fund_id)).await
 }
 
-pub async fn deposit(
+// Another member's fund is reported as missing rather than forbidden
+pub async fn get_fund(
+    auth_user: AuthUser,
+    State(fund_service): State<FundService>,
     Path(fund_id): Path<Uuid>,
-    Json(payload): Json<TransactionRequest>,
-) -> Json<FundResponse> {
-    // TODO: Implement deposit logic
-    Json(FundResponse {
-        id: fund_id,
-        user_id: Uuid::new_v4(),
-        investment_plan: InvestmentPlan::Moderate,
-        balance: payload.amount,
-        created_at: chrono::Utc::now(),
-    })
-}
+) -> Result<Json<FundResponse>, Error> {
+    let fund = fund_service
+        .get_fund(fund_id)
+        .await?
+        .filter(|fund| fund.user_id == auth_user.user_id)
+        .ok_or_else(|| Error::NotFound("Pension fund".to_string()))?;
 
-pub async fn withdraw(
-    Path(fund_id): Path<Uuid>,
-    Json(payload): Json<TransactionRequest>,
-) -> Json<FundResponse> {
-    // TODO: Implement withdrawal logic
-    Json(FundResponse {
-        id: fund_id,
-        user_id: Uuid::new_v4(),
-        investment_plan: InvestmentPlan::Moderate,
-        balance: payload.amount,
-        created_at: chrono::Utc::now(),
-    })
-} 
\ No newline at end of file
+    Ok(Json(fund.into()))
+}
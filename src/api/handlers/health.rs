@@ -0,0 +1,23 @@
+use axum::{extract::State, Json};
+use serde::Serialize;
+use sqlx::PgPool;
+
+#[derive(Serialize)]
+pub struct HealthResponse {
+    status: &'static str,
+    database: &'static str,
+    version: &'static str,
+}
+
+pub async fn health_check(State(pool): State<PgPool>) -> Json<HealthResponse> {
+    let database = match sqlx::query("SELECT 1").execute(&pool).await {
+        Ok(_) => "healthy",
+        Err(_) => "unhealthy",
+    };
+
+    Json(HealthResponse {
+        status: "ok",
+        database,
+        version: env!("CARGO_PKG_VERSION"),
+    })
+}
@@ -1,15 +1,28 @@
 use axum::{
     extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
     Json,
 };
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Arc;
 use uuid::Uuid;
 
 use crate::{
-    ai::investment_strategy::{AssetAllocation, RiskProfile, RiskTolerance},
+    ai::{
+        glide_path::GlideStep,
+        investment_strategy::{
+            AllocationStrategy, Asset, AssetAllocation, RiskProfile, RiskTolerance,
+        },
+    },
     auth::AuthUser,
     error::Error,
-    services::investment_service::InvestmentService,
+    services::{
+        investment_service::InvestmentService,
+        rebalancing::{Recommendation, RebalancingService},
+    },
 };
 
 #[derive(Deserialize)]
@@ -18,6 +31,8 @@ pub struct UpdateRiskProfileRequest {
     income: f64,
     risk_tolerance: RiskTolerance,
     investment_horizon: u8,
+    #[serde(default)]
+    strategy: AllocationStrategy,
 }
 
 #[derive(Serialize)]
@@ -27,19 +42,37 @@ pub struct RiskProfileResponse {
     income: f64,
     risk_tolerance: RiskTolerance,
     investment_horizon: u8,
+    strategy: AllocationStrategy,
     created_at: chrono::DateTime<chrono::Utc>,
 }
 
+#[derive(Deserialize)]
+pub struct UpdateAllocationRequest {
+    weights: BTreeMap<Asset, Decimal>, // Percentage per asset, adding up to 100
+}
+
 #[derive(Serialize)]
 pub struct AllocationResponse {
-    stablecoin: f64,      // Percentage in stablecoins
-    growing_assets: f64,   // Percentage in growing assets
+    weights: BTreeMap<Asset, Decimal>, // Percentage per asset
+    defensive: Decimal,                // Share in USDC, T-bills and money-market
+    growth: Decimal,                   // Share in BTC and XLM
     last_updated: chrono::DateTime<chrono::Utc>,
 }
 
+impl AllocationResponse {
+    fn new(allocation: AssetAllocation, last_updated: chrono::DateTime<chrono::Utc>) -> Self {
+        Self {
+            defensive: allocation.defensive_weight(),
+            growth: allocation.growth_weight(),
+            weights: allocation.weights,
+            last_updated,
+        }
+    }
+}
+
 pub async fn update_risk_profile(
     auth_user: AuthUser,
-    State(investment_service): State<InvestmentService>,
+    State(investment_service): State<Arc<InvestmentService>>,
     Json(payload): Json<UpdateRiskProfileRequest>,
 ) -> Result<Json<RiskProfileResponse>, Error> {
     let profile = RiskProfile {
@@ -47,73 +80,185 @@ pub async fn update_risk_profile(
         income: payload.income,
         risk_tolerance: payload.risk_tolerance,
         investment_horizon: payload.investment_horizon,
+        strategy: payload.strategy,
     };
 
-    let response = investment_service
-        .update_risk_profile(auth_user.user_id, profile)
+    let updated_at = investment_service
+        .update_risk_profile(auth_user.user_id, &profile)
         .await?;
 
-    Ok(Json(response))
+    Ok(Json(RiskProfileResponse {
+        user_id: auth_user.user_id,
+        age: profile.age,
+        income: profile.income,
+        risk_tolerance: profile.risk_tolerance,
+        investment_horizon: profile.investment_horizon,
+        strategy: profile.strategy,
+        created_at: updated_at,
+    }))
 }
 
 pub async fn get_current_allocation(
     auth_user: AuthUser,
-    State(investment_service): State<InvestmentService>,
+    State(investment_service): State<Arc<InvestmentService>>,
 ) -> Result<Json<AllocationResponse>, Error> {
     let allocation = investment_service
         .get_current_allocation(auth_user.user_id)
         .await?;
 
-    Ok(Json(AllocationResponse {
-        stablecoin: allocation.stablecoin,
-        growing_assets: allocation.growing_assets,
-        last_updated: chrono::Utc::now(),
-    }))
+    Ok(Json(AllocationResponse::new(
+        allocation,
+        chrono::Utc::now(),
+    )))
+}
+
+pub async fn update_allocation(
+    auth_user: AuthUser,
+    State(investment_service): State<Arc<InvestmentService>>,
+    Json(payload): Json<UpdateAllocationRequest>,
+) -> Result<Json<AllocationResponse>, Error> {
+    let (allocation, created_at) = investment_service
+        .set_allocation(auth_user.user_id, payload.weights)
+        .await?;
+
+    Ok(Json(AllocationResponse::new(allocation, created_at)))
 }
 
 pub async fn get_recommendation(
     auth_user: AuthUser,
-    State(investment_service): State<InvestmentService>,
+    State(investment_service): State<Arc<InvestmentService>>,
 ) -> Result<Json<AllocationResponse>, Error> {
     let recommendation = investment_service
         .get_investment_recommendation(auth_user.user_id)
         .await?;
 
-    Ok(Json(AllocationResponse {
-        stablecoin: recommendation.stablecoin,
-        growing_assets: recommendation.growing_assets,
-        last_updated: chrono::Utc::now(),
-    }))
+    Ok(Json(AllocationResponse::new(
+        recommendation,
+        chrono::Utc::now(),
+    )))
+}
+
+// Upcoming yearly shifts for a target-date member
+pub async fn get_glide_path(
+    auth_user: AuthUser,
+    State(investment_service): State<Arc<InvestmentService>>,
+) -> Result<Json<Vec<GlideStep>>, Error> {
+    let schedule = investment_service
+        .get_glide_schedule(auth_user.user_id)
+        .await?;
+
+    Ok(Json(schedule))
+}
+
+#[derive(Deserialize)]
+pub struct AutoRebalanceRequest {
+    enabled: bool,
+}
+
+// Checks the member against their target and, when they have drifted,
+// proposes the trades for them to accept
+pub async fn propose_rebalance(
+    auth_user: AuthUser,
+    State(investment_service): State<Arc<InvestmentService>>,
+) -> Result<Response, Error> {
+    let response = match investment_service
+        .check_rebalance(auth_user.user_id)
+        .await?
+    {
+        Some((_, recommendation)) => (StatusCode::CREATED, Json(recommendation)).into_response(),
+        None => StatusCode::NO_CONTENT.into_response(),
+    };
+
+    Ok(response)
+}
+
+// The trades waiting on the member's decision
+pub async fn get_pending_rebalance(
+    auth_user: AuthUser,
+    State(rebalancing_service): State<RebalancingService>,
+) -> Result<Json<Recommendation>, Error> {
+    let recommendation = rebalancing_service
+        .get_pending_recommendation(auth_user.user_id)
+        .await?;
+
+    Ok(Json(recommendation))
+}
+
+pub async fn get_rebalance(
+    auth_user: AuthUser,
+    State(rebalancing_service): State<RebalancingService>,
+    Path(recommendation_id): Path<Uuid>,
+) -> Result<Json<Recommendation>, Error> {
+    let recommendation = rebalancing_service
+        .get_recommendation(auth_user.user_id, recommendation_id)
+        .await?;
+
+    Ok(Json(recommendation))
+}
+
+pub async fn accept_rebalance(
+    auth_user: AuthUser,
+    State(rebalancing_service): State<RebalancingService>,
+    Path(recommendation_id): Path<Uuid>,
+) -> Result<Json<Recommendation>, Error> {
+    let recommendation = rebalancing_service
+        .accept(auth_user.user_id, recommendation_id)
+        .await?;
+
+    Ok(Json(recommendation))
+}
+
+pub async fn reject_rebalance(
+    auth_user: AuthUser,
+    State(rebalancing_service): State<RebalancingService>,
+    Path(recommendation_id): Path<Uuid>,
+) -> Result<Json<Recommendation>, Error> {
+    let recommendation = rebalancing_service
+        .reject(auth_user.user_id, recommendation_id)
+        .await?;
+
+    Ok(Json(recommendation))
+}
+
+pub async fn update_auto_rebalance(
+    auth_user: AuthUser,
+    State(rebalancing_service): State<RebalancingService>,
+    Json(payload): Json<AutoRebalanceRequest>,
+) -> Result<StatusCode, Error> {
+    rebalancing_service
+        .set_auto_rebalance(auth_user.user_id, payload.enabled)
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
 }
 
 // Add a new endpoint to explain the investment plans
 #[derive(Serialize)]
 pub struct InvestmentPlanInfo {
     plan_type: RiskTolerance,
-    stablecoin_percentage: f64,
-    growing_assets_percentage: f64,
+    weights: BTreeMap<Asset, Decimal>,
     description: String,
 }
 
 pub async fn get_investment_plans() -> Json<Vec<InvestmentPlanInfo>> {
+    let plan = |plan_type: RiskTolerance, description: &str| InvestmentPlanInfo {
+        weights: AssetAllocation::from_risk_tolerance(&plan_type).weights,
+        plan_type,
+        description: description.to_string(),
+    };
+
     Json(vec![
-        InvestmentPlanInfo {
-            plan_type: RiskTolerance::Conservative,
-            stablecoin_percentage: 80.0,
-            growing_assets_percentage: 20.0,
-            description: "Conservative plan: 80% in stablecoins (USDC) for stability, 20% in growing assets (Bitcoin) for growth potential".to_string(),
-        },
-        InvestmentPlanInfo {
-            plan_type: RiskTolerance::Moderate,
-            stablecoin_percentage: 50.0,
-            growing_assets_percentage: 50.0,
-            description: "Moderate plan: Balanced 50-50 split between stablecoins and growing assets for moderate risk and return".to_string(),
-        },
-        InvestmentPlanInfo {
-            plan_type: RiskTolerance::Aggressive,
-            stablecoin_percentage: 20.0,
-            growing_assets_percentage: 80.0,
-            description: "Aggressive plan: 20% in stablecoins, 80% in growing assets for maximum growth potential with higher risk".to_string(),
-        },
+        plan(
+            RiskTolerance::Conservative,
+            "Conservative plan: 80% in USDC, tokenized T-bills and a KES money-market fund for stability, 20% in Bitcoin and XLM for growth potential",
+        ),
+        plan(
+            RiskTolerance::Moderate,
+            "Moderate plan: half in stable assets (USDC, T-bills, KES money-market), half in Bitcoin and XLM for moderate risk and return",
+        ),
+        plan(
+            RiskTolerance::Aggressive,
+            "Aggressive plan: 20% in USDC and T-bills, 80% in Bitcoin and XLM for maximum growth potential with higher risk",
+        ),
     ])
-} 
\ No newline at end of file
+}
@@ -0,0 +1,46 @@
+use axum::{body::Bytes, extract::State, http::StatusCode, Json};
+use serde::Serialize;
+
+use crate::{
+    auth::AuthUser,
+    error::Error,
+    services::kyc_service::{KycService, KycSubmission, KycVerification},
+};
+
+#[derive(Serialize)]
+pub struct CallbackAck {
+    received: bool,
+}
+
+pub async fn submit_verification(
+    auth_user: AuthUser,
+    State(kyc_service): State<KycService>,
+    Json(payload): Json<KycSubmission>,
+) -> Result<(StatusCode, Json<KycVerification>), Error> {
+    let verification = kyc_service.submit(auth_user.user_id, &payload).await?;
+
+    // The provider's verdict arrives later through the callback
+    Ok((StatusCode::ACCEPTED, Json(verification)))
+}
+
+pub async fn get_verification(
+    auth_user: AuthUser,
+    State(kyc_service): State<KycService>,
+) -> Result<Json<KycVerification>, Error> {
+    let verification = kyc_service
+        .latest_verification(auth_user.user_id)
+        .await?
+        .ok_or_else(|| Error::NotFound("KYC verification".to_string()))?;
+
+    Ok(Json(verification))
+}
+
+// Provider callback; the raw body is needed to check its signature
+pub async fn provider_callback(
+    State(kyc_service): State<KycService>,
+    body: Bytes,
+) -> Result<Json<CallbackAck>, Error> {
+    kyc_service.handle_callback(&body).await?;
+
+    Ok(Json(CallbackAck { received: true }))
+}
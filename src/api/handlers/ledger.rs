@@ -0,0 +1,28 @@
+use axum::{extract::State, Json};
+use serde::Serialize;
+use sqlx::PgPool;
+
+use crate::{
+    auth::AdminUser,
+    error::Error,
+    services::{ledger::BalanceDiscrepancy, LedgerService},
+};
+
+#[derive(Serialize)]
+pub struct ConsistencyReport {
+    consistent: bool,
+    discrepancies: Vec<BalanceDiscrepancy>,
+}
+
+// Every fund whose stored balance has drifted from its journal
+pub async fn check_consistency(
+    _admin: AdminUser,
+    State(pool): State<PgPool>,
+) -> Result<Json<ConsistencyReport>, Error> {
+    let discrepancies = LedgerService::new(pool).check_consistency().await?;
+
+    Ok(Json(ConsistencyReport {
+        consistent: discrepancies.is_empty(),
+        discrepancies,
+    }))
+}
@@ -0,0 +1,16 @@
+use axum::{extract::State, Json};
+
+use crate::{
+    auth::AuthUser,
+    error::Error,
+    services::fund_service::{FundService, LimitHeadroom},
+};
+
+// The member's tier, its caps and how much of them is left
+pub async fn get_limits(
+    auth_user: AuthUser,
+    State(fund_service): State<FundService>,
+) -> Result<Json<LimitHeadroom>, Error> {
+    let headroom = fund_service.get_limit_headroom(auth_user.user_id).await?;
+    Ok(Json(headroom))
+}
@@ -0,0 +1,89 @@
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    auth::AuthUser,
+    error::Error,
+    services::loan_service::{Loan, LoanInstallment, LoanService, RepaymentRequest},
+};
+
+#[derive(Deserialize)]
+pub struct LoanRequest {
+    amount: Decimal,
+    term_months: u32,
+    phone_number: String,
+}
+
+#[derive(Deserialize)]
+pub struct RepayRequest {
+    phone_number: String,
+    // Defaults to the next installment due
+    amount: Option<Decimal>,
+}
+
+#[derive(Serialize)]
+pub struct LoanDetailResponse {
+    loan: Loan,
+    schedule: Vec<LoanInstallment>,
+}
+
+pub async fn request_loan(
+    auth_user: AuthUser,
+    State(loan_service): State<LoanService>,
+    Json(payload): Json<LoanRequest>,
+) -> Result<Json<Loan>, Error> {
+    let loan = loan_service
+        .request_loan(
+            auth_user.user_id,
+            payload.amount,
+            payload.term_months,
+            &payload.phone_number,
+        )
+        .await?;
+
+    Ok(Json(loan))
+}
+
+pub async fn get_loans(
+    auth_user: AuthUser,
+    State(loan_service): State<LoanService>,
+) -> Result<Json<Vec<Loan>>, Error> {
+    let loans = loan_service.get_user_loans(auth_user.user_id).await?;
+    Ok(Json(loans))
+}
+
+pub async fn get_loan(
+    auth_user: AuthUser,
+    State(loan_service): State<LoanService>,
+    Path(loan_id): Path<Uuid>,
+) -> Result<Json<LoanDetailResponse>, Error> {
+    let loan = loan_service.get_loan(auth_user.user_id, loan_id).await?;
+    let schedule = loan_service
+        .get_schedule(auth_user.user_id, loan_id)
+        .await?;
+
+    Ok(Json(LoanDetailResponse { loan, schedule }))
+}
+
+pub async fn repay_loan(
+    auth_user: AuthUser,
+    State(loan_service): State<LoanService>,
+    Path(loan_id): Path<Uuid>,
+    Json(payload): Json<RepayRequest>,
+) -> Result<Json<RepaymentRequest>, Error> {
+    let request = loan_service
+        .request_repayment(
+            auth_user.user_id,
+            loan_id,
+            &payload.phone_number,
+            payload.amount,
+        )
+        .await?;
+
+    Ok(Json(request))
+}
@@ -1,6 +1,16 @@
 pub mod auth;
 pub mod user;
 pub mod bpt;
-pub mod payments;
 pub mod fund;
-pub mod investment; 
\ No newline at end of file
+pub mod investment;
+pub mod deposit;
+pub mod withdrawal;
+pub mod loan;
+pub mod health;
+pub mod ussd;
+pub mod kyc;
+pub mod limits;
+pub mod contribution;
+pub mod employer;
+pub mod prices;
+pub mod ledger;
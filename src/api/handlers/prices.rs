@@ -0,0 +1,58 @@
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+
+use crate::{
+    auth::AuthUser,
+    error::Error,
+    services::price_history::{Candle, CandleInterval, PriceHistoryService, PriceTick},
+};
+
+#[derive(Deserialize)]
+pub struct RangeQuery {
+    from: DateTime<Utc>,
+    to: Option<DateTime<Utc>>, // Defaults to now
+}
+
+#[derive(Deserialize)]
+pub struct CandleQuery {
+    interval: CandleInterval,
+    from: DateTime<Utc>,
+    to: Option<DateTime<Utc>>,
+}
+
+// Recorded ticks, oldest first
+pub async fn get_ticks(
+    _auth_user: AuthUser,
+    State(price_history): State<PriceHistoryService>,
+    Path(symbol): Path<String>,
+    Query(query): Query<RangeQuery>,
+) -> Result<Json<Vec<PriceTick>>, Error> {
+    let ticks = price_history
+        .get_ticks(&symbol, query.from, query.to.unwrap_or_else(Utc::now))
+        .await?;
+
+    Ok(Json(ticks))
+}
+
+// OHLC candles, oldest first
+pub async fn get_candles(
+    _auth_user: AuthUser,
+    State(price_history): State<PriceHistoryService>,
+    Path(symbol): Path<String>,
+    Query(query): Query<CandleQuery>,
+) -> Result<Json<Vec<Candle>>, Error> {
+    let candles = price_history
+        .get_candles(
+            &symbol,
+            query.interval,
+            query.from,
+            query.to.unwrap_or_else(Utc::now),
+        )
+        .await?;
+
+    Ok(Json(candles))
+}
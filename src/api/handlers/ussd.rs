@@ -0,0 +1,35 @@
+use axum::{extract::State, Form};
+use serde::Deserialize;
+
+use crate::{auth::UssdGateway, services::ussd_service::UssdService};
+
+// Africa's Talking posts every step of a session as a form; `text` holds
+// all inputs so far joined by `*`.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UssdCallback {
+    session_id: String,
+    service_code: String,
+    phone_number: String,
+    #[serde(default)]
+    text: String,
+}
+
+pub async fn ussd_callback(
+    _gateway: UssdGateway,
+    State(ussd_service): State<UssdService>,
+    Form(payload): Form<UssdCallback>,
+) -> String {
+    // `text` carries PINs, so only its depth is logged
+    tracing::debug!(
+        "USSD session {} on {} at step {}",
+        payload.session_id,
+        payload.service_code,
+        payload.text.split('*').filter(|s| !s.is_empty()).count()
+    );
+
+    ussd_service
+        .handle(&payload.phone_number, &payload.text)
+        .await
+        .into_body()
+}
@@ -1,35 +1,51 @@
-use axum::{extract::State, Json};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 use crate::{
-    auth::AuthUser,
+    auth::{AdminUser, AuthUser, MPesaCallback},
     error::Error,
-    services::fund_service::FundService,
+    services::{
+        fund_service::{FundService, Transaction},
+        loan_service::LoanService,
+        mpesa_service::{B2CResultEnvelope, MPesaService},
+        withdrawal_policy::WithdrawalKind,
+    },
 };
 
 #[derive(Deserialize)]
 pub struct WithdrawalRequest {
     amount: f64,
     phone_number: String,
+    #[serde(default)]
+    kind: WithdrawalKind,
+    reason: Option<String>,
 }
 
 #[derive(Serialize)]
 pub struct WithdrawalResponse {
     transaction_id: Uuid,
     status: String,
+    payout: Decimal,
+    penalty: Decimal,
     message: String,
 }
 
 pub async fn initiate_withdrawal(
     auth_user: AuthUser,
-    State(fund_service): State<FundService>,
+    State((fund_service, mpesa_service)): State<(FundService, MPesaService)>,
     Json(payload): Json<WithdrawalRequest>,
 ) -> Result<Json<WithdrawalResponse>, Error> {
     // Validate amount
     if payload.amount <= 0.0 {
         return Err(Error::InvalidAmount);
     }
+    let amount = Decimal::try_from(payload.amount).map_err(|_| Error::InvalidAmount)?;
 
     // Check if user has sufficient balance
     let balance = fund_service.get_user_balance(auth_user.user_id).await?;
@@ -37,22 +53,206 @@ pub async fn initiate_withdrawal(
         return Err(Error::InsufficientFunds);
     }
 
-    // Process withdrawal
+    // Hold the amount and record a pending withdrawal
     let transaction = fund_service
         .process_withdrawal(
             auth_user.user_id,
-            payload.amount,
+            amount,
             &payload.phone_number,
+            payload.kind,
+            payload.reason.as_deref(),
         )
         .await?;
 
+    if transaction.status == "PENDING_REVIEW" {
+        return Ok(Json(WithdrawalResponse {
+            transaction_id: transaction.id,
+            status: transaction.status,
+            payout: transaction.payout,
+            penalty: transaction.penalty,
+            message: "Your hardship claim will be reviewed before it is paid".to_string(),
+        }));
+    }
+
+    pay_out(&fund_service, &mpesa_service, &transaction).await?;
+
+    Ok(Json(WithdrawalResponse {
+        transaction_id: transaction.id,
+        status: transaction.status,
+        payout: transaction.payout,
+        penalty: transaction.penalty,
+        message: "Withdrawal initiated. You will receive an M-Pesa payment shortly".to_string(),
+    }))
+}
+
+#[derive(Deserialize)]
+pub struct RejectWithdrawalRequest {
+    reason: String,
+}
+
+// Pays out a hardship claim an administrator has reviewed
+pub async fn approve_withdrawal(
+    admin: AdminUser,
+    State((fund_service, mpesa_service)): State<(FundService, MPesaService)>,
+    Path(transaction_id): Path<Uuid>,
+) -> Result<Json<WithdrawalResponse>, Error> {
+    let transaction = fund_service
+        .approve_withdrawal(transaction_id, admin.user_id)
+        .await?;
+
+    pay_out(&fund_service, &mpesa_service, &transaction).await?;
+
     Ok(Json(WithdrawalResponse {
         transaction_id: transaction.id,
         status: transaction.status,
-        message: "Withdrawal request processed successfully".to_string(),
+        payout: transaction.payout,
+        penalty: transaction.penalty,
+        message: "Hardship withdrawal approved and sent to M-Pesa".to_string(),
     }))
 }
 
+pub async fn reject_withdrawal(
+    admin: AdminUser,
+    State(fund_service): State<FundService>,
+    Path(transaction_id): Path<Uuid>,
+    Json(payload): Json<RejectWithdrawalRequest>,
+) -> Result<StatusCode, Error> {
+    fund_service
+        .reject_withdrawal(transaction_id, admin.user_id, payload.reason.trim())
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// Pays the member; the withdrawal completes only when the B2C result arrives
+async fn pay_out(
+    fund_service: &FundService,
+    mpesa_service: &MPesaService,
+    transaction: &Transaction,
+) -> Result<(), Error> {
+    match mpesa_service
+        .initiate_b2c_payment(
+            &transaction.phone_number,
+            transaction.payout,
+            &format!("PEN{}", transaction.id),
+        )
+        .await
+    {
+        Ok(b2c_response) => {
+            fund_service
+                .record_payout_request(transaction.id, &b2c_response.originator_conversation_id)
+                .await?;
+            Ok(())
+        }
+        Err(e) => {
+            fund_service
+                .fail_withdrawal(transaction.id, "M-Pesa payout request failed")
+                .await?;
+            Err(Error::MPesa(e.to_string()))
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct ResultAck {
+    #[serde(rename = "ResultCode")]
+    result_code: i32,
+    #[serde(rename = "ResultDesc")]
+    result_desc: String,
+}
+
+impl ResultAck {
+    fn accepted() -> Json<Self> {
+        Json(Self {
+            result_code: 0,
+            result_desc: "Accepted".to_string(),
+        })
+    }
+}
+
+// B2C ResultURL handler
+pub async fn b2c_result(
+    _callback: MPesaCallback,
+    State((fund_service, loan_service)): State<(FundService, LoanService)>,
+    Json(envelope): Json<B2CResultEnvelope>,
+) -> Result<Json<ResultAck>, Error> {
+    let result = envelope.result;
+
+    // Loan disbursements are paid out through the same B2C result URL
+    if let Some(transaction_id) = loan_service
+        .find_disbursement_by_conversation(&result.originator_conversation_id)
+        .await?
+    {
+        if result.is_successful() {
+            let receipt = result
+                .transaction_id
+                .unwrap_or_else(|| result.conversation_id.clone());
+            loan_service
+                .complete_disbursement(transaction_id, &receipt)
+                .await?;
+        } else {
+            loan_service
+                .fail_disbursement(transaction_id, &result.result_desc)
+                .await?;
+        }
+        return Ok(ResultAck::accepted());
+    }
+
+    let Some(transaction_id) = fund_service
+        .find_withdrawal_by_conversation(&result.originator_conversation_id)
+        .await?
+    else {
+        tracing::warn!(
+            "B2C result for unknown conversation {}",
+            result.originator_conversation_id
+        );
+        return Ok(ResultAck::accepted());
+    };
+
+    if result.is_successful() {
+        let receipt = result
+            .transaction_id
+            .unwrap_or_else(|| result.conversation_id.clone());
+        fund_service
+            .complete_withdrawal(transaction_id, &receipt)
+            .await?;
+    } else {
+        fund_service
+            .fail_withdrawal(transaction_id, &result.result_desc)
+            .await?;
+    }
+
+    Ok(ResultAck::accepted())
+}
+
+// B2C QueueTimeOutURL handler: the request expired before M-Pesa processed it
+pub async fn b2c_timeout(
+    _callback: MPesaCallback,
+    State((fund_service, loan_service)): State<(FundService, LoanService)>,
+    Json(envelope): Json<B2CResultEnvelope>,
+) -> Result<Json<ResultAck>, Error> {
+    if let Some(transaction_id) = loan_service
+        .find_disbursement_by_conversation(&envelope.result.originator_conversation_id)
+        .await?
+    {
+        loan_service
+            .fail_disbursement(transaction_id, "M-Pesa payout request timed out")
+            .await?;
+        return Ok(ResultAck::accepted());
+    }
+
+    if let Some(transaction_id) = fund_service
+        .find_withdrawal_by_conversation(&envelope.result.originator_conversation_id)
+        .await?
+    {
+        fund_service
+            .fail_withdrawal(transaction_id, "M-Pesa payout request timed out")
+            .await?;
+    }
+
+    Ok(ResultAck::accepted())
+}
+
 #[derive(Serialize)]
 pub struct WithdrawalHistoryResponse {
     withdrawals: Vec<WithdrawalRecord>,
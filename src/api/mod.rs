@@ -1,14 +1,189 @@
-use actix_web::web;
-
-pub fn config(cfg: &mut web::ServiceConfig) {
-    cfg.service(
-        web::scope("/api")
-            .service(auth::auth_config())
-            .service(investments::investment_config())
-            .service(transactions::transaction_config())
-    );
+pub mod handlers;
+pub mod state;
+
+pub use state::AppState;
+
+use axum::{
+    http::{header, HeaderValue, Method},
+    routing::{delete, get, post, put},
+    Router,
+};
+use tower_http::cors::{AllowOrigin, CorsLayer};
+
+use crate::config::cors::CorsConfig;
+
+use handlers::{
+    auth, bpt, contribution, deposit, employer, fund, health, investment, kyc, ledger, limits,
+    loan, prices, user, ussd, withdrawal,
+};
+
+// The one HTTP surface of the service: every handler module is mounted here
+// and `run()` serves the result.
+pub fn router(state: AppState, cors: &CorsConfig) -> Router {
+    let api = Router::new()
+        .route("/health", get(health::health_check))
+        // Auth
+        .route("/auth/register", post(auth::register))
+        .route("/auth/login", post(auth::login))
+        .route("/auth/refresh", post(auth::refresh))
+        .route("/auth/logout", post(auth::logout))
+        .route("/auth/logout-all", post(auth::logout_all))
+        .route("/auth/phone/otp", post(auth::request_registration_otp))
+        .route("/auth/phone/register", post(auth::register_phone))
+        .route("/auth/pin/reset/otp", post(auth::request_pin_reset_otp))
+        .route("/auth/pin/reset", post(auth::reset_pin))
+        // User
+        .route("/profile", get(user::get_profile))
+        // KYC; the callback path must match SMILE_ID_CALLBACK_URL
+        .route(
+            "/kyc",
+            get(kyc::get_verification).post(kyc::submit_verification),
+        )
+        .route("/kyc/callback", post(kyc::provider_callback))
+        // Funds; money moves through /deposit and /withdrawal
+        .route("/funds", post(fund::create_fund))
+        .route("/funds/{id}", get(fund::get_fund))
+        // Investment
+        .route("/investment/profile", put(investment::update_risk_profile))
+        .route(
+            "/investment/allocation",
+            get(investment::get_current_allocation).put(investment::update_allocation),
+        )
+        .route(
+            "/investment/recommendation",
+            get(investment::get_recommendation),
+        )
+        .route("/investment/plans", get(investment::get_investment_plans))
+        .route("/investment/glide-path", get(investment::get_glide_path))
+        // Rebalancing trades proposed against the member's target allocation
+        .route(
+            "/investment/rebalance",
+            get(investment::get_pending_rebalance).post(investment::propose_rebalance),
+        )
+        .route(
+            "/investment/rebalance/{id}",
+            get(investment::get_rebalance),
+        )
+        .route(
+            "/investment/rebalance/{id}/accept",
+            post(investment::accept_rebalance),
+        )
+        .route(
+            "/investment/rebalance/{id}/reject",
+            post(investment::reject_rebalance),
+        )
+        .route(
+            "/investment/auto-rebalance",
+            put(investment::update_auto_rebalance),
+        )
+        // Transaction limits for the member's KYC tier
+        .route("/limits", get(limits::get_limits))
+        // Deposits
+        .route("/deposit", post(deposit::initiate_deposit))
+        // Recurring contributions
+        .route(
+            "/contributions/schedules",
+            get(contribution::get_schedules).post(contribution::create_schedule),
+        )
+        .route(
+            "/contributions/schedules/{id}",
+            delete(contribution::cancel_schedule),
+        )
+        .route(
+            "/contributions/schedules/{id}/pause",
+            post(contribution::pause_schedule),
+        )
+        .route(
+            "/contributions/schedules/{id}/resume",
+            post(contribution::resume_schedule),
+        )
+        // Employers and payroll
+        .route("/employers", post(employer::create_employer))
+        .route("/employers/{id}", get(employer::get_employer))
+        .route(
+            "/employers/{id}/members",
+            get(employer::get_members).post(employer::add_member),
+        )
+        .route(
+            "/employers/{id}/members/{staff_number}",
+            delete(employer::remove_member),
+        )
+        .route("/employers/{id}/payroll", post(employer::upload_payroll))
+        .route(
+            "/employers/{id}/payroll/{batch_id}",
+            get(employer::get_payroll_batch),
+        )
+        // Back office; platform administrators only
+        .route(
+            "/admin/employers/{id}/approve",
+            post(employer::approve_employer),
+        )
+        .route(
+            "/admin/employers/{id}/payroll/{batch_id}/settle",
+            post(employer::settle_payroll_batch),
+        )
+        .route(
+            "/admin/withdrawals/{id}/approve",
+            post(withdrawal::approve_withdrawal),
+        )
+        .route(
+            "/admin/withdrawals/{id}/reject",
+            post(withdrawal::reject_withdrawal),
+        )
+        .route("/admin/ledger/consistency", get(ledger::check_consistency))
+        // Price history
+        .route("/prices/{symbol}/ticks", get(prices::get_ticks))
+        .route("/prices/{symbol}/candles", get(prices::get_candles))
+        // Withdrawals
+        .route("/withdrawal", post(withdrawal::initiate_withdrawal))
+        .route(
+            "/withdrawal/history",
+            get(withdrawal::get_withdrawal_history),
+        )
+        // M-Pesa callbacks; the paths must match MPESA_CALLBACK_URL and the
+        // B2C result/timeout URLs registered with Daraja, and each request
+        // must carry MPESA_CALLBACK_TOKEN
+        .route("/deposit/callback", post(deposit::mpesa_callback))
+        .route("/withdrawal/b2c/result", post(withdrawal::b2c_result))
+        .route("/withdrawal/b2c/timeout", post(withdrawal::b2c_timeout))
+        // USSD gateway; the path must match the callback URL registered
+        // with Africa's Talking for the service code
+        .route("/ussd", post(ussd::ussd_callback))
+        // BPT
+        .route("/bpt/balance", get(bpt::get_balance))
+        .route("/bpt/tokenize", post(bpt::tokenize_savings))
+        .route("/bpt/redeem", post(bpt::redeem_tokens))
+        .route("/bpt/stakes", get(bpt::get_stakes))
+        .route("/bpt/stake", post(bpt::stake_tokens))
+        .route("/bpt/unstake", post(bpt::unstake_tokens))
+        .route("/bpt/transfer", post(bpt::transfer_tokens))
+        .route("/bpt/collateral", post(bpt::create_collateral))
+        // Loans
+        .route("/loans", get(loan::get_loans).post(loan::request_loan))
+        .route("/loans/{id}", get(loan::get_loan))
+        .route("/loans/{id}/repay", post(loan::repay_loan));
+
+    Router::new()
+        .nest("/api", api)
+        .layer(cors_layer(cors))
+        .with_state(state)
 }
 
-pub mod auth;
-pub mod investments;
-pub mod transactions; 
\ No newline at end of file
+fn cors_layer(config: &CorsConfig) -> CorsLayer {
+    let origins: Vec<HeaderValue> = config
+        .allowed_origins
+        .iter()
+        .filter_map(|origin| match HeaderValue::from_str(origin) {
+            Ok(value) => Some(value),
+            Err(_) => {
+                tracing::warn!("Ignoring invalid CORS origin {:?}", origin);
+                None
+            }
+        })
+        .collect();
+
+    CorsLayer::new()
+        .allow_origin(AllowOrigin::list(origins))
+        .allow_methods([Method::GET, Method::POST, Method::PUT, Method::DELETE])
+        .allow_headers([header::AUTHORIZATION, header::CONTENT_TYPE])
+}
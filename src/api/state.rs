@@ -0,0 +1,142 @@
+use axum::extract::FromRef;
+use sqlx::PgPool;
+use std::sync::Arc;
+
+use crate::services::{
+    fund_service::FundService, investment_service::InvestmentService, kyc_service::KycService,
+    mpesa_service::MPesaService, user_service::UserService, AuthService, BPTManager,
+    ContributionService, EmployerService, LoanService, PhoneAuthService, PriceHistoryService,
+    RebalancingService, UssdService,
+};
+
+// Everything the handlers extract with `State<...>`. Services are cheap to
+// clone (a pool handle plus config), so each request gets its own copy.
+#[derive(Clone)]
+pub struct AppState {
+    pub pool: PgPool,
+    pub auth_service: AuthService,
+    pub user_service: UserService,
+    pub phone_auth_service: PhoneAuthService,
+    pub fund_service: FundService,
+    pub mpesa_service: MPesaService,
+    pub investment_service: Arc<InvestmentService>,
+    pub bpt_manager: BPTManager,
+    pub loan_service: LoanService,
+    pub ussd_service: UssdService,
+    pub kyc_service: KycService,
+    pub contribution_service: ContributionService,
+    pub employer_service: EmployerService,
+    pub price_history_service: PriceHistoryService,
+    pub rebalancing_service: RebalancingService,
+}
+
+impl FromRef<AppState> for PgPool {
+    fn from_ref(state: &AppState) -> Self {
+        state.pool.clone()
+    }
+}
+
+impl FromRef<AppState> for AuthService {
+    fn from_ref(state: &AppState) -> Self {
+        state.auth_service.clone()
+    }
+}
+
+impl FromRef<AppState> for UserService {
+    fn from_ref(state: &AppState) -> Self {
+        state.user_service.clone()
+    }
+}
+
+impl FromRef<AppState> for PhoneAuthService {
+    fn from_ref(state: &AppState) -> Self {
+        state.phone_auth_service.clone()
+    }
+}
+
+impl FromRef<AppState> for FundService {
+    fn from_ref(state: &AppState) -> Self {
+        state.fund_service.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<InvestmentService> {
+    fn from_ref(state: &AppState) -> Self {
+        state.investment_service.clone()
+    }
+}
+
+impl FromRef<AppState> for MPesaService {
+    fn from_ref(state: &AppState) -> Self {
+        state.mpesa_service.clone()
+    }
+}
+
+impl FromRef<AppState> for BPTManager {
+    fn from_ref(state: &AppState) -> Self {
+        state.bpt_manager.clone()
+    }
+}
+
+impl FromRef<AppState> for LoanService {
+    fn from_ref(state: &AppState) -> Self {
+        state.loan_service.clone()
+    }
+}
+
+impl FromRef<AppState> for UssdService {
+    fn from_ref(state: &AppState) -> Self {
+        state.ussd_service.clone()
+    }
+}
+
+impl FromRef<AppState> for KycService {
+    fn from_ref(state: &AppState) -> Self {
+        state.kyc_service.clone()
+    }
+}
+
+impl FromRef<AppState> for ContributionService {
+    fn from_ref(state: &AppState) -> Self {
+        state.contribution_service.clone()
+    }
+}
+
+impl FromRef<AppState> for EmployerService {
+    fn from_ref(state: &AppState) -> Self {
+        state.employer_service.clone()
+    }
+}
+
+impl FromRef<AppState> for PriceHistoryService {
+    fn from_ref(state: &AppState) -> Self {
+        state.price_history_service.clone()
+    }
+}
+
+impl FromRef<AppState> for RebalancingService {
+    fn from_ref(state: &AppState) -> Self {
+        state.rebalancing_service.clone()
+    }
+}
+
+// Login accepts either email/password or phone/PIN
+impl FromRef<AppState> for (UserService, PhoneAuthService) {
+    fn from_ref(state: &AppState) -> Self {
+        (state.user_service.clone(), state.phone_auth_service.clone())
+    }
+}
+
+// Deposit and withdrawal handlers need a payment rail alongside the fund
+impl FromRef<AppState> for (FundService, MPesaService) {
+    fn from_ref(state: &AppState) -> Self {
+        (state.fund_service.clone(), state.mpesa_service.clone())
+    }
+}
+
+// M-Pesa callbacks are shared between fund deposits and loan repayments
+impl FromRef<AppState> for (FundService, LoanService) {
+    fn from_ref(state: &AppState) -> Self {
+        (state.fund_service.clone(), state.loan_service.clone())
+    }
+}
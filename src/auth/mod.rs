@@ -1,19 +1,24 @@
 use axum::{
-    async_trait,
-    extract::{FromRequestParts, TypedHeader},
+    extract::{FromRef, FromRequestParts, Query},
     http::request::Parts,
     RequestPartsExt,
 };
-use axum_extra::headers::{authorization::Bearer, Authorization};
+use axum_extra::{
+    headers::{authorization::Bearer, Authorization},
+    TypedHeader,
+};
 use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
+use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
 use time::{Duration, OffsetDateTime};
 use uuid::Uuid;
 
 use crate::error::Error;
+use crate::services::{mpesa_service::MPesaService, ussd_service::UssdService};
 
-const JWT_SECRET: &[u8] = b"your-secret-key"; // In production, use environment variables
-const TOKEN_DURATION_HOURS: i64 = 24;
+static JWT_SECRET: Lazy<String> =
+    Lazy::new(|| std::env::var("JWT_SECRET").expect("JWT_SECRET must be set"));
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Claims {
@@ -23,9 +28,9 @@ pub struct Claims {
 }
 
 impl Claims {
-    pub fn new(user_id: Uuid) -> Self {
+    pub fn new(user_id: Uuid, ttl: Duration) -> Self {
         let now = OffsetDateTime::now_utc();
-        let exp = now + Duration::hours(TOKEN_DURATION_HOURS);
+        let exp = now + ttl;
 
         Self {
             sub: user_id,
@@ -40,7 +45,6 @@ pub struct AuthUser {
     pub user_id: Uuid,
 }
 
-#[async_trait]
 impl<S> FromRequestParts<S> for AuthUser
 where
     S: Send + Sync,
@@ -55,7 +59,7 @@ where
 
         let token_data = decode::<Claims>(
             bearer.token(),
-            &DecodingKey::from_secret(JWT_SECRET),
+            &DecodingKey::from_secret(jwt_secret()),
             &Validation::default(),
         )
         .map_err(|_| Error::Unauthorized)?;
@@ -66,18 +70,105 @@ where
     }
 }
 
-pub fn create_token(user_id: Uuid) -> Result<String, Error> {
-    let claims = Claims::new(user_id);
+// Platform staff, marked by users.is_admin. Back-office actions such as
+// vetting employers take this instead of AuthUser.
+#[derive(Debug, Clone)]
+pub struct AdminUser {
+    pub user_id: Uuid,
+}
+
+impl<S> FromRequestParts<S> for AdminUser
+where
+    PgPool: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = Error;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let AuthUser { user_id } = AuthUser::from_request_parts(parts, state).await?;
+
+        let is_admin = sqlx::query_scalar!("SELECT is_admin FROM users WHERE id = $1", user_id)
+            .fetch_optional(&PgPool::from_ref(state))
+            .await?
+            .unwrap_or(false);
+        if !is_admin {
+            return Err(Error::AdminOnly);
+        }
+
+        Ok(AdminUser { user_id })
+    }
+}
+
+// Guards the M-Pesa callback routes: Daraja posts back to the URLs we
+// registered, which carry the shared callback token
+#[derive(Debug, Clone)]
+pub struct MPesaCallback;
+
+#[derive(Deserialize)]
+struct CallbackToken {
+    token: String,
+}
+
+impl<S> FromRequestParts<S> for MPesaCallback
+where
+    MPesaService: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = Error;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let Query(callback) = parts
+            .extract::<Query<CallbackToken>>()
+            .await
+            .map_err(|_| Error::Unauthorized)?;
+
+        if MPesaService::from_ref(state).verify_callback_token(&callback.token) {
+            Ok(MPesaCallback)
+        } else {
+            Err(Error::Unauthorized)
+        }
+    }
+}
+
+// Guards the USSD callback: the gateway posts to the URL we registered with
+// it, which carries the shared gateway token. Without it anyone could post a
+// session for any phone number.
+#[derive(Debug, Clone)]
+pub struct UssdGateway;
+
+impl<S> FromRequestParts<S> for UssdGateway
+where
+    UssdService: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = Error;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let Query(callback) = parts
+            .extract::<Query<CallbackToken>>()
+            .await
+            .map_err(|_| Error::Unauthorized)?;
+
+        if UssdService::from_ref(state).verify_gateway_token(&callback.token) {
+            Ok(UssdGateway)
+        } else {
+            Err(Error::Unauthorized)
+        }
+    }
+}
+
+pub fn jwt_secret() -> &'static [u8] {
+    JWT_SECRET.as_bytes()
+}
+
+// Access tokens only; sessions are renewed through AuthService refresh tokens
+pub fn create_token(user_id: Uuid, ttl: Duration) -> Result<String, Error> {
+    let claims = Claims::new(user_id, ttl);
     encode(
         &Header::default(),
         &claims,
-        &EncodingKey::from_secret(JWT_SECRET),
+        &EncodingKey::from_secret(jwt_secret()),
     )
     .map_err(|_| Error::TokenCreation)
 }
-
-mod jwt;
-mod middleware;
-
-pub use jwt::*;
-pub use middleware::*; 
\ No newline at end of file
+ 
\ No newline at end of file
@@ -0,0 +1,33 @@
+use serde::Deserialize;
+
+use crate::config::env::read;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AuthConfig {
+    pub access_token_ttl_secs: i64, // Short-lived; clients renew with the refresh token
+    pub refresh_token_ttl_days: i64,
+}
+
+impl Default for AuthConfig {
+    fn default() -> Self {
+        Self {
+            access_token_ttl_secs: 15 * 60,
+            refresh_token_ttl_days: 30,
+        }
+    }
+}
+
+impl AuthConfig {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+
+        Self {
+            access_token_ttl_secs: read("ACCESS_TOKEN_TTL_SECS")
+                .filter(|secs: &i64| *secs > 0)
+                .unwrap_or(defaults.access_token_ttl_secs),
+            refresh_token_ttl_days: read("REFRESH_TOKEN_TTL_DAYS")
+                .filter(|days: &i64| *days > 0)
+                .unwrap_or(defaults.refresh_token_ttl_days),
+        }
+    }
+}
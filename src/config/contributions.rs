@@ -0,0 +1,35 @@
+use serde::Deserialize;
+
+use crate::config::env::read;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ContributionConfig {
+    pub scheduler_interval_secs: u64,
+    pub max_consecutive_failures: i32, // Declined prompts in a row before a schedule is paused
+    pub reminder_lead_secs: i64,       // How long before a prompt the member is reminded
+}
+
+impl Default for ContributionConfig {
+    fn default() -> Self {
+        Self {
+            scheduler_interval_secs: 60,
+            max_consecutive_failures: 3,
+            reminder_lead_secs: 60 * 60,
+        }
+    }
+}
+
+impl ContributionConfig {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+
+        Self {
+            scheduler_interval_secs: read("CONTRIBUTION_SCHEDULER_INTERVAL_SECS")
+                .unwrap_or(defaults.scheduler_interval_secs),
+            max_consecutive_failures: read("CONTRIBUTION_MAX_CONSECUTIVE_FAILURES")
+                .unwrap_or(defaults.max_consecutive_failures),
+            reminder_lead_secs: read("CONTRIBUTION_REMINDER_LEAD_SECS")
+                .unwrap_or(defaults.reminder_lead_secs),
+        }
+    }
+}
@@ -0,0 +1,24 @@
+use serde::Deserialize;
+use std::env;
+
+// Browser origins allowed to call the API. Unset means none: the mobile
+// app and USSD/M-Pesa callbacks do not go through CORS.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct CorsConfig {
+    pub allowed_origins: Vec<String>,
+}
+
+impl CorsConfig {
+    pub fn from_env() -> Self {
+        Self {
+            allowed_origins: env::var("CORS_ALLOWED_ORIGINS")
+                .map(|v| {
+                    v.split(',')
+                        .map(|s| s.trim().trim_end_matches('/').to_string())
+                        .filter(|s| !s.is_empty())
+                        .collect()
+                })
+                .unwrap_or_default(),
+        }
+    }
+}
@@ -0,0 +1,36 @@
+use serde::Deserialize;
+
+use crate::config::env::read;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DepositSweeperConfig {
+    pub interval_secs: u64,      // How often the sweeper wakes up
+    pub min_age_secs: i64,       // Leave deposits alone while a callback is still likely
+    pub timeout_after_secs: i64, // Stop sweeping deposits M-Pesa still reports as processing
+}
+
+impl Default for DepositSweeperConfig {
+    fn default() -> Self {
+        Self {
+            interval_secs: 60,
+            min_age_secs: 2 * 60,
+            timeout_after_secs: 30 * 60,
+        }
+    }
+}
+
+impl DepositSweeperConfig {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+
+        Self {
+            // A zero period would panic the sweeper's timer
+            interval_secs: read("MPESA_SWEEP_INTERVAL_SECS")
+                .filter(|secs: &u64| *secs > 0)
+                .unwrap_or(defaults.interval_secs),
+            min_age_secs: read("MPESA_SWEEP_MIN_AGE_SECS").unwrap_or(defaults.min_age_secs),
+            timeout_after_secs: read("MPESA_SWEEP_TIMEOUT_SECS")
+                .unwrap_or(defaults.timeout_after_secs),
+        }
+    }
+}
@@ -0,0 +1,8 @@
+use std::env;
+use std::str::FromStr;
+
+// A missing or unparsable variable reads as unset, so callers fall back to
+// their defaults
+pub fn read<T: FromStr>(key: &str) -> Option<T> {
+    env::var(key).ok().and_then(|v| v.parse().ok())
+}
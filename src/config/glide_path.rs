@@ -0,0 +1,60 @@
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use std::env;
+
+use crate::config::env::read;
+
+// Share of savings in growth assets at a given distance from retirement
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct GlidePoint {
+    pub years_to_retirement: u32,
+    pub growth: Decimal, // Percent
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct GlidePathConfig {
+    pub points: Vec<GlidePoint>,  // Linear between points, flat beyond the ends
+    pub tolerance_shift: Decimal, // Growth points added or removed by risk tolerance
+}
+
+impl Default for GlidePathConfig {
+    fn default() -> Self {
+        Self {
+            points: vec![point(40, 85), point(25, 75), point(10, 50), point(0, 25)],
+            tolerance_shift: Decimal::new(10, 0),
+        }
+    }
+}
+
+impl GlidePathConfig {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+
+        Self {
+            points: read_points("GLIDE_PATH_POINTS").unwrap_or(defaults.points),
+            tolerance_shift: read("GLIDE_PATH_TOLERANCE_SHIFT").unwrap_or(defaults.tolerance_shift),
+        }
+    }
+}
+
+fn point(years_to_retirement: u32, growth: i64) -> GlidePoint {
+    GlidePoint {
+        years_to_retirement,
+        growth: Decimal::new(growth, 0),
+    }
+}
+
+// Comma separated years:growth pairs, e.g. "40:85,25:75,10:50,0:25"
+fn read_points(key: &str) -> Option<Vec<GlidePoint>> {
+    env::var(key)
+        .ok()?
+        .split(',')
+        .map(|pair| {
+            let (years, growth) = pair.trim().split_once(':')?;
+            Some(GlidePoint {
+                years_to_retirement: years.trim().parse().ok()?,
+                growth: growth.trim().parse().ok()?,
+            })
+        })
+        .collect()
+}
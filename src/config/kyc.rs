@@ -0,0 +1,30 @@
+use serde::Deserialize;
+
+use crate::config::env::read;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct KycConfig {
+    pub poll_interval_secs: u64, // How often the poller wakes up
+    pub poll_min_age_secs: i64,  // Leave jobs alone while a callback is still likely
+}
+
+impl Default for KycConfig {
+    fn default() -> Self {
+        Self {
+            poll_interval_secs: 5 * 60,
+            poll_min_age_secs: 10 * 60,
+        }
+    }
+}
+
+impl KycConfig {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+
+        Self {
+            poll_interval_secs: read("KYC_POLL_INTERVAL_SECS")
+                .unwrap_or(defaults.poll_interval_secs),
+            poll_min_age_secs: read("KYC_POLL_MIN_AGE_SECS").unwrap_or(defaults.poll_min_age_secs),
+        }
+    }
+}
@@ -0,0 +1,51 @@
+use rust_decimal::Decimal;
+use serde::Deserialize;
+
+use crate::config::env::read;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct LoanConfig {
+    pub annual_interest_rate: Decimal,
+    pub min_amount: Decimal,
+    pub max_amount: Decimal,
+    pub max_term_months: u32,
+    pub stablecoin_ltv: Decimal, // Advance rate against stablecoin-backed savings
+    pub growth_ltv: Decimal,     // Advance rate against growth-asset savings
+    pub default_after_days: i64, // Days an installment may be overdue before liquidation
+    pub monitor_interval_secs: u64,
+}
+
+impl Default for LoanConfig {
+    fn default() -> Self {
+        Self {
+            annual_interest_rate: Decimal::new(12, 2), // 12%
+            min_amount: Decimal::new(500, 0),
+            max_amount: Decimal::new(50_000, 0),
+            max_term_months: 12,
+            stablecoin_ltv: Decimal::new(70, 2),
+            growth_ltv: Decimal::new(40, 2),
+            default_after_days: 30,
+            monitor_interval_secs: 60 * 60,
+        }
+    }
+}
+
+impl LoanConfig {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+
+        Self {
+            annual_interest_rate: read("LOAN_ANNUAL_INTEREST_RATE")
+                .unwrap_or(defaults.annual_interest_rate),
+            min_amount: read("LOAN_MIN_AMOUNT").unwrap_or(defaults.min_amount),
+            max_amount: read("LOAN_MAX_AMOUNT").unwrap_or(defaults.max_amount),
+            max_term_months: read("LOAN_MAX_TERM_MONTHS").unwrap_or(defaults.max_term_months),
+            stablecoin_ltv: read("LOAN_STABLECOIN_LTV").unwrap_or(defaults.stablecoin_ltv),
+            growth_ltv: read("LOAN_GROWTH_LTV").unwrap_or(defaults.growth_ltv),
+            default_after_days: read("LOAN_DEFAULT_AFTER_DAYS")
+                .unwrap_or(defaults.default_after_days),
+            monitor_interval_secs: read("LOAN_MONITOR_INTERVAL_SECS")
+                .unwrap_or(defaults.monitor_interval_secs),
+        }
+    }
+}
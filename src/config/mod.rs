@@ -38,4 +38,18 @@ impl Settings {
 
         s.try_deserialize()
     }
-} 
\ No newline at end of file
+} 
+pub mod env;
+pub mod auth;
+pub mod cors;
+pub mod deposit_sweeper;
+pub mod transaction_limits;
+pub mod loans;
+pub mod phone_auth;
+pub mod kyc;
+pub mod withdrawal_policy;
+pub mod contributions;
+pub mod price_feed;
+pub mod price_history;
+pub mod rebalancing;
+pub mod glide_path;
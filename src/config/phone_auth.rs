@@ -0,0 +1,42 @@
+use serde::Deserialize;
+
+use crate::config::env::read;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PhoneAuthConfig {
+    pub otp_length: usize,
+    pub otp_ttl_secs: i64,
+    pub otp_max_attempts: i32, // Wrong guesses before a code is burned
+    pub otp_resend_interval_secs: i64, // Minimum gap between SMS to one number
+    pub max_pin_attempts: i32, // Consecutive wrong PINs before lockout
+    pub lockout_secs: i64,
+}
+
+impl Default for PhoneAuthConfig {
+    fn default() -> Self {
+        Self {
+            otp_length: 6,
+            otp_ttl_secs: 5 * 60,
+            otp_max_attempts: 3,
+            otp_resend_interval_secs: 60,
+            max_pin_attempts: 5,
+            lockout_secs: 15 * 60,
+        }
+    }
+}
+
+impl PhoneAuthConfig {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+
+        Self {
+            otp_length: read("OTP_LENGTH").unwrap_or(defaults.otp_length),
+            otp_ttl_secs: read("OTP_TTL_SECS").unwrap_or(defaults.otp_ttl_secs),
+            otp_max_attempts: read("OTP_MAX_ATTEMPTS").unwrap_or(defaults.otp_max_attempts),
+            otp_resend_interval_secs: read("OTP_RESEND_INTERVAL_SECS")
+                .unwrap_or(defaults.otp_resend_interval_secs),
+            max_pin_attempts: read("PIN_MAX_ATTEMPTS").unwrap_or(defaults.max_pin_attempts),
+            lockout_secs: read("PIN_LOCKOUT_SECS").unwrap_or(defaults.lockout_secs),
+        }
+    }
+}
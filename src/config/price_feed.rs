@@ -0,0 +1,44 @@
+use serde::Deserialize;
+
+use crate::config::env::read;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PriceFeedConfig {
+    pub cache_ttl_secs: u64,     // How long an agreed price is served from cache
+    pub max_quote_age_secs: i64, // Older quotes are ignored as stale
+    pub max_deviation: f64,      // Largest fractional distance from the median
+    pub min_sources: usize,      // Agreeing quotes needed to trust a price
+    pub request_timeout_secs: u64,
+    pub fx_max_quote_age_secs: i64, // Reference FX rates are published about daily
+}
+
+impl Default for PriceFeedConfig {
+    fn default() -> Self {
+        Self {
+            cache_ttl_secs: 30,
+            max_quote_age_secs: 5 * 60,
+            max_deviation: 0.02,
+            min_sources: 2,
+            request_timeout_secs: 10,
+            fx_max_quote_age_secs: 36 * 60 * 60,
+        }
+    }
+}
+
+impl PriceFeedConfig {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+
+        Self {
+            cache_ttl_secs: read("PRICE_FEED_CACHE_TTL_SECS").unwrap_or(defaults.cache_ttl_secs),
+            max_quote_age_secs: read("PRICE_FEED_MAX_QUOTE_AGE_SECS")
+                .unwrap_or(defaults.max_quote_age_secs),
+            max_deviation: read("PRICE_FEED_MAX_DEVIATION").unwrap_or(defaults.max_deviation),
+            min_sources: read("PRICE_FEED_MIN_SOURCES").unwrap_or(defaults.min_sources),
+            request_timeout_secs: read("PRICE_FEED_REQUEST_TIMEOUT_SECS")
+                .unwrap_or(defaults.request_timeout_secs),
+            fx_max_quote_age_secs: read("PRICE_FEED_FX_MAX_QUOTE_AGE_SECS")
+                .unwrap_or(defaults.fx_max_quote_age_secs),
+        }
+    }
+}
@@ -0,0 +1,48 @@
+use serde::Deserialize;
+use std::env;
+
+use crate::config::env::read;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PriceHistoryConfig {
+    pub ingest_interval_secs: u64,
+    pub market_symbols: Vec<String>, // Priced by the market data providers
+    pub fx_symbols: Vec<String>,     // Priced by the reference FX rate source
+    pub max_points: i64,             // Most ticks or candles one range query returns
+}
+
+impl Default for PriceHistoryConfig {
+    fn default() -> Self {
+        Self {
+            ingest_interval_secs: 60,
+            market_symbols: vec!["BTC".to_string(), "USDC".to_string(), "XLM".to_string()],
+            fx_symbols: vec!["KES".to_string()],
+            max_points: 5_000,
+        }
+    }
+}
+
+impl PriceHistoryConfig {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+
+        Self {
+            ingest_interval_secs: read("PRICE_HISTORY_INGEST_INTERVAL_SECS")
+                .unwrap_or(defaults.ingest_interval_secs),
+            market_symbols: read_list("PRICE_HISTORY_MARKET_SYMBOLS")
+                .unwrap_or(defaults.market_symbols),
+            fx_symbols: read_list("PRICE_HISTORY_FX_SYMBOLS").unwrap_or(defaults.fx_symbols),
+            max_points: read("PRICE_HISTORY_MAX_POINTS").unwrap_or(defaults.max_points),
+        }
+    }
+}
+
+// Comma separated, e.g. "BTC,USDC,XLM"
+fn read_list(key: &str) -> Option<Vec<String>> {
+    env::var(key).ok().map(|v| {
+        v.split(',')
+            .map(|s| s.trim().to_uppercase())
+            .filter(|s| !s.is_empty())
+            .collect()
+    })
+}
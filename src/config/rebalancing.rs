@@ -0,0 +1,47 @@
+use rust_decimal::Decimal;
+use serde::Deserialize;
+
+use crate::config::env::read;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RebalancingConfig {
+    pub min_trade_value: Decimal, // USD; smaller orders are left out of the plan
+    pub fee_rate: Decimal,        // Venue fee as a share of each order's value
+    pub slippage_rate: Decimal,   // Expected price impact as a share of each order's value
+    pub auto_rebalance_interval_secs: u64,
+    // Fill trades on paper at recorded prices. Only for development and
+    // staging: without it, and with no live venue, nothing is executed.
+    pub paper_trading: bool,
+}
+
+impl Default for RebalancingConfig {
+    fn default() -> Self {
+        Self {
+            min_trade_value: Decimal::new(10, 0),
+            fee_rate: Decimal::new(10, 4),      // 0.10%
+            slippage_rate: Decimal::new(25, 4), // 0.25%
+            auto_rebalance_interval_secs: 24 * 60 * 60,
+            paper_trading: false,
+        }
+    }
+}
+
+impl RebalancingConfig {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+
+        Self {
+            min_trade_value: read("REBALANCE_MIN_TRADE_VALUE").unwrap_or(defaults.min_trade_value),
+            fee_rate: read("REBALANCE_FEE_RATE").unwrap_or(defaults.fee_rate),
+            slippage_rate: read("REBALANCE_SLIPPAGE_RATE").unwrap_or(defaults.slippage_rate),
+            auto_rebalance_interval_secs: read("REBALANCE_AUTO_INTERVAL_SECS")
+                .unwrap_or(defaults.auto_rebalance_interval_secs),
+            paper_trading: read("REBALANCE_PAPER_TRADING").unwrap_or(defaults.paper_trading),
+        }
+    }
+
+    // Fee plus slippage, charged against every order
+    pub fn cost_rate(&self) -> Decimal {
+        self.fee_rate + self.slippage_rate
+    }
+}
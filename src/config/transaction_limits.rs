@@ -0,0 +1,148 @@
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::config::env::read;
+use crate::services::kyc_service::KycTier;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct PeriodLimits {
+    pub daily: Decimal,   // Rolling 24 hours
+    pub monthly: Decimal, // Rolling 30 days
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct TierLimits {
+    pub deposit: PeriodLimits,
+    pub withdrawal: PeriodLimits,
+}
+
+impl TierLimits {
+    // A zero withdrawal cap means the tier cannot withdraw at all
+    pub fn can_withdraw(&self) -> bool {
+        self.withdrawal.daily > Decimal::ZERO && self.withdrawal.monthly > Decimal::ZERO
+    }
+}
+
+// Caps on money moved through M-Pesa, by how well we know the member
+#[derive(Debug, Clone, Deserialize)]
+pub struct TransactionLimits {
+    pub min_deposit: Decimal,
+    pub min_withdrawal: Decimal,
+    pub min_secs_between_withdrawals: i64,
+    pub phone_only: TierLimits,
+    pub id_verified: TierLimits,
+    pub fully_verified: TierLimits,
+}
+
+impl Default for TransactionLimits {
+    fn default() -> Self {
+        Self {
+            min_deposit: Decimal::new(10, 0),
+            min_withdrawal: Decimal::new(10, 0),
+            min_secs_between_withdrawals: 24 * 60 * 60,
+            phone_only: TierLimits {
+                deposit: PeriodLimits {
+                    daily: Decimal::new(10_000, 0),
+                    monthly: Decimal::new(50_000, 0),
+                },
+                withdrawal: PeriodLimits {
+                    daily: Decimal::ZERO,
+                    monthly: Decimal::ZERO,
+                },
+            },
+            id_verified: TierLimits {
+                deposit: PeriodLimits {
+                    daily: Decimal::new(70_000, 0),
+                    monthly: Decimal::new(300_000, 0),
+                },
+                withdrawal: PeriodLimits {
+                    daily: Decimal::new(70_000, 0),
+                    monthly: Decimal::new(300_000, 0),
+                },
+            },
+            fully_verified: TierLimits {
+                deposit: PeriodLimits {
+                    daily: Decimal::new(150_000, 0),
+                    monthly: Decimal::new(1_000_000, 0),
+                },
+                withdrawal: PeriodLimits {
+                    daily: Decimal::new(150_000, 0),
+                    monthly: Decimal::new(1_000_000, 0),
+                },
+            },
+        }
+    }
+}
+
+impl TransactionLimits {
+    // Tier caps are read from LIMITS_<TIER>_<DEPOSIT|WITHDRAWAL>_<DAILY|MONTHLY>,
+    // e.g. LIMITS_ID_VERIFIED_WITHDRAWAL_DAILY
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+
+        Self {
+            min_deposit: read("LIMITS_MIN_DEPOSIT").unwrap_or(defaults.min_deposit),
+            min_withdrawal: read("LIMITS_MIN_WITHDRAWAL").unwrap_or(defaults.min_withdrawal),
+            min_secs_between_withdrawals: read("LIMITS_MIN_SECS_BETWEEN_WITHDRAWALS")
+                .unwrap_or(defaults.min_secs_between_withdrawals),
+            phone_only: tier_from_env("PHONE_ONLY", defaults.phone_only),
+            id_verified: tier_from_env("ID_VERIFIED", defaults.id_verified),
+            fully_verified: tier_from_env("FULLY_VERIFIED", defaults.fully_verified),
+        }
+    }
+
+    pub fn for_tier(&self, tier: KycTier) -> &TierLimits {
+        match tier {
+            KycTier::PhoneOnly => &self.phone_only,
+            KycTier::IdVerified => &self.id_verified,
+            KycTier::FullyVerified => &self.fully_verified,
+        }
+    }
+}
+
+fn tier_from_env(tier: &str, defaults: TierLimits) -> TierLimits {
+    let period = |direction: &str, defaults: PeriodLimits| PeriodLimits {
+        daily: read(&format!("LIMITS_{}_{}_DAILY", tier, direction)).unwrap_or(defaults.daily),
+        monthly: read(&format!("LIMITS_{}_{}_MONTHLY", tier, direction))
+            .unwrap_or(defaults.monthly),
+    };
+
+    TierLimits {
+        deposit: period("DEPOSIT", defaults.deposit),
+        withdrawal: period("WITHDRAWAL", defaults.withdrawal),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_tiers_increase() {
+        let limits = TransactionLimits::default();
+        let tiers = [
+            limits.for_tier(KycTier::PhoneOnly),
+            limits.for_tier(KycTier::IdVerified),
+            limits.for_tier(KycTier::FullyVerified),
+        ];
+
+        for pair in tiers.windows(2) {
+            assert!(pair[0].deposit.daily < pair[1].deposit.daily);
+            assert!(pair[0].deposit.monthly < pair[1].deposit.monthly);
+            assert!(pair[0].withdrawal.monthly < pair[1].withdrawal.monthly);
+        }
+        for tier in tiers {
+            assert!(tier.deposit.daily <= tier.deposit.monthly);
+            assert!(tier.withdrawal.daily <= tier.withdrawal.monthly);
+        }
+    }
+
+    #[test]
+    fn test_only_verified_tiers_can_withdraw() {
+        let limits = TransactionLimits::default();
+
+        assert!(!limits.for_tier(KycTier::PhoneOnly).can_withdraw());
+        assert!(limits.for_tier(KycTier::IdVerified).can_withdraw());
+        assert!(limits.for_tier(KycTier::FullyVerified).can_withdraw());
+    }
+}
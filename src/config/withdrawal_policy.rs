@@ -0,0 +1,51 @@
+use rust_decimal::Decimal;
+use serde::Deserialize;
+
+use crate::config::env::read;
+
+// Rules for getting at savings before retirement
+#[derive(Debug, Clone, Deserialize)]
+pub struct WithdrawalPolicyConfig {
+    pub retirement_age: u8,
+    pub vesting_days: i64, // Contributions younger than this are locked
+    pub early_access_min_membership_days: i64,
+    pub early_access_max_portion: Decimal, // Share of vested savings accessible before retirement
+    pub early_access_penalty_rate: Decimal,
+    pub hardship_max_portion: Decimal,
+    pub hardship_penalty_rate: Decimal,
+}
+
+impl Default for WithdrawalPolicyConfig {
+    fn default() -> Self {
+        Self {
+            retirement_age: 60,
+            vesting_days: 90,
+            early_access_min_membership_days: 365,
+            early_access_max_portion: Decimal::new(50, 2),
+            early_access_penalty_rate: Decimal::new(10, 2),
+            hardship_max_portion: Decimal::new(75, 2),
+            hardship_penalty_rate: Decimal::ZERO,
+        }
+    }
+}
+
+impl WithdrawalPolicyConfig {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+
+        Self {
+            retirement_age: read("WITHDRAWAL_RETIREMENT_AGE").unwrap_or(defaults.retirement_age),
+            vesting_days: read("WITHDRAWAL_VESTING_DAYS").unwrap_or(defaults.vesting_days),
+            early_access_min_membership_days: read("EARLY_ACCESS_MIN_MEMBERSHIP_DAYS")
+                .unwrap_or(defaults.early_access_min_membership_days),
+            early_access_max_portion: read("EARLY_ACCESS_MAX_PORTION")
+                .unwrap_or(defaults.early_access_max_portion),
+            early_access_penalty_rate: read("EARLY_ACCESS_PENALTY_RATE")
+                .unwrap_or(defaults.early_access_penalty_rate),
+            hardship_max_portion: read("HARDSHIP_MAX_PORTION")
+                .unwrap_or(defaults.hardship_max_portion),
+            hardship_penalty_rate: read("HARDSHIP_PENALTY_RATE")
+                .unwrap_or(defaults.hardship_penalty_rate),
+        }
+    }
+}
@@ -1 +1,405 @@
+use soroban_sdk::{
+    contract, contracterror, contractimpl, contracttype, panic_with_error, symbol_short,
+    token::TokenInterface, Address, Env, String,
+};
 
+// One BPT represents one shilling of tokenized pension savings
+pub const BPT_DECIMALS: u32 = 7;
+
+const DAY_IN_LEDGERS: u32 = 17_280;
+const BALANCE_TTL_THRESHOLD: u32 = 30 * DAY_IN_LEDGERS;
+const BALANCE_TTL_EXTEND_TO: u32 = 365 * DAY_IN_LEDGERS;
+
+#[contracttype]
+#[derive(Clone)]
+struct AllowanceKey {
+    from: Address,
+    spender: Address,
+}
+
+#[contracttype]
+#[derive(Clone)]
+struct AllowanceValue {
+    amount: i128,
+    expiration_ledger: u32,
+}
+
+#[contracttype]
+#[derive(Clone)]
+struct Metadata {
+    decimals: u32,
+    name: String,
+    symbol: String,
+}
+
+#[contracttype]
+#[derive(Clone)]
+enum BptDataKey {
+    Admin,
+    Metadata,
+    TotalSupply,
+    Balance(Address),
+    Locked(Address),
+    Allowance(AllowanceKey),
+}
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum BptError {
+    AlreadyInitialized = 1,
+    NotInitialized = 2,
+    InvalidAmount = 3,
+    InsufficientBalance = 4,
+    InsufficientAllowance = 5,
+    BalanceLocked = 6,
+    InvalidExpiration = 7,
+}
+
+// SEP-41 token for tokenized pension savings. Only the fund admin mints;
+// the admin can also lock part of a member's balance (staking lockups,
+// loan collateral) so it cannot be moved until released.
+#[contract]
+pub struct BptToken;
+
+#[contractimpl]
+impl BptToken {
+    pub fn initialize(
+        env: Env,
+        admin: Address,
+        decimals: u32,
+        name: String,
+        symbol: String,
+    ) -> Result<(), BptError> {
+        if env.storage().instance().has(&BptDataKey::Admin) {
+            return Err(BptError::AlreadyInitialized);
+        }
+        admin.require_auth();
+
+        env.storage().instance().set(&BptDataKey::Admin, &admin);
+        env.storage().instance().set(
+            &BptDataKey::Metadata,
+            &Metadata {
+                decimals,
+                name,
+                symbol,
+            },
+        );
+        env.storage()
+            .instance()
+            .set(&BptDataKey::TotalSupply, &0i128);
+        Ok(())
+    }
+
+    pub fn mint(env: Env, to: Address, amount: i128) -> Result<(), BptError> {
+        let admin = Self::admin(env.clone())?;
+        admin.require_auth();
+        check_amount(amount)?;
+
+        write_balance(&env, &to, read_balance(&env, &to) + amount);
+        write_total_supply(&env, read_total_supply(&env) + amount);
+
+        env.events()
+            .publish((symbol_short!("mint"), admin, to), amount);
+        Ok(())
+    }
+
+    // Seizes tokens, drawing on the locked portion first. Used when a
+    // loan secured by BPT defaults or a member's savings are redeemed.
+    pub fn clawback(env: Env, from: Address, amount: i128) -> Result<(), BptError> {
+        let admin = Self::admin(env.clone())?;
+        admin.require_auth();
+        check_amount(amount)?;
+
+        let balance = read_balance(&env, &from);
+        if amount > balance {
+            return Err(BptError::InsufficientBalance);
+        }
+
+        let locked = read_locked(&env, &from);
+        write_locked(&env, &from, locked - amount.min(locked));
+        write_balance(&env, &from, balance - amount);
+        write_total_supply(&env, read_total_supply(&env) - amount);
+
+        env.events()
+            .publish((symbol_short!("clawback"), admin, from), amount);
+        Ok(())
+    }
+
+    pub fn lock(env: Env, id: Address, amount: i128) -> Result<i128, BptError> {
+        let admin = Self::admin(env.clone())?;
+        admin.require_auth();
+        check_amount(amount)?;
+
+        if amount > Self::spendable(env.clone(), id.clone()) {
+            return Err(BptError::InsufficientBalance);
+        }
+
+        let locked = read_locked(&env, &id) + amount;
+        write_locked(&env, &id, locked);
+
+        env.events().publish((symbol_short!("lock"), id), amount);
+        Ok(locked)
+    }
+
+    pub fn unlock(env: Env, id: Address, amount: i128) -> Result<i128, BptError> {
+        let admin = Self::admin(env.clone())?;
+        admin.require_auth();
+        check_amount(amount)?;
+
+        let locked = read_locked(&env, &id);
+        if amount > locked {
+            return Err(BptError::InsufficientBalance);
+        }
+        write_locked(&env, &id, locked - amount);
+
+        env.events().publish((symbol_short!("unlock"), id), amount);
+        Ok(locked - amount)
+    }
+
+    pub fn set_admin(env: Env, new_admin: Address) -> Result<(), BptError> {
+        let admin = Self::admin(env.clone())?;
+        admin.require_auth();
+
+        env.storage().instance().set(&BptDataKey::Admin, &new_admin);
+        env.events()
+            .publish((symbol_short!("set_admin"), admin), new_admin);
+        Ok(())
+    }
+
+    pub fn total_supply(env: Env) -> i128 {
+        read_total_supply(&env)
+    }
+
+    pub fn locked(env: Env, id: Address) -> i128 {
+        read_locked(&env, &id)
+    }
+
+    pub fn spendable(env: Env, id: Address) -> i128 {
+        read_balance(&env, &id) - read_locked(&env, &id)
+    }
+
+    pub fn admin(env: Env) -> Result<Address, BptError> {
+        env.storage()
+            .instance()
+            .get(&BptDataKey::Admin)
+            .ok_or(BptError::NotInitialized)
+    }
+}
+
+#[contractimpl]
+impl TokenInterface for BptToken {
+    fn allowance(env: Env, from: Address, spender: Address) -> i128 {
+        read_allowance(&env, &from, &spender).amount
+    }
+
+    fn approve(env: Env, from: Address, spender: Address, amount: i128, expiration_ledger: u32) {
+        from.require_auth();
+        if amount < 0 {
+            panic_with_error!(&env, BptError::InvalidAmount);
+        }
+        if amount > 0 && expiration_ledger < env.ledger().sequence() {
+            panic_with_error!(&env, BptError::InvalidExpiration);
+        }
+
+        write_allowance(&env, &from, &spender, amount, expiration_ledger);
+        env.events().publish(
+            (symbol_short!("approve"), from, spender),
+            (amount, expiration_ledger),
+        );
+    }
+
+    fn balance(env: Env, id: Address) -> i128 {
+        read_balance(&env, &id)
+    }
+
+    fn transfer(env: Env, from: Address, to: Address, amount: i128) {
+        from.require_auth();
+        move_balance(&env, &from, &to, amount);
+
+        env.events()
+            .publish((symbol_short!("transfer"), from, to), amount);
+    }
+
+    fn transfer_from(env: Env, spender: Address, from: Address, to: Address, amount: i128) {
+        spender.require_auth();
+        spend_allowance(&env, &from, &spender, amount);
+        move_balance(&env, &from, &to, amount);
+
+        env.events()
+            .publish((symbol_short!("transfer"), from, to), amount);
+    }
+
+    fn burn(env: Env, from: Address, amount: i128) {
+        from.require_auth();
+        burn_balance(&env, &from, amount);
+
+        env.events().publish((symbol_short!("burn"), from), amount);
+    }
+
+    fn burn_from(env: Env, spender: Address, from: Address, amount: i128) {
+        spender.require_auth();
+        spend_allowance(&env, &from, &spender, amount);
+        burn_balance(&env, &from, amount);
+
+        env.events().publish((symbol_short!("burn"), from), amount);
+    }
+
+    fn decimals(env: Env) -> u32 {
+        read_metadata(&env).decimals
+    }
+
+    fn name(env: Env) -> String {
+        read_metadata(&env).name
+    }
+
+    fn symbol(env: Env) -> String {
+        read_metadata(&env).symbol
+    }
+}
+
+fn check_amount(amount: i128) -> Result<(), BptError> {
+    if amount <= 0 {
+        return Err(BptError::InvalidAmount);
+    }
+    Ok(())
+}
+
+fn read_metadata(env: &Env) -> Metadata {
+    env.storage()
+        .instance()
+        .get(&BptDataKey::Metadata)
+        .unwrap_or_else(|| panic_with_error!(env, BptError::NotInitialized))
+}
+
+fn read_total_supply(env: &Env) -> i128 {
+    env.storage()
+        .instance()
+        .get(&BptDataKey::TotalSupply)
+        .unwrap_or(0)
+}
+
+fn write_total_supply(env: &Env, amount: i128) {
+    env.storage()
+        .instance()
+        .set(&BptDataKey::TotalSupply, &amount);
+}
+
+fn read_balance(env: &Env, id: &Address) -> i128 {
+    env.storage()
+        .persistent()
+        .get(&BptDataKey::Balance(id.clone()))
+        .unwrap_or(0)
+}
+
+fn write_balance(env: &Env, id: &Address, amount: i128) {
+    let key = BptDataKey::Balance(id.clone());
+    env.storage().persistent().set(&key, &amount);
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, BALANCE_TTL_THRESHOLD, BALANCE_TTL_EXTEND_TO);
+}
+
+fn read_locked(env: &Env, id: &Address) -> i128 {
+    env.storage()
+        .persistent()
+        .get(&BptDataKey::Locked(id.clone()))
+        .unwrap_or(0)
+}
+
+fn write_locked(env: &Env, id: &Address, amount: i128) {
+    let key = BptDataKey::Locked(id.clone());
+    env.storage().persistent().set(&key, &amount);
+    env.storage()
+        .persistent()
+        .extend_ttl(&key, BALANCE_TTL_THRESHOLD, BALANCE_TTL_EXTEND_TO);
+}
+
+fn read_allowance(env: &Env, from: &Address, spender: &Address) -> AllowanceValue {
+    let key = BptDataKey::Allowance(AllowanceKey {
+        from: from.clone(),
+        spender: spender.clone(),
+    });
+
+    match env.storage().temporary().get::<_, AllowanceValue>(&key) {
+        Some(allowance) if allowance.expiration_ledger >= env.ledger().sequence() => allowance,
+        Some(allowance) => AllowanceValue {
+            amount: 0,
+            expiration_ledger: allowance.expiration_ledger,
+        },
+        None => AllowanceValue {
+            amount: 0,
+            expiration_ledger: 0,
+        },
+    }
+}
+
+fn write_allowance(
+    env: &Env,
+    from: &Address,
+    spender: &Address,
+    amount: i128,
+    expiration_ledger: u32,
+) {
+    let key = BptDataKey::Allowance(AllowanceKey {
+        from: from.clone(),
+        spender: spender.clone(),
+    });
+    env.storage().temporary().set(
+        &key,
+        &AllowanceValue {
+            amount,
+            expiration_ledger,
+        },
+    );
+
+    // Keep the entry alive exactly as long as the allowance is valid
+    if amount > 0 {
+        let live_for = expiration_ledger - env.ledger().sequence();
+        env.storage()
+            .temporary()
+            .extend_ttl(&key, live_for, live_for);
+    }
+}
+
+fn spend_allowance(env: &Env, from: &Address, spender: &Address, amount: i128) {
+    let allowance = read_allowance(env, from, spender);
+    if amount > allowance.amount {
+        panic_with_error!(env, BptError::InsufficientAllowance);
+    }
+    write_allowance(
+        env,
+        from,
+        spender,
+        allowance.amount - amount,
+        allowance.expiration_ledger,
+    );
+}
+
+fn debit_spendable(env: &Env, id: &Address, amount: i128) {
+    if amount < 0 {
+        panic_with_error!(env, BptError::InvalidAmount);
+    }
+
+    let balance = read_balance(env, id);
+    if amount > balance {
+        panic_with_error!(env, BptError::InsufficientBalance);
+    }
+    if amount > balance - read_locked(env, id) {
+        panic_with_error!(env, BptError::BalanceLocked);
+    }
+
+    write_balance(env, id, balance - amount);
+}
+
+fn move_balance(env: &Env, from: &Address, to: &Address, amount: i128) {
+    debit_spendable(env, from, amount);
+    write_balance(env, to, read_balance(env, to) + amount);
+}
+
+fn burn_balance(env: &Env, from: &Address, amount: i128) {
+    debit_spendable(env, from, amount);
+    write_total_supply(env, read_total_supply(env) - amount);
+}
+
+mod test;
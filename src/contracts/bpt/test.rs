@@ -0,0 +1,152 @@
+#![cfg(test)]
+
+use super::*;
+use soroban_sdk::testutils::{Address as _, Ledger};
+use soroban_sdk::Env;
+
+fn setup(env: &Env) -> (BptTokenClient<'_>, Address) {
+    env.mock_all_auths();
+
+    let admin = Address::generate(env);
+    let contract_id = env.register(BptToken, ());
+    let client = BptTokenClient::new(env, &contract_id);
+    client.initialize(
+        &admin,
+        &BPT_DECIMALS,
+        &String::from_str(env, "Blupension Pension Token"),
+        &String::from_str(env, "BPT"),
+    );
+
+    (client, admin)
+}
+
+#[test]
+fn test_mint_increases_balance_and_supply() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+    let member = Address::generate(&env);
+
+    client.mint(&member, &1_000);
+
+    assert_eq!(client.balance(&member), 1_000);
+    assert_eq!(client.total_supply(), 1_000);
+    assert_eq!(client.decimals(), BPT_DECIMALS);
+    assert_eq!(client.symbol(), String::from_str(&env, "BPT"));
+
+    let (authorized, _) = env.auths().into_iter().last().unwrap();
+    assert_eq!(authorized, admin);
+}
+
+#[test]
+fn test_locked_balance_cannot_be_transferred() {
+    let env = Env::default();
+    let (client, _) = setup(&env);
+    let member = Address::generate(&env);
+    let other = Address::generate(&env);
+
+    client.mint(&member, &1_000);
+    client.lock(&member, &600);
+
+    assert_eq!(client.spendable(&member), 400);
+    assert_eq!(
+        client.try_transfer(&member, &other, &500),
+        Err(Ok(BptError::BalanceLocked.into()))
+    );
+
+    client.transfer(&member, &other, &400);
+    assert_eq!(client.balance(&other), 400);
+
+    client.unlock(&member, &600);
+    client.transfer(&member, &other, &600);
+    assert_eq!(client.balance(&member), 0);
+}
+
+#[test]
+fn test_cannot_lock_more_than_spendable() {
+    let env = Env::default();
+    let (client, _) = setup(&env);
+    let member = Address::generate(&env);
+
+    client.mint(&member, &1_000);
+    client.lock(&member, &700);
+
+    assert_eq!(
+        client.try_lock(&member, &400),
+        Err(Ok(BptError::InsufficientBalance))
+    );
+}
+
+#[test]
+fn test_clawback_takes_locked_tokens_first() {
+    let env = Env::default();
+    let (client, _) = setup(&env);
+    let member = Address::generate(&env);
+
+    client.mint(&member, &1_000);
+    client.lock(&member, &300);
+    client.clawback(&member, &300);
+
+    assert_eq!(client.balance(&member), 700);
+    assert_eq!(client.locked(&member), 0);
+    assert_eq!(client.total_supply(), 700);
+}
+
+#[test]
+fn test_transfer_from_spends_allowance() {
+    let env = Env::default();
+    let (client, _) = setup(&env);
+    let member = Address::generate(&env);
+    let spender = Address::generate(&env);
+
+    client.mint(&member, &1_000);
+    client.approve(&member, &spender, &500, &100);
+    client.transfer_from(&spender, &member, &spender, &200);
+
+    assert_eq!(client.allowance(&member, &spender), 300);
+    assert_eq!(
+        client.try_transfer_from(&spender, &member, &spender, &301),
+        Err(Ok(BptError::InsufficientAllowance.into()))
+    );
+}
+
+#[test]
+fn test_expired_allowance_is_zero() {
+    let env = Env::default();
+    let (client, _) = setup(&env);
+    let member = Address::generate(&env);
+    let spender = Address::generate(&env);
+
+    client.approve(&member, &spender, &500, &10);
+    env.ledger().with_mut(|ledger| ledger.sequence_number = 11);
+
+    assert_eq!(client.allowance(&member, &spender), 0);
+}
+
+#[test]
+fn test_burn_reduces_supply() {
+    let env = Env::default();
+    let (client, _) = setup(&env);
+    let member = Address::generate(&env);
+
+    client.mint(&member, &1_000);
+    client.burn(&member, &250);
+
+    assert_eq!(client.balance(&member), 750);
+    assert_eq!(client.total_supply(), 750);
+}
+
+#[test]
+fn test_initialize_twice_fails() {
+    let env = Env::default();
+    let (client, admin) = setup(&env);
+
+    assert_eq!(
+        client.try_initialize(
+            &admin,
+            &BPT_DECIMALS,
+            &String::from_str(&env, "Blupension Pension Token"),
+            &String::from_str(&env, "BPT"),
+        ),
+        Err(Ok(BptError::AlreadyInitialized))
+    );
+}
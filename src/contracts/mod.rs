@@ -1,6 +1,14 @@
-// Blockchain contract interactions
+// Blockchain contract interactions. The two contracts both export
+// `initialize`, `admin` and `balance`, so each wasm build carries one of them,
+// picked by its feature; native builds compile both for the services and tests.
+#[cfg(any(not(target_family = "wasm"), feature = "bpt-contract"))]
 pub mod bpt;
+#[cfg(any(not(target_family = "wasm"), feature = "pension-contract"))]
 pub mod pension;
 
-pub use bpt::*;
-pub use pension::*; 
\ No newline at end of file
+#[cfg(all(
+    target_family = "wasm",
+    feature = "bpt-contract",
+    feature = "pension-contract"
+))]
+compile_error!("Build one contract per wasm: enable bpt-contract or pension-contract, not both");
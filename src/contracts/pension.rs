@@ -1 +1,323 @@
+use soroban_sdk::{
+    contract, contracterror, contractimpl, contracttype, symbol_short, token, Address, Env, Map,
+    Symbol,
+};
 
+// Basis points, so allocations can be expressed to 0.01%
+pub const BPS_DENOMINATOR: u32 = 10_000;
+
+const DAY_IN_LEDGERS: u32 = 17_280;
+const MEMBER_TTL_THRESHOLD: u32 = 30 * DAY_IN_LEDGERS;
+const MEMBER_TTL_EXTEND_TO: u32 = 365 * DAY_IN_LEDGERS;
+
+#[contracttype]
+#[derive(Clone)]
+pub enum DataKey {
+    Admin,
+    Token,
+    AssetTotals,
+    Holdings(Address),
+}
+
+// A member's savings, held in one sleeve per asset
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Position {
+    pub holdings: Map<Symbol, i128>,
+    pub allocation: Map<Symbol, u32>, // Basis points per asset, summing to 10,000
+}
+
+impl Position {
+    pub fn balance(&self) -> i128 {
+        self.holdings.values().iter().sum()
+    }
+}
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum PensionError {
+    AlreadyInitialized = 1,
+    NotInitialized = 2,
+    InvalidAmount = 3,
+    InsufficientBalance = 4,
+    InvalidAllocation = 5,
+    NoPosition = 6,
+}
+
+// New members start on the moderate plan (50% USDC / 50% BTC)
+pub fn default_allocation(env: &Env) -> Map<Symbol, u32> {
+    Map::from_array(
+        env,
+        [
+            (symbol_short!("USDC"), 5_000),
+            (symbol_short!("BTC"), 5_000),
+        ],
+    )
+}
+
+#[contract]
+pub struct PensionFund;
+
+#[contractimpl]
+impl PensionFund {
+    pub fn initialize(env: Env, admin: Address, token: Address) -> Result<(), PensionError> {
+        if env.storage().instance().has(&DataKey::Admin) {
+            return Err(PensionError::AlreadyInitialized);
+        }
+        admin.require_auth();
+
+        env.storage().instance().set(&DataKey::Admin, &admin);
+        env.storage().instance().set(&DataKey::Token, &token);
+        env.storage()
+            .instance()
+            .set(&DataKey::AssetTotals, &Map::<Symbol, i128>::new(&env));
+
+        env.events()
+            .publish((symbol_short!("init"),), (admin, token));
+        Ok(())
+    }
+
+    // Changes the member's allocation and moves their existing savings onto it
+    pub fn set_allocation(
+        env: Env,
+        user: Address,
+        allocation: Map<Symbol, u32>,
+    ) -> Result<Position, PensionError> {
+        Self::token(&env)?;
+        user.require_auth();
+
+        let allocation = validate_allocation(&env, &allocation)?;
+
+        let mut position = Self::load_position(&env, &user);
+        let mut totals = Self::load_totals(&env);
+        subtract(&mut totals, &position.holdings);
+
+        position.holdings = split(&env, position.balance(), &allocation);
+        position.allocation = allocation.clone();
+
+        add(&mut totals, &position.holdings);
+        Self::save(&env, &user, &position, &totals);
+
+        env.events()
+            .publish((symbol_short!("alloc"), user), allocation);
+        Ok(position)
+    }
+
+    pub fn deposit(env: Env, user: Address, amount: i128) -> Result<Position, PensionError> {
+        let token = Self::token(&env)?;
+        user.require_auth();
+
+        if amount <= 0 {
+            return Err(PensionError::InvalidAmount);
+        }
+
+        token::Client::new(&env, &token).transfer(&user, &env.current_contract_address(), &amount);
+
+        let mut position = Self::load_position(&env, &user);
+        let parts = split(&env, amount, &position.allocation);
+        add(&mut position.holdings, &parts);
+
+        let mut totals = Self::load_totals(&env);
+        add(&mut totals, &parts);
+        Self::save(&env, &user, &position, &totals);
+
+        env.events()
+            .publish((symbol_short!("deposit"), user), (amount, parts));
+        Ok(position)
+    }
+
+    pub fn withdraw(env: Env, user: Address, amount: i128) -> Result<Position, PensionError> {
+        let token = Self::token(&env)?;
+        user.require_auth();
+
+        if amount <= 0 {
+            return Err(PensionError::InvalidAmount);
+        }
+
+        let mut position = Self::position(env.clone(), user.clone())?;
+
+        let balance = position.balance();
+        if amount > balance {
+            return Err(PensionError::InsufficientBalance);
+        }
+
+        // Draw from every sleeve in proportion to what the member holds
+        let parts = draw(&env, amount, &position.holdings);
+        subtract(&mut position.holdings, &parts);
+
+        let mut totals = Self::load_totals(&env);
+        subtract(&mut totals, &parts);
+        Self::save(&env, &user, &position, &totals);
+
+        token::Client::new(&env, &token).transfer(&env.current_contract_address(), &user, &amount);
+
+        env.events()
+            .publish((symbol_short!("withdraw"), user), (amount, parts));
+        Ok(position)
+    }
+
+    pub fn balance(env: Env, user: Address) -> i128 {
+        Self::load_position(&env, &user).balance()
+    }
+
+    pub fn position(env: Env, user: Address) -> Result<Position, PensionError> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Holdings(user))
+            .ok_or(PensionError::NoPosition)
+    }
+
+    pub fn totals(env: Env) -> Map<Symbol, i128> {
+        Self::load_totals(&env)
+    }
+
+    pub fn admin(env: Env) -> Result<Address, PensionError> {
+        env.storage()
+            .instance()
+            .get(&DataKey::Admin)
+            .ok_or(PensionError::NotInitialized)
+    }
+
+    fn token(env: &Env) -> Result<Address, PensionError> {
+        env.storage()
+            .instance()
+            .get(&DataKey::Token)
+            .ok_or(PensionError::NotInitialized)
+    }
+
+    fn load_position(env: &Env, user: &Address) -> Position {
+        Self::position(env.clone(), user.clone()).unwrap_or_else(|_| Position {
+            holdings: Map::new(env),
+            allocation: default_allocation(env),
+        })
+    }
+
+    fn load_totals(env: &Env) -> Map<Symbol, i128> {
+        env.storage()
+            .instance()
+            .get(&DataKey::AssetTotals)
+            .unwrap_or_else(|| Map::new(env))
+    }
+
+    fn save(env: &Env, user: &Address, position: &Position, totals: &Map<Symbol, i128>) {
+        let key = DataKey::Holdings(user.clone());
+        env.storage().persistent().set(&key, position);
+        env.storage()
+            .persistent()
+            .extend_ttl(&key, MEMBER_TTL_THRESHOLD, MEMBER_TTL_EXTEND_TO);
+
+        env.storage().instance().set(&DataKey::AssetTotals, totals);
+    }
+}
+
+// Drops zero weights; what is left must cover exactly 100%
+fn validate_allocation(
+    env: &Env,
+    allocation: &Map<Symbol, u32>,
+) -> Result<Map<Symbol, u32>, PensionError> {
+    let mut kept = Map::new(env);
+    let mut total: u64 = 0;
+    for (asset, bps) in allocation.iter() {
+        if bps > BPS_DENOMINATOR {
+            return Err(PensionError::InvalidAllocation);
+        }
+        if bps > 0 {
+            kept.set(asset, bps);
+            total += bps as u64;
+        }
+    }
+    if total != BPS_DENOMINATOR as u64 {
+        return Err(PensionError::InvalidAllocation);
+    }
+
+    Ok(kept)
+}
+
+// Splits an amount by allocation. Rounding leftovers go to the asset with
+// the largest weight so the parts always add back up to the amount.
+fn split(env: &Env, amount: i128, allocation: &Map<Symbol, u32>) -> Map<Symbol, i128> {
+    let mut parts = Map::new(env);
+    let mut assigned = 0;
+    let mut largest: Option<(Symbol, u32)> = None;
+
+    for (asset, bps) in allocation.iter() {
+        let part = amount * bps as i128 / BPS_DENOMINATOR as i128;
+        assigned += part;
+        parts.set(asset.clone(), part);
+
+        let is_largest = match &largest {
+            Some((_, most)) => bps > *most,
+            None => true,
+        };
+        if is_largest {
+            largest = Some((asset, bps));
+        }
+    }
+
+    if let Some((asset, _)) = largest {
+        let part = parts.get(asset.clone()).unwrap_or(0);
+        parts.set(asset, part + amount - assigned);
+    }
+    without_zeros(env, parts)
+}
+
+// Takes an amount out of the holdings pro rata. Rounding leftovers come out
+// of whichever sleeves still have room, so the draw is exact.
+fn draw(env: &Env, amount: i128, holdings: &Map<Symbol, i128>) -> Map<Symbol, i128> {
+    let balance: i128 = holdings.values().iter().sum();
+    let mut parts = Map::new(env);
+    if balance <= 0 {
+        return parts;
+    }
+
+    let mut assigned = 0;
+    for (asset, held) in holdings.iter() {
+        let part = amount * held / balance;
+        assigned += part;
+        parts.set(asset, part);
+    }
+
+    let mut left = amount - assigned;
+    for (asset, held) in holdings.iter() {
+        if left == 0 {
+            break;
+        }
+        let part = parts.get(asset.clone()).unwrap_or(0);
+        let extra = (held - part).min(left);
+        parts.set(asset, part + extra);
+        left -= extra;
+    }
+
+    without_zeros(env, parts)
+}
+
+fn add(target: &mut Map<Symbol, i128>, parts: &Map<Symbol, i128>) {
+    for (asset, part) in parts.iter() {
+        let current = target.get(asset.clone()).unwrap_or(0);
+        target.set(asset, current + part);
+    }
+}
+
+fn subtract(target: &mut Map<Symbol, i128>, parts: &Map<Symbol, i128>) {
+    for (asset, part) in parts.iter() {
+        let remaining = target.get(asset.clone()).unwrap_or(0) - part;
+        if remaining == 0 {
+            target.remove(asset);
+        } else {
+            target.set(asset, remaining);
+        }
+    }
+}
+
+fn without_zeros(env: &Env, amounts: Map<Symbol, i128>) -> Map<Symbol, i128> {
+    let mut kept = Map::new(env);
+    for (asset, amount) in amounts.iter() {
+        if amount != 0 {
+            kept.set(asset, amount);
+        }
+    }
+    kept
+}
+
+mod test;
@@ -0,0 +1,176 @@
+#![cfg(test)]
+
+use super::*;
+use soroban_sdk::testutils::{Address as _, Events};
+use soroban_sdk::{token::StellarAssetClient, Env};
+
+fn setup(env: &Env) -> (PensionFundClient<'_>, Address, token::Client<'_>) {
+    env.mock_all_auths();
+
+    let admin = Address::generate(env);
+    let asset = env.register_stellar_asset_contract_v2(admin.clone());
+    let contract_id = env.register(PensionFund, ());
+    let client = PensionFundClient::new(env, &contract_id);
+    client.initialize(&admin, &asset.address());
+
+    let member = Address::generate(env);
+    StellarAssetClient::new(env, &asset.address()).mint(&member, &10_000);
+
+    (client, member, token::Client::new(env, &asset.address()))
+}
+
+#[test]
+fn test_deposit_splits_by_member_allocation() {
+    let env = Env::default();
+    let (client, member, token) = setup(&env);
+
+    let allocation = Map::from_array(
+        &env,
+        [
+            (symbol_short!("USDC"), 6_000),
+            (symbol_short!("BTC"), 2_500),
+            (symbol_short!("TBILL"), 1_500),
+        ],
+    );
+    client.set_allocation(&member, &allocation);
+    let position = client.deposit(&member, &1_000);
+
+    let expected = Map::from_array(
+        &env,
+        [
+            (symbol_short!("USDC"), 600),
+            (symbol_short!("BTC"), 250),
+            (symbol_short!("TBILL"), 150),
+        ],
+    );
+    assert_eq!(position.holdings, expected);
+    assert_eq!(position.allocation, allocation);
+    assert_eq!(client.balance(&member), 1_000);
+    assert_eq!(token.balance(&member), 9_000);
+    assert_eq!(token.balance(&client.address), 1_000);
+    assert_eq!(client.totals(), expected);
+}
+
+#[test]
+fn test_deposit_assigns_rounding_to_largest_weight() {
+    let env = Env::default();
+    let (client, member, _) = setup(&env);
+
+    client.set_allocation(
+        &member,
+        &Map::from_array(
+            &env,
+            [
+                (symbol_short!("USDC"), 3_333),
+                (symbol_short!("BTC"), 3_334),
+                (symbol_short!("XLM"), 3_333),
+            ],
+        ),
+    );
+    let position = client.deposit(&member, &100);
+
+    assert_eq!(position.holdings.get(symbol_short!("USDC")), Some(33));
+    assert_eq!(position.holdings.get(symbol_short!("BTC")), Some(34));
+    assert_eq!(position.holdings.get(symbol_short!("XLM")), Some(33));
+}
+
+#[test]
+fn test_deposit_requires_member_auth() {
+    let env = Env::default();
+    let (client, member, _) = setup(&env);
+
+    client.deposit(&member, &1_000);
+
+    let (authorized, _) = env.auths().into_iter().last().unwrap();
+    assert_eq!(authorized, member);
+}
+
+#[test]
+fn test_withdraw_draws_proportionally_and_pays_member() {
+    let env = Env::default();
+    let (client, member, token) = setup(&env);
+
+    client.deposit(&member, &1_000);
+    let position = client.withdraw(&member, &400);
+
+    assert_eq!(position.holdings.get(symbol_short!("USDC")), Some(300));
+    assert_eq!(position.holdings.get(symbol_short!("BTC")), Some(300));
+    assert_eq!(token.balance(&member), 9_400);
+    assert!(!env.events().all().is_empty());
+}
+
+#[test]
+fn test_set_allocation_rebalances_existing_savings() {
+    let env = Env::default();
+    let (client, member, _) = setup(&env);
+
+    client.deposit(&member, &1_000);
+    let position = client.set_allocation(
+        &member,
+        &Map::from_array(
+            &env,
+            [
+                (symbol_short!("USDC"), 2_000),
+                (symbol_short!("KES_MMF"), 8_000),
+            ],
+        ),
+    );
+
+    let expected = Map::from_array(
+        &env,
+        [
+            (symbol_short!("USDC"), 200),
+            (symbol_short!("KES_MMF"), 800),
+        ],
+    );
+    assert_eq!(position.holdings, expected);
+    assert_eq!(client.totals(), expected);
+}
+
+#[test]
+fn test_overdraft_is_rejected() {
+    let env = Env::default();
+    let (client, member, token) = setup(&env);
+
+    client.deposit(&member, &1_000);
+
+    assert_eq!(
+        client.try_withdraw(&member, &1_001),
+        Err(Ok(PensionError::InsufficientBalance))
+    );
+    assert_eq!(client.balance(&member), 1_000);
+    assert_eq!(token.balance(&member), 9_000);
+}
+
+#[test]
+fn test_cannot_initialize_twice() {
+    let env = Env::default();
+    let (client, member, token) = setup(&env);
+
+    assert_eq!(
+        client.try_initialize(&member, &token.address),
+        Err(Ok(PensionError::AlreadyInitialized))
+    );
+}
+
+#[test]
+fn test_rejects_invalid_allocation() {
+    let env = Env::default();
+    let (client, member, _) = setup(&env);
+
+    let short = Map::from_array(
+        &env,
+        [
+            (symbol_short!("USDC"), 6_000),
+            (symbol_short!("BTC"), 3_000),
+        ],
+    );
+    let oversized = Map::from_array(&env, [(symbol_short!("BTC"), 10_001)]);
+
+    for allocation in [short, oversized] {
+        assert_eq!(
+            client.try_set_allocation(&member, &allocation),
+            Err(Ok(PensionError::InvalidAllocation))
+        );
+    }
+}
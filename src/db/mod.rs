@@ -1,16 +1,86 @@
-use sqlx::PgPool;
+use sqlx::{migrate::Migrator, PgPool};
 use anyhow::Result;
 
-pub mod schema;
 pub mod models;
 
+// Every table the services touch is created by these migrations; nothing
+// else creates schema at runtime.
+pub static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
+
 pub async fn init_pool(database_url: &str) -> Result<PgPool> {
     let pool = PgPool::connect(database_url).await?;
     
     // Run migrations
-    sqlx::migrate!("./migrations")
-        .run(&pool)
-        .await?;
+    MIGRATOR.run(&pool).await?;
     
     Ok(pool)
-} 
\ No newline at end of file
+}
+
+// Applies every pending migration, returning the versions now applied
+pub async fn migrate(pool: &PgPool) -> Result<Vec<i64>> {
+    MIGRATOR.run(pool).await?;
+    applied_versions(pool).await
+}
+
+// Reverts the `steps` most recently applied migrations, returning the
+// versions that were undone. The baseline migration has no down script and
+// stays applied.
+pub async fn rollback(pool: &PgPool, steps: usize) -> Result<Vec<i64>> {
+    let applied = applied_versions(pool).await?;
+    let target = rollback_target(&applied, steps);
+
+    MIGRATOR.undo(pool, target).await?;
+
+    let remaining = applied_versions(pool).await?;
+    Ok(applied.into_iter().filter(|v| !remaining.contains(v)).collect())
+}
+
+async fn applied_versions(pool: &PgPool) -> Result<Vec<i64>> {
+    let versions = sqlx::query_scalar::<_, i64>(
+        "SELECT version FROM _sqlx_migrations WHERE success ORDER BY version",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(versions)
+}
+
+// Version to roll back to: everything above it is undone, 0 undoes all
+fn rollback_target(applied: &[i64], steps: usize) -> i64 {
+    if steps >= applied.len() {
+        0
+    } else {
+        applied[applied.len() - steps - 1]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rollback_target() {
+        let applied = [20240101000000, 20250301000000, 20250302000000];
+
+        assert_eq!(rollback_target(&applied, 0), 20250302000000);
+        assert_eq!(rollback_target(&applied, 1), 20250301000000);
+        assert_eq!(rollback_target(&applied, 2), 20240101000000);
+        assert_eq!(rollback_target(&applied, 3), 0);
+        assert_eq!(rollback_target(&applied, 10), 0);
+    }
+
+    // Shipped before migrations had down scripts, and left as shipped so
+    // its checksum still matches databases that applied it
+    const BASELINE_VERSION: i64 = 20240101000000;
+
+    #[test]
+    fn test_migrations_are_reversible() {
+        for migration in MIGRATOR.iter().filter(|m| m.version != BASELINE_VERSION) {
+            assert!(
+                migration.migration_type.is_reversible(),
+                "migration {} has no down script",
+                migration.version
+            );
+        }
+    }
+}
@@ -6,7 +6,7 @@ use axum::{
 use serde_json::json;
 use thiserror::Error;
 use rust_decimal::Decimal;
-use actix_web::{HttpResponse, ResponseError};
+use chrono::{DateTime, Utc};
 use jsonwebtoken::errors;
 
 #[derive(Error, Debug)]
@@ -21,7 +21,7 @@ pub enum Error {
     Database(#[from] sqlx::Error),
     
     #[error(transparent)]
-    Other(#[from] anyhow::Error),
+    Other(anyhow::Error),
     
     #[error("Invalid amount")]
     InvalidAmount,
@@ -47,8 +47,113 @@ pub enum Error {
     #[error("Monthly withdrawal limit exceeded")]
     MonthlyWithdrawalLimitExceeded,
 
+    #[error("Withdrawal not permitted: {0}")]
+    WithdrawalNotPermitted(String),
+
+    #[error("Invalid contribution schedule: {0}")]
+    InvalidSchedule(String),
+
+    #[error("Deposit amount too small (minimum: {0})")]
+    DepositTooSmall(Decimal),
+
+    #[error("Daily deposit limit exceeded")]
+    DailyDepositLimitExceeded,
+
+    #[error("Monthly deposit limit exceeded")]
+    MonthlyDepositLimitExceeded,
+
     #[error("JWT error: {0}")]
     JWT(#[from] errors::Error),
+
+    #[error("{0} not found")]
+    NotFound(String),
+
+    #[error("Member already has a pension fund")]
+    FundAlreadyExists,
+
+    #[error("No wallet linked to this account")]
+    WalletNotFound,
+
+    #[error("BPT cannot be sent to another member")]
+    MemberTransfer,
+
+    #[error("Invalid lockup period: {0} days")]
+    InvalidLockupPeriod(u32),
+
+    #[error("Stake is locked until {0}")]
+    StakeLocked(DateTime<Utc>),
+
+    #[error("Blockchain error: {0}")]
+    Blockchain(String),
+
+    #[error("Loan not available: {0}")]
+    LoanNotEligible(String),
+
+    #[error("Invalid phone number")]
+    InvalidPhoneNumber,
+
+    #[error("Invalid PIN")]
+    InvalidPin,
+
+    #[error("Invalid or expired verification code")]
+    InvalidOtp,
+
+    #[error("Too many verification codes requested")]
+    OtpRateLimited,
+
+    #[error("Phone number already registered")]
+    PhoneAlreadyRegistered,
+
+    #[error("Account locked until {0}")]
+    AccountLocked(DateTime<Utc>),
+
+    #[error("Identity verification required")]
+    KycNotApproved,
+
+    #[error("Identity verification already submitted")]
+    KycAlreadySubmitted,
+
+    #[error("KYC provider error: {0}")]
+    KycProvider(String),
+
+    #[error("Platform administrators only")]
+    AdminOnly,
+
+    #[error("Not an administrator of this employer")]
+    NotEmployerAdmin,
+
+    #[error("Employer has not been approved")]
+    EmployerNotApproved,
+
+    #[error("Payroll batch is not awaiting remittance")]
+    PayrollBatchNotPending,
+
+    #[error("Remittance of {0} does not match the payroll batch")]
+    RemittanceMismatch(Decimal),
+
+    #[error("Employer already registered")]
+    EmployerAlreadyRegistered,
+
+    #[error("Invalid payroll: {0}")]
+    InvalidPayroll(String),
+
+    #[error("Payroll batch {0} already uploaded")]
+    DuplicatePayrollBatch(String),
+
+    #[error("Staff number {0} already assigned")]
+    StaffNumberTaken(String),
+
+    #[error("Invalid price query: {0}")]
+    InvalidPriceQuery(String),
+
+    #[error("Invalid price import: {0}")]
+    InvalidPriceImport(String),
+
+    #[error("Invalid allocation: {0}")]
+    InvalidAllocation(String),
+
+    #[error("No execution venue configured")]
+    NoExecutionVenue,
 }
 
 impl IntoResponse for Error {
@@ -78,7 +183,117 @@ impl IntoResponse for Error {
                 StatusCode::BAD_REQUEST,
                 "Monthly withdrawal limit exceeded".to_string(),
             ),
+            Error::WithdrawalNotPermitted(ref reason) => (StatusCode::FORBIDDEN, reason.clone()),
+            Error::InvalidSchedule(ref reason) => (StatusCode::BAD_REQUEST, reason.clone()),
+            Error::DepositTooSmall(min) => (
+                StatusCode::BAD_REQUEST,
+                format!("Minimum deposit amount is {}", min),
+            ),
+            Error::DailyDepositLimitExceeded => (
+                StatusCode::BAD_REQUEST,
+                "Daily deposit limit exceeded".to_string(),
+            ),
+            Error::MonthlyDepositLimitExceeded => (
+                StatusCode::BAD_REQUEST,
+                "Monthly deposit limit exceeded".to_string(),
+            ),
             Error::JWT(_) => (StatusCode::INTERNAL_SERVER_ERROR, "JWT error"),
+            Error::NotFound(ref what) => (StatusCode::NOT_FOUND, format!("{} not found", what)),
+            Error::FundAlreadyExists => (
+                StatusCode::CONFLICT,
+                "You already have a pension fund".to_string(),
+            ),
+            Error::WalletNotFound => (
+                StatusCode::BAD_REQUEST,
+                "Link a Stellar wallet before using BPT".to_string(),
+            ),
+            Error::MemberTransfer => (
+                StatusCode::FORBIDDEN,
+                "Pension tokens cannot be sent to another member's wallet".to_string(),
+            ),
+            Error::InvalidLockupPeriod(days) => (
+                StatusCode::BAD_REQUEST,
+                format!("Unsupported lockup period of {} days", days),
+            ),
+            Error::StakeLocked(until) => (
+                StatusCode::BAD_REQUEST,
+                format!("Stake is locked until {}", until.to_rfc3339()),
+            ),
+            Error::Blockchain(ref e) => (StatusCode::BAD_GATEWAY, e.clone()),
+            Error::LoanNotEligible(ref reason) => (StatusCode::BAD_REQUEST, reason.clone()),
+            Error::InvalidPhoneNumber => (
+                StatusCode::BAD_REQUEST,
+                "Enter a Kenyan mobile number".to_string(),
+            ),
+            Error::InvalidPin => (
+                StatusCode::BAD_REQUEST,
+                "PIN must be 4 to 6 digits".to_string(),
+            ),
+            Error::InvalidOtp => (
+                StatusCode::BAD_REQUEST,
+                "Invalid or expired verification code".to_string(),
+            ),
+            Error::OtpRateLimited => (
+                StatusCode::TOO_MANY_REQUESTS,
+                "Please wait before requesting another code".to_string(),
+            ),
+            Error::PhoneAlreadyRegistered => (
+                StatusCode::CONFLICT,
+                "Phone number already registered".to_string(),
+            ),
+            Error::AccountLocked(until) => (
+                StatusCode::LOCKED,
+                format!("Too many wrong PINs, try again after {}", until.to_rfc3339()),
+            ),
+            Error::KycNotApproved => (
+                StatusCode::FORBIDDEN,
+                "Verify your identity before withdrawing or investing".to_string(),
+            ),
+            Error::KycAlreadySubmitted => (
+                StatusCode::CONFLICT,
+                "Identity verification already submitted".to_string(),
+            ),
+            Error::KycProvider(ref e) => (StatusCode::BAD_GATEWAY, e.clone()),
+            Error::AdminOnly => (
+                StatusCode::FORBIDDEN,
+                "Only platform administrators can do this".to_string(),
+            ),
+            Error::EmployerNotApproved => (
+                StatusCode::FORBIDDEN,
+                "The employer is awaiting approval".to_string(),
+            ),
+            Error::PayrollBatchNotPending => (
+                StatusCode::CONFLICT,
+                "The payroll batch is not awaiting remittance".to_string(),
+            ),
+            Error::RemittanceMismatch(amount) => (
+                StatusCode::BAD_REQUEST,
+                format!("A remittance of {} does not match the batch total", amount),
+            ),
+            Error::NotEmployerAdmin => (
+                StatusCode::FORBIDDEN,
+                "Only the employer's administrators can do this".to_string(),
+            ),
+            Error::EmployerAlreadyRegistered => (
+                StatusCode::CONFLICT,
+                "An employer with this registration number already exists".to_string(),
+            ),
+            Error::InvalidPayroll(ref reason) => (StatusCode::BAD_REQUEST, reason.clone()),
+            Error::DuplicatePayrollBatch(ref reference) => (
+                StatusCode::CONFLICT,
+                format!("Payroll batch {} has already been uploaded", reference),
+            ),
+            Error::StaffNumberTaken(ref staff_number) => (
+                StatusCode::CONFLICT,
+                format!("Staff number {} belongs to another member", staff_number),
+            ),
+            Error::InvalidPriceQuery(ref reason) => (StatusCode::BAD_REQUEST, reason.clone()),
+            Error::InvalidPriceImport(ref reason) => (StatusCode::BAD_REQUEST, reason.clone()),
+            Error::InvalidAllocation(ref reason) => (StatusCode::BAD_REQUEST, reason.clone()),
+            Error::NoExecutionVenue => (
+                StatusCode::SERVICE_UNAVAILABLE,
+                "Rebalancing trades cannot be executed right now".to_string(),
+            ),
         };
 
         let body = Json(json!({
@@ -89,12 +304,11 @@ impl IntoResponse for Error {
     }
 }
 
-impl ResponseError for Error {
-    fn error_response(&self) -> HttpResponse {
-        match self {
-            Error::Unauthorized => HttpResponse::Unauthorized().finish(),
-            _ => HttpResponse::InternalServerError().finish(),
-        }
+// Services on anyhow wrap our own errors; unwrap them so a limit or KYC
+// failure keeps its status code instead of becoming a 500
+impl From<anyhow::Error> for Error {
+    fn from(err: anyhow::Error) -> Self {
+        err.downcast::<Error>().unwrap_or_else(Error::Other)
     }
 }
 
@@ -1,23 +1,23 @@
 #![cfg_attr(target_family = "wasm", no_std)]
 
+pub mod ai;
 pub mod api;
 pub mod contracts;
 pub mod db;
+pub mod error;
 pub mod services;
 pub mod models;
 pub mod auth;
+pub mod config;
 pub mod utils;
 
 #[cfg(not(target_family = "wasm"))]
 use {
     anyhow::Result,
     sqlx::postgres::PgPool,
-    actix_web::{web, App, HttpServer, http},
-    std::env,
+    std::sync::Arc,
 };
 
-mod controllers;
-
 use soroban_sdk::{contractimpl, symbol_short, vec, Env, Symbol, Vec};
 
 pub struct Contract;
@@ -53,52 +53,232 @@ pub async fn initialize() -> Result<PgPool> {
     // Initialize database connection
     let database_url = std::env::var("DATABASE_URL")
         .expect("DATABASE_URL must be set");
-    
-    let pool = PgPool::connect(&database_url).await?;
-    
-    // Initialize database schema
-    db::schema::init_db(&pool).await?;
-    
-    Ok(pool)
+
+    // Bring the schema up to date before anything queries it
+    db::init_pool(&database_url).await
 }
 
 #[cfg(not(target_family = "wasm"))]
-pub async fn run() -> std::io::Result<()> {
+async fn connect() -> Result<PgPool> {
     dotenv::dotenv().ok();
-    env_logger::init();
 
     let database_url = std::env::var("DATABASE_URL")
         .expect("DATABASE_URL must be set");
-    
-    let pool = PgPool::connect(&database_url)
-        .await
-        .expect("Failed to connect to Postgres");
+
+    Ok(PgPool::connect(&database_url).await?)
+}
+
+// `blupension migrate`
+#[cfg(not(target_family = "wasm"))]
+pub async fn migrate() -> Result<()> {
+    let pool = connect().await?;
+    let applied = db::migrate(&pool).await?;
+
+    match applied.last() {
+        Some(version) => println!(
+            "Schema at version {} ({} migrations applied)",
+            version,
+            applied.len()
+        ),
+        None => println!("No migrations applied"),
+    }
+    Ok(())
+}
+
+// `blupension rollback [steps]`
+#[cfg(not(target_family = "wasm"))]
+pub async fn rollback(steps: usize) -> Result<()> {
+    let pool = connect().await?;
+    let reverted = db::rollback(&pool, steps).await?;
+
+    for version in reverted.iter().rev() {
+        println!("Reverted {}", version);
+    }
+    Ok(())
+}
+
+// `blupension import-prices <file>`: backfills price history from a CSV of
+// symbol,timestamp,price[,volume_24h] rows
+#[cfg(not(target_family = "wasm"))]
+pub async fn import_prices(path: &str) -> Result<()> {
+    let pool = connect().await?;
+    let body = std::fs::read(path)?;
+
+    let report = services::PriceHistoryService::new(
+        pool,
+        config::price_history::PriceHistoryConfig::from_env(),
+    )
+    .import_csv(&body)
+    .await?;
+
+    println!(
+        "Imported {} prices ({} already recorded, {} rejected)",
+        report.inserted,
+        report.duplicates,
+        report.rejected.len()
+    );
+    for row in &report.rejected {
+        println!("  line {}: {}", row.line_number, row.reason);
+    }
+    Ok(())
+}
+
+#[cfg(not(target_family = "wasm"))]
+pub async fn run() -> Result<()> {
+    dotenv::dotenv().ok();
+    tracing_subscriber::fmt::init();
+
+    // Fail at boot rather than on the first authenticated request
+    auth::jwt_secret();
+    let pool = initialize().await?;
 
     let stellar_service = services::stellar::StellarService::new(
         &std::env::var("STELLAR_NETWORK").unwrap_or_else(|_| "testnet".to_string()),
         &std::env::var("STELLAR_SECRET_KEY").expect("STELLAR_SECRET_KEY must be set"),
     )?;
 
-    HttpServer::new(move || {
-        App::new()
-            .app_data(web::Data::new(pool.clone()))
-            .app_data(web::Data::new(stellar_service.clone()))
-            .wrap(
-                actix_cors::Cors::default()
-                    .allowed_headers(vec![
-                        http::header::AUTHORIZATION,
-                        http::header::ACCEPT,
-                        http::header::CONTENT_TYPE,
-                    ])
-                    .max_age(3600)
-            )
-            .configure(api::config)
-    })
-    .bind("127.0.0.1:8080")?
-    .run()
-    .await
-}
+    let withdrawal_policy = config::withdrawal_policy::WithdrawalPolicyConfig::from_env();
+    let fund_service = services::fund_service::FundService::new(
+        pool.clone(),
+        config::transaction_limits::TransactionLimits::from_env(),
+        withdrawal_policy.clone(),
+    );
+    let mpesa_service = services::mpesa_service::MPesaService::new()?;
+    let bpt_manager = services::BPTManager::new(
+        pool.clone(),
+        stellar_service,
+        std::env::var("BPT_CONTRACT_ID").expect("BPT_CONTRACT_ID must be set"),
+    )
+    .await;
+    let loan_service = services::LoanService::new(
+        pool.clone(),
+        bpt_manager.clone(),
+        mpesa_service.clone(),
+        config::loans::LoanConfig::from_env(),
+    );
+
+    // Resolve deposits whose M-Pesa callback never arrived
+    services::DepositSweeper::new(
+        Arc::new(fund_service.clone()),
+        Arc::new(mpesa_service.clone()),
+        config::deposit_sweeper::DepositSweeperConfig::from_env(),
+    )
+    .spawn();
+
+    // Accrue loan interest, chase due installments and liquidate defaults
+    services::LoanMonitor::new(Arc::new(loan_service.clone())).spawn();
+
+    let phone_auth_service = services::PhoneAuthService::new(
+        pool.clone(),
+        services::notification_service::NotificationService::new()?,
+        config::phone_auth::PhoneAuthConfig::from_env(),
+    );
+    let ussd_service = services::UssdService::new(
+        phone_auth_service.clone(),
+        fund_service.clone(),
+        mpesa_service.clone(),
+        std::env::var("USSD_GATEWAY_TOKEN")?,
+    );
+
+    let kyc_service = services::KycService::new(
+        pool.clone(),
+        Arc::new(services::SmileIDClient::new()?),
+    );
+
+    // Resolve KYC jobs whose provider callback never arrived
+    services::KycPoller::new(
+        Arc::new(kyc_service.clone()),
+        config::kyc::KycConfig::from_env(),
+    )
+    .spawn();
+
+    let contribution_service = services::ContributionService::new(
+        pool.clone(),
+        fund_service.clone(),
+        mpesa_service.clone(),
+        services::notification_service::NotificationService::new()?,
+        config::contributions::ContributionConfig::from_env(),
+    );
+
+    // Prompt members for their scheduled daily and weekly contributions
+    services::ContributionScheduler::new(Arc::new(contribution_service.clone())).spawn();
 
-pub async fn start_server() {
-    // Server implementation
-} 
\ No newline at end of file
+    let price_history_service = services::PriceHistoryService::new(
+        pool.clone(),
+        config::price_history::PriceHistoryConfig::from_env(),
+    );
+
+    // One feed, and so one price cache, for ingestion and the rebalancer
+    let price_feed = services::price_feed::PriceFeedService::from_env()?;
+
+    // Record asset prices and the KES rate, and roll them into candles
+    services::PriceIngestor::new(
+        Arc::new(price_history_service.clone()),
+        price_feed.clone(),
+        services::price_feed::PriceFeedService::fx_from_env()?,
+    )
+    .spawn();
+
+    // There is no live venue yet. Paper fills at the latest recorded prices
+    // are opt-in for non-production deployments; otherwise rebalancing is
+    // refused rather than quoted or filled against a market that isn't real.
+    let rebalancing_config = config::rebalancing::RebalancingConfig::from_env();
+    let venue: Option<Arc<dyn services::rebalancing::ExecutionVenue>> =
+        if rebalancing_config.paper_trading {
+            tracing::warn!("REBALANCE_PAPER_TRADING is set: rebalancing trades fill on paper");
+            Some(Arc::new(
+                services::PaperTradingVenue::new(&rebalancing_config)
+                    .with_price_history(Arc::new(price_history_service.clone())),
+            ))
+        } else {
+            tracing::warn!("No execution venue configured; rebalancing trades will not execute");
+            None
+        };
+    let rebalancing_service =
+        services::RebalancingService::new(pool.clone(), venue, rebalancing_config);
+    // Target-date members de-risk along the glide path as retirement nears
+    let glide_path = ai::glide_path::GlidePath::new(
+        config::glide_path::GlidePathConfig::from_env(),
+        withdrawal_policy.retirement_age,
+    )?;
+    let investment_service = Arc::new(services::InvestmentService::new(
+        pool.clone(),
+        rebalancing_service.clone(),
+        glide_path,
+        price_feed,
+        price_history_service.clone(),
+    )?);
+
+    // Apply rebalancing for members who opted in to automatic rebalancing
+    services::AutoRebalancer::new(investment_service.clone(), rebalancing_service.clone()).spawn();
+
+    let state = api::AppState {
+        pool: pool.clone(),
+        auth_service: services::AuthService::new(
+            pool.clone(),
+            config::auth::AuthConfig::from_env(),
+        ),
+        user_service: services::user_service::UserService::new(pool.clone()),
+        phone_auth_service,
+        fund_service,
+        mpesa_service,
+        investment_service,
+        bpt_manager,
+        loan_service,
+        ussd_service,
+        kyc_service,
+        contribution_service,
+        employer_service: services::EmployerService::new(pool.clone()),
+        price_history_service,
+        rebalancing_service,
+    };
+
+    let addr = std::env::var("BIND_ADDRESS").unwrap_or_else(|_| "127.0.0.1:8080".to_string());
+    let listener = tokio::net::TcpListener::bind(&addr).await?;
+    tracing::info!("listening on {}", addr);
+
+    let cors = config::cors::CorsConfig::from_env();
+    axum::serve(listener, api::router(state, &cors)).await?;
+
+    Ok(())
+}
@@ -1,38 +0,0 @@
-use serde::{Deserialize, Serialize};
-use actix_web::dev::{ServiceRequest, ServiceResponse};
-use actix_web::{Error, HttpMessage};
-use futures::future::LocalBoxFuture;
-use std::time::{SystemTime, UNIX_EPOCH};
-use actix_web_httpauth::extractors::bearer::BearerAuth;
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct Claims {
-    pub sub: String,
-    pub exp: usize,
-}
-
-impl Claims {
-    pub fn new(user_id: String) -> Self {
-        let expiration = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs() as usize + 24 * 3600; // 24 hours from now
-        
-        Self {
-            sub: user_id,
-            exp: expiration,
-        }
-    }
-}
-
-pub async fn validator(req: ServiceRequest, credentials: BearerAuth) -> Result<ServiceRequest, Error> {
-    // Temporary: Accept the bypass token
-    if credentials.token() == "temporary_bypass_token" {
-        return Ok(req);
-    }
-    
-    // Keep the original validation logic for later
-    // ... existing validation code ...
-    
-    Ok(req)
-} 
\ No newline at end of file
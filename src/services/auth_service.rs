@@ -1 +1,269 @@
- 
\ No newline at end of file
+use chrono::{DateTime, Duration, Utc};
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use serde::Serialize;
+use sha2::Sha256;
+use sqlx::{PgPool, Postgres, Transaction};
+use uuid::Uuid;
+
+use crate::auth::{create_token, jwt_secret};
+use crate::config::auth::AuthConfig;
+use crate::error::{Error, Result};
+
+const REFRESH_TOKEN_BYTES: usize = 32;
+
+#[derive(Debug, Serialize)]
+pub struct TokenPair {
+    pub user_id: Uuid,
+    pub access_token: String,
+    pub refresh_token: String,
+    // Seconds until the access token expires
+    pub expires_in: i64,
+}
+
+// What a stored refresh token looked like when it was presented
+#[derive(Debug, PartialEq)]
+enum RefreshTokenState {
+    Active,
+    Expired,
+    Revoked,
+    // Already exchanged for a successor: someone is replaying it
+    Reused,
+}
+
+fn refresh_token_state(
+    expires_at: DateTime<Utc>,
+    rotated_at: Option<DateTime<Utc>>,
+    revoked_at: Option<DateTime<Utc>>,
+    now: DateTime<Utc>,
+) -> RefreshTokenState {
+    if revoked_at.is_some() {
+        RefreshTokenState::Revoked
+    } else if rotated_at.is_some() {
+        RefreshTokenState::Reused
+    } else if expires_at <= now {
+        RefreshTokenState::Expired
+    } else {
+        RefreshTokenState::Active
+    }
+}
+
+// Sessions for every sign-in method: a short-lived access JWT plus an
+// opaque refresh token that rotates on each use.
+#[derive(Clone)]
+pub struct AuthService {
+    pool: PgPool,
+    config: AuthConfig,
+}
+
+impl AuthService {
+    pub fn new(pool: PgPool, config: AuthConfig) -> Self {
+        Self { pool, config }
+    }
+
+    // Called once the member has proven who they are
+    pub async fn start_session(&self, user_id: Uuid) -> Result<TokenPair> {
+        let mut tx = self.pool.begin().await?;
+        let (refresh_token, _) = self
+            .issue_refresh_token(&mut tx, user_id, Uuid::new_v4())
+            .await?;
+        tx.commit().await?;
+
+        self.token_pair(user_id, refresh_token)
+    }
+
+    // Exchanges a refresh token for a new pair. Each token is single use:
+    // presenting one that was already rotated means it leaked, so the
+    // whole session family is revoked and the caller must log in again.
+    pub async fn refresh(&self, refresh_token: &str) -> Result<TokenPair> {
+        let token_hash = hash_refresh_token(refresh_token);
+        let mut tx = self.pool.begin().await?;
+
+        let stored = sqlx::query!(
+            r#"
+            SELECT id, user_id, family_id, expires_at, rotated_at, revoked_at
+            FROM refresh_tokens
+            WHERE token_hash = $1
+            FOR UPDATE
+            "#,
+            token_hash
+        )
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(Error::Unauthorized)?;
+
+        match refresh_token_state(
+            stored.expires_at,
+            stored.rotated_at,
+            stored.revoked_at,
+            Utc::now(),
+        ) {
+            RefreshTokenState::Active => {}
+            RefreshTokenState::Reused => {
+                revoke_family(&mut tx, stored.family_id).await?;
+                tx.commit().await?;
+                tracing::warn!(
+                    "Refresh token reuse for user {}; revoked session family {}",
+                    stored.user_id,
+                    stored.family_id
+                );
+                return Err(Error::Unauthorized);
+            }
+            RefreshTokenState::Expired | RefreshTokenState::Revoked => {
+                return Err(Error::Unauthorized);
+            }
+        }
+
+        let (refresh_token, successor_id) = self
+            .issue_refresh_token(&mut tx, stored.user_id, stored.family_id)
+            .await?;
+
+        sqlx::query!(
+            r#"
+            UPDATE refresh_tokens
+            SET rotated_at = NOW(), replaced_by = $2
+            WHERE id = $1
+            "#,
+            stored.id,
+            successor_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        self.token_pair(stored.user_id, refresh_token)
+    }
+
+    // Ends the session the refresh token belongs to
+    pub async fn logout(&self, refresh_token: &str) -> Result<()> {
+        let token_hash = hash_refresh_token(refresh_token);
+        let mut tx = self.pool.begin().await?;
+
+        let family_id = sqlx::query_scalar!(
+            "SELECT family_id FROM refresh_tokens WHERE token_hash = $1",
+            token_hash
+        )
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(Error::Unauthorized)?;
+
+        revoke_family(&mut tx, family_id).await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    // Ends every session of the member, on every device. Access tokens
+    // already issued run out within the access TTL.
+    pub async fn logout_all(&self, user_id: Uuid) -> Result<u64> {
+        let revoked = sqlx::query!(
+            r#"
+            UPDATE refresh_tokens
+            SET revoked_at = NOW()
+            WHERE user_id = $1 AND revoked_at IS NULL
+            "#,
+            user_id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(revoked.rows_affected())
+    }
+
+    fn token_pair(&self, user_id: Uuid, refresh_token: String) -> Result<TokenPair> {
+        let ttl = self.config.access_token_ttl_secs;
+
+        Ok(TokenPair {
+            user_id,
+            access_token: create_token(user_id, time::Duration::seconds(ttl))?,
+            refresh_token,
+            expires_in: ttl,
+        })
+    }
+
+    async fn issue_refresh_token(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        user_id: Uuid,
+        family_id: Uuid,
+    ) -> Result<(String, Uuid)> {
+        let mut bytes = [0u8; REFRESH_TOKEN_BYTES];
+        rand::rng().fill_bytes(&mut bytes);
+        let refresh_token = hex::encode(bytes);
+
+        let id = Uuid::new_v4();
+        sqlx::query!(
+            r#"
+            INSERT INTO refresh_tokens (id, user_id, family_id, token_hash, expires_at)
+            VALUES ($1, $2, $3, $4, $5)
+            "#,
+            id,
+            user_id,
+            family_id,
+            hash_refresh_token(&refresh_token),
+            Utc::now() + Duration::days(self.config.refresh_token_ttl_days),
+        )
+        .execute(&mut **tx)
+        .await?;
+
+        Ok((refresh_token, id))
+    }
+}
+
+async fn revoke_family(tx: &mut Transaction<'_, Postgres>, family_id: Uuid) -> Result<()> {
+    sqlx::query!(
+        r#"
+        UPDATE refresh_tokens
+        SET revoked_at = NOW()
+        WHERE family_id = $1 AND revoked_at IS NULL
+        "#,
+        family_id
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+// Only the keyed hash is stored, so a leaked table cannot be replayed
+fn hash_refresh_token(refresh_token: &str) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(jwt_secret()).expect("HMAC accepts keys of any length");
+    mac.update(refresh_token.as_bytes());
+    hex::encode(mac.finalize().into_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_refresh_token_state() {
+        let now = Utc::now();
+        let later = now + Duration::days(1);
+        let earlier = now - Duration::days(1);
+
+        assert_eq!(
+            refresh_token_state(later, None, None, now),
+            RefreshTokenState::Active
+        );
+        assert_eq!(
+            refresh_token_state(earlier, None, None, now),
+            RefreshTokenState::Expired
+        );
+        assert_eq!(
+            refresh_token_state(later, Some(earlier), None, now),
+            RefreshTokenState::Reused
+        );
+        // A replay after expiry is still reuse, so the family gets revoked
+        assert_eq!(
+            refresh_token_state(earlier, Some(earlier), None, now),
+            RefreshTokenState::Reused
+        );
+        assert_eq!(
+            refresh_token_state(later, Some(earlier), Some(now), now),
+            RefreshTokenState::Revoked
+        );
+    }
+}
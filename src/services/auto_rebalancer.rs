@@ -0,0 +1,101 @@
+use anyhow::Result;
+use std::sync::Arc;
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+use crate::services::investment_service::InvestmentService;
+use crate::services::rebalancing::{RebalancingService, Recommendation, RecommendationStatus};
+
+#[derive(Debug, Default)]
+pub struct AutoRebalanceReport {
+    pub checked: usize,
+    pub rebalanced: usize,
+    pub failed: usize,
+}
+
+// Checks every opted-in member against their target, proposes the
+// rebalancing trades for those who have drifted and accepts them on the
+// member's behalf
+pub struct AutoRebalancer {
+    investment_service: Arc<InvestmentService>,
+    rebalancing_service: RebalancingService,
+}
+
+impl AutoRebalancer {
+    pub fn new(
+        investment_service: Arc<InvestmentService>,
+        rebalancing_service: RebalancingService,
+    ) -> Self {
+        Self {
+            investment_service,
+            rebalancing_service,
+        }
+    }
+
+    pub fn spawn(self) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(std::time::Duration::from_secs(
+                self.rebalancing_service
+                    .config()
+                    .auto_rebalance_interval_secs,
+            ));
+
+            loop {
+                interval.tick().await;
+
+                match self.run_once().await {
+                    Ok(report) => tracing::info!("Auto-rebalance finished: {:?}", report),
+                    Err(e) => tracing::error!("Auto-rebalance failed: {}", e),
+                }
+            }
+        })
+    }
+
+    pub async fn run_once(&self) -> Result<AutoRebalanceReport> {
+        let mut report = AutoRebalanceReport::default();
+
+        for user_id in self
+            .rebalancing_service
+            .get_auto_rebalance_members()
+            .await?
+        {
+            report.checked += 1;
+
+            match self.rebalance(user_id).await {
+                Ok(Some(recommendation))
+                    if recommendation.status == RecommendationStatus::Applied.as_str() =>
+                {
+                    report.rebalanced += 1;
+                }
+                Ok(Some(recommendation)) => {
+                    tracing::warn!(
+                        "Rebalance {} for {} ended {}: {}",
+                        recommendation.id,
+                        user_id,
+                        recommendation.status,
+                        recommendation.failure_reason.unwrap_or_default()
+                    );
+                    report.failed += 1;
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    tracing::error!("Auto-rebalance for {} failed: {}", user_id, e);
+                    report.failed += 1;
+                }
+            }
+        }
+
+        Ok(report)
+    }
+
+    async fn rebalance(&self, user_id: Uuid) -> crate::error::Result<Option<Recommendation>> {
+        let Some((_, proposed)) = self.investment_service.check_rebalance(user_id).await? else {
+            return Ok(None);
+        };
+
+        self.rebalancing_service
+            .accept(user_id, proposed.id)
+            .await
+            .map(Some)
+    }
+}
@@ -1,14 +1,927 @@
+use chrono::{DateTime, Duration, Utc};
+use rust_decimal::Decimal;
+use serde::Serialize;
 use sqlx::PgPool;
-use super::stellar::StellarService;
+use uuid::Uuid;
+
+use super::stellar::{ContractArg, StellarService};
+use crate::contracts::bpt::BPT_DECIMALS;
+use crate::error::{Error, Result};
+
+// Lockup periods a member can choose when staking
+pub const STAKE_LOCKUP_DAYS: [u32; 4] = [30, 90, 180, 365];
+
+#[derive(Debug, Serialize)]
+pub struct BptBalance {
+    pub wallet_address: String,
+    pub balance: Decimal,
+    pub locked: Decimal,
+    pub staked: Decimal,
+    pub collateral: Decimal,
+    // Savings that could still be tokenized
+    pub tokenizable: Decimal,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Tokenization {
+    pub id: Uuid,
+    pub amount: Decimal,
+    pub tx_hash: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Stake {
+    pub id: Uuid,
+    pub amount: Decimal,
+    pub lockup_days: i32,
+    pub status: String,
+    pub locked_until: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CollateralPledge {
+    pub id: Uuid,
+    pub amount: Decimal,
+    pub status: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BptTransfer {
+    pub id: Uuid,
+    pub to_address: String,
+    pub amount: Decimal,
+    pub tx_hash: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BptReconciliation {
+    pub tokenized_balance: Decimal,
+    pub pending_mints: Decimal,
+    pub pending_redemptions: Decimal,
+    pub on_chain_supply: Decimal,
+    // On-chain supply minus what the funds say has settled on-chain
+    pub difference: Decimal,
+    // Funds that have tokenized more than they hold
+    pub over_tokenized_funds: Vec<Uuid>,
+}
+
+impl BptReconciliation {
+    pub fn new(
+        tokenized_balance: Decimal,
+        pending_mints: Decimal,
+        pending_redemptions: Decimal,
+        on_chain_supply: Decimal,
+        over_tokenized_funds: Vec<Uuid>,
+    ) -> Self {
+        // Pending mints are already reserved off-chain but not yet minted;
+        // pending redemptions are released off-chain but not yet burned
+        let expected_supply = tokenized_balance - pending_mints + pending_redemptions;
+
+        Self {
+            tokenized_balance,
+            pending_mints,
+            pending_redemptions,
+            on_chain_supply,
+            difference: on_chain_supply - expected_supply,
+            over_tokenized_funds,
+        }
+    }
+
+    pub fn is_reconciled(&self) -> bool {
+        self.difference.is_zero() && self.over_tokenized_funds.is_empty()
+    }
+}
 
 #[derive(Clone)]
 pub struct BPTManager {
     pool: PgPool,
     stellar: StellarService,
+    contract_id: String,
 }
 
 impl BPTManager {
-    pub async fn new(pool: PgPool, stellar: StellarService) -> Self {
-        Self { pool, stellar }
+    pub async fn new(pool: PgPool, stellar: StellarService, contract_id: String) -> Self {
+        Self {
+            pool,
+            stellar,
+            contract_id,
+        }
     }
-} 
\ No newline at end of file
+
+    pub async fn get_user_bpt_balance(&self, user_id: Uuid) -> Result<BptBalance> {
+        let wallet = self.wallet_address(user_id).await?;
+        let balance = self.read_units("balance", &wallet).await?;
+        let locked = self.read_units("locked", &wallet).await?;
+
+        let fund = sqlx::query!(
+            r#"
+            SELECT balance, tokenized_balance FROM pension_funds
+            WHERE user_id = $1
+            "#,
+            user_id
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        let staked = sqlx::query!(
+            r#"
+            SELECT COALESCE(SUM(amount), 0) as "total!" FROM bpt_stakes
+            WHERE user_id = $1 AND status IN ('ACTIVE', 'UNSTAKING')
+            "#,
+            user_id
+        )
+        .fetch_one(&self.pool)
+        .await?
+        .total;
+
+        let collateral = sqlx::query!(
+            r#"
+            SELECT COALESCE(SUM(amount), 0) as "total!" FROM bpt_collateral
+            WHERE user_id = $1 AND status IN ('PLEDGED', 'RELEASING')
+            "#,
+            user_id
+        )
+        .fetch_one(&self.pool)
+        .await?
+        .total;
+
+        Ok(BptBalance {
+            wallet_address: wallet,
+            balance,
+            locked,
+            staked,
+            collateral,
+            tokenizable: (fund.balance - fund.tokenized_balance).max(Decimal::ZERO),
+        })
+    }
+
+    // Mints BPT against savings that are not yet tokenized. The fund's
+    // tokenized balance is reserved first and released if the mint fails,
+    // so two concurrent requests can never tokenize the same shilling.
+    pub async fn tokenize_savings(&self, user_id: Uuid, amount: Decimal) -> Result<Tokenization> {
+        let units = to_units(amount)?;
+        let wallet = self.wallet_address(user_id).await?;
+        let tokenization_id = Uuid::new_v4();
+
+        let mut tx = self.pool.begin().await?;
+        let fund_id = sqlx::query!(
+            r#"
+            UPDATE pension_funds
+            SET tokenized_balance = tokenized_balance + $1
+            WHERE user_id = $2 AND balance - tokenized_balance >= $1
+            RETURNING id
+            "#,
+            amount,
+            user_id,
+        )
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(Error::InsufficientFunds)?
+        .id;
+
+        sqlx::query!(
+            r#"
+            INSERT INTO bpt_tokenizations (id, user_id, fund_id, direction, amount, status)
+            VALUES ($1, $2, $3, 'MINT', $4, 'PENDING')
+            "#,
+            tokenization_id,
+            user_id,
+            fund_id,
+            amount,
+        )
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        let minted = self
+            .invoke(
+                "mint",
+                vec![ContractArg::Address(wallet), ContractArg::I128(units)],
+            )
+            .await;
+        let tx_hash = self.settle_tokenization(tokenization_id, minted).await?;
+
+        Ok(Tokenization {
+            id: tokenization_id,
+            amount,
+            tx_hash,
+        })
+    }
+
+    // Burns spendable BPT and returns the savings to the untokenized pool
+    pub async fn redeem_tokens(&self, user_id: Uuid, amount: Decimal) -> Result<Tokenization> {
+        let units = to_units(amount)?;
+        let wallet = self.wallet_address(user_id).await?;
+
+        // Clawback draws on locked tokens first, so never let a redemption
+        // eat into stakes or collateral
+        let spendable = self
+            .stellar
+            .read_contract_i128(
+                &self.contract_id,
+                "spendable",
+                vec![ContractArg::Address(wallet.clone())],
+            )
+            .await
+            .map_err(|e| Error::Blockchain(e.to_string()))?;
+        if units > spendable {
+            return Err(Error::InsufficientFunds);
+        }
+
+        let tokenization_id = Uuid::new_v4();
+        let mut tx = self.pool.begin().await?;
+        let fund_id = sqlx::query!(
+            r#"
+            UPDATE pension_funds
+            SET tokenized_balance = tokenized_balance - $1
+            WHERE user_id = $2 AND tokenized_balance >= $1
+            RETURNING id
+            "#,
+            amount,
+            user_id,
+        )
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(Error::InsufficientFunds)?
+        .id;
+
+        sqlx::query!(
+            r#"
+            INSERT INTO bpt_tokenizations (id, user_id, fund_id, direction, amount, status)
+            VALUES ($1, $2, $3, 'REDEEM', $4, 'PENDING')
+            "#,
+            tokenization_id,
+            user_id,
+            fund_id,
+            amount,
+        )
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        let burned = self
+            .invoke(
+                "clawback",
+                vec![ContractArg::Address(wallet), ContractArg::I128(units)],
+            )
+            .await;
+        let tx_hash = self.settle_tokenization(tokenization_id, burned).await?;
+
+        Ok(Tokenization {
+            id: tokenization_id,
+            amount,
+            tx_hash,
+        })
+    }
+
+    // Completes a pending mint/redeem, or undoes its reservation on failure
+    async fn settle_tokenization(
+        &self,
+        tokenization_id: Uuid,
+        outcome: Result<String>,
+    ) -> Result<String> {
+        let mut tx = self.pool.begin().await?;
+
+        match outcome {
+            Ok(tx_hash) => {
+                sqlx::query!(
+                    r#"
+                    UPDATE bpt_tokenizations
+                    SET status = 'COMPLETED', tx_hash = $1, completed_at = NOW()
+                    WHERE id = $2 AND status = 'PENDING'
+                    "#,
+                    tx_hash,
+                    tokenization_id,
+                )
+                .execute(&mut *tx)
+                .await?;
+                tx.commit().await?;
+                Ok(tx_hash)
+            }
+            Err(e) => {
+                let failed = sqlx::query!(
+                    r#"
+                    UPDATE bpt_tokenizations
+                    SET status = 'FAILED', failure_reason = $1, completed_at = NOW()
+                    WHERE id = $2 AND status = 'PENDING'
+                    RETURNING fund_id, direction, amount
+                    "#,
+                    e.to_string(),
+                    tokenization_id,
+                )
+                .fetch_optional(&mut *tx)
+                .await?;
+
+                if let Some(failed) = failed {
+                    let reversal = if failed.direction == "MINT" {
+                        -failed.amount
+                    } else {
+                        failed.amount
+                    };
+                    sqlx::query!(
+                        r#"
+                        UPDATE pension_funds
+                        SET tokenized_balance = tokenized_balance + $1
+                        WHERE id = $2
+                        "#,
+                        reversal,
+                        failed.fund_id,
+                    )
+                    .execute(&mut *tx)
+                    .await?;
+                }
+
+                tx.commit().await?;
+                Err(e)
+            }
+        }
+    }
+
+    pub async fn stake_tokens(
+        &self,
+        user_id: Uuid,
+        amount: Decimal,
+        lockup_days: u32,
+    ) -> Result<Stake> {
+        validate_lockup(lockup_days)?;
+        let units = to_units(amount)?;
+        let wallet = self.wallet_address(user_id).await?;
+
+        let stake_id = Uuid::new_v4();
+        let locked_until = Utc::now() + Duration::days(lockup_days as i64);
+        sqlx::query!(
+            r#"
+            INSERT INTO bpt_stakes (id, user_id, amount, lockup_days, status, locked_until)
+            VALUES ($1, $2, $3, $4, 'PENDING', $5)
+            "#,
+            stake_id,
+            user_id,
+            amount,
+            lockup_days as i32,
+            locked_until,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        match self
+            .invoke(
+                "lock",
+                vec![ContractArg::Address(wallet), ContractArg::I128(units)],
+            )
+            .await
+        {
+            Ok(tx_hash) => {
+                sqlx::query!(
+                    r#"
+                    UPDATE bpt_stakes SET status = 'ACTIVE', stake_tx_hash = $1
+                    WHERE id = $2
+                    "#,
+                    tx_hash,
+                    stake_id,
+                )
+                .execute(&self.pool)
+                .await?;
+            }
+            Err(e) => {
+                sqlx::query!(
+                    "UPDATE bpt_stakes SET status = 'FAILED' WHERE id = $1",
+                    stake_id
+                )
+                .execute(&self.pool)
+                .await?;
+                return Err(e);
+            }
+        }
+
+        Ok(Stake {
+            id: stake_id,
+            amount,
+            lockup_days: lockup_days as i32,
+            status: "ACTIVE".to_string(),
+            locked_until,
+        })
+    }
+
+    pub async fn unstake_tokens(&self, user_id: Uuid, stake_id: Uuid) -> Result<Stake> {
+        let stake = sqlx::query_as!(
+            Stake,
+            r#"
+            SELECT id, amount, lockup_days, status, locked_until
+            FROM bpt_stakes
+            WHERE id = $1 AND user_id = $2
+            "#,
+            stake_id,
+            user_id,
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| Error::NotFound("Stake".to_string()))?;
+
+        if stake.status != "ACTIVE" {
+            return Err(Error::NotFound("Active stake".to_string()));
+        }
+        if Utc::now() < stake.locked_until {
+            return Err(Error::StakeLocked(stake.locked_until));
+        }
+
+        // Claim the stake so a concurrent request cannot unlock it twice
+        sqlx::query!(
+            r#"
+            UPDATE bpt_stakes SET status = 'UNSTAKING'
+            WHERE id = $1 AND status = 'ACTIVE'
+            RETURNING id
+            "#,
+            stake_id,
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| Error::NotFound("Active stake".to_string()))?;
+
+        let wallet = self.wallet_address(user_id).await?;
+        match self
+            .invoke(
+                "unlock",
+                vec![
+                    ContractArg::Address(wallet),
+                    ContractArg::I128(to_units(stake.amount)?),
+                ],
+            )
+            .await
+        {
+            Ok(tx_hash) => {
+                sqlx::query!(
+                    r#"
+                    UPDATE bpt_stakes
+                    SET status = 'UNSTAKED', unstake_tx_hash = $1, unstaked_at = NOW()
+                    WHERE id = $2
+                    "#,
+                    tx_hash,
+                    stake_id,
+                )
+                .execute(&self.pool)
+                .await?;
+            }
+            Err(e) => {
+                sqlx::query!(
+                    "UPDATE bpt_stakes SET status = 'ACTIVE' WHERE id = $1",
+                    stake_id
+                )
+                .execute(&self.pool)
+                .await?;
+                return Err(e);
+            }
+        }
+
+        Ok(Stake {
+            status: "UNSTAKED".to_string(),
+            ..stake
+        })
+    }
+
+    pub async fn get_user_stakes(&self, user_id: Uuid) -> Result<Vec<Stake>> {
+        let stakes = sqlx::query_as!(
+            Stake,
+            r#"
+            SELECT id, amount, lockup_days, status, locked_until
+            FROM bpt_stakes
+            WHERE user_id = $1 AND status <> 'FAILED'
+            ORDER BY created_at DESC
+            "#,
+            user_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(stakes)
+    }
+
+    // Locks BPT on-chain so it can secure a loan
+    pub async fn pledge_collateral(
+        &self,
+        user_id: Uuid,
+        amount: Decimal,
+    ) -> Result<CollateralPledge> {
+        let units = to_units(amount)?;
+        let wallet = self.wallet_address(user_id).await?;
+
+        let pledge_id = Uuid::new_v4();
+        sqlx::query!(
+            r#"
+            INSERT INTO bpt_collateral (id, user_id, amount, status)
+            VALUES ($1, $2, $3, 'PENDING')
+            "#,
+            pledge_id,
+            user_id,
+            amount,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        match self
+            .invoke(
+                "lock",
+                vec![ContractArg::Address(wallet), ContractArg::I128(units)],
+            )
+            .await
+        {
+            Ok(tx_hash) => {
+                sqlx::query!(
+                    r#"
+                    UPDATE bpt_collateral SET status = 'PLEDGED', lock_tx_hash = $1
+                    WHERE id = $2
+                    "#,
+                    tx_hash,
+                    pledge_id,
+                )
+                .execute(&self.pool)
+                .await?;
+            }
+            Err(e) => {
+                sqlx::query!(
+                    "UPDATE bpt_collateral SET status = 'FAILED' WHERE id = $1",
+                    pledge_id
+                )
+                .execute(&self.pool)
+                .await?;
+                return Err(e);
+            }
+        }
+
+        Ok(CollateralPledge {
+            id: pledge_id,
+            amount,
+            status: "PLEDGED".to_string(),
+        })
+    }
+
+    pub async fn release_collateral(&self, pledge_id: Uuid) -> Result<()> {
+        let pledge = sqlx::query!(
+            r#"
+            UPDATE bpt_collateral SET status = 'RELEASING'
+            WHERE id = $1 AND status = 'PLEDGED'
+            RETURNING user_id, amount
+            "#,
+            pledge_id,
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| Error::NotFound("Collateral pledge".to_string()))?;
+
+        let wallet = self.wallet_address(pledge.user_id).await?;
+        match self
+            .invoke(
+                "unlock",
+                vec![
+                    ContractArg::Address(wallet),
+                    ContractArg::I128(to_units(pledge.amount)?),
+                ],
+            )
+            .await
+        {
+            Ok(tx_hash) => {
+                sqlx::query!(
+                    r#"
+                    UPDATE bpt_collateral
+                    SET status = 'RELEASED', release_tx_hash = $1, released_at = NOW()
+                    WHERE id = $2
+                    "#,
+                    tx_hash,
+                    pledge_id,
+                )
+                .execute(&self.pool)
+                .await?;
+                Ok(())
+            }
+            Err(e) => {
+                sqlx::query!(
+                    "UPDATE bpt_collateral SET status = 'PLEDGED' WHERE id = $1",
+                    pledge_id
+                )
+                .execute(&self.pool)
+                .await?;
+                Err(e)
+            }
+        }
+    }
+
+    // Claws back up to the pledged amount and releases whatever is left of
+    // the pledge. Returns the clawback transaction hash.
+    pub async fn seize_collateral(&self, pledge_id: Uuid, amount: Decimal) -> Result<String> {
+        let pledge = sqlx::query!(
+            r#"
+            UPDATE bpt_collateral SET status = 'SEIZING'
+            WHERE id = $1 AND status = 'PLEDGED'
+            RETURNING user_id, amount
+            "#,
+            pledge_id,
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| Error::NotFound("Collateral pledge".to_string()))?;
+
+        let seized = amount.min(pledge.amount);
+        let wallet = self.wallet_address(pledge.user_id).await?;
+        let tx_hash = match self
+            .invoke(
+                "clawback",
+                vec![
+                    ContractArg::Address(wallet.clone()),
+                    ContractArg::I128(to_units(seized)?),
+                ],
+            )
+            .await
+        {
+            Ok(tx_hash) => tx_hash,
+            Err(e) => {
+                sqlx::query!(
+                    "UPDATE bpt_collateral SET status = 'PLEDGED' WHERE id = $1",
+                    pledge_id
+                )
+                .execute(&self.pool)
+                .await?;
+                return Err(e);
+            }
+        };
+
+        // Clawback consumes locked tokens first, so the unseized remainder of
+        // this pledge is still locked and has to be released explicitly
+        let remainder = pledge.amount - seized;
+        if remainder > Decimal::ZERO {
+            if let Err(e) = self
+                .invoke(
+                    "unlock",
+                    vec![
+                        ContractArg::Address(wallet),
+                        ContractArg::I128(to_units(remainder)?),
+                    ],
+                )
+                .await
+            {
+                tracing::error!(
+                    "Failed to release {} BPT left over from pledge {}: {}",
+                    remainder,
+                    pledge_id,
+                    e
+                );
+            }
+        }
+
+        sqlx::query!(
+            r#"
+            UPDATE bpt_collateral
+            SET status = 'SEIZED', release_tx_hash = $1, released_at = NOW()
+            WHERE id = $2
+            "#,
+            tx_hash,
+            pledge_id,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(tx_hash)
+    }
+
+    // Moves BPT out of the member's wallet. The member must have approved
+    // the platform account as a spender on the token contract. Each fund's
+    // tokenized balance backs the BPT in its member's wallet, so tokens
+    // cannot be sent to another member's wallet.
+    pub async fn transfer_tokens(
+        &self,
+        user_id: Uuid,
+        to_address: &str,
+        amount: Decimal,
+    ) -> Result<BptTransfer> {
+        let units = to_units(amount)?;
+        let wallet = self.wallet_address(user_id).await?;
+
+        let to_member = sqlx::query_scalar!(
+            r#"
+            SELECT EXISTS (SELECT 1 FROM wallets WHERE address = $1)
+                OR EXISTS (SELECT 1 FROM users WHERE wallet_address = $1) as "exists!"
+            "#,
+            to_address
+        )
+        .fetch_one(&self.pool)
+        .await?;
+        if to_member {
+            return Err(Error::MemberTransfer);
+        }
+
+        let tx_hash = self
+            .invoke(
+                "transfer_from",
+                vec![
+                    ContractArg::Address(self.stellar.public_key()),
+                    ContractArg::Address(wallet),
+                    ContractArg::Address(to_address.to_string()),
+                    ContractArg::I128(units),
+                ],
+            )
+            .await?;
+
+        let transfer_id = Uuid::new_v4();
+        sqlx::query!(
+            r#"
+            INSERT INTO bpt_transfers (id, user_id, to_address, amount, tx_hash)
+            VALUES ($1, $2, $3, $4, $5)
+            "#,
+            transfer_id,
+            user_id,
+            to_address,
+            amount,
+            tx_hash,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(BptTransfer {
+            id: transfer_id,
+            to_address: to_address.to_string(),
+            amount,
+            tx_hash,
+        })
+    }
+
+    // Compares what the funds say is tokenized against the BPT actually
+    // in circulation
+    pub async fn reconcile(&self) -> Result<BptReconciliation> {
+        let tokenized_balance = sqlx::query!(
+            r#"SELECT COALESCE(SUM(tokenized_balance), 0) as "total!" FROM pension_funds"#
+        )
+        .fetch_one(&self.pool)
+        .await?
+        .total;
+
+        let pending = sqlx::query!(
+            r#"
+            SELECT
+                COALESCE(SUM(amount) FILTER (WHERE direction = 'MINT'), 0) as "mints!",
+                COALESCE(SUM(amount) FILTER (WHERE direction = 'REDEEM'), 0) as "redemptions!"
+            FROM bpt_tokenizations
+            WHERE status = 'PENDING'
+            "#
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        let over_tokenized_funds =
+            sqlx::query!("SELECT id FROM pension_funds WHERE tokenized_balance > balance")
+                .fetch_all(&self.pool)
+                .await?
+                .into_iter()
+                .map(|row| row.id)
+                .collect();
+
+        let supply = self
+            .stellar
+            .read_contract_i128(&self.contract_id, "total_supply", vec![])
+            .await
+            .map_err(|e| Error::Blockchain(e.to_string()))?;
+
+        let reconciliation = BptReconciliation::new(
+            tokenized_balance,
+            pending.mints,
+            pending.redemptions,
+            from_units(supply),
+            over_tokenized_funds,
+        );
+
+        if !reconciliation.is_reconciled() {
+            tracing::warn!(
+                "BPT supply out of line with pension funds: difference {}, {} over-tokenized fund(s)",
+                reconciliation.difference,
+                reconciliation.over_tokenized_funds.len()
+            );
+        }
+
+        Ok(reconciliation)
+    }
+
+    async fn wallet_address(&self, user_id: Uuid) -> Result<String> {
+        let wallet = sqlx::query!(
+            r#"
+            SELECT address FROM wallets
+            WHERE user_id = $1
+            ORDER BY created_at
+            LIMIT 1
+            "#,
+            user_id
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or(Error::WalletNotFound)?;
+
+        Ok(wallet.address)
+    }
+
+    async fn read_units(&self, function: &str, wallet: &str) -> Result<Decimal> {
+        let units = self
+            .stellar
+            .read_contract_i128(
+                &self.contract_id,
+                function,
+                vec![ContractArg::Address(wallet.to_string())],
+            )
+            .await
+            .map_err(|e| Error::Blockchain(e.to_string()))?;
+
+        Ok(from_units(units))
+    }
+
+    async fn invoke(&self, function: &str, args: Vec<ContractArg>) -> Result<String> {
+        self.stellar
+            .invoke_contract(&self.contract_id, function, args)
+            .await
+            .map_err(|e| Error::Blockchain(e.to_string()))
+    }
+}
+
+fn validate_lockup(lockup_days: u32) -> Result<()> {
+    if !STAKE_LOCKUP_DAYS.contains(&lockup_days) {
+        return Err(Error::InvalidLockupPeriod(lockup_days));
+    }
+    Ok(())
+}
+
+// Converts shillings to the token's smallest unit
+pub fn to_units(amount: Decimal) -> Result<i128> {
+    if amount <= Decimal::ZERO {
+        return Err(Error::InvalidAmount);
+    }
+
+    let mut scaled = amount.round_dp(BPT_DECIMALS);
+    scaled.rescale(BPT_DECIMALS);
+    if scaled.is_zero() {
+        return Err(Error::InvalidAmount);
+    }
+
+    Ok(scaled.mantissa())
+}
+
+pub fn from_units(units: i128) -> Decimal {
+    Decimal::from_i128_with_scale(units, BPT_DECIMALS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unit_conversion_round_trips() {
+        let amount = Decimal::new(150050, 2); // 1,500.50
+        let units = to_units(amount).unwrap();
+
+        assert_eq!(units, 15_005_000_000);
+        assert_eq!(from_units(units), amount);
+    }
+
+    #[test]
+    fn test_dust_and_negative_amounts_rejected() {
+        assert!(matches!(to_units(Decimal::ZERO), Err(Error::InvalidAmount)));
+        assert!(matches!(
+            to_units(Decimal::new(-1, 0)),
+            Err(Error::InvalidAmount)
+        ));
+        assert!(matches!(
+            to_units(Decimal::new(1, 9)),
+            Err(Error::InvalidAmount)
+        ));
+    }
+
+    #[test]
+    fn test_only_supported_lockups_accepted() {
+        assert!(validate_lockup(90).is_ok());
+        assert!(matches!(
+            validate_lockup(45),
+            Err(Error::InvalidLockupPeriod(45))
+        ));
+    }
+
+    #[test]
+    fn test_reconciliation_accounts_for_pending_mints() {
+        // 1,000 tokenized of which 200 is still being minted
+        let reconciliation = BptReconciliation::new(
+            Decimal::new(1000, 0),
+            Decimal::new(200, 0),
+            Decimal::ZERO,
+            Decimal::new(800, 0),
+            vec![],
+        );
+
+        assert!(reconciliation.is_reconciled());
+    }
+
+    #[test]
+    fn test_reconciliation_flags_excess_supply() {
+        let reconciliation = BptReconciliation::new(
+            Decimal::new(1000, 0),
+            Decimal::ZERO,
+            Decimal::ZERO,
+            Decimal::new(1050, 0),
+            vec![],
+        );
+
+        assert_eq!(reconciliation.difference, Decimal::new(50, 0));
+        assert!(!reconciliation.is_reconciled());
+    }
+}
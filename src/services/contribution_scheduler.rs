@@ -0,0 +1,52 @@
+use anyhow::Result;
+use std::sync::Arc;
+use tokio::task::JoinHandle;
+
+use crate::services::contribution_service::ContributionService;
+
+#[derive(Debug, Default)]
+pub struct ContributionRunReport {
+    pub paused: usize,
+    pub reminders_sent: usize,
+    pub prompted: usize,
+}
+
+// Fires scheduled contributions: pauses schedules that keep being
+// declined, reminds members of upcoming prompts and sends the STK pushes
+// that have come due
+pub struct ContributionScheduler {
+    contribution_service: Arc<ContributionService>,
+}
+
+impl ContributionScheduler {
+    pub fn new(contribution_service: Arc<ContributionService>) -> Self {
+        Self {
+            contribution_service,
+        }
+    }
+
+    pub fn spawn(self) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(std::time::Duration::from_secs(
+                self.contribution_service.config().scheduler_interval_secs,
+            ));
+
+            loop {
+                interval.tick().await;
+
+                match self.run_once().await {
+                    Ok(report) => tracing::info!("Contribution run finished: {:?}", report),
+                    Err(e) => tracing::error!("Contribution run failed: {}", e),
+                }
+            }
+        })
+    }
+
+    pub async fn run_once(&self) -> Result<ContributionRunReport> {
+        Ok(ContributionRunReport {
+            paused: self.contribution_service.pause_failing_schedules().await?,
+            reminders_sent: self.contribution_service.send_reminders().await?,
+            prompted: self.contribution_service.run_due_schedules().await?,
+        })
+    }
+}
@@ -0,0 +1,613 @@
+use chrono::{DateTime, Datelike, Duration, NaiveTime, Timelike, Utc};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::{PgExecutor, PgPool};
+use std::str::FromStr;
+use uuid::Uuid;
+
+use crate::config::contributions::ContributionConfig;
+use crate::error::{Error, Result};
+use crate::services::fund_service::FundService;
+use crate::services::mpesa_service::MPesaService;
+use crate::services::notification_service::NotificationService;
+use crate::utils::validation::normalize_msisdn;
+
+// Members pick times on the Kenyan clock, which has no daylight saving
+const EAST_AFRICA_OFFSET_SECS: i64 = 3 * 60 * 60;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ContributionFrequency {
+    Daily,
+    Weekly,
+}
+
+impl ContributionFrequency {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ContributionFrequency::Daily => "DAILY",
+            ContributionFrequency::Weekly => "WEEKLY",
+        }
+    }
+}
+
+impl FromStr for ContributionFrequency {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "DAILY" => Ok(ContributionFrequency::Daily),
+            "WEEKLY" => Ok(ContributionFrequency::Weekly),
+            _ => Err(anyhow::anyhow!("Unknown contribution frequency: {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct NewSchedule {
+    pub amount: Decimal,
+    pub frequency: ContributionFrequency,
+    pub day_of_week: Option<u8>, // 1 = Monday ... 7 = Sunday; weekly only
+    pub preferred_time: NaiveTime,
+    pub phone_number: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ContributionSchedule {
+    pub id: Uuid,
+    pub amount: Decimal,
+    pub frequency: String,
+    pub day_of_week: Option<i16>,
+    pub preferred_time: NaiveTime,
+    pub phone_number: String,
+    pub status: String,
+    pub next_run_at: DateTime<Utc>,
+    pub last_run_at: Option<DateTime<Utc>>,
+    pub success_streak: i32,
+    pub failure_streak: i32,
+    pub paused_reason: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+struct DueSchedule {
+    id: Uuid,
+    user_id: Uuid,
+    amount: Decimal,
+    frequency: String,
+    day_of_week: Option<i16>,
+    preferred_time: NaiveTime,
+    phone_number: String,
+    next_run_at: DateTime<Utc>,
+}
+
+// Standing instructions to prompt a member for a contribution by STK push
+// every day or week. Outcomes arrive with the deposit callback, which keeps
+// the streaks; repeated declines pause the schedule.
+#[derive(Clone)]
+pub struct ContributionService {
+    pool: PgPool,
+    fund_service: FundService,
+    mpesa_service: MPesaService,
+    notification_service: NotificationService,
+    config: ContributionConfig,
+}
+
+impl ContributionService {
+    pub fn new(
+        pool: PgPool,
+        fund_service: FundService,
+        mpesa_service: MPesaService,
+        notification_service: NotificationService,
+        config: ContributionConfig,
+    ) -> Self {
+        Self {
+            pool,
+            fund_service,
+            mpesa_service,
+            notification_service,
+            config,
+        }
+    }
+
+    pub fn config(&self) -> &ContributionConfig {
+        &self.config
+    }
+
+    pub async fn create_schedule(
+        &self,
+        user_id: Uuid,
+        schedule: NewSchedule,
+    ) -> Result<ContributionSchedule> {
+        if schedule.amount <= Decimal::ZERO || !schedule.amount.fract().is_zero() {
+            return Err(Error::InvalidAmount);
+        }
+        let day_of_week = validate_day_of_week(schedule.frequency, schedule.day_of_week)?;
+        let phone_number =
+            normalize_msisdn(&schedule.phone_number).ok_or(Error::InvalidPhoneNumber)?;
+
+        let fund_id =
+            sqlx::query_scalar!("SELECT id FROM pension_funds WHERE user_id = $1", user_id)
+                .fetch_optional(&self.pool)
+                .await?
+                .ok_or_else(|| Error::NotFound("Pension fund".to_string()))?;
+
+        let next_run_at = next_run_after(
+            schedule.frequency,
+            day_of_week,
+            schedule.preferred_time,
+            Utc::now(),
+        );
+
+        let schedule = sqlx::query_as!(
+            ContributionSchedule,
+            r#"
+            INSERT INTO contribution_schedules (
+                id, user_id, fund_id, amount, frequency, day_of_week, preferred_time,
+                phone_number, next_run_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            RETURNING id, amount, frequency, day_of_week, preferred_time, phone_number,
+                      status, next_run_at, last_run_at, success_streak, failure_streak,
+                      paused_reason, created_at
+            "#,
+            Uuid::new_v4(),
+            user_id,
+            fund_id,
+            schedule.amount,
+            schedule.frequency.as_str(),
+            day_of_week.map(i16::from),
+            schedule.preferred_time,
+            phone_number,
+            next_run_at,
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(schedule)
+    }
+
+    pub async fn get_schedules(&self, user_id: Uuid) -> Result<Vec<ContributionSchedule>> {
+        let schedules = sqlx::query_as!(
+            ContributionSchedule,
+            r#"
+            SELECT id, amount, frequency, day_of_week, preferred_time, phone_number,
+                   status, next_run_at, last_run_at, success_streak, failure_streak,
+                   paused_reason, created_at
+            FROM contribution_schedules
+            WHERE user_id = $1 AND status <> 'CANCELLED'
+            ORDER BY created_at
+            "#,
+            user_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(schedules)
+    }
+
+    pub async fn pause_schedule(&self, user_id: Uuid, schedule_id: Uuid) -> Result<()> {
+        let paused = sqlx::query!(
+            r#"
+            UPDATE contribution_schedules
+            SET status = 'PAUSED', paused_reason = 'Paused by member', updated_at = NOW()
+            WHERE id = $1 AND user_id = $2 AND status = 'ACTIVE'
+            "#,
+            schedule_id,
+            user_id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        if paused.rows_affected() == 0 {
+            return Err(Error::NotFound("Active contribution schedule".to_string()));
+        }
+        Ok(())
+    }
+
+    // Starts over with a clean failure streak from the next slot after now,
+    // so missed runs are not made up
+    pub async fn resume_schedule(&self, user_id: Uuid, schedule_id: Uuid) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        let schedule = sqlx::query!(
+            r#"
+            SELECT frequency, day_of_week, preferred_time
+            FROM contribution_schedules
+            WHERE id = $1 AND user_id = $2 AND status = 'PAUSED'
+            FOR UPDATE
+            "#,
+            schedule_id,
+            user_id
+        )
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| Error::NotFound("Paused contribution schedule".to_string()))?;
+
+        let next_run_at = next_run_after(
+            schedule.frequency.parse()?,
+            schedule.day_of_week.map(|d| d as u8),
+            schedule.preferred_time,
+            Utc::now(),
+        );
+
+        sqlx::query!(
+            r#"
+            UPDATE contribution_schedules
+            SET status = 'ACTIVE', paused_reason = NULL, failure_streak = 0,
+                next_run_at = $1, updated_at = NOW()
+            WHERE id = $2
+            "#,
+            next_run_at,
+            schedule_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    pub async fn cancel_schedule(&self, user_id: Uuid, schedule_id: Uuid) -> Result<()> {
+        let cancelled = sqlx::query!(
+            r#"
+            UPDATE contribution_schedules
+            SET status = 'CANCELLED', updated_at = NOW()
+            WHERE id = $1 AND user_id = $2 AND status <> 'CANCELLED'
+            "#,
+            schedule_id,
+            user_id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        if cancelled.rows_affected() == 0 {
+            return Err(Error::NotFound("Contribution schedule".to_string()));
+        }
+        Ok(())
+    }
+
+    // Pauses schedules whose prompts keep being declined and tells the
+    // member why the prompts stopped
+    pub async fn pause_failing_schedules(&self) -> Result<usize> {
+        let paused = sqlx::query!(
+            r#"
+            UPDATE contribution_schedules
+            SET status = 'PAUSED',
+                paused_reason = 'Too many declined payment requests',
+                updated_at = NOW()
+            WHERE status = 'ACTIVE' AND failure_streak >= $1
+            RETURNING phone_number, failure_streak
+            "#,
+            self.config.max_consecutive_failures
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        for schedule in &paused {
+            if let Err(e) = self
+                .notification_service
+                .send_contribution_schedule_paused(&schedule.phone_number, schedule.failure_streak)
+                .await
+            {
+                tracing::warn!("Pause notice to {} failed: {}", schedule.phone_number, e);
+            }
+        }
+
+        Ok(paused.len())
+    }
+
+    pub async fn send_reminders(&self) -> Result<usize> {
+        let upcoming = sqlx::query!(
+            r#"
+            UPDATE contribution_schedules
+            SET reminded_for = next_run_at
+            WHERE status = 'ACTIVE'
+            AND next_run_at > NOW()
+            AND next_run_at <= $1
+            AND reminded_for IS DISTINCT FROM next_run_at
+            RETURNING phone_number, amount, next_run_at
+            "#,
+            Utc::now() + Duration::seconds(self.config.reminder_lead_secs)
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut sent = 0;
+        for schedule in upcoming {
+            match self
+                .notification_service
+                .send_contribution_reminder(
+                    &schedule.phone_number,
+                    schedule.amount.to_f64().unwrap_or_default(),
+                    &local_time(schedule.next_run_at).format("%H:%M").to_string(),
+                )
+                .await
+            {
+                Ok(()) => sent += 1,
+                Err(e) => tracing::warn!("Reminder to {} failed: {}", schedule.phone_number, e),
+            }
+        }
+
+        Ok(sent)
+    }
+
+    // Sends the STK push for every schedule that has come due
+    pub async fn run_due_schedules(&self) -> Result<usize> {
+        let due = sqlx::query_as!(
+            DueSchedule,
+            r#"
+            SELECT id, user_id, amount, frequency, day_of_week, preferred_time,
+                   phone_number, next_run_at
+            FROM contribution_schedules
+            WHERE status = 'ACTIVE' AND next_run_at <= NOW()
+            ORDER BY next_run_at
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut prompted = 0;
+        for schedule in due {
+            if !self.claim(&schedule).await? {
+                continue;
+            }
+
+            match self.prompt(&schedule).await {
+                Ok(()) => prompted += 1,
+                Err(e) => {
+                    tracing::warn!("Scheduled contribution {} failed: {}", schedule.id, e);
+                    // A push that never reached the member counts as a miss
+                    if let Err(e) = record_schedule_outcome(&self.pool, schedule.id, false).await {
+                        tracing::error!(
+                            "Could not record the miss for schedule {}: {}",
+                            schedule.id,
+                            e
+                        );
+                    }
+                }
+            }
+        }
+
+        Ok(prompted)
+    }
+
+    // Moves the schedule to its next slot. Guarded on next_run_at so that
+    // two scheduler instances cannot both prompt for the same run.
+    async fn claim(&self, schedule: &DueSchedule) -> Result<bool> {
+        let next_run_at = next_run_after(
+            schedule.frequency.parse()?,
+            schedule.day_of_week.map(|d| d as u8),
+            schedule.preferred_time,
+            Utc::now(),
+        );
+
+        let claimed = sqlx::query!(
+            r#"
+            UPDATE contribution_schedules
+            SET next_run_at = $1, last_run_at = NOW(), updated_at = NOW()
+            WHERE id = $2 AND status = 'ACTIVE' AND next_run_at = $3
+            "#,
+            next_run_at,
+            schedule.id,
+            schedule.next_run_at
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(claimed.rows_affected() == 1)
+    }
+
+    async fn prompt(&self, schedule: &DueSchedule) -> Result<()> {
+        // A prompt the member has not answered yet is not stacked on
+        let outstanding = sqlx::query_scalar!(
+            r#"
+            SELECT EXISTS (
+                SELECT 1 FROM transactions
+                WHERE schedule_id = $1 AND status = 'PENDING'
+            ) as "exists!"
+            "#,
+            schedule.id
+        )
+        .fetch_one(&self.pool)
+        .await?;
+        if outstanding {
+            return Ok(());
+        }
+
+        self.fund_service.require_fund(schedule.user_id).await?;
+        self.fund_service
+            .validate_deposit(schedule.user_id, schedule.amount)
+            .await?;
+
+        let amount = schedule.amount.to_f64().ok_or(Error::InvalidAmount)?;
+        let stk_response = self
+            .mpesa_service
+            .initiate_payment(
+                &schedule.phone_number,
+                amount,
+                &format!("PEN{}", schedule.user_id),
+            )
+            .await
+            .map_err(|e| Error::MPesa(e.to_string()))?;
+
+        self.fund_service
+            .record_pending_deposit(
+                schedule.user_id,
+                amount,
+                &stk_response.checkout_request_id,
+                Some(schedule.id),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+// Called as a scheduled deposit settles: a payment extends the success
+// streak, a decline extends the failure streak
+pub async fn record_schedule_outcome(
+    executor: impl PgExecutor<'_>,
+    schedule_id: Uuid,
+    succeeded: bool,
+) -> anyhow::Result<()> {
+    sqlx::query!(
+        r#"
+        UPDATE contribution_schedules
+        SET success_streak = CASE WHEN $1 THEN success_streak + 1 ELSE 0 END,
+            failure_streak = CASE WHEN $1 THEN 0 ELSE failure_streak + 1 END,
+            updated_at = NOW()
+        WHERE id = $2
+        "#,
+        succeeded,
+        schedule_id
+    )
+    .execute(executor)
+    .await?;
+
+    Ok(())
+}
+
+fn validate_day_of_week(
+    frequency: ContributionFrequency,
+    day_of_week: Option<u8>,
+) -> Result<Option<u8>> {
+    match (frequency, day_of_week) {
+        (ContributionFrequency::Daily, _) => Ok(None),
+        (ContributionFrequency::Weekly, Some(day @ 1..=7)) => Ok(Some(day)),
+        (ContributionFrequency::Weekly, _) => Err(Error::InvalidSchedule(
+            "Weekly contributions need a day_of_week from 1 (Monday) to 7 (Sunday)".to_string(),
+        )),
+    }
+}
+
+fn local_time(at: DateTime<Utc>) -> chrono::NaiveDateTime {
+    at.naive_utc() + Duration::seconds(EAST_AFRICA_OFFSET_SECS)
+}
+
+// The first slot strictly after `after`, in East Africa Time
+pub fn next_run_after(
+    frequency: ContributionFrequency,
+    day_of_week: Option<u8>,
+    preferred_time: NaiveTime,
+    after: DateTime<Utc>,
+) -> DateTime<Utc> {
+    let preferred_time = preferred_time.with_nanosecond(0).unwrap_or(preferred_time);
+    let mut date = local_time(after).date();
+
+    loop {
+        let candidate =
+            (date.and_time(preferred_time) - Duration::seconds(EAST_AFRICA_OFFSET_SECS)).and_utc();
+
+        let on_day = match frequency {
+            ContributionFrequency::Daily => true,
+            ContributionFrequency::Weekly => {
+                day_of_week.map(u32::from) == Some(date.weekday().number_from_monday())
+            }
+        };
+
+        if on_day && candidate > after {
+            return candidate;
+        }
+        date = date.succ_opt().expect("date within chrono's range");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(s: &str) -> DateTime<Utc> {
+        s.parse().unwrap()
+    }
+
+    fn time(s: &str) -> NaiveTime {
+        NaiveTime::parse_from_str(s, "%H:%M").unwrap()
+    }
+
+    #[test]
+    fn test_daily_runs_at_preferred_local_time() {
+        // 07:00 EAT is 04:00 UTC
+        assert_eq!(
+            next_run_after(
+                ContributionFrequency::Daily,
+                None,
+                time("07:00"),
+                at("2025-03-10T03:00:00Z")
+            ),
+            at("2025-03-10T04:00:00Z")
+        );
+        assert_eq!(
+            next_run_after(
+                ContributionFrequency::Daily,
+                None,
+                time("07:00"),
+                at("2025-03-10T04:00:00Z")
+            ),
+            at("2025-03-11T04:00:00Z")
+        );
+    }
+
+    #[test]
+    fn test_daily_uses_the_local_date() {
+        // 22:30 UTC on the 10th is already 01:30 on the 11th in Nairobi
+        assert_eq!(
+            next_run_after(
+                ContributionFrequency::Daily,
+                None,
+                time("02:00"),
+                at("2025-03-10T22:30:00Z")
+            ),
+            at("2025-03-10T23:00:00Z")
+        );
+    }
+
+    #[test]
+    fn test_weekly_runs_on_chosen_day() {
+        // 2025-03-10 is a Monday; Friday is day 5
+        assert_eq!(
+            next_run_after(
+                ContributionFrequency::Weekly,
+                Some(5),
+                time("18:00"),
+                at("2025-03-10T12:00:00Z")
+            ),
+            at("2025-03-14T15:00:00Z")
+        );
+        assert_eq!(
+            next_run_after(
+                ContributionFrequency::Weekly,
+                Some(5),
+                time("18:00"),
+                at("2025-03-14T15:00:00Z")
+            ),
+            at("2025-03-21T15:00:00Z")
+        );
+    }
+
+    #[test]
+    fn test_weekly_needs_a_day() {
+        assert!(validate_day_of_week(ContributionFrequency::Weekly, None).is_err());
+        assert!(validate_day_of_week(ContributionFrequency::Weekly, Some(0)).is_err());
+        assert!(validate_day_of_week(ContributionFrequency::Weekly, Some(8)).is_err());
+        assert_eq!(
+            validate_day_of_week(ContributionFrequency::Weekly, Some(7)).unwrap(),
+            Some(7)
+        );
+        assert_eq!(
+            validate_day_of_week(ContributionFrequency::Daily, Some(3)).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_frequency_round_trip() {
+        for frequency in [ContributionFrequency::Daily, ContributionFrequency::Weekly] {
+            assert_eq!(
+                frequency.as_str().parse::<ContributionFrequency>().unwrap(),
+                frequency
+            );
+        }
+        assert!("MONTHLY".parse::<ContributionFrequency>().is_err());
+    }
+}
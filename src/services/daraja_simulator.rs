@@ -0,0 +1,1030 @@
+use anyhow::{anyhow, Result};
+use axum::{
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    routing::{get, post},
+    Json, Router,
+};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use chrono::Utc;
+use reqwest::Client;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use tokio::task::JoinHandle;
+
+use crate::services::mpesa_service::{MPesaConfig, MPesaService};
+
+// In-process stand-in for the Daraja API. It answers the endpoints
+// MPesaService calls and lets a test script the callbacks Safaricom would
+// send back, so deposit and withdrawal flows run without network access.
+pub struct DarajaSimulator {
+    addr: SocketAddr,
+    state: Arc<Mutex<SimulatorState>>,
+    client: Client,
+    server: JoinHandle<()>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum StkOutcome {
+    Pending,
+    Completed { receipt: String },
+    Failed { result_code: i32, reason: String },
+}
+
+#[derive(Debug, Clone)]
+pub struct SimulatedStkPush {
+    pub checkout_request_id: String,
+    pub merchant_request_id: String,
+    pub phone_number: String,
+    pub amount: String,
+    pub callback_url: String,
+    pub outcome: StkOutcome,
+}
+
+#[derive(Debug, Clone)]
+pub struct SimulatedB2C {
+    pub originator_conversation_id: String,
+    pub conversation_id: String,
+    pub phone_number: String,
+    pub amount: String,
+    pub result_url: String,
+    pub timeout_url: String,
+}
+
+struct SimulatorState {
+    consumer_key: String,
+    consumer_secret: String,
+    token_requests: usize,
+    issued_tokens: Vec<String>,
+    next_id: u64,
+    stk_pushes: HashMap<String, SimulatedStkPush>,
+    b2c_payments: HashMap<String, SimulatedB2C>,
+}
+
+impl SimulatorState {
+    fn next_id(&mut self) -> u64 {
+        self.next_id += 1;
+        self.next_id
+    }
+
+    fn authorized(&self, headers: &HeaderMap) -> bool {
+        headers
+            .get("Authorization")
+            .and_then(|h| h.to_str().ok())
+            .and_then(|h| h.strip_prefix("Bearer "))
+            .map(|token| self.issued_tokens.iter().any(|t| t == token))
+            .unwrap_or(false)
+    }
+}
+
+impl DarajaSimulator {
+    pub async fn start(consumer_key: &str, consumer_secret: &str) -> Result<Self> {
+        let state = Arc::new(Mutex::new(SimulatorState {
+            consumer_key: consumer_key.to_string(),
+            consumer_secret: consumer_secret.to_string(),
+            token_requests: 0,
+            issued_tokens: Vec::new(),
+            next_id: 0,
+            stk_pushes: HashMap::new(),
+            b2c_payments: HashMap::new(),
+        }));
+
+        let app = Router::new()
+            .route("/oauth/v1/generate", get(generate_token))
+            .route("/mpesa/stkpush/v1/processrequest", post(stk_push))
+            .route("/mpesa/stkpushquery/v1/query", post(stk_query))
+            .route("/mpesa/b2c/v1/paymentrequest", post(b2c_payment))
+            .with_state(state.clone());
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let server = tokio::spawn(async move {
+            axum::serve(listener, app).await.ok();
+        });
+
+        Ok(Self {
+            addr,
+            state,
+            client: Client::new(),
+            server,
+        })
+    }
+
+    pub fn base_url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    // Config pointing at the simulator, with callbacks posted to `callback_base`
+    pub fn config(&self, callback_base: &str) -> MPesaConfig {
+        let state = self.state.lock().unwrap();
+
+        MPesaConfig {
+            base_url: self.base_url(),
+            consumer_key: state.consumer_key.clone(),
+            consumer_secret: state.consumer_secret.clone(),
+            business_shortcode: "174379".to_string(),
+            passkey: "simulator-passkey".to_string(),
+            callback_url: format!("{}/api/deposit/callback", callback_base),
+            initiator_name: "testapi".to_string(),
+            security_credential: "simulator-credential".to_string(),
+            b2c_result_url: format!("{}/api/withdrawal/b2c/result", callback_base),
+            b2c_timeout_url: format!("{}/api/withdrawal/b2c/timeout", callback_base),
+            callback_token: "simulator-callback-token".to_string(),
+        }
+    }
+
+    pub fn mpesa_service(&self, callback_base: &str) -> MPesaService {
+        MPesaService::from_config(self.config(callback_base))
+    }
+
+    pub fn token_requests(&self) -> usize {
+        self.state.lock().unwrap().token_requests
+    }
+
+    // Revokes every token issued so far, as Daraja does ahead of the
+    // advertised expiry
+    pub fn revoke_tokens(&self) {
+        self.state.lock().unwrap().issued_tokens.clear();
+    }
+
+    pub fn stk_pushes(&self) -> Vec<SimulatedStkPush> {
+        self.state
+            .lock()
+            .unwrap()
+            .stk_pushes
+            .values()
+            .cloned()
+            .collect()
+    }
+
+    pub fn b2c_payments(&self) -> Vec<SimulatedB2C> {
+        self.state
+            .lock()
+            .unwrap()
+            .b2c_payments
+            .values()
+            .cloned()
+            .collect()
+    }
+
+    // Changes what the STK query endpoint reports without sending a callback,
+    // which is how a lost callback looks from our side
+    pub fn set_stk_outcome(&self, checkout_request_id: &str, outcome: StkOutcome) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        let push = state
+            .stk_pushes
+            .get_mut(checkout_request_id)
+            .ok_or_else(|| anyhow!("Unknown CheckoutRequestID {}", checkout_request_id))?;
+        push.outcome = outcome;
+        Ok(())
+    }
+
+    pub async fn complete_stk_push(&self, checkout_request_id: &str, receipt: &str) -> Result<()> {
+        self.set_stk_outcome(
+            checkout_request_id,
+            StkOutcome::Completed {
+                receipt: receipt.to_string(),
+            },
+        )?;
+        self.send_stk_callback(checkout_request_id).await
+    }
+
+    pub async fn cancel_stk_push(&self, checkout_request_id: &str) -> Result<()> {
+        self.set_stk_outcome(
+            checkout_request_id,
+            StkOutcome::Failed {
+                result_code: 1032,
+                reason: "Request cancelled by user".to_string(),
+            },
+        )?;
+        self.send_stk_callback(checkout_request_id).await
+    }
+
+    // Sends (or re-sends, to exercise idempotency) the callback for a push
+    pub async fn send_stk_callback(&self, checkout_request_id: &str) -> Result<()> {
+        let push = self
+            .state
+            .lock()
+            .unwrap()
+            .stk_pushes
+            .get(checkout_request_id)
+            .cloned()
+            .ok_or_else(|| anyhow!("Unknown CheckoutRequestID {}", checkout_request_id))?;
+
+        let body = stk_callback_body(&push)?;
+        self.deliver(&push.callback_url, &body).await
+    }
+
+    pub async fn complete_b2c(
+        &self,
+        originator_conversation_id: &str,
+        receipt: &str,
+    ) -> Result<()> {
+        let payment = self.b2c_payment(originator_conversation_id)?;
+        let body = b2c_result_body(
+            &payment,
+            0,
+            "The service request is processed successfully.",
+            Some(receipt),
+        );
+        self.deliver(&payment.result_url, &body).await
+    }
+
+    pub async fn fail_b2c(
+        &self,
+        originator_conversation_id: &str,
+        result_code: i32,
+        reason: &str,
+    ) -> Result<()> {
+        let payment = self.b2c_payment(originator_conversation_id)?;
+        let body = b2c_result_body(&payment, result_code, reason, None);
+        self.deliver(&payment.result_url, &body).await
+    }
+
+    pub async fn timeout_b2c(&self, originator_conversation_id: &str) -> Result<()> {
+        let payment = self.b2c_payment(originator_conversation_id)?;
+        let body = b2c_result_body(&payment, 1, "The request timed out in the queue", None);
+        self.deliver(&payment.timeout_url, &body).await
+    }
+
+    fn b2c_payment(&self, originator_conversation_id: &str) -> Result<SimulatedB2C> {
+        self.state
+            .lock()
+            .unwrap()
+            .b2c_payments
+            .get(originator_conversation_id)
+            .cloned()
+            .ok_or_else(|| {
+                anyhow!(
+                    "Unknown OriginatorConversationID {}",
+                    originator_conversation_id
+                )
+            })
+    }
+
+    async fn deliver(&self, url: &str, body: &Value) -> Result<()> {
+        let response = self.client.post(url).json(body).send().await?;
+        if !response.status().is_success() {
+            return Err(anyhow!(
+                "Callback to {} returned {}",
+                url,
+                response.status()
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl Drop for DarajaSimulator {
+    fn drop(&mut self) {
+        self.server.abort();
+    }
+}
+
+fn stk_callback_body(push: &SimulatedStkPush) -> Result<Value> {
+    let stk_callback = match &push.outcome {
+        StkOutcome::Completed { receipt } => json!({
+            "MerchantRequestID": push.merchant_request_id,
+            "CheckoutRequestID": push.checkout_request_id,
+            "ResultCode": 0,
+            "ResultDesc": "The service request is processed successfully.",
+            "CallbackMetadata": {
+                "Item": [
+                    { "Name": "Amount", "Value": push.amount.parse::<f64>()? },
+                    { "Name": "MpesaReceiptNumber", "Value": receipt },
+                    { "Name": "TransactionDate", "Value": Utc::now().format("%Y%m%d%H%M%S").to_string().parse::<u64>()? },
+                    { "Name": "PhoneNumber", "Value": push.phone_number.parse::<u64>()? }
+                ]
+            }
+        }),
+        StkOutcome::Failed {
+            result_code,
+            reason,
+        } => json!({
+            "MerchantRequestID": push.merchant_request_id,
+            "CheckoutRequestID": push.checkout_request_id,
+            "ResultCode": result_code,
+            "ResultDesc": reason
+        }),
+        StkOutcome::Pending => return Err(anyhow!("STK push has no outcome yet")),
+    };
+
+    Ok(json!({ "Body": { "stkCallback": stk_callback } }))
+}
+
+fn b2c_result_body(
+    payment: &SimulatedB2C,
+    result_code: i32,
+    reason: &str,
+    receipt: Option<&str>,
+) -> Value {
+    json!({
+        "Result": {
+            "ResultType": 0,
+            "ResultCode": result_code,
+            "ResultDesc": reason,
+            "OriginatorConversationID": payment.originator_conversation_id,
+            "ConversationID": payment.conversation_id,
+            "TransactionID": receipt
+        }
+    })
+}
+
+fn daraja_error(status: StatusCode, code: &str, message: &str) -> (StatusCode, Json<Value>) {
+    (
+        status,
+        Json(json!({
+            "requestId": "simulator",
+            "errorCode": code,
+            "errorMessage": message
+        })),
+    )
+}
+
+fn invalid_token() -> (StatusCode, Json<Value>) {
+    daraja_error(
+        StatusCode::UNAUTHORIZED,
+        "404.001.04",
+        "Invalid Access Token",
+    )
+}
+
+async fn generate_token(
+    State(state): State<Arc<Mutex<SimulatorState>>>,
+    headers: HeaderMap,
+) -> (StatusCode, Json<Value>) {
+    let mut state = state.lock().unwrap();
+    state.token_requests += 1;
+
+    let expected = format!(
+        "Basic {}",
+        BASE64.encode(format!("{}:{}", state.consumer_key, state.consumer_secret))
+    );
+    let provided = headers
+        .get("Authorization")
+        .and_then(|h| h.to_str().ok())
+        .unwrap_or_default();
+
+    if provided != expected {
+        return daraja_error(
+            StatusCode::BAD_REQUEST,
+            "400.008.01",
+            "Invalid Authentication passed",
+        );
+    }
+
+    let token = format!("simulator-token-{}", state.next_id());
+    state.issued_tokens.push(token.clone());
+    (
+        StatusCode::OK,
+        Json(json!({ "access_token": token, "expires_in": "3599" })),
+    )
+}
+
+async fn stk_push(
+    State(state): State<Arc<Mutex<SimulatorState>>>,
+    headers: HeaderMap,
+    Json(request): Json<Value>,
+) -> (StatusCode, Json<Value>) {
+    let mut state = state.lock().unwrap();
+    if !state.authorized(&headers) {
+        return invalid_token();
+    }
+    let id = state.next_id();
+
+    let push = SimulatedStkPush {
+        checkout_request_id: format!("ws_CO_SIM_{}", id),
+        merchant_request_id: format!("SIM-{}", id),
+        phone_number: request["PhoneNumber"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string(),
+        amount: request["Amount"].as_str().unwrap_or_default().to_string(),
+        callback_url: request["CallBackURL"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string(),
+        outcome: StkOutcome::Pending,
+    };
+
+    let response = json!({
+        "MerchantRequestID": push.merchant_request_id,
+        "CheckoutRequestID": push.checkout_request_id,
+        "ResponseCode": "0",
+        "ResponseDescription": "Success. Request accepted for processing",
+        "CustomerMessage": "Success. Request accepted for processing"
+    });
+    state
+        .stk_pushes
+        .insert(push.checkout_request_id.clone(), push);
+
+    (StatusCode::OK, Json(response))
+}
+
+async fn stk_query(
+    State(state): State<Arc<Mutex<SimulatorState>>>,
+    headers: HeaderMap,
+    Json(request): Json<Value>,
+) -> (StatusCode, Json<Value>) {
+    let state = state.lock().unwrap();
+    if !state.authorized(&headers) {
+        return invalid_token();
+    }
+    let checkout_request_id = request["CheckoutRequestID"].as_str().unwrap_or_default();
+
+    let Some(push) = state.stk_pushes.get(checkout_request_id) else {
+        return daraja_error(
+            StatusCode::BAD_REQUEST,
+            "400.002.02",
+            "Invalid CheckoutRequestID",
+        );
+    };
+
+    let (result_code, result_desc) = match &push.outcome {
+        StkOutcome::Pending => {
+            return daraja_error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "500.001.1001",
+                "The transaction is being processed",
+            )
+        }
+        StkOutcome::Completed { .. } => (
+            "0".to_string(),
+            "The service request is processed successfully.".to_string(),
+        ),
+        StkOutcome::Failed {
+            result_code,
+            reason,
+        } => (result_code.to_string(), reason.clone()),
+    };
+
+    (
+        StatusCode::OK,
+        Json(json!({
+            "ResponseCode": "0",
+            "ResponseDescription": "The service request has been accepted successsfully",
+            "MerchantRequestID": push.merchant_request_id,
+            "CheckoutRequestID": push.checkout_request_id,
+            "ResultCode": result_code,
+            "ResultDesc": result_desc
+        })),
+    )
+}
+
+async fn b2c_payment(
+    State(state): State<Arc<Mutex<SimulatorState>>>,
+    headers: HeaderMap,
+    Json(request): Json<Value>,
+) -> (StatusCode, Json<Value>) {
+    let mut state = state.lock().unwrap();
+    if !state.authorized(&headers) {
+        return invalid_token();
+    }
+    let id = state.next_id();
+
+    let payment = SimulatedB2C {
+        originator_conversation_id: format!("SIM-B2C-{}", id),
+        conversation_id: format!("AG_SIM_{}", id),
+        phone_number: request["PartyB"].as_str().unwrap_or_default().to_string(),
+        amount: request["Amount"].as_str().unwrap_or_default().to_string(),
+        result_url: request["ResultURL"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string(),
+        timeout_url: request["QueueTimeOutURL"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string(),
+    };
+
+    let response = json!({
+        "ConversationID": payment.conversation_id,
+        "OriginatorConversationID": payment.originator_conversation_id,
+        "ResponseCode": "0",
+        "ResponseDescription": "Accept the service request successfully."
+    });
+    state
+        .b2c_payments
+        .insert(payment.originator_conversation_id.clone(), payment);
+
+    (StatusCode::OK, Json(response))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::glide_path::GlidePath;
+    use crate::api::{self, AppState};
+    use crate::config;
+    use crate::services::{self, mpesa_service::STKQueryStatus};
+    use rust_decimal::Decimal;
+    use sqlx::PgPool;
+    use uuid::Uuid;
+
+    const PHONE: &str = "254708374149";
+
+    // The whole API over the test database, with M-Pesa pointed at the
+    // simulator and its callbacks at this API
+    async fn start_api(simulator: &DarajaSimulator, pool: PgPool) -> String {
+        std::env::set_var("JWT_SECRET", "simulator-jwt-secret");
+        std::env::set_var("SMS_API_KEY", "simulator-sms-key");
+        std::env::set_var("SMS_SENDER_ID", "BLUPENSION");
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let api_base = format!("http://{}", listener.local_addr().unwrap());
+        let state = app_state(pool, simulator.mpesa_service(&api_base)).await;
+
+        tokio::spawn(async move {
+            let cors = config::cors::CorsConfig::default();
+            axum::serve(listener, api::router(state, &cors)).await.ok();
+        });
+
+        api_base
+    }
+
+    async fn app_state(pool: PgPool, mpesa_service: MPesaService) -> AppState {
+        let notifications = || services::notification_service::NotificationService::new().unwrap();
+        let mut keypair = stellar_sdk::Keypair::random().unwrap();
+        let stellar =
+            services::StellarService::new("testnet", &keypair.secret_key().unwrap()).unwrap();
+
+        let withdrawal_policy = config::withdrawal_policy::WithdrawalPolicyConfig::default();
+        let fund_service = services::fund_service::FundService::new(
+            pool.clone(),
+            config::transaction_limits::TransactionLimits::default(),
+            withdrawal_policy.clone(),
+        );
+        let phone_auth_service = services::PhoneAuthService::new(
+            pool.clone(),
+            notifications(),
+            config::phone_auth::PhoneAuthConfig::default(),
+        );
+        let bpt_manager =
+            services::BPTManager::new(pool.clone(), stellar, "simulator-contract".to_string())
+                .await;
+        let price_history_service = services::PriceHistoryService::new(
+            pool.clone(),
+            config::price_history::PriceHistoryConfig::default(),
+        );
+        let rebalancing_service = services::RebalancingService::new(
+            pool.clone(),
+            None,
+            config::rebalancing::RebalancingConfig::default(),
+        );
+
+        AppState {
+            pool: pool.clone(),
+            auth_service: services::AuthService::new(
+                pool.clone(),
+                config::auth::AuthConfig::default(),
+            ),
+            user_service: services::user_service::UserService::new(pool.clone()),
+            phone_auth_service: phone_auth_service.clone(),
+            fund_service: fund_service.clone(),
+            mpesa_service: mpesa_service.clone(),
+            investment_service: Arc::new(
+                services::InvestmentService::new(
+                    pool.clone(),
+                    rebalancing_service.clone(),
+                    GlidePath::new(
+                        config::glide_path::GlidePathConfig::default(),
+                        withdrawal_policy.retirement_age,
+                    )
+                    .unwrap(),
+                    services::price_feed::PriceFeedService::new(
+                        Vec::new(),
+                        config::price_feed::PriceFeedConfig::default(),
+                    ),
+                    price_history_service.clone(),
+                )
+                .unwrap(),
+            ),
+            bpt_manager: bpt_manager.clone(),
+            loan_service: services::LoanService::new(
+                pool.clone(),
+                bpt_manager,
+                mpesa_service.clone(),
+                config::loans::LoanConfig::default(),
+            ),
+            ussd_service: services::UssdService::new(
+                phone_auth_service,
+                fund_service.clone(),
+                mpesa_service.clone(),
+                "simulator-ussd-token".to_string(),
+            ),
+            kyc_service: services::KycService::new(
+                pool.clone(),
+                Arc::new(services::mock_kyc_provider::MockKycProvider::new()),
+            ),
+            contribution_service: services::ContributionService::new(
+                pool.clone(),
+                fund_service,
+                mpesa_service,
+                notifications(),
+                config::contributions::ContributionConfig::default(),
+            ),
+            employer_service: services::EmployerService::new(pool),
+            price_history_service,
+            rebalancing_service,
+        }
+    }
+
+    // A fully verified member whose fund holds `balance`, with an access
+    // token to act as them
+    async fn member(pool: &PgPool, balance: Decimal) -> (Uuid, String) {
+        let user_id = sqlx::query_scalar!(
+            r#"
+            INSERT INTO users (username, phone_number, kyc_status, phone_verified)
+            VALUES ('simulated', $1, 'APPROVED', TRUE)
+            RETURNING id
+            "#,
+            PHONE
+        )
+        .fetch_one(pool)
+        .await
+        .unwrap();
+
+        sqlx::query!(
+            "INSERT INTO pension_funds (user_id, investment_plan, balance) VALUES ($1, 'MODERATE', $2)",
+            user_id,
+            balance
+        )
+        .execute(pool)
+        .await
+        .unwrap();
+
+        let token = crate::auth::create_token(user_id, time::Duration::minutes(5)).unwrap();
+        (user_id, token)
+    }
+
+    async fn administrator(pool: &PgPool) -> String {
+        let user_id = sqlx::query_scalar!(
+            r#"
+            INSERT INTO users (username, phone_number, kyc_status, phone_verified, is_admin)
+            VALUES ('administrator', '254700000001', 'APPROVED', TRUE, TRUE)
+            RETURNING id
+            "#
+        )
+        .fetch_one(pool)
+        .await
+        .unwrap();
+
+        crate::auth::create_token(user_id, time::Duration::minutes(5)).unwrap()
+    }
+
+    // Submits a hardship withdrawal and has an administrator approve it,
+    // which is when the payout goes to M-Pesa
+    async fn approved_hardship_withdrawal(
+        simulator: &DarajaSimulator,
+        pool: &PgPool,
+        api_base: &str,
+        token: &str,
+    ) -> Uuid {
+        let response: Value = simulator
+            .client
+            .post(format!("{}/api/withdrawal", api_base))
+            .bearer_auth(token)
+            .json(&json!({
+                "amount": 1000.0,
+                "phone_number": PHONE,
+                "kind": "HARDSHIP",
+                "reason": "Medical bills"
+            }))
+            .send()
+            .await
+            .unwrap()
+            .error_for_status()
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        let transaction_id: Uuid = response["transaction_id"]
+            .as_str()
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert_eq!(
+            transaction_status(pool, transaction_id).await,
+            "PENDING_REVIEW"
+        );
+        assert!(simulator.b2c_payments().is_empty());
+
+        simulator
+            .client
+            .post(format!(
+                "{}/api/admin/withdrawals/{}/approve",
+                api_base, transaction_id
+            ))
+            .bearer_auth(administrator(pool).await)
+            .send()
+            .await
+            .unwrap()
+            .error_for_status()
+            .unwrap();
+
+        transaction_id
+    }
+
+    async fn transaction_status(pool: &PgPool, transaction_id: Uuid) -> String {
+        sqlx::query_scalar!(
+            "SELECT status FROM transactions WHERE id = $1",
+            transaction_id
+        )
+        .fetch_one(pool)
+        .await
+        .unwrap()
+    }
+
+    // Every posting made for the transaction, as (account, amount) with
+    // fund accounts shown as FUND
+    async fn postings(pool: &PgPool, transaction_id: Uuid) -> Vec<(String, Decimal)> {
+        sqlx::query!(
+            r#"
+            SELECT a.code, p.amount
+            FROM ledger_postings p
+            JOIN journal_entries e ON e.id = p.entry_id
+            JOIN ledger_accounts a ON a.id = p.account_id
+            WHERE e.transaction_id = $1
+            ORDER BY e.created_at, p.amount DESC
+            "#,
+            transaction_id
+        )
+        .fetch_all(pool)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|p| {
+            let account = if p.code.starts_with("FUND:") {
+                "FUND".to_string()
+            } else {
+                p.code
+            };
+            (account, p.amount.normalize())
+        })
+        .collect()
+    }
+
+    #[sqlx::test(migrator = "crate::db::MIGRATOR")]
+    async fn test_deposit_settles_through_the_callback(pool: PgPool) {
+        let simulator = DarajaSimulator::start("key", "secret").await.unwrap();
+        let api_base = start_api(&simulator, pool.clone()).await;
+        let (user_id, token) = member(&pool, Decimal::ZERO).await;
+        let mpesa = simulator.mpesa_service(&api_base);
+
+        let response: Value = simulator
+            .client
+            .post(format!("{}/api/deposit", api_base))
+            .bearer_auth(&token)
+            .json(&json!({ "amount": 500.0, "phone_number": PHONE }))
+            .send()
+            .await
+            .unwrap()
+            .error_for_status()
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        let checkout_request_id = response["checkout_request_id"].as_str().unwrap();
+        assert_eq!(
+            mpesa.query_stk_status(checkout_request_id).await.unwrap(),
+            STKQueryStatus::Pending
+        );
+
+        let transaction_id = sqlx::query_scalar!(
+            "SELECT id FROM transactions WHERE checkout_request_id = $1",
+            checkout_request_id
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(transaction_status(&pool, transaction_id).await, "PENDING");
+        assert!(postings(&pool, transaction_id).await.is_empty());
+
+        simulator
+            .complete_stk_push(checkout_request_id, "NLJ7RT61SV")
+            .await
+            .unwrap();
+        // Safaricom retries callbacks; the repeat must not credit twice
+        simulator
+            .send_stk_callback(checkout_request_id)
+            .await
+            .unwrap();
+
+        assert_eq!(transaction_status(&pool, transaction_id).await, "COMPLETED");
+        let receipt = sqlx::query_scalar!(
+            "SELECT mpesa_reference FROM transactions WHERE id = $1",
+            transaction_id
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(receipt.as_deref(), Some("NLJ7RT61SV"));
+        assert_eq!(
+            postings(&pool, transaction_id).await,
+            vec![
+                ("MPESA_CLEARING".to_string(), Decimal::new(500, 0)),
+                ("FUND".to_string(), Decimal::new(-500, 0)),
+            ]
+        );
+        let balance = sqlx::query_scalar!(
+            "SELECT balance FROM pension_funds WHERE user_id = $1",
+            user_id
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(balance.normalize(), Decimal::new(500, 0));
+    }
+
+    #[sqlx::test(migrator = "crate::db::MIGRATOR")]
+    async fn test_callback_without_the_token_is_rejected(pool: PgPool) {
+        let simulator = DarajaSimulator::start("key", "secret").await.unwrap();
+        let api_base = start_api(&simulator, pool.clone()).await;
+        let (_, token) = member(&pool, Decimal::ZERO).await;
+
+        let response: Value = simulator
+            .client
+            .post(format!("{}/api/deposit", api_base))
+            .bearer_auth(&token)
+            .json(&json!({ "amount": 500.0, "phone_number": PHONE }))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        let checkout_request_id = response["checkout_request_id"].as_str().unwrap();
+        simulator
+            .set_stk_outcome(
+                checkout_request_id,
+                StkOutcome::Completed {
+                    receipt: "NLJ7RT61SV".to_string(),
+                },
+            )
+            .unwrap();
+
+        let push = simulator
+            .stk_pushes()
+            .into_iter()
+            .find(|p| p.checkout_request_id == checkout_request_id)
+            .unwrap();
+        let forged = simulator
+            .client
+            .post(format!("{}/api/deposit/callback", api_base))
+            .json(&stk_callback_body(&push).unwrap())
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(forged.status(), StatusCode::UNAUTHORIZED);
+        let status = sqlx::query_scalar!(
+            "SELECT status FROM transactions WHERE checkout_request_id = $1",
+            checkout_request_id
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(status, "PENDING");
+    }
+
+    #[sqlx::test(migrator = "crate::db::MIGRATOR")]
+    async fn test_fractional_deposit_is_rejected(pool: PgPool) {
+        let simulator = DarajaSimulator::start("key", "secret").await.unwrap();
+        let api_base = start_api(&simulator, pool.clone()).await;
+        let (_, token) = member(&pool, Decimal::ZERO).await;
+
+        let response = simulator
+            .client
+            .post(format!("{}/api/deposit", api_base))
+            .bearer_auth(&token)
+            .json(&json!({ "amount": 500.5, "phone_number": PHONE }))
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert!(simulator.stk_pushes().is_empty());
+    }
+
+    #[sqlx::test(migrator = "crate::db::MIGRATOR")]
+    async fn test_withdrawal_pays_out_through_the_b2c_result(pool: PgPool) {
+        let simulator = DarajaSimulator::start("key", "secret").await.unwrap();
+        let api_base = start_api(&simulator, pool.clone()).await;
+        let (_, token) = member(&pool, Decimal::new(10_000, 0)).await;
+
+        let transaction_id =
+            approved_hardship_withdrawal(&simulator, &pool, &api_base, &token).await;
+        assert_eq!(transaction_status(&pool, transaction_id).await, "PENDING");
+
+        let payment = simulator.b2c_payments().pop().unwrap();
+        assert_eq!(payment.amount, "1000");
+        simulator
+            .complete_b2c(&payment.originator_conversation_id, "NLJ41HAY6Q")
+            .await
+            .unwrap();
+
+        assert_eq!(transaction_status(&pool, transaction_id).await, "COMPLETED");
+        assert_eq!(
+            postings(&pool, transaction_id).await,
+            vec![
+                ("FUND".to_string(), Decimal::new(1000, 0)),
+                ("WITHDRAWALS_PAYABLE".to_string(), Decimal::new(-1000, 0)),
+                ("WITHDRAWALS_PAYABLE".to_string(), Decimal::new(1000, 0)),
+                ("MPESA_CLEARING".to_string(), Decimal::new(-1000, 0)),
+            ]
+        );
+    }
+
+    #[sqlx::test(migrator = "crate::db::MIGRATOR")]
+    async fn test_failed_b2c_refunds_the_hold(pool: PgPool) {
+        let simulator = DarajaSimulator::start("key", "secret").await.unwrap();
+        let api_base = start_api(&simulator, pool.clone()).await;
+        let (user_id, token) = member(&pool, Decimal::new(10_000, 0)).await;
+
+        let transaction_id =
+            approved_hardship_withdrawal(&simulator, &pool, &api_base, &token).await;
+
+        let payment = simulator.b2c_payments().pop().unwrap();
+        simulator
+            .fail_b2c(
+                &payment.originator_conversation_id,
+                2001,
+                "The initiator information is invalid.",
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(transaction_status(&pool, transaction_id).await, "FAILED");
+        let balance = sqlx::query_scalar!(
+            "SELECT balance FROM pension_funds WHERE user_id = $1",
+            user_id
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(balance.normalize(), Decimal::new(10_000, 0));
+        // The reversal nets every account back to zero
+        let mut net = std::collections::BTreeMap::new();
+        for (account, amount) in postings(&pool, transaction_id).await {
+            *net.entry(account).or_insert(Decimal::ZERO) += amount;
+        }
+        assert!(net.values().all(|amount| amount.is_zero()));
+    }
+
+    #[tokio::test]
+    async fn test_access_token_is_reused() {
+        let simulator = DarajaSimulator::start("key", "secret").await.unwrap();
+        let mpesa = simulator.mpesa_service("http://127.0.0.1:1");
+
+        for _ in 0..3 {
+            mpesa
+                .initiate_payment("254708374149", 10.0, "PENtest")
+                .await
+                .unwrap();
+        }
+
+        assert_eq!(simulator.token_requests(), 1);
+        assert_eq!(simulator.stk_pushes().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_revoked_token_is_refreshed_and_retried() {
+        let simulator = DarajaSimulator::start("key", "secret").await.unwrap();
+        let mpesa = simulator.mpesa_service("http://127.0.0.1:1");
+
+        mpesa
+            .initiate_payment("254708374149", 10.0, "PENtest")
+            .await
+            .unwrap();
+        simulator.revoke_tokens();
+        mpesa
+            .initiate_payment("254708374149", 10.0, "PENtest")
+            .await
+            .unwrap();
+
+        assert_eq!(simulator.token_requests(), 2);
+        assert_eq!(simulator.stk_pushes().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_rejected_credentials_surface_mpesa_error() {
+        let simulator = DarajaSimulator::start("key", "secret").await.unwrap();
+        let mpesa = MPesaService::from_config(MPesaConfig {
+            consumer_secret: "wrong".to_string(),
+            ..simulator.config("http://127.0.0.1:1")
+        });
+
+        let err = mpesa
+            .initiate_payment("254708374149", 10.0, "PENtest")
+            .await
+            .unwrap_err();
+
+        assert!(matches!(
+            err.downcast_ref::<crate::error::Error>(),
+            Some(crate::error::Error::MPesa(_))
+        ));
+        assert!(simulator.stk_pushes().is_empty());
+    }
+}
@@ -0,0 +1,140 @@
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use std::sync::Arc;
+use tokio::task::JoinHandle;
+
+use crate::config::deposit_sweeper::DepositSweeperConfig;
+use crate::services::fund_service::{FundService, PendingDeposit};
+use crate::services::mpesa_service::{MPesaService, STKQueryStatus};
+
+#[derive(Debug, Default)]
+pub struct SweepReport {
+    pub completed: usize,
+    pub failed: usize,
+    pub timed_out: usize,
+    pub still_pending: usize,
+    pub errored: usize,
+}
+
+enum Resolution {
+    Completed,
+    Failed,
+    TimedOut,
+    StillPending,
+}
+
+// Resolves deposits whose STK callback never arrived by asking Daraja directly
+pub struct DepositSweeper {
+    fund_service: Arc<FundService>,
+    mpesa_service: Arc<MPesaService>,
+    config: DepositSweeperConfig,
+}
+
+impl DepositSweeper {
+    pub fn new(
+        fund_service: Arc<FundService>,
+        mpesa_service: Arc<MPesaService>,
+        config: DepositSweeperConfig,
+    ) -> Self {
+        Self {
+            fund_service,
+            mpesa_service,
+            config,
+        }
+    }
+
+    pub fn spawn(self) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval =
+                tokio::time::interval(std::time::Duration::from_secs(self.config.interval_secs));
+
+            loop {
+                interval.tick().await;
+
+                match self.sweep_once().await {
+                    Ok(report) => tracing::info!("Deposit sweep finished: {:?}", report),
+                    Err(e) => tracing::error!("Deposit sweep failed: {}", e),
+                }
+            }
+        })
+    }
+
+    // One deposit failing to resolve is logged and left for the next sweep
+    pub async fn sweep_once(&self) -> Result<SweepReport> {
+        let now = Utc::now();
+        let deposits = self
+            .fund_service
+            .get_stale_pending_deposits(now - Duration::seconds(self.config.min_age_secs))
+            .await?;
+
+        let mut report = SweepReport::default();
+        for deposit in deposits {
+            match self.resolve(&deposit, now).await {
+                Ok(Resolution::Completed) => report.completed += 1,
+                Ok(Resolution::Failed) => report.failed += 1,
+                Ok(Resolution::TimedOut) => report.timed_out += 1,
+                Ok(Resolution::StillPending) => report.still_pending += 1,
+                Err(e) => {
+                    tracing::error!(
+                        "Sweeping deposit {} failed: {}",
+                        deposit.checkout_request_id,
+                        e
+                    );
+                    report.errored += 1;
+                }
+            }
+        }
+
+        Ok(report)
+    }
+
+    // Only a definitive answer from M-Pesa fails a deposit. One still
+    // unconfirmed after the timeout is parked as TIMED_OUT, where a late
+    // callback can still settle it.
+    async fn resolve(&self, deposit: &PendingDeposit, now: DateTime<Utc>) -> Result<Resolution> {
+        let timed_out =
+            now - deposit.created_at > Duration::seconds(self.config.timeout_after_secs);
+
+        match self
+            .mpesa_service
+            .query_stk_status(&deposit.checkout_request_id)
+            .await
+        {
+            Ok(STKQueryStatus::Completed) => {
+                self.fund_service
+                    .complete_deposit_from_query(&deposit.checkout_request_id)
+                    .await?;
+                Ok(Resolution::Completed)
+            }
+            Ok(STKQueryStatus::Failed {
+                result_code,
+                reason,
+            }) => {
+                let reason = format!("{} (M-Pesa result {})", reason, result_code);
+                self.fund_service
+                    .fail_deposit(&deposit.checkout_request_id, &reason)
+                    .await?;
+                Ok(Resolution::Failed)
+            }
+            Ok(STKQueryStatus::Pending) if !timed_out => Ok(Resolution::StillPending),
+            Err(e) if !timed_out => {
+                tracing::warn!(
+                    "STK query for {} failed: {}",
+                    deposit.checkout_request_id,
+                    e
+                );
+                Ok(Resolution::StillPending)
+            }
+            Ok(STKQueryStatus::Pending) | Err(_) => {
+                let reason = format!(
+                    "Timed out: no M-Pesa confirmation within {} minutes",
+                    self.config.timeout_after_secs / 60
+                );
+                self.fund_service
+                    .time_out_deposit(&deposit.checkout_request_id, &reason)
+                    .await?;
+                Ok(Resolution::TimedOut)
+            }
+        }
+    }
+}
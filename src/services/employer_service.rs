@@ -0,0 +1,1221 @@
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+use crate::error::{Error, Result};
+use crate::services::ledger::{
+    fund_account_code, JournalEntry, LedgerService, EMPLOYER_REMITTANCES,
+};
+use crate::utils::validation::normalize_msisdn;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct NewEmployer {
+    pub name: String,
+    pub registration_number: String,
+    #[serde(default)]
+    pub employer_rate: Decimal,
+    #[serde(default)]
+    pub employee_rate: Decimal,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Employer {
+    pub id: Uuid,
+    pub name: String,
+    pub registration_number: String,
+    pub employer_rate: Decimal,
+    pub employee_rate: Decimal,
+    pub status: String, // PENDING until a platform administrator approves it
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RosterMember {
+    pub staff_number: String,
+    pub user_id: Uuid,
+    pub status: String,
+    pub joined_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PayrollFormat {
+    Csv,
+    Json,
+}
+
+impl PayrollFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PayrollFormat::Csv => "CSV",
+            PayrollFormat::Json => "JSON",
+        }
+    }
+}
+
+// One member's contribution for the pay period. Either the two portions
+// are given outright, or they are worked out from pensionable pay at the
+// employer's rates.
+#[derive(Debug, Clone, Deserialize)]
+pub struct PayrollLine {
+    pub staff_number: String,
+    #[serde(default)]
+    pub phone_number: Option<String>,
+    #[serde(default)]
+    pub pensionable_pay: Option<Decimal>,
+    #[serde(default)]
+    pub employer_amount: Option<Decimal>,
+    #[serde(default)]
+    pub employee_amount: Option<Decimal>,
+}
+
+#[derive(Debug)]
+pub struct ParsedLine {
+    pub line_number: i32,
+    pub line: std::result::Result<PayrollLine, String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContributionSplit {
+    pub employer: Decimal,
+    pub employee: Decimal,
+}
+
+impl ContributionSplit {
+    pub fn total(&self) -> Decimal {
+        self.employer + self.employee
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RosterEntry {
+    pub user_id: Uuid,
+    pub phone_number: Option<String>,
+    pub fund_id: Option<Uuid>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LineOutcome {
+    Accepted {
+        line_number: i32,
+        staff_number: String,
+        user_id: Uuid,
+        fund_id: Uuid,
+        split: ContributionSplit,
+    },
+    Rejected(RejectedLine),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RejectedLine {
+    pub line_number: i32,
+    pub staff_number: Option<String>,
+    pub reason: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PayrollBatchReport {
+    pub batch_id: Uuid,
+    pub employer_id: Uuid,
+    pub reference: String,
+    pub format: String,
+    pub status: String, // AWAITING_REMITTANCE until the employer pays, then SETTLED
+    pub total_lines: i32,
+    pub accepted_lines: i32,
+    pub rejected_lines: i32,
+    pub employer_total: Decimal,
+    pub employee_total: Decimal,
+    pub created_at: DateTime<Utc>,
+    pub rejected: Vec<RejectedLine>,
+}
+
+// Employers contribute for their staff through payroll batches: every line
+// is checked against the employer's roster, and the lines that pass are
+// credited to the members' funds once the employer's remittance for the
+// batch has been received.
+#[derive(Clone)]
+pub struct EmployerService {
+    pool: PgPool,
+}
+
+impl EmployerService {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    // The member who registers the employer administers it. It stays
+    // PENDING, with no roster or payroll, until an administrator approves it.
+    pub async fn create_employer(&self, user_id: Uuid, employer: NewEmployer) -> Result<Employer> {
+        let name = employer.name.trim();
+        let registration_number = employer.registration_number.trim().to_uppercase();
+        if name.is_empty() || registration_number.is_empty() {
+            return Err(Error::InvalidPayroll(
+                "Employer name and registration number are required".to_string(),
+            ));
+        }
+        for rate in [employer.employer_rate, employer.employee_rate] {
+            if rate < Decimal::ZERO || rate > Decimal::ONE {
+                return Err(Error::InvalidPayroll(
+                    "Contribution rates must be between 0 and 1".to_string(),
+                ));
+            }
+        }
+
+        let mut tx = self.pool.begin().await?;
+
+        let existing = sqlx::query_scalar!(
+            "SELECT id FROM employers WHERE registration_number = $1",
+            registration_number
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+        if existing.is_some() {
+            return Err(Error::EmployerAlreadyRegistered);
+        }
+
+        let employer = sqlx::query_as!(
+            Employer,
+            r#"
+            INSERT INTO employers (
+                id, name, registration_number, employer_rate, employee_rate, created_by
+            )
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING id, name, registration_number, employer_rate, employee_rate, status,
+                      created_at
+            "#,
+            Uuid::new_v4(),
+            name,
+            registration_number,
+            employer.employer_rate,
+            employer.employee_rate,
+            user_id,
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            "INSERT INTO employer_admins (employer_id, user_id) VALUES ($1, $2)",
+            employer.id,
+            user_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(employer)
+    }
+
+    pub async fn get_employer(&self, employer_id: Uuid, user_id: Uuid) -> Result<Employer> {
+        self.require_admin(employer_id, user_id).await?;
+
+        let employer = sqlx::query_as!(
+            Employer,
+            r#"
+            SELECT id, name, registration_number, employer_rate, employee_rate, status,
+                   created_at
+            FROM employers WHERE id = $1
+            "#,
+            employer_id
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(employer)
+    }
+
+    // Called by a platform administrator once the employer's registration
+    // has been checked
+    pub async fn approve_employer(&self, employer_id: Uuid, approver_id: Uuid) -> Result<Employer> {
+        let employer = sqlx::query_as!(
+            Employer,
+            r#"
+            UPDATE employers
+            SET status = 'APPROVED', approved_by = $2, approved_at = NOW()
+            WHERE id = $1 AND status = 'PENDING'
+            RETURNING id, name, registration_number, employer_rate, employee_rate, status,
+                      created_at
+            "#,
+            employer_id,
+            approver_id
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| Error::NotFound("Pending employer".to_string()))?;
+
+        Ok(employer)
+    }
+
+    // Enrols an existing member, found by phone number, under a staff number
+    pub async fn add_member(
+        &self,
+        employer_id: Uuid,
+        admin_id: Uuid,
+        staff_number: &str,
+        phone_number: &str,
+    ) -> Result<RosterMember> {
+        self.require_admin(employer_id, admin_id).await?;
+        self.require_approved(employer_id).await?;
+
+        let staff_number = staff_number.trim();
+        if staff_number.is_empty() {
+            return Err(Error::InvalidPayroll(
+                "Staff number is required".to_string(),
+            ));
+        }
+        let phone_number = normalize_msisdn(phone_number).ok_or(Error::InvalidPhoneNumber)?;
+
+        let user_id =
+            sqlx::query_scalar!("SELECT id FROM users WHERE phone_number = $1", phone_number)
+                .fetch_optional(&self.pool)
+                .await?
+                .ok_or_else(|| Error::NotFound("Member".to_string()))?;
+
+        let holder = sqlx::query_scalar!(
+            r#"
+            SELECT user_id FROM employer_members
+            WHERE employer_id = $1 AND staff_number = $2
+            "#,
+            employer_id,
+            staff_number
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+        if holder.is_some_and(|holder| holder != user_id) {
+            return Err(Error::StaffNumberTaken(staff_number.to_string()));
+        }
+
+        let member = sqlx::query_as!(
+            RosterMember,
+            r#"
+            INSERT INTO employer_members (id, employer_id, user_id, staff_number)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (employer_id, user_id) DO UPDATE
+            SET staff_number = EXCLUDED.staff_number, status = 'ACTIVE', left_at = NULL
+            RETURNING staff_number, user_id, status, joined_at
+            "#,
+            Uuid::new_v4(),
+            employer_id,
+            user_id,
+            staff_number,
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(member)
+    }
+
+    pub async fn get_members(
+        &self,
+        employer_id: Uuid,
+        admin_id: Uuid,
+    ) -> Result<Vec<RosterMember>> {
+        self.require_admin(employer_id, admin_id).await?;
+
+        let members = sqlx::query_as!(
+            RosterMember,
+            r#"
+            SELECT staff_number, user_id, status, joined_at
+            FROM employer_members
+            WHERE employer_id = $1 AND status = 'ACTIVE'
+            ORDER BY staff_number
+            "#,
+            employer_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(members)
+    }
+
+    // Staff who leave stay on record; later payroll lines for them are rejected
+    pub async fn remove_member(
+        &self,
+        employer_id: Uuid,
+        admin_id: Uuid,
+        staff_number: &str,
+    ) -> Result<()> {
+        self.require_admin(employer_id, admin_id).await?;
+        self.require_approved(employer_id).await?;
+
+        let removed = sqlx::query!(
+            r#"
+            UPDATE employer_members
+            SET status = 'LEFT', left_at = NOW()
+            WHERE employer_id = $1 AND staff_number = $2 AND status = 'ACTIVE'
+            "#,
+            employer_id,
+            staff_number
+        )
+        .execute(&self.pool)
+        .await?;
+
+        if removed.rows_affected() == 0 {
+            return Err(Error::NotFound("Staff member".to_string()));
+        }
+        Ok(())
+    }
+
+    pub async fn import_payroll(
+        &self,
+        employer_id: Uuid,
+        admin_id: Uuid,
+        reference: &str,
+        format: PayrollFormat,
+        body: &[u8],
+    ) -> Result<PayrollBatchReport> {
+        self.require_admin(employer_id, admin_id).await?;
+        self.require_approved(employer_id).await?;
+
+        let reference = reference.trim();
+        if reference.is_empty() {
+            return Err(Error::InvalidPayroll(
+                "A batch reference is required".to_string(),
+            ));
+        }
+
+        let lines = match format {
+            PayrollFormat::Csv => parse_csv(body)?,
+            PayrollFormat::Json => parse_json(body)?,
+        };
+        if lines.is_empty() {
+            return Err(Error::InvalidPayroll(
+                "The payroll has no lines".to_string(),
+            ));
+        }
+
+        let mut tx = self.pool.begin().await?;
+
+        // Serialises uploads per employer so a reference is only used once
+        let employer = sqlx::query!(
+            "SELECT employer_rate, employee_rate FROM employers WHERE id = $1 FOR UPDATE",
+            employer_id
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let duplicate = sqlx::query_scalar!(
+            "SELECT id FROM payroll_batches WHERE employer_id = $1 AND reference = $2",
+            employer_id,
+            reference
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+        if duplicate.is_some() {
+            return Err(Error::DuplicatePayrollBatch(reference.to_string()));
+        }
+
+        // pension_funds is unique on user_id, so this is one row per member
+        let roster = sqlx::query!(
+            r#"
+            SELECT m.staff_number, m.user_id, u.phone_number, f.id as "fund_id?"
+            FROM employer_members m
+            JOIN users u ON u.id = m.user_id
+            LEFT JOIN pension_funds f ON f.user_id = m.user_id
+            WHERE m.employer_id = $1 AND m.status = 'ACTIVE'
+            "#,
+            employer_id
+        )
+        .fetch_all(&mut *tx)
+        .await?
+        .into_iter()
+        .map(|m| {
+            (
+                m.staff_number,
+                RosterEntry {
+                    user_id: m.user_id,
+                    phone_number: m.phone_number,
+                    fund_id: m.fund_id,
+                },
+            )
+        })
+        .collect::<HashMap<_, _>>();
+
+        let outcomes = validate_lines(
+            lines,
+            &roster,
+            employer.employer_rate,
+            employer.employee_rate,
+        );
+
+        let batch_id = Uuid::new_v4();
+        let mut employer_total = Decimal::ZERO;
+        let mut employee_total = Decimal::ZERO;
+        let mut rejected = Vec::new();
+
+        for outcome in &outcomes {
+            match outcome {
+                LineOutcome::Accepted { split, .. } => {
+                    employer_total += split.employer;
+                    employee_total += split.employee;
+                }
+                LineOutcome::Rejected(line) => rejected.push(line.clone()),
+            }
+        }
+
+        let total_lines = outcomes.len() as i32;
+        let rejected_lines = rejected.len() as i32;
+
+        let batch = sqlx::query!(
+            r#"
+            INSERT INTO payroll_batches (
+                id, employer_id, reference, format, uploaded_by, total_lines,
+                accepted_lines, rejected_lines, employer_total, employee_total
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            RETURNING status, created_at
+            "#,
+            batch_id,
+            employer_id,
+            reference,
+            format.as_str(),
+            admin_id,
+            total_lines,
+            total_lines - rejected_lines,
+            rejected_lines,
+            employer_total,
+            employee_total,
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        for outcome in &outcomes {
+            match outcome {
+                LineOutcome::Accepted {
+                    line_number,
+                    staff_number,
+                    user_id,
+                    fund_id,
+                    split,
+                } => {
+                    // Owed by the employer, not yet money in the fund
+                    for (transaction_type, amount) in [
+                        ("EMPLOYER_CONTRIBUTION", split.employer),
+                        ("EMPLOYEE_CONTRIBUTION", split.employee),
+                    ] {
+                        if amount.is_zero() {
+                            continue;
+                        }
+
+                        sqlx::query!(
+                            r#"
+                            INSERT INTO transactions (
+                                id, fund_id, user_id, transaction_type, amount, status,
+                                payroll_batch_id
+                            )
+                            VALUES ($1, $2, $3, $4, $5, 'PENDING', $6)
+                            "#,
+                            Uuid::new_v4(),
+                            fund_id,
+                            user_id,
+                            transaction_type,
+                            amount,
+                            batch_id,
+                        )
+                        .execute(&mut *tx)
+                        .await?;
+                    }
+
+                    sqlx::query!(
+                        r#"
+                        INSERT INTO payroll_batch_lines (
+                            id, batch_id, line_number, staff_number, user_id,
+                            employer_amount, employee_amount, status
+                        )
+                        VALUES ($1, $2, $3, $4, $5, $6, $7, 'ACCEPTED')
+                        "#,
+                        Uuid::new_v4(),
+                        batch_id,
+                        line_number,
+                        staff_number,
+                        user_id,
+                        split.employer,
+                        split.employee,
+                    )
+                    .execute(&mut *tx)
+                    .await?;
+                }
+                LineOutcome::Rejected(line) => {
+                    sqlx::query!(
+                        r#"
+                        INSERT INTO payroll_batch_lines (
+                            id, batch_id, line_number, staff_number, status, rejection_reason
+                        )
+                        VALUES ($1, $2, $3, $4, 'REJECTED', $5)
+                        "#,
+                        Uuid::new_v4(),
+                        batch_id,
+                        line.line_number,
+                        line.staff_number,
+                        line.reason,
+                    )
+                    .execute(&mut *tx)
+                    .await?;
+                }
+            }
+        }
+
+        tx.commit().await?;
+
+        Ok(PayrollBatchReport {
+            batch_id,
+            employer_id,
+            reference: reference.to_string(),
+            format: format.as_str().to_string(),
+            status: batch.status,
+            total_lines,
+            accepted_lines: total_lines - rejected_lines,
+            rejected_lines,
+            employer_total,
+            employee_total,
+            created_at: batch.created_at,
+            rejected,
+        })
+    }
+
+    // Called by a platform administrator once the employer's remittance for
+    // the batch has been received. Only then are the contributions credited.
+    pub async fn settle_payroll(
+        &self,
+        employer_id: Uuid,
+        batch_id: Uuid,
+        approver_id: Uuid,
+        amount: Decimal,
+        remittance_reference: &str,
+    ) -> Result<PayrollBatchReport> {
+        let remittance_reference = remittance_reference.trim();
+        if remittance_reference.is_empty() {
+            return Err(Error::InvalidPayroll(
+                "A remittance reference is required".to_string(),
+            ));
+        }
+
+        let mut tx = self.pool.begin().await?;
+
+        let batch = sqlx::query!(
+            r#"
+            SELECT status, employer_total, employee_total
+            FROM payroll_batches
+            WHERE id = $1 AND employer_id = $2
+            FOR UPDATE
+            "#,
+            batch_id,
+            employer_id
+        )
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| Error::NotFound("Payroll batch".to_string()))?;
+
+        if batch.status != "AWAITING_REMITTANCE" {
+            return Err(Error::PayrollBatchNotPending);
+        }
+        if amount != batch.employer_total + batch.employee_total {
+            return Err(Error::RemittanceMismatch(amount));
+        }
+
+        let contributions = sqlx::query!(
+            r#"
+            SELECT id, fund_id, transaction_type, amount
+            FROM transactions
+            WHERE payroll_batch_id = $1 AND status = 'PENDING'
+            FOR UPDATE
+            "#,
+            batch_id
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+
+        for contribution in contributions {
+            sqlx::query!(
+                "UPDATE pension_funds SET balance = balance + $1 WHERE id = $2",
+                contribution.amount,
+                contribution.fund_id
+            )
+            .execute(&mut *tx)
+            .await?;
+
+            sqlx::query!(
+                r#"
+                UPDATE transactions
+                SET status = 'COMPLETED', completed_at = NOW()
+                WHERE id = $1
+                "#,
+                contribution.id
+            )
+            .execute(&mut *tx)
+            .await?;
+
+            let entry = JournalEntry::new(&contribution.transaction_type, Some(contribution.id))
+                .debit(EMPLOYER_REMITTANCES, contribution.amount)
+                .credit(
+                    &fund_account_code(contribution.fund_id),
+                    contribution.amount,
+                );
+            LedgerService::post(&mut tx, &entry).await?;
+        }
+
+        sqlx::query!(
+            r#"
+            UPDATE payroll_batches
+            SET status = 'SETTLED', remittance_reference = $2, settled_by = $3,
+                settled_at = NOW()
+            WHERE id = $1
+            "#,
+            batch_id,
+            remittance_reference,
+            approver_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        self.batch_report(employer_id, batch_id).await
+    }
+
+    pub async fn get_batch_report(
+        &self,
+        employer_id: Uuid,
+        admin_id: Uuid,
+        batch_id: Uuid,
+    ) -> Result<PayrollBatchReport> {
+        self.require_admin(employer_id, admin_id).await?;
+        self.batch_report(employer_id, batch_id).await
+    }
+
+    async fn batch_report(&self, employer_id: Uuid, batch_id: Uuid) -> Result<PayrollBatchReport> {
+        let batch = sqlx::query!(
+            r#"
+            SELECT reference, format, status, total_lines, accepted_lines, rejected_lines,
+                   employer_total, employee_total, created_at
+            FROM payroll_batches
+            WHERE id = $1 AND employer_id = $2
+            "#,
+            batch_id,
+            employer_id
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| Error::NotFound("Payroll batch".to_string()))?;
+
+        let rejected = sqlx::query_as!(
+            RejectedLine,
+            r#"
+            SELECT line_number, staff_number, rejection_reason as "reason!"
+            FROM payroll_batch_lines
+            WHERE batch_id = $1 AND status = 'REJECTED'
+            ORDER BY line_number
+            "#,
+            batch_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(PayrollBatchReport {
+            batch_id,
+            employer_id,
+            reference: batch.reference,
+            format: batch.format,
+            status: batch.status,
+            total_lines: batch.total_lines,
+            accepted_lines: batch.accepted_lines,
+            rejected_lines: batch.rejected_lines,
+            employer_total: batch.employer_total,
+            employee_total: batch.employee_total,
+            created_at: batch.created_at,
+            rejected,
+        })
+    }
+
+    async fn require_admin(&self, employer_id: Uuid, user_id: Uuid) -> Result<()> {
+        let admin = sqlx::query_scalar!(
+            r#"
+            SELECT EXISTS (
+                SELECT 1 FROM employer_admins WHERE employer_id = $1 AND user_id = $2
+            ) as "exists!"
+            "#,
+            employer_id,
+            user_id
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        if !admin {
+            return Err(Error::NotEmployerAdmin);
+        }
+        Ok(())
+    }
+
+    async fn require_approved(&self, employer_id: Uuid) -> Result<()> {
+        let status = sqlx::query_scalar!("SELECT status FROM employers WHERE id = $1", employer_id)
+            .fetch_one(&self.pool)
+            .await?;
+
+        if status != "APPROVED" {
+            return Err(Error::EmployerNotApproved);
+        }
+        Ok(())
+    }
+}
+
+// A line that does not fit the columns is rejected on its own; a header
+// we cannot read rejects the whole file
+pub fn parse_csv(body: &[u8]) -> Result<Vec<ParsedLine>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .flexible(true)
+        .from_reader(body);
+
+    let headers = reader
+        .headers()
+        .map_err(|e| Error::InvalidPayroll(format!("Unreadable CSV header: {}", e)))?
+        .clone();
+    if !headers.iter().any(|h| h == "staff_number") {
+        return Err(Error::InvalidPayroll(
+            "The CSV header must include staff_number".to_string(),
+        ));
+    }
+
+    let mut lines = Vec::new();
+    for (index, record) in reader.records().enumerate() {
+        // The header is line 1
+        let line_number = record
+            .as_ref()
+            .ok()
+            .and_then(|r| r.position())
+            .map(|p| p.line() as i32)
+            .unwrap_or(index as i32 + 2);
+
+        let line = record
+            .map_err(|e| e.to_string())
+            .and_then(|r| {
+                r.deserialize::<PayrollLine>(Some(&headers))
+                    .map_err(|e| e.to_string())
+            })
+            .map_err(|e| format!("Unreadable line: {}", e));
+
+        lines.push(ParsedLine { line_number, line });
+    }
+
+    Ok(lines)
+}
+
+pub fn parse_json(body: &[u8]) -> Result<Vec<ParsedLine>> {
+    let values: Vec<serde_json::Value> = serde_json::from_slice(body)
+        .map_err(|e| Error::InvalidPayroll(format!("Expected a JSON array of lines: {}", e)))?;
+
+    Ok(values
+        .into_iter()
+        .enumerate()
+        .map(|(index, value)| ParsedLine {
+            line_number: index as i32 + 1,
+            line: serde_json::from_value(value).map_err(|e| format!("Unreadable line: {}", e)),
+        })
+        .collect())
+}
+
+pub fn split_contribution(
+    line: &PayrollLine,
+    employer_rate: Decimal,
+    employee_rate: Decimal,
+) -> std::result::Result<ContributionSplit, String> {
+    let split = match (
+        line.employer_amount,
+        line.employee_amount,
+        line.pensionable_pay,
+    ) {
+        (None, None, Some(pay)) => {
+            if pay < Decimal::ZERO {
+                return Err("Pensionable pay cannot be negative".to_string());
+            }
+            ContributionSplit {
+                employer: (pay * employer_rate).round_dp(2),
+                employee: (pay * employee_rate).round_dp(2),
+            }
+        }
+        (None, None, None) => {
+            return Err("Give either pensionable_pay or the contribution amounts".to_string())
+        }
+        (employer, employee, _) => ContributionSplit {
+            employer: employer.unwrap_or_default(),
+            employee: employee.unwrap_or_default(),
+        },
+    };
+
+    if split.employer < Decimal::ZERO || split.employee < Decimal::ZERO {
+        return Err("Contribution amounts cannot be negative".to_string());
+    }
+    if split.employer.scale() > 2 || split.employee.scale() > 2 {
+        return Err("Amounts can have at most two decimal places".to_string());
+    }
+    if split.total().is_zero() {
+        return Err("The contribution is zero".to_string());
+    }
+
+    Ok(split)
+}
+
+// Checks every line against the roster. A staff number may appear once per
+// batch; a phone number, if given, has to be the member's.
+pub fn validate_lines(
+    lines: Vec<ParsedLine>,
+    roster: &HashMap<String, RosterEntry>,
+    employer_rate: Decimal,
+    employee_rate: Decimal,
+) -> Vec<LineOutcome> {
+    let mut seen = HashSet::new();
+
+    lines
+        .into_iter()
+        .map(|parsed| {
+            let line_number = parsed.line_number;
+            let reject = |staff_number: Option<&str>, reason: &str| {
+                LineOutcome::Rejected(RejectedLine {
+                    line_number,
+                    staff_number: staff_number.map(str::to_string),
+                    reason: reason.to_string(),
+                })
+            };
+
+            let line = match parsed.line {
+                Ok(line) => line,
+                Err(reason) => return reject(None, &reason),
+            };
+            let staff_number = line.staff_number.trim();
+
+            if staff_number.is_empty() {
+                return reject(None, "Missing staff number");
+            }
+            if !seen.insert(staff_number.to_string()) {
+                return reject(Some(staff_number), "Staff number appears more than once");
+            }
+            let Some(member) = roster.get(staff_number) else {
+                return reject(Some(staff_number), "Not on the employer's roster");
+            };
+            if let Some(phone_number) = line.phone_number.as_deref().filter(|p| !p.is_empty()) {
+                let matches = normalize_msisdn(phone_number)
+                    .is_some_and(|p| member.phone_number.as_deref() == Some(p.as_str()));
+                if !matches {
+                    return reject(
+                        Some(staff_number),
+                        "Phone number does not match the member's",
+                    );
+                }
+            }
+            let Some(fund_id) = member.fund_id else {
+                return reject(Some(staff_number), "Member has no pension fund");
+            };
+
+            match split_contribution(&line, employer_rate, employee_rate) {
+                Ok(split) => LineOutcome::Accepted {
+                    line_number,
+                    staff_number: staff_number.to_string(),
+                    user_id: member.user_id,
+                    fund_id,
+                    split,
+                },
+                Err(reason) => reject(Some(staff_number), &reason),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn roster() -> HashMap<String, RosterEntry> {
+        HashMap::from([
+            (
+                "EMP001".to_string(),
+                RosterEntry {
+                    user_id: Uuid::from_u128(1),
+                    phone_number: Some("254708374149".to_string()),
+                    fund_id: Some(Uuid::from_u128(11)),
+                },
+            ),
+            (
+                "EMP002".to_string(),
+                RosterEntry {
+                    user_id: Uuid::from_u128(2),
+                    phone_number: Some("254711223344".to_string()),
+                    fund_id: Some(Uuid::from_u128(12)),
+                },
+            ),
+            (
+                "EMP003".to_string(),
+                RosterEntry {
+                    user_id: Uuid::from_u128(3),
+                    phone_number: None,
+                    fund_id: None,
+                },
+            ),
+        ])
+    }
+
+    fn rates() -> (Decimal, Decimal) {
+        (Decimal::new(6, 2), Decimal::new(5, 2))
+    }
+
+    #[test]
+    fn test_parse_csv() {
+        let body = b"staff_number,phone_number,pensionable_pay,employer_amount,employee_amount\n\
+EMP001,0708374149,50000,,\n\
+EMP002,,,1500.50,1000\n\
+EMP003,,abc,,\n";
+
+        let lines = parse_csv(body).unwrap();
+        assert_eq!(lines.len(), 3);
+
+        let first = lines[0].line.as_ref().unwrap();
+        assert_eq!(lines[0].line_number, 2);
+        assert_eq!(first.staff_number, "EMP001");
+        assert_eq!(first.phone_number.as_deref(), Some("0708374149"));
+        assert_eq!(first.pensionable_pay, Some(Decimal::new(50_000, 0)));
+        assert_eq!(first.employer_amount, None);
+
+        let second = lines[1].line.as_ref().unwrap();
+        assert_eq!(second.phone_number, None);
+        assert_eq!(second.employer_amount, Some(Decimal::new(150_050, 2)));
+
+        assert_eq!(lines[2].line_number, 4);
+        assert!(lines[2].line.is_err());
+    }
+
+    #[test]
+    fn test_parse_csv_needs_staff_number_column() {
+        assert!(parse_csv(b"phone_number,pensionable_pay\n0708374149,50000\n").is_err());
+    }
+
+    #[test]
+    fn test_parse_json() {
+        let body = br#"[
+            {"staff_number": "EMP001", "pensionable_pay": "50000"},
+            {"phone_number": "0708374149"}
+        ]"#;
+
+        let lines = parse_json(body).unwrap();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].line_number, 1);
+        assert_eq!(lines[0].line.as_ref().unwrap().staff_number, "EMP001");
+        assert!(lines[1].line.is_err());
+
+        assert!(parse_json(br#"{"staff_number": "EMP001"}"#).is_err());
+    }
+
+    #[test]
+    fn test_split_from_pay_and_explicit_amounts() {
+        let (employer_rate, employee_rate) = rates();
+        let line = |pay: Option<i64>, employer: Option<i64>, employee: Option<i64>| PayrollLine {
+            staff_number: "EMP001".to_string(),
+            phone_number: None,
+            pensionable_pay: pay.map(|v| Decimal::new(v, 0)),
+            employer_amount: employer.map(|v| Decimal::new(v, 0)),
+            employee_amount: employee.map(|v| Decimal::new(v, 0)),
+        };
+
+        assert_eq!(
+            split_contribution(
+                &line(Some(50_000), None, None),
+                employer_rate,
+                employee_rate
+            ),
+            Ok(ContributionSplit {
+                employer: Decimal::new(3_000, 0),
+                employee: Decimal::new(2_500, 0),
+            })
+        );
+        // Explicit amounts win over pay
+        assert_eq!(
+            split_contribution(
+                &line(Some(50_000), Some(100), None),
+                employer_rate,
+                employee_rate
+            ),
+            Ok(ContributionSplit {
+                employer: Decimal::new(100, 0),
+                employee: Decimal::ZERO,
+            })
+        );
+        assert!(split_contribution(&line(None, None, None), employer_rate, employee_rate).is_err());
+        assert!(
+            split_contribution(&line(None, Some(-1), Some(5)), employer_rate, employee_rate)
+                .is_err()
+        );
+        assert!(
+            split_contribution(&line(None, Some(0), Some(0)), employer_rate, employee_rate)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_validate_lines() {
+        let (employer_rate, employee_rate) = rates();
+        let body = b"staff_number,phone_number,pensionable_pay\n\
+EMP001,+254708374149,50000\n\
+EMP002,0700000000,40000\n\
+EMP404,,40000\n\
+EMP001,,10000\n\
+EMP003,,10000\n\
+,,10000\n";
+
+        let outcomes = validate_lines(
+            parse_csv(body).unwrap(),
+            &roster(),
+            employer_rate,
+            employee_rate,
+        );
+        assert_eq!(outcomes.len(), 6);
+
+        assert_eq!(
+            outcomes[0],
+            LineOutcome::Accepted {
+                line_number: 2,
+                staff_number: "EMP001".to_string(),
+                user_id: Uuid::from_u128(1),
+                fund_id: Uuid::from_u128(11),
+                split: ContributionSplit {
+                    employer: Decimal::new(3_000, 0),
+                    employee: Decimal::new(2_500, 0),
+                },
+            }
+        );
+
+        let reasons: Vec<_> = outcomes[1..]
+            .iter()
+            .map(|outcome| match outcome {
+                LineOutcome::Rejected(line) => (line.line_number, line.reason.as_str()),
+                LineOutcome::Accepted { .. } => panic!("expected a rejection"),
+            })
+            .collect();
+        assert_eq!(
+            reasons,
+            vec![
+                (3, "Phone number does not match the member's"),
+                (4, "Not on the employer's roster"),
+                (5, "Staff number appears more than once"),
+                (6, "Member has no pension fund"),
+                (7, "Missing staff number"),
+            ]
+        );
+    }
+
+    async fn insert_user(pool: &PgPool, username: &str, phone_number: &str) -> Uuid {
+        sqlx::query_scalar!(
+            "INSERT INTO users (username, phone_number) VALUES ($1, $2) RETURNING id",
+            username,
+            phone_number
+        )
+        .fetch_one(pool)
+        .await
+        .unwrap()
+    }
+
+    async fn fund_balance(pool: &PgPool, user_id: Uuid) -> Decimal {
+        sqlx::query_scalar!(
+            "SELECT balance FROM pension_funds WHERE user_id = $1",
+            user_id
+        )
+        .fetch_one(pool)
+        .await
+        .unwrap()
+    }
+
+    #[sqlx::test(migrator = "crate::db::MIGRATOR")]
+    async fn test_payroll_is_credited_only_once_remitted(pool: PgPool) {
+        let service = EmployerService::new(pool.clone());
+        let hr = insert_user(&pool, "hr", "254700000001").await;
+        let staff = insert_user(&pool, "staff", "254708374149").await;
+        let platform_admin = insert_user(&pool, "ops", "254700000002").await;
+        sqlx::query!(
+            "INSERT INTO pension_funds (user_id, investment_plan) VALUES ($1, 'MODERATE')",
+            staff
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let employer = service
+            .create_employer(
+                hr,
+                NewEmployer {
+                    name: "Acme".to_string(),
+                    registration_number: "P051234567A".to_string(),
+                    employer_rate: Decimal::ZERO,
+                    employee_rate: Decimal::ZERO,
+                },
+            )
+            .await
+            .unwrap();
+        assert!(matches!(
+            service
+                .add_member(employer.id, hr, "EMP001", "0708374149")
+                .await,
+            Err(Error::EmployerNotApproved)
+        ));
+
+        service
+            .approve_employer(employer.id, platform_admin)
+            .await
+            .unwrap();
+        service
+            .add_member(employer.id, hr, "EMP001", "0708374149")
+            .await
+            .unwrap();
+
+        let body = b"staff_number,employer_amount,employee_amount\nEMP001,600,400\n";
+        let report = service
+            .import_payroll(employer.id, hr, "2025-03", PayrollFormat::Csv, body)
+            .await
+            .unwrap();
+        assert_eq!(report.status, "AWAITING_REMITTANCE");
+        assert_eq!(fund_balance(&pool, staff).await, Decimal::ZERO);
+
+        assert!(matches!(
+            service
+                .settle_payroll(
+                    employer.id,
+                    report.batch_id,
+                    platform_admin,
+                    Decimal::new(900, 0),
+                    "RT1"
+                )
+                .await,
+            Err(Error::RemittanceMismatch(_))
+        ));
+        let settled = service
+            .settle_payroll(
+                employer.id,
+                report.batch_id,
+                platform_admin,
+                Decimal::new(1000, 0),
+                "RT1",
+            )
+            .await
+            .unwrap();
+        assert_eq!(settled.status, "SETTLED");
+        assert_eq!(
+            fund_balance(&pool, staff).await.normalize(),
+            Decimal::new(1000, 0)
+        );
+
+        // A second confirmation of the same remittance credits nothing
+        assert!(matches!(
+            service
+                .settle_payroll(
+                    employer.id,
+                    report.batch_id,
+                    platform_admin,
+                    Decimal::new(1000, 0),
+                    "RT1"
+                )
+                .await,
+            Err(Error::PayrollBatchNotPending)
+        ));
+        assert_eq!(
+            fund_balance(&pool, staff).await.normalize(),
+            Decimal::new(1000, 0)
+        );
+    }
+}
@@ -1,19 +1,44 @@
 use anyhow::Result;
+use rust_decimal::prelude::ToPrimitive;
 use rust_decimal::Decimal;
-use sqlx::PgPool;
+use sqlx::{PgConnection, PgPool};
 use uuid::Uuid;
 use crate::api::handlers::fund::InvestmentPlan;
-use chrono::{DateTime, Utc};
-use crate::config::withdrawal_limits::WithdrawalLimits;
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use crate::config::transaction_limits::{PeriodLimits, TransactionLimits};
+use crate::config::withdrawal_policy::WithdrawalPolicyConfig;
+use crate::error::Error;
+use crate::services::contribution_service::record_schedule_outcome;
+use crate::services::kyc_service::{kyc_tier, require_approved_kyc, KycTier};
+use crate::services::ledger::{
+    fund_account_code, JournalEntry, LedgerService, EARLY_WITHDRAWAL_PENALTIES, FEE_INCOME,
+    INVESTMENT_HOLDINGS, MPESA_CLEARING, WITHDRAWALS_PAYABLE,
+};
+use crate::services::mpesa_service::PaymentDetails;
 use crate::services::notification_service::NotificationService;
+use crate::services::withdrawal_policy::{
+    evaluate, MemberFacts, PolicyDecision, WithdrawalKind,
+};
 
+#[derive(Clone)]
 pub struct FundService {
     pool: PgPool,
+    limits: TransactionLimits,
+    withdrawal_policy: WithdrawalPolicyConfig,
 }
 
 impl FundService {
-    pub fn new(pool: PgPool) -> Self {
-        Self { pool }
+    pub fn new(
+        pool: PgPool,
+        limits: TransactionLimits,
+        withdrawal_policy: WithdrawalPolicyConfig,
+    ) -> Self {
+        Self {
+            pool,
+            limits,
+            withdrawal_policy,
+        }
     }
 
     pub async fn create_fund(
@@ -23,35 +48,54 @@ impl FundService {
         initial_deposit: Decimal,
     ) -> Result<Uuid> {
         let fund_id = Uuid::new_v4();
-        
-        sqlx::query!(
+        let transaction_id = Uuid::new_v4();
+        let mut tx = self.pool.begin().await?;
+
+        // One fund per member; balances, withdrawals and tokenization are
+        // all looked up by member
+        let created = sqlx::query!(
             r#"
             INSERT INTO pension_funds (id, user_id, investment_plan, balance)
             VALUES ($1, $2, $3, $4)
+            ON CONFLICT (user_id) DO NOTHING
             "#,
             fund_id,
             user_id,
             investment_plan as InvestmentPlan,
             initial_deposit,
         )
-        .execute(&self.pool)
+        .execute(&mut *tx)
         .await?;
+        if created.rows_affected() == 0 {
+            return Err(Error::FundAlreadyExists.into());
+        }
 
         // Create initial deposit transaction
-        sqlx::query!(
-            r#"
-            INSERT INTO transactions (id, fund_id, transaction_type, amount, status)
-            VALUES ($1, $2, $3, $4, $5)
-            "#,
-            Uuid::new_v4(),
-            fund_id,
-            "DEPOSIT",
-            initial_deposit,
-            "COMPLETED",
-        )
-        .execute(&self.pool)
-        .await?;
+        if !initial_deposit.is_zero() {
+            sqlx::query!(
+                r#"
+                INSERT INTO transactions (id, fund_id, transaction_type, amount, status)
+                VALUES ($1, $2, $3, $4, $5)
+                "#,
+                transaction_id,
+                fund_id,
+                "DEPOSIT",
+                initial_deposit,
+                "COMPLETED",
+            )
+            .execute(&mut *tx)
+            .await?;
 
+            let entry = ledger_entry(
+                &TransactionType::Deposit,
+                fund_id,
+                initial_deposit,
+                transaction_id,
+            );
+            LedgerService::post(&mut tx, &entry).await?;
+        }
+
+        tx.commit().await?;
         Ok(fund_id)
     }
 
@@ -59,7 +103,9 @@ impl FundService {
         let fund = sqlx::query_as!(
             PensionFund,
             r#"
-            SELECT * FROM pension_funds WHERE id = $1
+            SELECT id, user_id, investment_plan as "investment_plan: InvestmentPlan",
+                   balance, created_at, updated_at
+            FROM pension_funds WHERE id = $1
             "#,
             fund_id
         )
@@ -76,12 +122,10 @@ impl FundService {
         transaction_type: TransactionType,
     ) -> Result<()> {
         let mut tx = self.pool.begin().await?;
+        let transaction_id = Uuid::new_v4();
 
         // Update fund balance
-        let modifier = match transaction_type {
-            TransactionType::Deposit => Decimal::ONE,
-            TransactionType::Withdrawal => -Decimal::ONE,
-        };
+        let modifier = balance_modifier(&transaction_type);
 
         sqlx::query!(
             r#"
@@ -92,7 +136,7 @@ impl FundService {
             amount * modifier,
             fund_id,
         )
-        .execute(&mut tx)
+        .execute(&mut *tx)
         .await?;
 
         // Record transaction
@@ -101,19 +145,273 @@ impl FundService {
             INSERT INTO transactions (id, fund_id, transaction_type, amount, status)
             VALUES ($1, $2, $3, $4, $5)
             "#,
-            Uuid::new_v4(),
+            transaction_id,
             fund_id,
             transaction_type.to_string(),
             amount,
             "COMPLETED",
         )
-        .execute(&mut tx)
+        .execute(&mut *tx)
         .await?;
 
+        // Post to the ledger
+        let entry = ledger_entry(&transaction_type, fund_id, amount, transaction_id);
+        LedgerService::post(&mut tx, &entry).await?;
+
         tx.commit().await?;
         Ok(())
     }
 
+    // Deposits are credited to the member's fund, so callers check for one
+    // before asking the member to pay
+    pub async fn require_fund(&self, user_id: Uuid) -> Result<Uuid> {
+        let fund_id = sqlx::query_scalar!(
+            r#"
+            SELECT id FROM pension_funds
+            WHERE user_id = $1
+            ORDER BY created_at
+            LIMIT 1
+            "#,
+            user_id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        fund_id.ok_or_else(|| Error::NotFound("Pension fund".to_string()).into())
+    }
+
+    pub async fn record_pending_deposit(
+        &self,
+        user_id: Uuid,
+        amount: f64,
+        checkout_request_id: &str,
+        schedule_id: Option<Uuid>,
+    ) -> Result<Uuid> {
+        let amount = Decimal::try_from(amount)?;
+
+        let transaction_id = sqlx::query_scalar!(
+            r#"
+            INSERT INTO transactions (
+                id, fund_id, user_id, transaction_type, amount, status, checkout_request_id,
+                schedule_id
+            )
+            SELECT $1, id, user_id, 'DEPOSIT', $2, 'PENDING', $3, $5
+            FROM pension_funds
+            WHERE user_id = $4
+            ORDER BY created_at
+            LIMIT 1
+            RETURNING id
+            "#,
+            Uuid::new_v4(),
+            amount,
+            checkout_request_id,
+            user_id,
+            schedule_id,
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(transaction_id)
+    }
+
+    // Safaricom retries callbacks, so only an unsettled (PENDING or
+    // TIMED_OUT) row is ever transitioned; repeated callbacks for the same
+    // CheckoutRequestID are no-ops. A callback whose amount differs from the
+    // one requested is not credited; the row is flagged and left for the
+    // deposit sweeper to confirm through the STK query.
+    pub async fn complete_deposit(
+        &self,
+        checkout_request_id: &str,
+        payment: &PaymentDetails,
+    ) -> Result<()> {
+        self.settle_pending_deposit(
+            checkout_request_id,
+            Some(payment.amount),
+            Some(&payment.mpesa_receipt_number),
+            Some(&payment.phone_number),
+        )
+        .await
+    }
+
+    // Used when the STK query confirms payment but no callback ever arrived.
+    // The query result carries no receipt, so the recorded amount is credited.
+    pub async fn complete_deposit_from_query(&self, checkout_request_id: &str) -> Result<()> {
+        self.settle_pending_deposit(checkout_request_id, None, None, None)
+            .await
+    }
+
+    async fn settle_pending_deposit(
+        &self,
+        checkout_request_id: &str,
+        amount: Option<Decimal>,
+        mpesa_reference: Option<&str>,
+        phone_number: Option<&str>,
+    ) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        let transaction = sqlx::query!(
+            r#"
+            UPDATE transactions
+            SET status = 'COMPLETED',
+                mpesa_reference = COALESCE($2, mpesa_reference),
+                phone_number = COALESCE($3, phone_number),
+                failure_reason = NULL,
+                completed_at = CURRENT_TIMESTAMP
+            WHERE checkout_request_id = $4
+            AND transaction_type = 'DEPOSIT'
+            AND status IN ('PENDING', 'TIMED_OUT')
+            AND ($1::NUMERIC IS NULL OR amount = $1)
+            RETURNING id, fund_id, amount, schedule_id
+            "#,
+            amount,
+            mpesa_reference,
+            phone_number,
+            checkout_request_id,
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let Some(transaction) = transaction else {
+            if let Some(amount) = amount {
+                self.flag_amount_mismatch(checkout_request_id, amount).await?;
+            } else {
+                tracing::info!("Ignoring duplicate settlement for {}", checkout_request_id);
+            }
+            return Ok(());
+        };
+
+        sqlx::query!(
+            r#"
+            UPDATE pension_funds
+            SET balance = balance + $1
+            WHERE id = $2
+            "#,
+            transaction.amount,
+            transaction.fund_id,
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        let entry = ledger_entry(
+            &TransactionType::Deposit,
+            transaction.fund_id,
+            transaction.amount,
+            transaction.id,
+        );
+        LedgerService::post(&mut tx, &entry).await?;
+
+        if let Some(schedule_id) = transaction.schedule_id {
+            record_schedule_outcome(&mut *tx, schedule_id, true).await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    // Leaves the deposit unsettled with the discrepancy recorded, so the
+    // sweeper credits the requested amount only once M-Pesa confirms it
+    async fn flag_amount_mismatch(&self, checkout_request_id: &str, amount: Decimal) -> Result<()> {
+        let requested = sqlx::query_scalar!(
+            r#"
+            UPDATE transactions
+            SET failure_reason = 'Callback reported ' || $1::NUMERIC || ' against '
+                || amount || ' requested; awaiting STK query'
+            WHERE checkout_request_id = $2
+            AND transaction_type = 'DEPOSIT'
+            AND status IN ('PENDING', 'TIMED_OUT')
+            RETURNING amount
+            "#,
+            amount,
+            checkout_request_id,
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        match requested {
+            Some(requested) => tracing::warn!(
+                "Deposit callback for {} reported {} against {} requested; not credited",
+                checkout_request_id,
+                amount,
+                requested
+            ),
+            None => tracing::info!("Ignoring duplicate settlement for {}", checkout_request_id),
+        }
+
+        Ok(())
+    }
+
+    pub async fn fail_deposit(&self, checkout_request_id: &str, reason: &str) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        let transaction = sqlx::query!(
+            r#"
+            UPDATE transactions
+            SET status = 'FAILED',
+                failure_reason = $1
+            WHERE checkout_request_id = $2
+            AND transaction_type = 'DEPOSIT'
+            AND status IN ('PENDING', 'TIMED_OUT')
+            RETURNING schedule_id
+            "#,
+            reason,
+            checkout_request_id,
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        if let Some(schedule_id) = transaction.and_then(|t| t.schedule_id) {
+            record_schedule_outcome(&mut *tx, schedule_id, false).await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    // For deposits M-Pesa never confirmed either way. They leave the sweep,
+    // and the schedule outcome waits for a late callback to settle or fail
+    // them.
+    pub async fn time_out_deposit(&self, checkout_request_id: &str, reason: &str) -> Result<()> {
+        sqlx::query!(
+            r#"
+            UPDATE transactions
+            SET status = 'TIMED_OUT',
+                failure_reason = $1
+            WHERE checkout_request_id = $2
+            AND transaction_type = 'DEPOSIT'
+            AND status = 'PENDING'
+            "#,
+            reason,
+            checkout_request_id,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn get_stale_pending_deposits(
+        &self,
+        older_than: DateTime<Utc>,
+    ) -> Result<Vec<PendingDeposit>> {
+        let deposits = sqlx::query_as!(
+            PendingDeposit,
+            r#"
+            SELECT id, checkout_request_id as "checkout_request_id!", created_at
+            FROM transactions
+            WHERE transaction_type = 'DEPOSIT'
+            AND status = 'PENDING'
+            AND checkout_request_id IS NOT NULL
+            AND created_at < $1
+            ORDER BY created_at
+            "#,
+            older_than
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(deposits)
+    }
+
     pub async fn get_user_balance(&self, user_id: Uuid) -> Result<f64> {
         let balance = sqlx::query!(
             r#"
@@ -129,85 +427,191 @@ impl FundService {
         Ok(balance)
     }
 
-    pub async fn validate_withdrawal(
+    // Checked before the STK push, so a member over their cap is never
+    // asked to pay
+    pub async fn validate_deposit(&self, user_id: Uuid, amount: Decimal) -> Result<()> {
+        if amount < self.limits.min_deposit {
+            return Err(Error::DepositTooSmall(self.limits.min_deposit).into());
+        }
+
+        let tier = kyc_tier(&self.pool, user_id).await?;
+        let mut conn = self.pool.acquire().await?;
+        let usage = self.get_usage(&mut conn, user_id, "DEPOSIT").await?;
+
+        match check_period_limits(&self.limits.for_tier(tier).deposit, &usage, amount) {
+            Some(LimitBreach::Daily) => Err(Error::DailyDepositLimitExceeded.into()),
+            Some(LimitBreach::Monthly) => Err(Error::MonthlyDepositLimitExceeded.into()),
+            None => Ok(()),
+        }
+    }
+
+    // Run inside the withdrawal's transaction, with the fund row locked, so
+    // two withdrawals at once cannot both fit under the same headroom
+    async fn validate_withdrawal(
         &self,
+        conn: &mut PgConnection,
         user_id: Uuid,
         amount: Decimal,
     ) -> Result<()> {
-        let limits = WithdrawalLimits::default();
+        // Members we have not identified cannot take money out, whatever
+        // limits their tier is configured with
+        require_approved_kyc(&self.pool, user_id).await?;
+
+        let tier = kyc_tier(&self.pool, user_id).await?;
+        let tier_limits = self.limits.for_tier(tier);
+        if !tier_limits.can_withdraw() {
+            return Err(Error::KycNotApproved.into());
+        }
+
+        if amount < self.limits.min_withdrawal {
+            return Err(Error::WithdrawalTooSmall(self.limits.min_withdrawal).into());
+        }
 
-        // Check minimum amount
-        if amount < limits.min_amount {
-            return Err(Error::WithdrawalTooSmall(limits.min_amount));
+        if let Some(next) = self.next_withdrawal_at(&mut *conn, user_id).await? {
+            if next > Utc::now() {
+                return Err(Error::WithdrawalTooFrequent.into());
+            }
+        }
+
+        let usage = self.get_usage(conn, user_id, "WITHDRAWAL").await?;
+
+        match check_period_limits(&tier_limits.withdrawal, &usage, amount) {
+            Some(LimitBreach::Daily) => Err(Error::DailyWithdrawalLimitExceeded.into()),
+            Some(LimitBreach::Monthly) => Err(Error::MonthlyWithdrawalLimitExceeded.into()),
+            None => Ok(()),
         }
+    }
+
+    pub async fn get_limit_headroom(&self, user_id: Uuid) -> Result<LimitHeadroom> {
+        let tier = kyc_tier(&self.pool, user_id).await?;
+        let tier_limits = self.limits.for_tier(tier);
+
+        let mut conn = self.pool.acquire().await?;
+        let deposit_usage = self.get_usage(&mut conn, user_id, "DEPOSIT").await?;
+        let withdrawal_usage = self.get_usage(&mut conn, user_id, "WITHDRAWAL").await?;
 
-        // Check last withdrawal time
+        let next_withdrawal_at = self
+            .next_withdrawal_at(&mut conn, user_id)
+            .await?
+            .filter(|next| *next > Utc::now());
+
+        Ok(LimitHeadroom {
+            tier,
+            min_deposit: self.limits.min_deposit,
+            min_withdrawal: self.limits.min_withdrawal,
+            deposit: PeriodHeadroom::new(tier_limits.deposit, &deposit_usage),
+            withdrawal: PeriodHeadroom::new(tier_limits.withdrawal, &withdrawal_usage),
+            next_withdrawal_at,
+        })
+    }
+
+    async fn next_withdrawal_at(
+        &self,
+        conn: &mut PgConnection,
+        user_id: Uuid,
+    ) -> Result<Option<DateTime<Utc>>> {
+        // A failed payout was refunded and should not hold up a retry
         let last_withdrawal = sqlx::query!(
             r#"
-            SELECT created_at 
-            FROM transactions 
-            WHERE user_id = $1 
+            SELECT created_at
+            FROM transactions
+            WHERE user_id = $1
             AND transaction_type = 'WITHDRAWAL'
+            AND status <> 'FAILED'
             ORDER BY created_at DESC
             LIMIT 1
             "#,
             user_id
         )
-        .fetch_optional(&self.pool)
+        .fetch_optional(conn)
         .await?;
 
-        if let Some(last) = last_withdrawal {
-            let time_since_last = Utc::now() - last.created_at;
-            if time_since_last < limits.min_time_between_withdrawals {
-                return Err(Error::WithdrawalTooFrequent);
-            }
-        }
-
-        // Check daily limit
-        let daily_total = self.get_withdrawal_total_for_period(
-            user_id,
-            Utc::now() - chrono::Duration::days(1),
-        ).await?;
+        Ok(last_withdrawal.map(|last| {
+            last.created_at + Duration::seconds(self.limits.min_secs_between_withdrawals)
+        }))
+    }
 
-        if daily_total + amount > limits.max_daily_amount {
-            return Err(Error::DailyWithdrawalLimitExceeded);
-        }
+    async fn get_usage(
+        &self,
+        conn: &mut PgConnection,
+        user_id: Uuid,
+        transaction_type: &str,
+    ) -> Result<PeriodUsage> {
+        let now = Utc::now();
 
-        // Check monthly limit
-        let monthly_total = self.get_withdrawal_total_for_period(
-            user_id,
-            Utc::now() - chrono::Duration::days(30),
-        ).await?;
+        Ok(PeriodUsage {
+            daily: get_total_for_period(
+                &mut *conn,
+                user_id,
+                transaction_type,
+                now - Duration::days(1),
+            )
+            .await?,
+            monthly: get_total_for_period(
+                conn,
+                user_id,
+                transaction_type,
+                now - Duration::days(30),
+            )
+            .await?,
+        })
+    }
 
-        if monthly_total + amount > limits.max_monthly_amount {
-            return Err(Error::MonthlyWithdrawalLimitExceeded);
-        }
+    async fn member_facts(&self, conn: &mut PgConnection, user_id: Uuid) -> Result<MemberFacts> {
+        let age = member_age(&mut *conn, user_id).await?;
 
-        Ok(())
-    }
+        let available_balance = sqlx::query_scalar!(
+            r#"
+            SELECT balance - tokenized_balance as "available!"
+            FROM pension_funds
+            WHERE user_id = $1
+            "#,
+            user_id
+        )
+        .fetch_optional(&mut *conn)
+        .await?
+        .unwrap_or_default();
 
-    async fn get_withdrawal_total_for_period(
-        &self,
-        user_id: Uuid,
-        since: DateTime<Utc>,
-    ) -> Result<Decimal> {
-        let total = sqlx::query!(
+        let contributions = sqlx::query!(
             r#"
-            SELECT COALESCE(SUM(amount), 0) as total
+            SELECT MIN(COALESCE(completed_at, created_at)) as first_at,
+                   COALESCE(SUM(amount) FILTER (
+                       WHERE COALESCE(completed_at, created_at) > $2
+                   ), 0) as unvested
             FROM transactions
             WHERE user_id = $1
-            AND transaction_type = 'WITHDRAWAL'
-            AND created_at > $2
+            AND transaction_type IN ('DEPOSIT', 'EMPLOYER_CONTRIBUTION', 'EMPLOYEE_CONTRIBUTION')
+            AND status = 'COMPLETED'
             "#,
             user_id,
-            since
+            Utc::now() - Duration::days(self.withdrawal_policy.vesting_days),
         )
-        .fetch_one(&self.pool)
+        .fetch_one(&mut *conn)
+        .await?;
+
+        // Refunded payouts do not use up the early access allowance
+        let early_withdrawn = sqlx::query_scalar!(
+            r#"
+            SELECT COALESCE(SUM(t.amount), 0)
+            FROM withdrawal_decisions d
+            JOIN transactions t ON t.id = d.transaction_id
+            WHERE d.user_id = $1
+            AND d.applied_kind <> 'RETIREMENT'
+            AND t.status <> 'FAILED'
+            "#,
+            user_id
+        )
+        .fetch_one(conn)
         .await?
-        .total
         .unwrap_or_default();
 
-        Ok(total)
+        Ok(MemberFacts {
+            age,
+            available_balance,
+            unvested: contributions.unvested.unwrap_or_default(),
+            first_contribution_at: contributions.first_at,
+            early_withdrawn,
+        })
     }
 
     pub async fn process_withdrawal(
@@ -215,100 +619,265 @@ impl FundService {
         user_id: Uuid,
         amount: Decimal,
         phone_number: &str,
+        kind: WithdrawalKind,
+        reason: Option<&str>,
     ) -> Result<Transaction> {
-        // Validate withdrawal
-        self.validate_withdrawal(user_id, amount).await?;
-
         let mut tx = self.pool.begin().await?;
-        let notification_service = NotificationService::new()?;
+
+        // Lock the member's fund first: a concurrent withdrawal waits here,
+        // then sees the usage and balance this one leaves behind
+        let fund_id = sqlx::query_scalar!(
+            "SELECT id FROM pension_funds WHERE user_id = $1 FOR UPDATE",
+            user_id
+        )
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| Error::NotFound("Pension fund".to_string()))?;
+
+        self.validate_withdrawal(&mut *tx, user_id, amount).await?;
+
+        // Apply the early-withdrawal rules; denials are kept too
+        let facts = self.member_facts(&mut *tx, user_id).await?;
+        let decision = evaluate(
+            &self.withdrawal_policy,
+            kind,
+            reason,
+            &facts,
+            amount,
+            Utc::now(),
+        );
+
+        if !decision.allowed {
+            record_decision(&mut *tx, user_id, None, kind, reason, &facts, &decision).await?;
+            tx.commit().await?;
+            return Err(Error::WithdrawalNotPermitted(decision.denial_message()).into());
+        }
+
+        // Hold the amount against the member's fund. Tokenized savings are
+        // backed by BPT in circulation and must be redeemed first.
+        let amount = decision.debited();
+        let held = sqlx::query!(
+            r#"
+            UPDATE pension_funds
+            SET balance = balance - $1
+            WHERE id = $2 AND balance - tokenized_balance >= $1
+            "#,
+            amount,
+            fund_id,
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        if held.rows_affected() == 0 {
+            return Err(Error::InsufficientFunds.into());
+        }
+
+        // Hardship claims are held, not paid, until an administrator has
+        // reviewed the member's evidence
+        let status = if decision.kind == WithdrawalKind::Hardship {
+            "PENDING_REVIEW"
+        } else {
+            "PENDING"
+        };
 
         // Create withdrawal transaction
         let transaction_id = Uuid::new_v4();
         sqlx::query!(
             r#"
             INSERT INTO transactions (
-                id, user_id, transaction_type, amount, status, phone_number
+                id, fund_id, user_id, transaction_type, amount, penalty_amount, status,
+                phone_number
             )
-            VALUES ($1, $2, 'WITHDRAWAL', $3, 'PENDING', $4)
+            VALUES ($1, $2, $3, 'WITHDRAWAL', $4, $5, $6, $7)
             "#,
             transaction_id,
+            fund_id,
             user_id,
             amount,
+            decision.penalty,
+            status,
             phone_number,
         )
-        .execute(&mut tx)
+        .execute(&mut *tx)
         .await?;
 
-        // Update fund balance
-        sqlx::query!(
-            r#"
-            UPDATE pension_funds
-            SET balance = balance - $1
-            WHERE user_id = $2
-            "#,
-            amount,
+        record_decision(
+            &mut *tx,
             user_id,
+            Some(transaction_id),
+            kind,
+            reason,
+            &facts,
+            &decision,
         )
-        .execute(&mut tx)
         .await?;
 
-        // Send notification
-        notification_service
-            .send_withdrawal_initiated(phone_number, amount.to_f64().unwrap())
-            .await?;
+        // Move the payout to withdrawals payable until M-Pesa confirms; any
+        // early-withdrawal penalty is kept back as income
+        let mut entry = JournalEntry::new("Withdrawal hold", Some(transaction_id))
+            .debit(&fund_account_code(fund_id), amount)
+            .credit(WITHDRAWALS_PAYABLE, decision.payout);
+        if !decision.penalty.is_zero() {
+            entry = entry.credit(EARLY_WITHDRAWAL_PENALTIES, decision.penalty);
+        }
+        LedgerService::post(&mut tx, &entry).await?;
 
         tx.commit().await?;
 
+        if status == "PENDING" {
+            let payout = decision.payout.to_f64().unwrap_or_default();
+            notify_member(transaction_id, |sms| async move {
+                sms.send_withdrawal_initiated(phone_number, payout).await
+            })
+            .await;
+        }
+
         Ok(Transaction {
             id: transaction_id,
-            status: "PENDING".to_string(),
+            status: status.to_string(),
+            phone_number: phone_number.to_string(),
+            payout: decision.payout,
+            penalty: decision.penalty,
         })
     }
 
-    pub async fn complete_withdrawal(
+    pub async fn record_payout_request(
         &self,
         transaction_id: Uuid,
-        mpesa_reference: &str,
+        originator_conversation_id: &str,
     ) -> Result<()> {
-        let mut tx = self.pool.begin().await?;
+        sqlx::query!(
+            r#"
+            UPDATE transactions
+            SET conversation_id = $1
+            WHERE id = $2
+            "#,
+            originator_conversation_id,
+            transaction_id,
+        )
+        .execute(&self.pool)
+        .await?;
 
-        // Get transaction details
+        Ok(())
+    }
+
+    pub async fn find_withdrawal_by_conversation(
+        &self,
+        originator_conversation_id: &str,
+    ) -> Result<Option<Uuid>> {
         let transaction = sqlx::query!(
             r#"
-            SELECT amount, phone_number
+            SELECT id
             FROM transactions
-            WHERE id = $1
+            WHERE conversation_id = $1 AND transaction_type = 'WITHDRAWAL'
             "#,
-            transaction_id
+            originator_conversation_id
         )
-        .fetch_one(&self.pool)
+        .fetch_optional(&self.pool)
         .await?;
 
+        Ok(transaction.map(|t| t.id))
+    }
+
+    // Only a PENDING withdrawal is transitioned, so a repeated B2C result
+    // callback can neither pay out twice nor refund twice.
+    pub async fn complete_withdrawal(
+        &self,
+        transaction_id: Uuid,
+        mpesa_reference: &str,
+    ) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
         // Update transaction status
-        sqlx::query!(
+        let transaction = sqlx::query!(
             r#"
             UPDATE transactions
             SET status = 'COMPLETED',
                 completed_at = CURRENT_TIMESTAMP,
                 mpesa_reference = $1
-            WHERE id = $2
+            WHERE id = $2 AND status = 'PENDING'
+            RETURNING amount - penalty_amount as "payout!", phone_number
             "#,
             mpesa_reference,
             transaction_id,
         )
-        .execute(&mut tx)
+        .fetch_optional(&mut *tx)
         .await?;
 
-        // Send completion notification
-        let notification_service = NotificationService::new()?;
-        notification_service
-            .send_withdrawal_completed(
-                &transaction.phone_number,
-                transaction.amount.to_f64().unwrap(),
-            )
-            .await?;
+        let Some(transaction) = transaction else {
+            tracing::info!("Withdrawal {} already settled", transaction_id);
+            return Ok(());
+        };
+
+        // Settle the payable against the M-Pesa clearing account
+        let entry = JournalEntry::new("Withdrawal payout", Some(transaction_id))
+            .debit(WITHDRAWALS_PAYABLE, transaction.payout)
+            .credit(MPESA_CLEARING, transaction.payout);
+        LedgerService::post(&mut tx, &entry).await?;
 
         tx.commit().await?;
+
+        let payout = transaction.payout.to_f64().unwrap_or_default();
+        notify_member(transaction_id, |sms| async move {
+            sms.send_withdrawal_completed(&transaction.phone_number, payout)
+                .await
+        })
+        .await;
+
+        Ok(())
+    }
+
+    // Releases a reviewed hardship withdrawal for payout. The caller then
+    // sends the B2C payment exactly as for any other withdrawal.
+    pub async fn approve_withdrawal(
+        &self,
+        transaction_id: Uuid,
+        reviewer_id: Uuid,
+    ) -> Result<Transaction> {
+        let transaction = sqlx::query!(
+            r#"
+            UPDATE transactions
+            SET status = 'PENDING', reviewed_by = $2, reviewed_at = NOW()
+            WHERE id = $1 AND transaction_type = 'WITHDRAWAL' AND status = 'PENDING_REVIEW'
+            RETURNING amount - penalty_amount as "payout!", penalty_amount,
+                      phone_number as "phone_number!"
+            "#,
+            transaction_id,
+            reviewer_id,
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| Error::NotFound("Withdrawal awaiting review".to_string()))?;
+
+        let payout = transaction.payout.to_f64().unwrap_or_default();
+        let phone_number = transaction.phone_number.as_str();
+        notify_member(transaction_id, |sms| async move {
+            sms.send_withdrawal_initiated(phone_number, payout).await
+        })
+        .await;
+
+        Ok(Transaction {
+            id: transaction_id,
+            status: "PENDING".to_string(),
+            phone_number: transaction.phone_number.clone(),
+            payout: transaction.payout,
+            penalty: transaction.penalty_amount,
+        })
+    }
+
+    // Turns down a hardship claim and returns the held amount to the fund
+    pub async fn reject_withdrawal(
+        &self,
+        transaction_id: Uuid,
+        reviewer_id: Uuid,
+        reason: &str,
+    ) -> Result<()> {
+        let reversed = self
+            .reverse_withdrawal(transaction_id, "PENDING_REVIEW", Some(reviewer_id), reason)
+            .await?;
+        if !reversed {
+            return Err(Error::NotFound("Withdrawal awaiting review".to_string()).into());
+        }
         Ok(())
     }
 
@@ -317,55 +886,83 @@ impl FundService {
         transaction_id: Uuid,
         reason: &str,
     ) -> Result<()> {
+        if !self
+            .reverse_withdrawal(transaction_id, "PENDING", None, reason)
+            .await?
+        {
+            tracing::info!("Withdrawal {} already settled", transaction_id);
+        }
+        Ok(())
+    }
+
+    // Refunds a held withdrawal still in `status`. Returns false when it
+    // has already moved on, so a repeated callback is a no-op.
+    async fn reverse_withdrawal(
+        &self,
+        transaction_id: Uuid,
+        status: &str,
+        reviewer_id: Option<Uuid>,
+        reason: &str,
+    ) -> Result<bool> {
         let mut tx = self.pool.begin().await?;
 
-        // Get transaction details
+        // Update transaction status
         let transaction = sqlx::query!(
             r#"
-            SELECT amount, phone_number, user_id
-            FROM transactions
-            WHERE id = $1
+            UPDATE transactions
+            SET status = 'FAILED',
+                failure_reason = $1,
+                reviewed_by = COALESCE($4, reviewed_by),
+                reviewed_at = CASE WHEN $4::UUID IS NULL THEN reviewed_at ELSE NOW() END
+            WHERE id = $2 AND transaction_type = 'WITHDRAWAL' AND status = $3
+            RETURNING amount, penalty_amount, phone_number, fund_id
             "#,
-            transaction_id
+            reason,
+            transaction_id,
+            status,
+            reviewer_id,
         )
-        .fetch_one(&self.pool)
+        .fetch_optional(&mut *tx)
         .await?;
 
+        let Some(transaction) = transaction else {
+            return Ok(false);
+        };
+
         // Refund the amount
         sqlx::query!(
             r#"
             UPDATE pension_funds
             SET balance = balance + $1
-            WHERE user_id = $2
-            "#,
-            transaction.amount,
-            transaction.user_id,
-        )
-        .execute(&mut tx)
-        .await?;
-
-        // Update transaction status
-        sqlx::query!(
-            r#"
-            UPDATE transactions
-            SET status = 'FAILED',
-                failure_reason = $1
             WHERE id = $2
             "#,
-            reason,
-            transaction_id,
+            transaction.amount,
+            transaction.fund_id,
         )
-        .execute(&mut tx)
+        .execute(&mut *tx)
         .await?;
 
-        // Send failure notification
-        let notification_service = NotificationService::new()?;
-        notification_service
-            .send_withdrawal_failed(&transaction.phone_number, reason)
-            .await?;
+        // Release the hold back to the member's fund, penalty included
+        let mut entry = JournalEntry::new("Withdrawal reversal", Some(transaction_id))
+            .debit(
+                WITHDRAWALS_PAYABLE,
+                transaction.amount - transaction.penalty_amount,
+            )
+            .credit(&fund_account_code(transaction.fund_id), transaction.amount);
+        if !transaction.penalty_amount.is_zero() {
+            entry = entry.debit(EARLY_WITHDRAWAL_PENALTIES, transaction.penalty_amount);
+        }
+        LedgerService::post(&mut tx, &entry).await?;
 
         tx.commit().await?;
-        Ok(())
+
+        notify_member(transaction_id, |sms| async move {
+            sms.send_withdrawal_failed(&transaction.phone_number, reason)
+                .await
+        })
+        .await;
+
+        Ok(true)
     }
 
     pub async fn get_user_withdrawals(
@@ -392,6 +989,199 @@ impl FundService {
 
         Ok(withdrawals)
     }
+
+    pub async fn get_recent_transactions(
+        &self,
+        user_id: Uuid,
+        limit: i64,
+    ) -> Result<Vec<TransactionSummary>> {
+        let transactions = sqlx::query_as!(
+            TransactionSummary,
+            r#"
+            SELECT transaction_type, amount, status, created_at
+            FROM transactions
+            WHERE user_id = $1
+            ORDER BY created_at DESC
+            LIMIT $2
+            "#,
+            user_id,
+            limit
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(transactions)
+    }
+}
+
+// Pending transactions count against the caps; failed ones do not
+async fn get_total_for_period(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    transaction_type: &str,
+    since: DateTime<Utc>,
+) -> Result<Decimal> {
+    let total = sqlx::query!(
+        r#"
+        SELECT COALESCE(SUM(amount), 0) as total
+        FROM transactions
+        WHERE user_id = $1
+        AND transaction_type = $2
+        AND status <> 'FAILED'
+        AND created_at > $3
+        "#,
+        user_id,
+        transaction_type,
+        since
+    )
+    .fetch_one(conn)
+    .await?
+    .total
+    .unwrap_or_default();
+
+    Ok(total)
+}
+
+// Texts the member about a withdrawal once it is committed. The money has
+// moved either way, so a failed SMS is logged rather than failing the
+// request or the M-Pesa callback that triggered it.
+async fn notify_member<F, Fut>(transaction_id: Uuid, send: F)
+where
+    F: FnOnce(NotificationService) -> Fut,
+    Fut: std::future::Future<Output = Result<()>>,
+{
+    let sent = match NotificationService::new() {
+        Ok(sms) => send(sms).await,
+        Err(e) => Err(e),
+    };
+    if let Err(e) = sent {
+        tracing::warn!("Notification for withdrawal {} failed: {}", transaction_id, e);
+    }
+}
+
+// The member's age on the date of birth the ID registry confirmed. The age
+// on the risk profile is the member's own say-so and never unlocks the
+// retirement rules, so without a verified date of birth the age is unknown.
+async fn member_age(conn: &mut PgConnection, user_id: Uuid) -> Result<Option<u8>> {
+    let date_of_birth = sqlx::query_scalar!(
+        r#"
+        SELECT date_of_birth FROM kyc_verifications
+        WHERE user_id = $1 AND status = 'APPROVED' AND date_of_birth IS NOT NULL
+        ORDER BY completed_at DESC NULLS LAST
+        LIMIT 1
+        "#,
+        user_id
+    )
+    .fetch_optional(conn)
+    .await?
+    .flatten();
+
+    Ok(date_of_birth.and_then(|date_of_birth| {
+        Utc::now()
+            .date_naive()
+            .years_since(date_of_birth)
+            .and_then(|age| u8::try_from(age).ok())
+    }))
+}
+
+async fn record_decision(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    transaction_id: Option<Uuid>,
+    requested_kind: WithdrawalKind,
+    reason: Option<&str>,
+    facts: &MemberFacts,
+    decision: &PolicyDecision,
+) -> Result<()> {
+    sqlx::query!(
+        r#"
+        INSERT INTO withdrawal_decisions (
+            id, user_id, transaction_id, requested_kind, applied_kind, reason, rule,
+            allowed, requested_amount, accessible_amount, penalty_amount, member_age
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+        "#,
+        Uuid::new_v4(),
+        user_id,
+        transaction_id,
+        requested_kind.as_str(),
+        decision.kind.as_str(),
+        reason,
+        decision.rule.as_str(),
+        decision.allowed,
+        decision.requested,
+        decision.accessible,
+        decision.penalty,
+        facts.age.map(i16::from),
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LimitBreach {
+    Daily,
+    Monthly,
+}
+
+// Amount moved in the rolling windows the caps apply to
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PeriodUsage {
+    pub daily: Decimal,
+    pub monthly: Decimal,
+}
+
+pub fn check_period_limits(
+    limits: &PeriodLimits,
+    usage: &PeriodUsage,
+    amount: Decimal,
+) -> Option<LimitBreach> {
+    if usage.daily + amount > limits.daily {
+        Some(LimitBreach::Daily)
+    } else if usage.monthly + amount > limits.monthly {
+        Some(LimitBreach::Monthly)
+    } else {
+        None
+    }
+}
+
+// Builds the journal entry for a transaction that settles immediately
+// How a transaction moves the member's balance; `ledger_entry` moves the
+// fund account the same way
+fn balance_modifier(transaction_type: &TransactionType) -> Decimal {
+    match transaction_type {
+        TransactionType::Deposit | TransactionType::Investment => Decimal::ONE,
+        TransactionType::Withdrawal | TransactionType::Fee => -Decimal::ONE,
+    }
+}
+
+fn ledger_entry(
+    transaction_type: &TransactionType,
+    fund_id: Uuid,
+    amount: Decimal,
+    transaction_id: Uuid,
+) -> JournalEntry {
+    let fund_account = fund_account_code(fund_id);
+    let entry = JournalEntry::new(&transaction_type.to_string(), Some(transaction_id));
+
+    match transaction_type {
+        TransactionType::Deposit => entry
+            .debit(MPESA_CLEARING, amount)
+            .credit(&fund_account, amount),
+        TransactionType::Withdrawal => entry
+            .debit(&fund_account, amount)
+            .credit(MPESA_CLEARING, amount),
+        // Investment returns grow the assets held for the member and the
+        // member's claim on them alike
+        TransactionType::Investment => entry
+            .debit(INVESTMENT_HOLDINGS, amount)
+            .credit(&fund_account, amount),
+        TransactionType::Fee => entry
+            .debit(&fund_account, amount)
+            .credit(FEE_INCOME, amount),
+    }
 }
 
 #[derive(sqlx::Type)]
@@ -399,6 +1189,8 @@ impl FundService {
 pub enum TransactionType {
     Deposit,
     Withdrawal,
+    Investment,
+    Fee,
 }
 
 impl ToString for TransactionType {
@@ -406,6 +1198,8 @@ impl ToString for TransactionType {
         match self {
             TransactionType::Deposit => "DEPOSIT",
             TransactionType::Withdrawal => "WITHDRAWAL",
+            TransactionType::Investment => "INVESTMENT",
+            TransactionType::Fee => "FEE",
         }
         .to_string()
     }
@@ -423,6 +1217,15 @@ pub struct PensionFund {
 pub struct Transaction {
     pub id: Uuid,
     pub status: String,
+    pub phone_number: String,
+    pub payout: Decimal,  // What M-Pesa pays the member
+    pub penalty: Decimal, // Kept back for early withdrawal
+}
+
+pub struct PendingDeposit {
+    pub id: Uuid,
+    pub checkout_request_id: String,
+    pub created_at: DateTime<Utc>,
 }
 
 pub struct WithdrawalRecord {
@@ -431,4 +1234,143 @@ pub struct WithdrawalRecord {
     pub status: String,
     pub created_at: DateTime<Utc>,
     pub completed_at: Option<DateTime<Utc>>,
-} 
\ No newline at end of file
+}
+
+#[derive(Debug, Serialize)]
+pub struct PeriodHeadroom {
+    pub limits: PeriodLimits,
+    pub used_daily: Decimal,
+    pub used_monthly: Decimal,
+    pub remaining_daily: Decimal,
+    pub remaining_monthly: Decimal,
+    // The most a single transaction can be right now
+    pub available: Decimal,
+}
+
+impl PeriodHeadroom {
+    pub fn new(limits: PeriodLimits, usage: &PeriodUsage) -> Self {
+        let remaining_daily = (limits.daily - usage.daily).max(Decimal::ZERO);
+        let remaining_monthly = (limits.monthly - usage.monthly).max(Decimal::ZERO);
+
+        Self {
+            limits,
+            used_daily: usage.daily,
+            used_monthly: usage.monthly,
+            remaining_daily,
+            remaining_monthly,
+            available: remaining_daily.min(remaining_monthly),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct LimitHeadroom {
+    pub tier: KycTier,
+    pub min_deposit: Decimal,
+    pub min_withdrawal: Decimal,
+    pub deposit: PeriodHeadroom,
+    pub withdrawal: PeriodHeadroom,
+    pub next_withdrawal_at: Option<DateTime<Utc>>,
+}
+
+pub struct TransactionSummary {
+    pub transaction_type: String,
+    pub amount: Decimal,
+    pub status: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limits() -> PeriodLimits {
+        PeriodLimits {
+            daily: Decimal::new(70_000, 0),
+            monthly: Decimal::new(300_000, 0),
+        }
+    }
+
+    fn usage(daily: i64, monthly: i64) -> PeriodUsage {
+        PeriodUsage {
+            daily: Decimal::new(daily, 0),
+            monthly: Decimal::new(monthly, 0),
+        }
+    }
+
+    #[test]
+    fn test_fund_postings_match_the_balance_change() {
+        let fund_id = Uuid::new_v4();
+        let amount = Decimal::new(2_500, 0);
+
+        for transaction_type in [
+            TransactionType::Deposit,
+            TransactionType::Withdrawal,
+            TransactionType::Investment,
+            TransactionType::Fee,
+        ] {
+            let entry = ledger_entry(&transaction_type, fund_id, amount, Uuid::new_v4());
+            entry.validate().unwrap();
+
+            // Fund accounts are liabilities, so a credit raises the balance
+            let fund_change: Decimal = entry
+                .postings
+                .iter()
+                .filter(|p| p.account_code == fund_account_code(fund_id))
+                .map(|p| -p.amount)
+                .sum();
+            assert_eq!(
+                fund_change,
+                amount * balance_modifier(&transaction_type),
+                "{}",
+                transaction_type.to_string()
+            );
+        }
+    }
+
+    #[test]
+    fn test_check_period_limits() {
+        let amount = Decimal::new(20_000, 0);
+
+        assert_eq!(check_period_limits(&limits(), &usage(0, 0), amount), None);
+        assert_eq!(
+            check_period_limits(&limits(), &usage(50_000, 50_000), amount),
+            None
+        );
+        assert_eq!(
+            check_period_limits(&limits(), &usage(50_001, 50_001), amount),
+            Some(LimitBreach::Daily)
+        );
+        assert_eq!(
+            check_period_limits(&limits(), &usage(0, 290_000), amount),
+            Some(LimitBreach::Monthly)
+        );
+    }
+
+    #[test]
+    fn test_zero_caps_block_everything() {
+        let closed = PeriodLimits {
+            daily: Decimal::ZERO,
+            monthly: Decimal::ZERO,
+        };
+
+        assert_eq!(
+            check_period_limits(&closed, &usage(0, 0), Decimal::ONE),
+            Some(LimitBreach::Daily)
+        );
+    }
+
+    #[test]
+    fn test_headroom() {
+        let headroom = PeriodHeadroom::new(limits(), &usage(10_000, 280_000));
+
+        assert_eq!(headroom.remaining_daily, Decimal::new(60_000, 0));
+        assert_eq!(headroom.remaining_monthly, Decimal::new(20_000, 0));
+        assert_eq!(headroom.available, Decimal::new(20_000, 0));
+
+        // Usage above a cap that was since lowered never goes negative
+        let headroom = PeriodHeadroom::new(limits(), &usage(80_000, 80_000));
+        assert_eq!(headroom.remaining_daily, Decimal::ZERO);
+        assert_eq!(headroom.available, Decimal::ZERO);
+    }
+}
@@ -1,55 +1,95 @@
-use anyhow::Result;
+use chrono::{DateTime, Utc};
+use rust_decimal::{prelude::ToPrimitive, Decimal};
 use sqlx::PgPool;
+use std::collections::BTreeMap;
 use uuid::Uuid;
 use crate::error::{Result, Error};
+use crate::services::kyc_service::require_approved_kyc;
+use crate::services::price_feed::PriceFeedService;
+use crate::services::price_history::PriceHistoryService;
+use crate::services::rebalancing::{Recommendation, RebalancingService};
 
+use crate::ai::glide_path::{aged_profile, whole_years_between, GlidePath, GlideStep};
 use crate::ai::investment_strategy::{
-    AssetAllocation, PortfolioRebalancer, RiskProfile, RiskTolerance,
+    AllocationStrategy, Asset, AssetAllocation, PortfolioRebalancer, RiskProfile, RiskTolerance,
 };
 
 pub struct InvestmentService {
     pool: PgPool,
     rebalancer: PortfolioRebalancer,
+    rebalancing: RebalancingService,
+    glide_path: GlidePath,
 }
 
 impl InvestmentService {
-    pub fn new(pool: PgPool) -> Result<Self> {
+    pub fn new(
+        pool: PgPool,
+        rebalancing: RebalancingService,
+        glide_path: GlidePath,
+        price_feed: PriceFeedService,
+        price_history: PriceHistoryService,
+    ) -> Result<Self> {
         Ok(Self {
             pool,
-            rebalancer: PortfolioRebalancer::new(0.05)?, // 5% threshold
+            rebalancer: PortfolioRebalancer::new(Decimal::new(5, 0)) // 5 percentage points
+                .with_glide_path(glide_path.clone())
+                .with_price_feed(price_feed)
+                .with_price_history(price_history),
+            rebalancing,
+            glide_path,
         })
     }
 
+    // The target for the member's profile. Read only: nothing is proposed
+    // or traded, see `check_rebalance` for that.
     pub async fn get_investment_recommendation(
         &self,
         user_id: Uuid,
     ) -> Result<AssetAllocation> {
+        let risk_profile = self.get_user_risk_profile(user_id).await?;
+
+        Ok(self.rebalancer.target_allocation(&risk_profile).await?)
+    }
+
+    // Compares what the member actually holds (or, before their first trade,
+    // their chosen allocation) with the target for their profile. When they
+    // have drifted, the trades that close the gap are proposed for the
+    // member to accept; the auto-rebalancer applies them for opted-in members.
+    pub async fn check_rebalance(
+        &self,
+        user_id: Uuid,
+    ) -> Result<Option<(AssetAllocation, Recommendation)>> {
+        require_approved_kyc(&self.pool, user_id).await?;
+
         // Fetch user's risk profile from database
         let risk_profile = self.get_user_risk_profile(user_id).await?;
-        
-        // Get current allocation
-        let current_allocation = self.get_current_allocation(user_id).await?;
-        
+
+        let current_allocation = match self.rebalancing.get_held_allocation(user_id).await? {
+            Some(held) => held,
+            None => self.get_current_allocation(user_id).await?,
+        };
+
         // Check if rebalancing is needed
-        if let Some(new_allocation) = self
+        match self
             .rebalancer
-            .check_and_rebalance(user_id, ¤t_allocation, &risk_profile)
+            .check_and_rebalance(user_id, &current_allocation, &risk_profile)
             .await?
         {
-            // Record the rebalancing recommendation
-            self.record_rebalancing_recommendation(user_id, &new_allocation)
-                .await?;
-            Ok(new_allocation)
-        } else {
-            Ok(current_allocation)
+            Some(target) => {
+                let recommendation = self.rebalancing.propose(user_id, &target).await?;
+                Ok(Some((target, recommendation)))
+            }
+            None => Ok(None),
         }
     }
 
+    // Age and horizon move on a year for every year since the member
+    // answered, which is what steps target-date members down the glide path
     pub async fn get_user_risk_profile(&self, user_id: Uuid) -> Result<RiskProfile> {
-        let profile = sqlx::query_as!(
-            RiskProfile,
+        let profile = sqlx::query!(
             r#"
-            SELECT age, income, risk_tolerance, investment_horizon
+            SELECT age, income, risk_tolerance, investment_horizon,
+                   allocation_strategy, answered_at
             FROM user_risk_profiles
             WHERE user_id = $1
             "#,
@@ -59,52 +99,154 @@ impl InvestmentService {
         .await
         .map_err(Error::Database)?;
 
-        Ok(profile)
+        let answered = RiskProfile {
+            age: profile.age as u8,
+            income: profile.income.to_f64().unwrap_or_default(),
+            risk_tolerance: profile.risk_tolerance.parse()?,
+            investment_horizon: profile.investment_horizon as u8,
+            strategy: profile.allocation_strategy.parse()?,
+        };
+
+        Ok(aged_profile(
+            &answered,
+            whole_years_between(profile.answered_at, Utc::now()),
+        ))
+    }
+
+    // The yearly shifts ahead of a target-date member, starting with the
+    // target that applies today. Risk-based members have none.
+    pub async fn get_glide_schedule(&self, user_id: Uuid) -> Result<Vec<GlideStep>> {
+        let profile = self.get_user_risk_profile(user_id).await?;
+        if profile.strategy != AllocationStrategy::TargetDate {
+            return Ok(Vec::new());
+        }
+
+        self.glide_path
+            .schedule(&profile, Utc::now().date_naive())
+            .map_err(|e| Error::InvalidAllocation(e.to_string()))
     }
 
-    async fn get_current_allocation(&self, user_id: Uuid) -> Result<AssetAllocation> {
-        // Fetch current allocation from database
-        let allocation = sqlx::query!(
+    // One profile per member; answering the questionnaire again replaces it
+    pub async fn update_risk_profile(
+        &self,
+        user_id: Uuid,
+        profile: &RiskProfile,
+    ) -> Result<DateTime<Utc>> {
+        require_approved_kyc(&self.pool, user_id).await?;
+
+        let income = Decimal::from_f64_retain(profile.income)
+            .ok_or(Error::Other(anyhow::anyhow!("Invalid income")))?
+            .round_dp(2);
+
+        let updated = sqlx::query!(
             r#"
-            SELECT stocks, bonds, real_estate, crypto
-            FROM portfolio_allocations
-            WHERE user_id = $1
-            ORDER BY created_at DESC
-            LIMIT 1
+            INSERT INTO user_risk_profiles
+                (user_id, age, income, risk_tolerance, investment_horizon, allocation_strategy)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (user_id) DO UPDATE SET
+                age = EXCLUDED.age,
+                income = EXCLUDED.income,
+                risk_tolerance = EXCLUDED.risk_tolerance,
+                investment_horizon = EXCLUDED.investment_horizon,
+                allocation_strategy = EXCLUDED.allocation_strategy,
+                answered_at = NOW(),
+                updated_at = NOW()
+            RETURNING updated_at
             "#,
-            user_id
+            user_id,
+            i16::from(profile.age),
+            income,
+            profile.risk_tolerance.as_str(),
+            i16::from(profile.investment_horizon),
+            profile.strategy.as_str(),
         )
         .fetch_one(&self.pool)
         .await?;
 
-        Ok(AssetAllocation {
-            stocks: allocation.stocks,
-            bonds: allocation.bonds,
-            real_estate: allocation.real_estate,
-            crypto: allocation.crypto,
-        })
+        Ok(updated.updated_at)
     }
 
-    async fn record_rebalancing_recommendation(
+    pub async fn get_current_allocation(&self, user_id: Uuid) -> Result<AssetAllocation> {
+        latest_allocation(&self.pool, user_id)
+            .await?
+            .ok_or_else(|| Error::NotFound("Allocation".to_string()))
+    }
+
+    // Member-chosen weights; replaces the current allocation going forward
+    pub async fn set_allocation(
         &self,
         user_id: Uuid,
-        allocation: &AssetAllocation,
-    ) -> Result<()> {
-        sqlx::query!(
+        weights: BTreeMap<Asset, Decimal>,
+    ) -> Result<(AssetAllocation, DateTime<Utc>)> {
+        require_approved_kyc(&self.pool, user_id).await?;
+
+        let allocation =
+            AssetAllocation::new(weights).map_err(|e| Error::InvalidAllocation(e.to_string()))?;
+
+        let mut tx = self.pool.begin().await?;
+
+        let created = sqlx::query!(
             r#"
-            INSERT INTO portfolio_recommendations
-            (user_id, stocks, bonds, real_estate, crypto)
-            VALUES ($1, $2, $3, $4, $5)
+            INSERT INTO portfolio_allocations (user_id)
+            VALUES ($1)
+            RETURNING id, created_at
             "#,
-            user_id,
-            allocation.stocks,
-            allocation.bonds,
-            allocation.real_estate,
-            allocation.crypto,
+            user_id
         )
-        .execute(&self.pool)
+        .fetch_one(&mut *tx)
         .await?;
 
-        Ok(())
+        for (asset, weight) in &allocation.weights {
+            sqlx::query!(
+                r#"
+                INSERT INTO portfolio_allocation_weights (allocation_id, asset, weight)
+                VALUES ($1, $2, $3)
+                "#,
+                created.id,
+                asset.as_str(),
+                weight,
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        Ok((allocation, created.created_at))
     }
-}
\ No newline at end of file
+}
+
+// The member's most recent allocation, if they have one
+pub async fn latest_allocation(pool: &PgPool, user_id: Uuid) -> Result<Option<AssetAllocation>> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT asset, weight
+        FROM portfolio_allocation_weights
+        WHERE allocation_id = (
+            SELECT id FROM portfolio_allocations
+            WHERE user_id = $1
+            ORDER BY created_at DESC
+            LIMIT 1
+        )
+        "#,
+        user_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    if rows.is_empty() {
+        return Ok(None);
+    }
+
+    let mut weights = BTreeMap::new();
+    for row in rows {
+        let asset = row
+            .asset
+            .parse::<Asset>()
+            .map_err(|e| Error::Other(e.into()))?;
+        weights.insert(asset, row.weight);
+    }
+
+    let allocation = AssetAllocation::new(weights).map_err(|e| Error::Other(e.into()))?;
+    Ok(Some(allocation))
+}
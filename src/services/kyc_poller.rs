@@ -0,0 +1,45 @@
+use anyhow::Result;
+use chrono::{Duration, Utc};
+use std::sync::Arc;
+use tokio::task::JoinHandle;
+
+use crate::config::kyc::KycConfig;
+use crate::services::kyc_service::KycService;
+
+// Resolves KYC jobs whose provider callback never arrived by asking the
+// provider for the job status
+pub struct KycPoller {
+    kyc_service: Arc<KycService>,
+    config: KycConfig,
+}
+
+impl KycPoller {
+    pub fn new(kyc_service: Arc<KycService>, config: KycConfig) -> Self {
+        Self {
+            kyc_service,
+            config,
+        }
+    }
+
+    pub fn spawn(self) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(std::time::Duration::from_secs(
+                self.config.poll_interval_secs,
+            ));
+
+            loop {
+                interval.tick().await;
+
+                match self.run_once().await {
+                    Ok(resolved) => tracing::info!("KYC poll resolved {} jobs", resolved),
+                    Err(e) => tracing::error!("KYC poll failed: {}", e),
+                }
+            }
+        })
+    }
+
+    pub async fn run_once(&self) -> Result<usize> {
+        let older_than = Utc::now() - Duration::seconds(self.config.poll_min_age_secs);
+        Ok(self.kyc_service.poll_pending(older_than).await?)
+    }
+}
@@ -0,0 +1,477 @@
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use std::str::FromStr;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::error::{Error, Result};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum KycStatus {
+    Submitted, // Recorded, not yet accepted by the provider
+    Pending,   // Provider is working on the job
+    Approved,
+    Rejected,
+    NeedsReview, // Provider could not decide; an operator has to
+}
+
+impl KycStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            KycStatus::Submitted => "SUBMITTED",
+            KycStatus::Pending => "PENDING",
+            KycStatus::Approved => "APPROVED",
+            KycStatus::Rejected => "REJECTED",
+            KycStatus::NeedsReview => "NEEDS_REVIEW",
+        }
+    }
+
+    // Approved and rejected are terminal: trying again means a new
+    // verification. Providers may answer before we mark a job pending.
+    pub fn can_transition_to(&self, next: KycStatus) -> bool {
+        use KycStatus::*;
+
+        matches!(
+            (self, next),
+            (Submitted, Pending)
+                | (Submitted | Pending, Approved | Rejected | NeedsReview)
+                | (NeedsReview, Approved | Rejected)
+        )
+    }
+}
+
+impl FromStr for KycStatus {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "SUBMITTED" => Ok(KycStatus::Submitted),
+            "PENDING" => Ok(KycStatus::Pending),
+            "APPROVED" => Ok(KycStatus::Approved),
+            "REJECTED" => Ok(KycStatus::Rejected),
+            "NEEDS_REVIEW" => Ok(KycStatus::NeedsReview),
+            _ => Err(anyhow::anyhow!("Unknown KYC status: {}", s)),
+        }
+    }
+}
+
+// How well we know a member, which sets their transaction limits. Fully
+// verified members have also proven they hold the M-Pesa number on file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum KycTier {
+    PhoneOnly,
+    IdVerified,
+    FullyVerified,
+}
+
+impl KycTier {
+    pub fn derive(kyc_status: &str, phone_verified: bool) -> Self {
+        match (kyc_status == KycStatus::Approved.as_str(), phone_verified) {
+            (false, _) => KycTier::PhoneOnly,
+            (true, false) => KycTier::IdVerified,
+            (true, true) => KycTier::FullyVerified,
+        }
+    }
+}
+
+// What the member hands over to be checked against the ID registry
+#[derive(Debug, Clone, Deserialize)]
+pub struct KycSubmission {
+    pub id_type: String, // NATIONAL_ID, PASSPORT, ALIEN_CARD...
+    pub id_number: String,
+    pub country: String, // ISO 3166-1 alpha-2
+    pub first_name: String,
+    pub last_name: String,
+    pub dob: NaiveDate,
+    pub phone_number: Option<String>,
+}
+
+// A provider's verdict on a job
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KycResult {
+    pub status: KycStatus,
+    pub result_code: String,
+    pub result_text: String,
+    pub provider_reference: Option<String>,
+}
+
+// A result pushed to our callback URL
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KycCallback {
+    pub job_id: Uuid,
+    pub result: KycResult,
+}
+
+// An identity verification service. Jobs are asynchronous: results come
+// back through the callback URL or, if that is lost, by polling.
+#[async_trait]
+pub trait KycProvider: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    async fn submit(
+        &self,
+        job_id: Uuid,
+        user_id: Uuid,
+        submission: &KycSubmission,
+    ) -> anyhow::Result<()>;
+
+    // None while the provider is still working on the job
+    async fn job_status(&self, job_id: Uuid, user_id: Uuid) -> anyhow::Result<Option<KycResult>>;
+
+    // Authenticates and decodes a callback body
+    fn parse_callback(&self, body: &[u8]) -> anyhow::Result<KycCallback>;
+}
+
+#[derive(Debug, Serialize)]
+pub struct KycVerification {
+    pub id: Uuid,
+    pub provider: String,
+    pub id_type: String,
+    pub id_number_last4: String,
+    pub status: KycStatus,
+    pub result_text: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+pub struct PendingVerification {
+    pub id: Uuid,
+    pub user_id: Uuid,
+}
+
+#[derive(Clone)]
+pub struct KycService {
+    pool: PgPool,
+    provider: Arc<dyn KycProvider>,
+}
+
+impl KycService {
+    pub fn new(pool: PgPool, provider: Arc<dyn KycProvider>) -> Self {
+        Self { pool, provider }
+    }
+
+    pub async fn submit(
+        &self,
+        user_id: Uuid,
+        submission: &KycSubmission,
+    ) -> Result<KycVerification> {
+        // A verification stuck in SUBMITTED never reached the provider, so
+        // it does not stand in the way of another attempt
+        let active = sqlx::query_scalar!(
+            r#"
+            SELECT EXISTS (
+                SELECT 1 FROM kyc_verifications
+                WHERE user_id = $1 AND status IN ('PENDING', 'NEEDS_REVIEW', 'APPROVED')
+            ) as "active!"
+            "#,
+            user_id
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        if active {
+            return Err(Error::KycAlreadySubmitted);
+        }
+
+        let job_id = Uuid::new_v4();
+        sqlx::query!(
+            r#"
+            INSERT INTO kyc_verifications
+                (id, user_id, provider, id_type, country, id_number_last4, date_of_birth)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            "#,
+            job_id,
+            user_id,
+            self.provider.name(),
+            submission.id_type,
+            submission.country,
+            last4(&submission.id_number),
+            submission.dob,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        self.provider
+            .submit(job_id, user_id, submission)
+            .await
+            .map_err(|e| Error::KycProvider(e.to_string()))?;
+
+        let result = KycResult {
+            status: KycStatus::Pending,
+            result_code: String::new(),
+            result_text: "Submitted for verification".to_string(),
+            provider_reference: None,
+        };
+        self.apply_result(job_id, &result).await?;
+
+        self.get_verification(job_id).await
+    }
+
+    pub async fn latest_verification(&self, user_id: Uuid) -> Result<Option<KycVerification>> {
+        let row = sqlx::query!(
+            r#"
+            SELECT id, provider, id_type, id_number_last4, status, result_text,
+                   created_at, completed_at
+            FROM kyc_verifications
+            WHERE user_id = $1
+            ORDER BY created_at DESC
+            LIMIT 1
+            "#,
+            user_id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(|row| {
+            Ok(KycVerification {
+                id: row.id,
+                provider: row.provider,
+                id_type: row.id_type,
+                id_number_last4: row.id_number_last4,
+                status: row.status.parse()?,
+                result_text: row.result_text,
+                created_at: row.created_at,
+                completed_at: row.completed_at,
+            })
+        })
+        .transpose()
+    }
+
+    // Providers retry callbacks and polls can race them, so a result that
+    // is not a valid transition from the stored state is ignored.
+    pub async fn handle_callback(&self, body: &[u8]) -> Result<()> {
+        let callback = self.provider.parse_callback(body).map_err(|e| {
+            tracing::warn!("Rejected KYC callback: {}", e);
+            Error::Unauthorized
+        })?;
+
+        self.apply_result(callback.job_id, &callback.result).await?;
+        Ok(())
+    }
+
+    // Asks the provider about jobs whose callback has not arrived
+    pub async fn poll_pending(&self, older_than: DateTime<Utc>) -> Result<usize> {
+        let pending = sqlx::query_as!(
+            PendingVerification,
+            r#"
+            SELECT id, user_id FROM kyc_verifications
+            WHERE status = 'PENDING' AND updated_at < $1
+            ORDER BY updated_at
+            "#,
+            older_than
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut resolved = 0;
+        for verification in pending {
+            match self
+                .provider
+                .job_status(verification.id, verification.user_id)
+                .await
+            {
+                Ok(Some(result)) => {
+                    if self.apply_result(verification.id, &result).await? {
+                        resolved += 1;
+                    }
+                }
+                Ok(None) => {}
+                Err(e) => tracing::warn!("KYC job status for {} failed: {}", verification.id, e),
+            }
+        }
+
+        Ok(resolved)
+    }
+
+    // Settles a verification the provider sent to manual review
+    pub async fn resolve_review(&self, job_id: Uuid, approve: bool, note: &str) -> Result<()> {
+        let result = KycResult {
+            status: if approve {
+                KycStatus::Approved
+            } else {
+                KycStatus::Rejected
+            },
+            result_code: "MANUAL_REVIEW".to_string(),
+            result_text: note.to_string(),
+            provider_reference: None,
+        };
+
+        if !self.apply_result(job_id, &result).await? {
+            return Err(Error::NotFound("Verification awaiting review".to_string()));
+        }
+        Ok(())
+    }
+
+    // Returns whether the verification moved
+    async fn apply_result(&self, job_id: Uuid, result: &KycResult) -> Result<bool> {
+        let mut tx = self.pool.begin().await?;
+
+        let current = sqlx::query!(
+            "SELECT user_id, status FROM kyc_verifications WHERE id = $1 FOR UPDATE",
+            job_id
+        )
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| Error::NotFound("KYC verification".to_string()))?;
+
+        let status: KycStatus = current.status.parse()?;
+        if !status.can_transition_to(result.status) {
+            tracing::info!(
+                "Ignoring KYC result {} for {} in state {}",
+                result.status.as_str(),
+                job_id,
+                status.as_str()
+            );
+            return Ok(false);
+        }
+
+        let is_final = matches!(result.status, KycStatus::Approved | KycStatus::Rejected);
+        sqlx::query!(
+            r#"
+            UPDATE kyc_verifications
+            SET status = $2,
+                result_code = NULLIF($3, ''),
+                result_text = $4,
+                provider_reference = COALESCE($5, provider_reference),
+                updated_at = NOW(),
+                completed_at = CASE WHEN $6 THEN NOW() END
+            WHERE id = $1
+            "#,
+            job_id,
+            result.status.as_str(),
+            result.result_code,
+            result.result_text,
+            result.provider_reference,
+            is_final,
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            "UPDATE users SET kyc_status = $2, updated_at = NOW() WHERE id = $1",
+            current.user_id,
+            result.status.as_str()
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    async fn get_verification(&self, job_id: Uuid) -> Result<KycVerification> {
+        let row = sqlx::query!(
+            r#"
+            SELECT id, provider, id_type, id_number_last4, status, result_text,
+                   created_at, completed_at
+            FROM kyc_verifications
+            WHERE id = $1
+            "#,
+            job_id
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(KycVerification {
+            id: row.id,
+            provider: row.provider,
+            id_type: row.id_type,
+            id_number_last4: row.id_number_last4,
+            status: row.status.parse()?,
+            result_text: row.result_text,
+            created_at: row.created_at,
+            completed_at: row.completed_at,
+        })
+    }
+}
+
+// Withdrawals and investments are only open to verified members
+pub async fn require_approved_kyc(pool: &PgPool, user_id: Uuid) -> Result<()> {
+    let status = sqlx::query_scalar!("SELECT kyc_status FROM users WHERE id = $1", user_id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or(Error::Unauthorized)?;
+
+    if status != KycStatus::Approved.as_str() {
+        return Err(Error::KycNotApproved);
+    }
+    Ok(())
+}
+
+pub async fn kyc_tier(pool: &PgPool, user_id: Uuid) -> Result<KycTier> {
+    let user = sqlx::query!(
+        "SELECT kyc_status, phone_verified FROM users WHERE id = $1",
+        user_id
+    )
+    .fetch_optional(pool)
+    .await?
+    .ok_or(Error::Unauthorized)?;
+
+    Ok(KycTier::derive(&user.kyc_status, user.phone_verified))
+}
+
+fn last4(id_number: &str) -> String {
+    let chars: Vec<char> = id_number.trim().chars().collect();
+    chars[chars.len().saturating_sub(4)..].iter().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALL: [KycStatus; 5] = [
+        KycStatus::Submitted,
+        KycStatus::Pending,
+        KycStatus::Approved,
+        KycStatus::Rejected,
+        KycStatus::NeedsReview,
+    ];
+
+    #[test]
+    fn test_status_round_trip() {
+        for status in ALL {
+            assert_eq!(status.as_str().parse::<KycStatus>().unwrap(), status);
+        }
+        assert!("UNVERIFIED".parse::<KycStatus>().is_err());
+    }
+
+    #[test]
+    fn test_status_transitions() {
+        use KycStatus::*;
+
+        assert!(Submitted.can_transition_to(Pending));
+        assert!(Submitted.can_transition_to(Approved));
+        assert!(Pending.can_transition_to(Approved));
+        assert!(Pending.can_transition_to(Rejected));
+        assert!(Pending.can_transition_to(NeedsReview));
+        assert!(NeedsReview.can_transition_to(Approved));
+        assert!(NeedsReview.can_transition_to(Rejected));
+
+        assert!(!Pending.can_transition_to(Pending));
+        assert!(!Pending.can_transition_to(Submitted));
+        assert!(!NeedsReview.can_transition_to(Pending));
+        for next in ALL {
+            assert!(!Approved.can_transition_to(next));
+            assert!(!Rejected.can_transition_to(next));
+        }
+    }
+
+    #[test]
+    fn test_tier_derivation() {
+        assert_eq!(KycTier::derive("UNVERIFIED", true), KycTier::PhoneOnly);
+        assert_eq!(KycTier::derive("NEEDS_REVIEW", true), KycTier::PhoneOnly);
+        assert_eq!(KycTier::derive("APPROVED", false), KycTier::IdVerified);
+        assert_eq!(KycTier::derive("APPROVED", true), KycTier::FullyVerified);
+    }
+
+    #[test]
+    fn test_last4() {
+        assert_eq!(last4("12345678"), "5678");
+        assert_eq!(last4(" 987 "), "987");
+        assert_eq!(last4(""), "");
+    }
+}
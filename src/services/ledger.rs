@@ -0,0 +1,285 @@
+use anyhow::{anyhow, Result};
+use rust_decimal::Decimal;
+use serde::Serialize;
+use sqlx::{PgPool, Postgres, Transaction};
+use uuid::Uuid;
+
+// System accounts shared by every fund
+pub const MPESA_CLEARING: &str = "MPESA_CLEARING";
+pub const WITHDRAWALS_PAYABLE: &str = "WITHDRAWALS_PAYABLE";
+pub const INVESTMENT_HOLDINGS: &str = "INVESTMENT_HOLDINGS";
+pub const FEE_INCOME: &str = "FEE_INCOME";
+pub const LOANS_RECEIVABLE: &str = "LOANS_RECEIVABLE";
+pub const LOAN_INTEREST_INCOME: &str = "LOAN_INTEREST_INCOME";
+pub const EARLY_WITHDRAWAL_PENALTIES: &str = "EARLY_WITHDRAWAL_PENALTIES";
+pub const EMPLOYER_REMITTANCES: &str = "EMPLOYER_REMITTANCES"; // Payroll money received from employers
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccountType {
+    Asset,
+    Liability,
+    Income,
+}
+
+impl AccountType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AccountType::Asset => "ASSET",
+            AccountType::Liability => "LIABILITY",
+            AccountType::Income => "INCOME",
+        }
+    }
+
+    // Postings are stored debit-positive; liability and income accounts
+    // carry credit balances, so their sign is flipped when reporting.
+    pub fn normal_sign(&self) -> Decimal {
+        match self {
+            AccountType::Asset => Decimal::ONE,
+            AccountType::Liability | AccountType::Income => -Decimal::ONE,
+        }
+    }
+
+    fn for_code(code: &str) -> Self {
+        match code {
+            MPESA_CLEARING | INVESTMENT_HOLDINGS | LOANS_RECEIVABLE | EMPLOYER_REMITTANCES => {
+                AccountType::Asset
+            }
+            FEE_INCOME | LOAN_INTEREST_INCOME | EARLY_WITHDRAWAL_PENALTIES => {
+                AccountType::Income
+            }
+            _ => AccountType::Liability,
+        }
+    }
+}
+
+pub fn fund_account_code(fund_id: Uuid) -> String {
+    format!("FUND:{}", fund_id)
+}
+
+#[derive(Debug, Clone)]
+pub struct Posting {
+    pub account_code: String,
+    pub amount: Decimal, // Positive = debit, negative = credit
+}
+
+#[derive(Debug, Clone)]
+pub struct JournalEntry {
+    pub description: String,
+    pub transaction_id: Option<Uuid>,
+    pub postings: Vec<Posting>,
+}
+
+impl JournalEntry {
+    pub fn new(description: &str, transaction_id: Option<Uuid>) -> Self {
+        Self {
+            description: description.to_string(),
+            transaction_id,
+            postings: Vec::new(),
+        }
+    }
+
+    pub fn debit(mut self, account_code: &str, amount: Decimal) -> Self {
+        self.postings.push(Posting {
+            account_code: account_code.to_string(),
+            amount,
+        });
+        self
+    }
+
+    pub fn credit(mut self, account_code: &str, amount: Decimal) -> Self {
+        self.postings.push(Posting {
+            account_code: account_code.to_string(),
+            amount: -amount,
+        });
+        self
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if self.postings.len() < 2 {
+            return Err(anyhow!("Journal entry needs at least two postings"));
+        }
+
+        if self.postings.iter().any(|p| p.amount.is_zero()) {
+            return Err(anyhow!("Journal entry contains a zero posting"));
+        }
+
+        let net: Decimal = self.postings.iter().map(|p| p.amount).sum();
+        if !net.is_zero() {
+            return Err(anyhow!("Journal entry does not balance (net {})", net));
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct BalanceDiscrepancy {
+    pub fund_id: Uuid,
+    pub stored_balance: Decimal,
+    pub ledger_balance: Decimal,
+}
+
+pub struct LedgerService {
+    pool: PgPool,
+}
+
+impl LedgerService {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    // Posts the entry inside the caller's transaction so the journal and
+    // whatever else the caller writes commit or roll back together.
+    pub async fn post(tx: &mut Transaction<'_, Postgres>, entry: &JournalEntry) -> Result<Uuid> {
+        entry.validate()?;
+
+        let entry_id = Uuid::new_v4();
+        sqlx::query!(
+            r#"
+            INSERT INTO journal_entries (id, description, transaction_id)
+            VALUES ($1, $2, $3)
+            "#,
+            entry_id,
+            entry.description,
+            entry.transaction_id,
+        )
+        .execute(&mut **tx)
+        .await?;
+
+        for posting in &entry.postings {
+            let account_id = Self::ensure_account(tx, &posting.account_code).await?;
+
+            sqlx::query!(
+                r#"
+                INSERT INTO ledger_postings (id, entry_id, account_id, amount)
+                VALUES ($1, $2, $3, $4)
+                "#,
+                Uuid::new_v4(),
+                entry_id,
+                account_id,
+                posting.amount,
+            )
+            .execute(&mut **tx)
+            .await?;
+        }
+
+        Ok(entry_id)
+    }
+
+    async fn ensure_account(tx: &mut Transaction<'_, Postgres>, code: &str) -> Result<Uuid> {
+        let fund_id = code
+            .strip_prefix("FUND:")
+            .map(Uuid::parse_str)
+            .transpose()?;
+
+        let account = sqlx::query!(
+            r#"
+            INSERT INTO ledger_accounts (id, code, account_type, fund_id)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (code) DO UPDATE SET code = EXCLUDED.code
+            RETURNING id
+            "#,
+            Uuid::new_v4(),
+            code,
+            AccountType::for_code(code).as_str(),
+            fund_id,
+        )
+        .fetch_one(&mut **tx)
+        .await?;
+
+        Ok(account.id)
+    }
+
+    pub async fn account_balance(&self, code: &str) -> Result<Decimal> {
+        let total = sqlx::query!(
+            r#"
+            SELECT COALESCE(SUM(p.amount), 0) as total
+            FROM ledger_postings p
+            JOIN ledger_accounts a ON a.id = p.account_id
+            WHERE a.code = $1
+            "#,
+            code
+        )
+        .fetch_one(&self.pool)
+        .await?
+        .total
+        .unwrap_or_default();
+
+        Ok(total * AccountType::for_code(code).normal_sign())
+    }
+
+    pub async fn fund_balance(&self, fund_id: Uuid) -> Result<Decimal> {
+        self.account_balance(&fund_account_code(fund_id)).await
+    }
+
+    // Flags every fund whose stored balance disagrees with its journal
+    pub async fn check_consistency(&self) -> Result<Vec<BalanceDiscrepancy>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT
+                f.id as fund_id,
+                f.balance as stored_balance,
+                -COALESCE(SUM(p.amount), 0) as "ledger_balance!"
+            FROM pension_funds f
+            LEFT JOIN ledger_accounts a ON a.fund_id = f.id
+            LEFT JOIN ledger_postings p ON p.account_id = a.id
+            GROUP BY f.id, f.balance
+            HAVING f.balance <> -COALESCE(SUM(p.amount), 0)
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| BalanceDiscrepancy {
+                fund_id: row.fund_id,
+                stored_balance: row.stored_balance,
+                ledger_balance: row.ledger_balance,
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_balanced_entry_validates() {
+        let fund = fund_account_code(Uuid::new_v4());
+        let entry = JournalEntry::new("Deposit", None)
+            .debit(MPESA_CLEARING, Decimal::new(50000, 2))
+            .credit(&fund, Decimal::new(50000, 2));
+
+        assert!(entry.validate().is_ok());
+    }
+
+    #[test]
+    fn test_unbalanced_entry_rejected() {
+        let fund = fund_account_code(Uuid::new_v4());
+        let entry = JournalEntry::new("Deposit", None)
+            .debit(MPESA_CLEARING, Decimal::new(50000, 2))
+            .credit(&fund, Decimal::new(49999, 2));
+
+        assert!(entry.validate().is_err());
+    }
+
+    #[test]
+    fn test_single_posting_rejected() {
+        let entry = JournalEntry::new("Fee", None).debit(FEE_INCOME, Decimal::ONE);
+        assert!(entry.validate().is_err());
+    }
+
+    #[test]
+    fn test_fund_accounts_are_liabilities() {
+        let fund = fund_account_code(Uuid::new_v4());
+        assert_eq!(AccountType::for_code(&fund), AccountType::Liability);
+        assert_eq!(AccountType::for_code(MPESA_CLEARING), AccountType::Asset);
+        assert_eq!(AccountType::for_code(FEE_INCOME), AccountType::Income);
+        assert_eq!(
+            AccountType::for_code(EARLY_WITHDRAWAL_PENALTIES),
+            AccountType::Income
+        );
+    }
+}
@@ -0,0 +1,83 @@
+use anyhow::Result;
+use std::sync::Arc;
+use tokio::task::JoinHandle;
+
+use crate::services::loan_service::LoanService;
+
+#[derive(Debug, Default)]
+pub struct LoanMonitorReport {
+    pub accrued: usize,
+    pub overdue: u64,
+    pub reminders_sent: usize,
+    pub liquidated: usize,
+}
+
+// Accrues interest, prompts members for installments that have fallen due
+// and liquidates the collateral of loans that have defaulted, finishing any
+// liquidation an earlier run was interrupted in
+pub struct LoanMonitor {
+    loan_service: Arc<LoanService>,
+}
+
+impl LoanMonitor {
+    pub fn new(loan_service: Arc<LoanService>) -> Self {
+        Self { loan_service }
+    }
+
+    pub fn spawn(self) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(std::time::Duration::from_secs(
+                self.loan_service.config().monitor_interval_secs,
+            ));
+
+            loop {
+                interval.tick().await;
+
+                match self.run_once().await {
+                    Ok(report) => tracing::info!("Loan monitor finished: {:?}", report),
+                    Err(e) => tracing::error!("Loan monitor failed: {}", e),
+                }
+            }
+        })
+    }
+
+    pub async fn run_once(&self) -> Result<LoanMonitorReport> {
+        let mut report = LoanMonitorReport {
+            accrued: self.loan_service.accrue_all().await?,
+            overdue: self.loan_service.mark_overdue_installments().await?,
+            reminders_sent: self.loan_service.send_due_repayment_requests().await?,
+            ..Default::default()
+        };
+
+        // Liquidations interrupted on an earlier tick are finished first
+        for loan_id in self.loan_service.get_seizing_loans().await? {
+            match self.loan_service.resume_liquidation(loan_id).await {
+                Ok(seized) => {
+                    tracing::warn!(
+                        "Finished liquidating {} BPT of collateral for loan {}",
+                        seized,
+                        loan_id
+                    );
+                    report.liquidated += 1;
+                }
+                Err(e) => tracing::error!("Liquidation of loan {} is unfinished: {}", loan_id, e),
+            }
+        }
+
+        for loan_id in self.loan_service.get_defaulted_loans().await? {
+            match self.loan_service.liquidate(loan_id).await {
+                Ok(seized) => {
+                    tracing::warn!(
+                        "Liquidated {} BPT of collateral for loan {}",
+                        seized,
+                        loan_id
+                    );
+                    report.liquidated += 1;
+                }
+                Err(e) => tracing::error!("Liquidation of loan {} failed: {}", loan_id, e),
+            }
+        }
+
+        Ok(report)
+    }
+}
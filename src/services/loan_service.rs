@@ -0,0 +1,1223 @@
+use chrono::{DateTime, Months, Utc};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::{Decimal, RoundingStrategy};
+use serde::Serialize;
+use sqlx::{PgPool, Postgres, Transaction};
+use uuid::Uuid;
+
+use crate::ai::investment_strategy::{AssetAllocation, RiskTolerance};
+use crate::config::loans::LoanConfig;
+use crate::error::{Error, Result};
+use crate::services::bpt_manager::BPTManager;
+use crate::services::investment_service::latest_allocation;
+use crate::services::ledger::{
+    fund_account_code, JournalEntry, LedgerService, LOANS_RECEIVABLE, LOAN_INTEREST_INCOME,
+    MPESA_CLEARING,
+};
+use crate::services::mpesa_service::{MPesaService, PaymentDetails};
+
+const SECONDS_PER_YEAR: i64 = 365 * 24 * 60 * 60;
+
+#[derive(Debug, Serialize)]
+pub struct Loan {
+    pub id: Uuid,
+    pub principal: Decimal,
+    pub annual_interest_rate: Decimal,
+    pub loan_to_value: Decimal,
+    pub term_months: i32,
+    pub outstanding_principal: Decimal,
+    pub accrued_interest: Decimal,
+    pub status: String,
+    pub disbursed_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LoanInstallment {
+    pub seq: i32,
+    pub due_date: DateTime<Utc>,
+    pub principal_due: Decimal,
+    pub interest_due: Decimal,
+    pub amount_paid: Decimal,
+    pub status: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RepaymentRequest {
+    pub transaction_id: Uuid,
+    pub checkout_request_id: String,
+    pub amount: Decimal,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScheduledInstallment {
+    pub seq: i32,
+    pub due_date: DateTime<Utc>,
+    pub principal: Decimal,
+    pub interest: Decimal,
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct PaymentAllocation {
+    pub interest: Decimal,
+    pub principal: Decimal,
+    pub excess: Decimal,
+}
+
+// Micro-loans drawn against BPT locked as collateral. The loan is paid out
+// by B2C, repaid by STK push and, on default, settled from the collateral.
+#[derive(Clone)]
+pub struct LoanService {
+    pool: PgPool,
+    bpt_manager: BPTManager,
+    mpesa_service: MPesaService,
+    config: LoanConfig,
+}
+
+impl LoanService {
+    pub fn new(
+        pool: PgPool,
+        bpt_manager: BPTManager,
+        mpesa_service: MPesaService,
+        config: LoanConfig,
+    ) -> Self {
+        Self {
+            pool,
+            bpt_manager,
+            mpesa_service,
+            config,
+        }
+    }
+
+    pub fn config(&self) -> &LoanConfig {
+        &self.config
+    }
+
+    pub async fn request_loan(
+        &self,
+        user_id: Uuid,
+        amount: Decimal,
+        term_months: u32,
+        phone_number: &str,
+    ) -> Result<Loan> {
+        if amount < self.config.min_amount || amount > self.config.max_amount {
+            return Err(Error::LoanNotEligible(format!(
+                "Loan amount must be between {} and {}",
+                self.config.min_amount, self.config.max_amount
+            )));
+        }
+        // Disbursed over M-Pesa, which pays whole shillings only
+        if !amount.fract().is_zero() {
+            return Err(Error::LoanNotEligible(
+                "Loan amount must be in whole shillings".to_string(),
+            ));
+        }
+        if term_months == 0 || term_months > self.config.max_term_months {
+            return Err(Error::LoanNotEligible(format!(
+                "Loan term must be between 1 and {} months",
+                self.config.max_term_months
+            )));
+        }
+
+        let open_loans = sqlx::query!(
+            r#"
+            SELECT COUNT(*) as "count!" FROM loans
+            WHERE user_id = $1 AND status IN ('DISBURSING', 'ACTIVE', 'SEIZING')
+            "#,
+            user_id
+        )
+        .fetch_one(&self.pool)
+        .await?
+        .count;
+        if open_loans > 0 {
+            return Err(Error::LoanNotEligible(
+                "Repay your current loan before taking another".to_string(),
+            ));
+        }
+
+        let ltv = self.member_loan_to_value(user_id).await?;
+        let collateral = required_collateral(amount, ltv);
+        let bpt = self.bpt_manager.get_user_bpt_balance(user_id).await?;
+        if bpt.balance - bpt.locked < collateral {
+            return Err(Error::LoanNotEligible(format!(
+                "This loan needs {} BPT of free collateral",
+                collateral
+            )));
+        }
+
+        let pledge = self
+            .bpt_manager
+            .pledge_collateral(user_id, collateral)
+            .await?;
+
+        let (loan, transaction_id) = match self
+            .create_loan(user_id, pledge.id, amount, ltv, term_months, phone_number)
+            .await
+        {
+            Ok(created) => created,
+            Err(e) => {
+                self.bpt_manager.release_collateral(pledge.id).await?;
+                return Err(e);
+            }
+        };
+
+        // The loan becomes active only once the B2C result confirms payout
+        match self
+            .mpesa_service
+            .initiate_b2c_payment(phone_number, amount, &format!("LOAN{}", loan.id))
+            .await
+        {
+            Ok(b2c_response) => {
+                sqlx::query!(
+                    "UPDATE transactions SET conversation_id = $1 WHERE id = $2",
+                    b2c_response.originator_conversation_id,
+                    transaction_id,
+                )
+                .execute(&self.pool)
+                .await?;
+            }
+            Err(e) => {
+                self.fail_disbursement(transaction_id, "M-Pesa payout request failed")
+                    .await?;
+                return Err(Error::MPesa(e.to_string()));
+            }
+        }
+
+        Ok(loan)
+    }
+
+    async fn create_loan(
+        &self,
+        user_id: Uuid,
+        collateral_id: Uuid,
+        amount: Decimal,
+        ltv: Decimal,
+        term_months: u32,
+        phone_number: &str,
+    ) -> Result<(Loan, Uuid)> {
+        let loan_id = Uuid::new_v4();
+        let transaction_id = Uuid::new_v4();
+        let now = Utc::now();
+        let mut tx = self.pool.begin().await?;
+
+        let fund_id = sqlx::query!("SELECT id FROM pension_funds WHERE user_id = $1", user_id)
+            .fetch_one(&mut *tx)
+            .await?
+            .id;
+
+        sqlx::query!(
+            r#"
+            INSERT INTO loans (
+                id, user_id, fund_id, collateral_id, principal, annual_interest_rate,
+                loan_to_value, term_months, outstanding_principal, status, created_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $5, 'DISBURSING', $9)
+            "#,
+            loan_id,
+            user_id,
+            fund_id,
+            collateral_id,
+            amount,
+            self.config.annual_interest_rate,
+            ltv,
+            term_months as i32,
+            now,
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        let schedule =
+            amortization_schedule(amount, self.config.annual_interest_rate, term_months, now);
+        for installment in &schedule {
+            sqlx::query!(
+                r#"
+                INSERT INTO loan_installments (
+                    id, loan_id, seq, due_date, principal_due, interest_due
+                )
+                VALUES ($1, $2, $3, $4, $5, $6)
+                "#,
+                Uuid::new_v4(),
+                loan_id,
+                installment.seq,
+                installment.due_date,
+                installment.principal,
+                installment.interest,
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        sqlx::query!(
+            r#"
+            INSERT INTO transactions (
+                id, fund_id, user_id, loan_id, transaction_type, amount, status, phone_number
+            )
+            VALUES ($1, $2, $3, $4, 'LOAN_DISBURSEMENT', $5, 'PENDING', $6)
+            "#,
+            transaction_id,
+            fund_id,
+            user_id,
+            loan_id,
+            amount,
+            phone_number,
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        let loan = Loan {
+            id: loan_id,
+            principal: amount,
+            annual_interest_rate: self.config.annual_interest_rate,
+            loan_to_value: ltv,
+            term_months: term_months as i32,
+            outstanding_principal: amount,
+            accrued_interest: Decimal::ZERO,
+            status: "DISBURSING".to_string(),
+            disbursed_at: None,
+            created_at: now,
+        };
+        Ok((loan, transaction_id))
+    }
+
+    pub async fn find_disbursement_by_conversation(
+        &self,
+        originator_conversation_id: &str,
+    ) -> Result<Option<Uuid>> {
+        let transaction = sqlx::query!(
+            r#"
+            SELECT id
+            FROM transactions
+            WHERE conversation_id = $1 AND transaction_type = 'LOAN_DISBURSEMENT'
+            "#,
+            originator_conversation_id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(transaction.map(|t| t.id))
+    }
+
+    // Like withdrawals, only a PENDING disbursement is transitioned so
+    // repeated B2C results are harmless
+    pub async fn complete_disbursement(
+        &self,
+        transaction_id: Uuid,
+        mpesa_reference: &str,
+    ) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        let transaction = sqlx::query!(
+            r#"
+            UPDATE transactions
+            SET status = 'COMPLETED',
+                completed_at = CURRENT_TIMESTAMP,
+                mpesa_reference = $1
+            WHERE id = $2 AND status = 'PENDING'
+            RETURNING loan_id as "loan_id!", amount
+            "#,
+            mpesa_reference,
+            transaction_id,
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let Some(transaction) = transaction else {
+            tracing::info!("Loan disbursement {} already settled", transaction_id);
+            return Ok(());
+        };
+
+        // Interest runs from the moment the member actually has the money
+        sqlx::query!(
+            r#"
+            UPDATE loans
+            SET status = 'ACTIVE', disbursed_at = NOW(), last_accrued_at = NOW()
+            WHERE id = $1 AND status = 'DISBURSING'
+            "#,
+            transaction.loan_id,
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        let entry = JournalEntry::new("Loan disbursement", Some(transaction_id))
+            .debit(LOANS_RECEIVABLE, transaction.amount)
+            .credit(MPESA_CLEARING, transaction.amount);
+        LedgerService::post(&mut tx, &entry).await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    pub async fn fail_disbursement(&self, transaction_id: Uuid, reason: &str) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        let transaction = sqlx::query!(
+            r#"
+            UPDATE transactions
+            SET status = 'FAILED', failure_reason = $1
+            WHERE id = $2 AND status = 'PENDING'
+            RETURNING loan_id as "loan_id!"
+            "#,
+            reason,
+            transaction_id,
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let Some(transaction) = transaction else {
+            tracing::info!("Loan disbursement {} already settled", transaction_id);
+            return Ok(());
+        };
+
+        let collateral_id = sqlx::query!(
+            r#"
+            UPDATE loans
+            SET status = 'CANCELLED', closed_at = NOW()
+            WHERE id = $1 AND status = 'DISBURSING'
+            RETURNING collateral_id
+            "#,
+            transaction.loan_id,
+        )
+        .fetch_one(&mut *tx)
+        .await?
+        .collateral_id;
+
+        tx.commit().await?;
+
+        self.bpt_manager.release_collateral(collateral_id).await
+    }
+
+    // Sends an STK push for the next amount due, or a custom amount
+    pub async fn request_repayment(
+        &self,
+        user_id: Uuid,
+        loan_id: Uuid,
+        phone_number: &str,
+        amount: Option<Decimal>,
+    ) -> Result<RepaymentRequest> {
+        let loan = self.get_loan(user_id, loan_id).await?;
+        if loan.status != "ACTIVE" {
+            return Err(Error::LoanNotEligible("Loan is not active".to_string()));
+        }
+
+        let owed = loan.outstanding_principal + loan.accrued_interest;
+        let amount = match amount {
+            Some(amount) if amount <= Decimal::ZERO => return Err(Error::InvalidAmount),
+            Some(amount) => amount,
+            None => self.next_amount_due(loan_id).await?.min(owed),
+        };
+        // M-Pesa only takes whole shillings
+        let amount = whole_shillings(amount.min(owed));
+
+        let stk_response = self
+            .mpesa_service
+            .initiate_payment(
+                phone_number,
+                amount.to_f64().unwrap_or_default(),
+                &format!("LOAN{}", loan_id),
+            )
+            .await
+            .map_err(|e| Error::MPesa(e.to_string()))?;
+
+        let transaction_id = Uuid::new_v4();
+        sqlx::query!(
+            r#"
+            INSERT INTO transactions (
+                id, fund_id, user_id, loan_id, transaction_type, amount, status,
+                phone_number, checkout_request_id
+            )
+            SELECT $1, fund_id, user_id, id, 'LOAN_REPAYMENT', $2, 'PENDING', $3, $4
+            FROM loans
+            WHERE id = $5
+            "#,
+            transaction_id,
+            amount,
+            phone_number,
+            stk_response.checkout_request_id,
+            loan_id,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(RepaymentRequest {
+            transaction_id,
+            checkout_request_id: stk_response.checkout_request_id,
+            amount,
+        })
+    }
+
+    async fn next_amount_due(&self, loan_id: Uuid) -> Result<Decimal> {
+        let installment = sqlx::query!(
+            r#"
+            SELECT principal_due + interest_due - amount_paid as "remaining!"
+            FROM loan_installments
+            WHERE loan_id = $1 AND status <> 'PAID'
+            ORDER BY seq
+            LIMIT 1
+            "#,
+            loan_id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(installment.map(|i| i.remaining).unwrap_or_default())
+    }
+
+    pub async fn is_repayment(&self, checkout_request_id: &str) -> Result<bool> {
+        let repayment = sqlx::query!(
+            r#"
+            SELECT id FROM transactions
+            WHERE checkout_request_id = $1 AND transaction_type = 'LOAN_REPAYMENT'
+            "#,
+            checkout_request_id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(repayment.is_some())
+    }
+
+    // Applies a confirmed repayment: interest first, then principal, then
+    // any overpayment is credited to the member's pension fund
+    pub async fn complete_repayment(
+        &self,
+        checkout_request_id: &str,
+        payment: &PaymentDetails,
+    ) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        let transaction = sqlx::query!(
+            r#"
+            UPDATE transactions
+            SET status = 'COMPLETED',
+                amount = $1,
+                mpesa_reference = $2,
+                completed_at = CURRENT_TIMESTAMP
+            WHERE checkout_request_id = $3
+            AND transaction_type = 'LOAN_REPAYMENT'
+            AND status = 'PENDING'
+            RETURNING id, loan_id as "loan_id!", amount
+            "#,
+            payment.amount,
+            payment.mpesa_receipt_number,
+            checkout_request_id,
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let Some(transaction) = transaction else {
+            tracing::info!("Ignoring duplicate repayment for {}", checkout_request_id);
+            return Ok(());
+        };
+
+        let (allocation, fund_id, collateral_id) =
+            Self::apply_to_loan(&mut tx, transaction.loan_id, transaction.amount, "ACTIVE").await?;
+
+        let mut entry = JournalEntry::new("Loan repayment", Some(transaction.id))
+            .debit(MPESA_CLEARING, transaction.amount);
+        entry = allocation_postings(entry, &allocation);
+        if allocation.excess > Decimal::ZERO {
+            sqlx::query!(
+                "UPDATE pension_funds SET balance = balance + $1 WHERE id = $2",
+                allocation.excess,
+                fund_id,
+            )
+            .execute(&mut *tx)
+            .await?;
+            entry = entry.credit(&fund_account_code(fund_id), allocation.excess);
+        }
+        LedgerService::post(&mut tx, &entry).await?;
+
+        tx.commit().await?;
+
+        if let Some(collateral_id) = collateral_id {
+            self.bpt_manager.release_collateral(collateral_id).await?;
+        }
+        Ok(())
+    }
+
+    pub async fn fail_repayment(&self, checkout_request_id: &str, reason: &str) -> Result<()> {
+        sqlx::query!(
+            r#"
+            UPDATE transactions
+            SET status = 'FAILED', failure_reason = $1
+            WHERE checkout_request_id = $2
+            AND transaction_type = 'LOAN_REPAYMENT'
+            AND status = 'PENDING'
+            "#,
+            reason,
+            checkout_request_id,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    // Brings interest up to date, applies the payment to the loan and its
+    // installments and closes the loan once nothing is owed. Returns the
+    // collateral to release when the loan was repaid in full.
+    async fn apply_to_loan(
+        tx: &mut Transaction<'_, Postgres>,
+        loan_id: Uuid,
+        amount: Decimal,
+        closing_status: &str,
+    ) -> Result<(PaymentAllocation, Uuid, Option<Uuid>)> {
+        let loan = sqlx::query!(
+            r#"
+            SELECT fund_id, collateral_id, annual_interest_rate, outstanding_principal,
+                   accrued_interest, last_accrued_at
+            FROM loans
+            WHERE id = $1
+            FOR UPDATE
+            "#,
+            loan_id
+        )
+        .fetch_one(&mut **tx)
+        .await?;
+
+        let now = Utc::now();
+        let accrued = loan.accrued_interest
+            + loan
+                .last_accrued_at
+                .map(|since| {
+                    accrue_interest(
+                        loan.outstanding_principal,
+                        loan.annual_interest_rate,
+                        since,
+                        now,
+                    )
+                })
+                .unwrap_or_default();
+
+        let allocation = allocate_payment(accrued, loan.outstanding_principal, amount);
+        let outstanding = loan.outstanding_principal - allocation.principal;
+        let interest = accrued - allocation.interest;
+        let settled = outstanding.is_zero() && interest.is_zero();
+
+        sqlx::query!(
+            r#"
+            UPDATE loans
+            SET outstanding_principal = $1,
+                accrued_interest = $2,
+                last_accrued_at = $3,
+                status = CASE WHEN $4 THEN 'REPAID' ELSE $5 END,
+                closed_at = CASE WHEN $4 THEN NOW() ELSE closed_at END
+            WHERE id = $6
+            "#,
+            outstanding,
+            interest,
+            now,
+            settled,
+            closing_status,
+            loan_id,
+        )
+        .execute(&mut **tx)
+        .await?;
+
+        // Work through the schedule oldest installment first
+        let mut remaining = allocation.interest + allocation.principal;
+        let installments = sqlx::query!(
+            r#"
+            SELECT id, principal_due + interest_due - amount_paid as "owed!"
+            FROM loan_installments
+            WHERE loan_id = $1 AND status <> 'PAID'
+            ORDER BY seq
+            "#,
+            loan_id
+        )
+        .fetch_all(&mut **tx)
+        .await?;
+
+        for installment in installments {
+            if remaining <= Decimal::ZERO && !settled {
+                break;
+            }
+            let paid = remaining.min(installment.owed).max(Decimal::ZERO);
+            remaining -= paid;
+
+            sqlx::query!(
+                r#"
+                UPDATE loan_installments
+                SET amount_paid = amount_paid + $1,
+                    status = CASE WHEN $2 OR amount_paid + $1 >= principal_due + interest_due
+                             THEN 'PAID' ELSE status END,
+                    paid_at = CASE WHEN $2 OR amount_paid + $1 >= principal_due + interest_due
+                              THEN NOW() ELSE paid_at END
+                WHERE id = $3
+                "#,
+                paid,
+                settled,
+                installment.id,
+            )
+            .execute(&mut **tx)
+            .await?;
+        }
+
+        let release = settled.then_some(loan.collateral_id);
+        Ok((allocation, loan.fund_id, release))
+    }
+
+    // Brings every active loan's interest up to date
+    pub async fn accrue_all(&self) -> Result<usize> {
+        let now = Utc::now();
+        let loans = sqlx::query!(
+            r#"
+            SELECT id, outstanding_principal, annual_interest_rate,
+                   last_accrued_at as "last_accrued_at!"
+            FROM loans
+            WHERE status = 'ACTIVE' AND last_accrued_at IS NOT NULL
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut accrued = 0;
+        for loan in loans {
+            let interest = accrue_interest(
+                loan.outstanding_principal,
+                loan.annual_interest_rate,
+                loan.last_accrued_at,
+                now,
+            );
+
+            // Skip the loan if a repayment moved it on since it was read
+            let updated = sqlx::query!(
+                r#"
+                UPDATE loans
+                SET accrued_interest = accrued_interest + $1, last_accrued_at = $2
+                WHERE id = $3 AND last_accrued_at = $4
+                "#,
+                interest,
+                now,
+                loan.id,
+                loan.last_accrued_at,
+            )
+            .execute(&self.pool)
+            .await?
+            .rows_affected();
+            accrued += updated as usize;
+        }
+
+        Ok(accrued)
+    }
+
+    pub async fn mark_overdue_installments(&self) -> Result<u64> {
+        let updated = sqlx::query!(
+            r#"
+            UPDATE loan_installments
+            SET status = 'OVERDUE'
+            WHERE status = 'DUE' AND due_date < NOW()
+            "#
+        )
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+
+        Ok(updated)
+    }
+
+    // Prompts members by STK push for installments that have fallen due,
+    // at most once a day per installment
+    pub async fn send_due_repayment_requests(&self) -> Result<usize> {
+        let due = sqlx::query!(
+            r#"
+            SELECT i.id, l.id as loan_id, l.user_id, u.phone_number as "phone_number!"
+            FROM loan_installments i
+            JOIN loans l ON l.id = i.loan_id
+            JOIN users u ON u.id = l.user_id
+            WHERE l.status = 'ACTIVE'
+            AND i.status IN ('DUE', 'OVERDUE')
+            AND i.due_date <= NOW()
+            AND (i.last_requested_at IS NULL OR i.last_requested_at < NOW() - INTERVAL '1 day')
+            AND u.phone_number IS NOT NULL
+            ORDER BY i.due_date
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut sent = 0;
+        for installment in due {
+            match self
+                .request_repayment(
+                    installment.user_id,
+                    installment.loan_id,
+                    &installment.phone_number,
+                    None,
+                )
+                .await
+            {
+                Ok(_) => sent += 1,
+                Err(e) => tracing::warn!(
+                    "Repayment request for loan {} failed: {}",
+                    installment.loan_id,
+                    e
+                ),
+            }
+
+            sqlx::query!(
+                "UPDATE loan_installments SET last_requested_at = NOW() WHERE id = $1",
+                installment.id
+            )
+            .execute(&self.pool)
+            .await?;
+        }
+
+        Ok(sent)
+    }
+
+    pub async fn get_defaulted_loans(&self) -> Result<Vec<Uuid>> {
+        let loans = sqlx::query!(
+            r#"
+            SELECT DISTINCT l.id
+            FROM loans l
+            JOIN loan_installments i ON i.loan_id = l.id
+            WHERE l.status = 'ACTIVE'
+            AND i.status = 'OVERDUE'
+            AND i.due_date < NOW() - make_interval(days => $1)
+            "#,
+            self.config.default_after_days as i32
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(loans.into_iter().map(|l| l.id).collect())
+    }
+
+    // Loans whose collateral seizure was interrupted
+    pub async fn get_seizing_loans(&self) -> Result<Vec<Uuid>> {
+        let loans = sqlx::query_scalar!("SELECT id FROM loans WHERE status = 'SEIZING'")
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(loans)
+    }
+
+    // Settles a defaulted loan from its collateral. The seized BPT leaves
+    // the member's tokenized savings, so the fund balance and tokenized
+    // balance drop by the same amount as the on-chain supply. The loan is
+    // committed as SEIZING, with the amount to seize, before anything
+    // happens on chain.
+    pub async fn liquidate(&self, loan_id: Uuid) -> Result<Decimal> {
+        let mut tx = self.pool.begin().await?;
+        let loan = sqlx::query!(
+            r#"
+            SELECT l.outstanding_principal, l.accrued_interest, l.annual_interest_rate,
+                   l.last_accrued_at, c.amount as pledged
+            FROM loans l
+            JOIN bpt_collateral c ON c.id = l.collateral_id
+            WHERE l.id = $1 AND l.status = 'ACTIVE'
+            FOR UPDATE OF l
+            "#,
+            loan_id
+        )
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| Error::NotFound("Active loan".to_string()))?;
+
+        let accrued = loan.accrued_interest
+            + loan
+                .last_accrued_at
+                .map(|since| {
+                    accrue_interest(
+                        loan.outstanding_principal,
+                        loan.annual_interest_rate,
+                        since,
+                        Utc::now(),
+                    )
+                })
+                .unwrap_or_default();
+        let owed = (loan.outstanding_principal + accrued)
+            .round_dp_with_strategy(2, RoundingStrategy::ToZero);
+
+        sqlx::query!(
+            "UPDATE loans SET status = 'SEIZING', seizure_amount = $2 WHERE id = $1",
+            loan_id,
+            owed.min(loan.pledged),
+        )
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        self.resume_liquidation(loan_id).await
+    }
+
+    // Carries a SEIZING loan through from wherever its pledge got to: a
+    // pledge still PLEDGED has not been clawed back (or the clawback was
+    // rolled back) and is seized now; a SEIZED one only needs booking. A
+    // pledge left SEIZING may or may not have moved on chain and is left
+    // for an operator to reconcile.
+    pub async fn resume_liquidation(&self, loan_id: Uuid) -> Result<Decimal> {
+        let loan = sqlx::query!(
+            r#"
+            SELECT l.collateral_id, l.seizure_amount as "seizure_amount!", c.status as pledge_status
+            FROM loans l
+            JOIN bpt_collateral c ON c.id = l.collateral_id
+            WHERE l.id = $1 AND l.status = 'SEIZING'
+            "#,
+            loan_id
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| Error::NotFound("Loan being liquidated".to_string()))?;
+
+        match loan.pledge_status.as_str() {
+            "PLEDGED" => {
+                self.bpt_manager
+                    .seize_collateral(loan.collateral_id, loan.seizure_amount)
+                    .await?;
+            }
+            "SEIZED" => {}
+            status => {
+                return Err(Error::Other(anyhow::anyhow!(
+                    "Collateral {} for loan {} is {}; check the clawback on chain",
+                    loan.collateral_id,
+                    loan_id,
+                    status
+                )))
+            }
+        }
+
+        self.book_liquidation(loan_id).await
+    }
+
+    // Records a seizure that has happened on chain against the member's fund
+    // and the loan. Only a SEIZING loan is booked, so this runs once.
+    async fn book_liquidation(&self, loan_id: Uuid) -> Result<Decimal> {
+        let mut tx = self.pool.begin().await?;
+        let loan = sqlx::query!(
+            r#"
+            SELECT user_id, fund_id, seizure_amount as "seizure_amount!"
+            FROM loans
+            WHERE id = $1 AND status = 'SEIZING'
+            FOR UPDATE
+            "#,
+            loan_id
+        )
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| Error::NotFound("Loan being liquidated".to_string()))?;
+        let seized = loan.seizure_amount;
+
+        let transaction_id = Uuid::new_v4();
+        sqlx::query!(
+            r#"
+            INSERT INTO transactions (
+                id, fund_id, user_id, loan_id, transaction_type, amount, status, completed_at
+            )
+            VALUES ($1, $2, $3, $4, 'LOAN_LIQUIDATION', $5, 'COMPLETED', CURRENT_TIMESTAMP)
+            "#,
+            transaction_id,
+            loan.fund_id,
+            loan.user_id,
+            loan_id,
+            seized,
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            UPDATE pension_funds
+            SET balance = balance - $1, tokenized_balance = tokenized_balance - $1
+            WHERE id = $2
+            "#,
+            seized,
+            loan.fund_id,
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        let (allocation, _, _) =
+            Self::apply_to_loan(&mut tx, loan_id, seized, "LIQUIDATED").await?;
+
+        let entry = JournalEntry::new("Loan collateral liquidation", Some(transaction_id))
+            .debit(&fund_account_code(loan.fund_id), seized);
+        LedgerService::post(&mut tx, &allocation_postings(entry, &allocation)).await?;
+
+        // Whatever the collateral did not cover stays on the loan record
+        sqlx::query!(
+            r#"
+            UPDATE loans SET status = 'LIQUIDATED', closed_at = NOW()
+            WHERE id = $1
+            "#,
+            loan_id,
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(seized)
+    }
+
+    pub async fn get_loan(&self, user_id: Uuid, loan_id: Uuid) -> Result<Loan> {
+        sqlx::query_as!(
+            Loan,
+            r#"
+            SELECT id, principal, annual_interest_rate, loan_to_value, term_months,
+                   outstanding_principal, accrued_interest, status, disbursed_at,
+                   created_at as "created_at!"
+            FROM loans
+            WHERE id = $1 AND user_id = $2
+            "#,
+            loan_id,
+            user_id,
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| Error::NotFound("Loan".to_string()))
+    }
+
+    pub async fn get_user_loans(&self, user_id: Uuid) -> Result<Vec<Loan>> {
+        let loans = sqlx::query_as!(
+            Loan,
+            r#"
+            SELECT id, principal, annual_interest_rate, loan_to_value, term_months,
+                   outstanding_principal, accrued_interest, status, disbursed_at,
+                   created_at as "created_at!"
+            FROM loans
+            WHERE user_id = $1
+            ORDER BY created_at DESC
+            "#,
+            user_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(loans)
+    }
+
+    pub async fn get_schedule(&self, user_id: Uuid, loan_id: Uuid) -> Result<Vec<LoanInstallment>> {
+        // Ownership check
+        self.get_loan(user_id, loan_id).await?;
+
+        let installments = sqlx::query_as!(
+            LoanInstallment,
+            r#"
+            SELECT seq, due_date, principal_due, interest_due, amount_paid, status
+            FROM loan_installments
+            WHERE loan_id = $1
+            ORDER BY seq
+            "#,
+            loan_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(installments)
+    }
+
+    // Derives the advance rate from the member's latest allocation;
+    // members without one are treated as the default moderate plan
+    async fn member_loan_to_value(&self, user_id: Uuid) -> Result<Decimal> {
+        let allocation = latest_allocation(&self.pool, user_id)
+            .await?
+            .unwrap_or_else(|| AssetAllocation::from_risk_tolerance(&RiskTolerance::Moderate));
+
+        Ok(loan_to_value(
+            allocation.defensive_weight(),
+            allocation.growth_weight(),
+            &self.config,
+        ))
+    }
+}
+
+fn allocation_postings(entry: JournalEntry, allocation: &PaymentAllocation) -> JournalEntry {
+    let mut entry = entry;
+    if allocation.interest > Decimal::ZERO {
+        entry = entry.credit(LOAN_INTEREST_INCOME, allocation.interest);
+    }
+    if allocation.principal > Decimal::ZERO {
+        entry = entry.credit(LOANS_RECEIVABLE, allocation.principal);
+    }
+    entry
+}
+
+// Blends the stablecoin and growth advance rates by the member's split;
+// every defensive asset borrows at the stablecoin rate
+pub fn loan_to_value(stablecoin_pct: Decimal, growth_pct: Decimal, config: &LoanConfig) -> Decimal {
+    let total = stablecoin_pct + growth_pct;
+    if total <= Decimal::ZERO {
+        return config.growth_ltv;
+    }
+
+    ((stablecoin_pct * config.stablecoin_ltv + growth_pct * config.growth_ltv) / total).round_dp(4)
+}
+
+pub fn required_collateral(amount: Decimal, ltv: Decimal) -> Decimal {
+    (amount / ltv).round_dp_with_strategy(2, RoundingStrategy::AwayFromZero)
+}
+
+fn whole_shillings(amount: Decimal) -> Decimal {
+    amount.round_dp_with_strategy(0, RoundingStrategy::AwayFromZero)
+}
+
+// Simple interest on the outstanding principal for the elapsed time
+pub fn accrue_interest(
+    outstanding: Decimal,
+    annual_rate: Decimal,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Decimal {
+    let seconds = (to - from).num_seconds();
+    if seconds <= 0 || outstanding <= Decimal::ZERO {
+        return Decimal::ZERO;
+    }
+
+    (outstanding * annual_rate * Decimal::from(seconds) / Decimal::from(SECONDS_PER_YEAR))
+        .round_dp(8)
+}
+
+pub fn allocate_payment(
+    accrued_interest: Decimal,
+    outstanding_principal: Decimal,
+    payment: Decimal,
+) -> PaymentAllocation {
+    let interest = payment.min(accrued_interest);
+    let principal = (payment - interest).min(outstanding_principal);
+
+    PaymentAllocation {
+        interest,
+        principal,
+        excess: payment - interest - principal,
+    }
+}
+
+// Equal monthly installments; the last one absorbs rounding
+pub fn amortization_schedule(
+    principal: Decimal,
+    annual_rate: Decimal,
+    term_months: u32,
+    start: DateTime<Utc>,
+) -> Vec<ScheduledInstallment> {
+    let monthly_rate = annual_rate / Decimal::from(12);
+    let payment = if monthly_rate.is_zero() {
+        principal / Decimal::from(term_months)
+    } else {
+        let growth =
+            (0..term_months).fold(Decimal::ONE, |acc, _| acc * (Decimal::ONE + monthly_rate));
+        principal * monthly_rate * growth / (growth - Decimal::ONE)
+    }
+    .round_dp(2);
+
+    let mut balance = principal;
+    (1..=term_months)
+        .map(|seq| {
+            let interest = (balance * monthly_rate).round_dp(2);
+            let principal_part = if seq == term_months {
+                balance
+            } else {
+                (payment - interest).min(balance)
+            };
+            balance -= principal_part;
+
+            ScheduledInstallment {
+                seq: seq as i32,
+                due_date: start + Months::new(seq),
+                principal: principal_part,
+                interest,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+
+    #[test]
+    fn test_ltv_follows_allocation() {
+        let config = LoanConfig::default();
+
+        assert_eq!(
+            loan_to_value(Decimal::new(100, 0), Decimal::ZERO, &config),
+            Decimal::new(70, 2)
+        );
+        assert_eq!(
+            loan_to_value(Decimal::ZERO, Decimal::new(100, 0), &config),
+            Decimal::new(40, 2)
+        );
+        assert_eq!(
+            loan_to_value(Decimal::new(50, 0), Decimal::new(50, 0), &config),
+            Decimal::new(55, 2)
+        );
+    }
+
+    #[test]
+    fn test_collateral_rounds_up() {
+        // 1,000 at 55% LTV needs 1,818.1818... BPT
+        assert_eq!(
+            required_collateral(Decimal::new(1000, 0), Decimal::new(55, 2)),
+            Decimal::new(181819, 2)
+        );
+    }
+
+    #[test]
+    fn test_schedule_repays_principal() {
+        let start = Utc.with_ymd_and_hms(2025, 1, 15, 0, 0, 0).unwrap();
+        let schedule =
+            amortization_schedule(Decimal::new(12_000, 0), Decimal::new(12, 2), 12, start);
+
+        assert_eq!(schedule.len(), 12);
+        let repaid: Decimal = schedule.iter().map(|i| i.principal).sum();
+        assert_eq!(repaid, Decimal::new(12_000, 0));
+
+        // Standard 1%/month amortization gives 1,066.19 a month
+        let first = &schedule[0];
+        assert_eq!(first.interest, Decimal::new(12000, 2));
+        assert_eq!(first.principal + first.interest, Decimal::new(106619, 2));
+        assert_eq!(
+            first.due_date,
+            Utc.with_ymd_and_hms(2025, 2, 15, 0, 0, 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_zero_rate_schedule_splits_evenly() {
+        let schedule = amortization_schedule(Decimal::new(900, 0), Decimal::ZERO, 3, Utc::now());
+
+        assert!(schedule
+            .iter()
+            .all(|i| i.principal == Decimal::new(300, 0) && i.interest.is_zero()));
+    }
+
+    #[test]
+    fn test_interest_accrues_for_elapsed_time() {
+        let from = Utc::now();
+        let interest = accrue_interest(
+            Decimal::new(36_500, 0),
+            Decimal::new(10, 2),
+            from,
+            from + Duration::days(1),
+        );
+
+        assert_eq!(interest, Decimal::new(10, 0));
+    }
+
+    #[test]
+    fn test_payment_covers_interest_first() {
+        let allocation = allocate_payment(
+            Decimal::new(50, 0),
+            Decimal::new(1000, 0),
+            Decimal::new(300, 0),
+        );
+        assert_eq!(
+            allocation,
+            PaymentAllocation {
+                interest: Decimal::new(50, 0),
+                principal: Decimal::new(250, 0),
+                excess: Decimal::ZERO,
+            }
+        );
+
+        let overpaid = allocate_payment(
+            Decimal::new(5, 0),
+            Decimal::new(100, 0),
+            Decimal::new(120, 0),
+        );
+        assert_eq!(overpaid.excess, Decimal::new(15, 0));
+    }
+}
@@ -0,0 +1,131 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use uuid::Uuid;
+
+use crate::services::kyc_service::{KycCallback, KycProvider, KycResult, KycStatus, KycSubmission};
+
+// Stand-in provider whose verdict depends only on the ID number, so tests
+// can pick the outcome: numbers ending in 0 are rejected, in 9 need review,
+// anything else is approved. Results are available to `job_status` as soon
+// as the job is submitted.
+#[derive(Default)]
+pub struct MockKycProvider {
+    jobs: Mutex<HashMap<Uuid, KycResult>>,
+}
+
+impl MockKycProvider {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn submitted_jobs(&self) -> usize {
+        self.jobs.lock().unwrap().len()
+    }
+
+    // The callback body the provider would send for a submitted job
+    pub fn callback_body(&self, job_id: Uuid) -> Result<Vec<u8>> {
+        let result = self
+            .jobs
+            .lock()
+            .unwrap()
+            .get(&job_id)
+            .cloned()
+            .ok_or_else(|| anyhow!("Unknown job {}", job_id))?;
+
+        Ok(serde_json::to_vec(&KycCallback { job_id, result })?)
+    }
+}
+
+pub fn mock_result(id_number: &str) -> KycResult {
+    let (status, result_code, result_text) = match id_number.trim().chars().last() {
+        Some('0') => (KycStatus::Rejected, "1013", "ID number not found"),
+        Some('9') => (KycStatus::NeedsReview, "1012", "Names partially match"),
+        _ => (KycStatus::Approved, "1012", "Valid ID number"),
+    };
+
+    KycResult {
+        status,
+        result_code: result_code.to_string(),
+        result_text: result_text.to_string(),
+        provider_reference: Some(format!("mock-{}", id_number.trim())),
+    }
+}
+
+#[async_trait]
+impl KycProvider for MockKycProvider {
+    fn name(&self) -> &'static str {
+        "MOCK"
+    }
+
+    async fn submit(&self, job_id: Uuid, _user_id: Uuid, submission: &KycSubmission) -> Result<()> {
+        self.jobs
+            .lock()
+            .unwrap()
+            .insert(job_id, mock_result(&submission.id_number));
+        Ok(())
+    }
+
+    async fn job_status(&self, job_id: Uuid, _user_id: Uuid) -> Result<Option<KycResult>> {
+        Ok(self.jobs.lock().unwrap().get(&job_id).cloned())
+    }
+
+    fn parse_callback(&self, body: &[u8]) -> Result<KycCallback> {
+        Ok(serde_json::from_slice(body)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn submission(id_number: &str) -> KycSubmission {
+        KycSubmission {
+            id_type: "NATIONAL_ID".to_string(),
+            id_number: id_number.to_string(),
+            country: "KE".to_string(),
+            first_name: "Wanjiku".to_string(),
+            last_name: "Kamau".to_string(),
+            dob: NaiveDate::from_ymd_opt(1990, 5, 17).unwrap(),
+            phone_number: None,
+        }
+    }
+
+    #[test]
+    fn test_mock_result_is_deterministic() {
+        assert_eq!(mock_result("12345678").status, KycStatus::Approved);
+        assert_eq!(mock_result("12345670").status, KycStatus::Rejected);
+        assert_eq!(mock_result("12345679").status, KycStatus::NeedsReview);
+        assert_eq!(mock_result("12345678"), mock_result("12345678"));
+    }
+
+    #[tokio::test]
+    async fn test_submit_then_poll_and_callback() {
+        let provider = MockKycProvider::new();
+        let job_id = Uuid::new_v4();
+
+        assert_eq!(
+            provider.job_status(job_id, Uuid::nil()).await.unwrap(),
+            None
+        );
+
+        provider
+            .submit(job_id, Uuid::nil(), &submission("12345670"))
+            .await
+            .unwrap();
+        let polled = provider
+            .job_status(job_id, Uuid::nil())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(polled.status, KycStatus::Rejected);
+
+        let callback = provider
+            .parse_callback(&provider.callback_body(job_id).unwrap())
+            .unwrap();
+        assert_eq!(callback.job_id, job_id);
+        assert_eq!(callback.result, polled);
+    }
+}
@@ -5,6 +5,36 @@ pub mod blockchain_service;
 pub mod stellar;
 pub mod bpt_manager;
 pub mod smile_id;
+pub mod kyc_service;
+pub mod kyc_poller;
+pub mod ledger;
+pub mod fund_service;
+pub mod user_service;
+pub mod price_feed;
+pub mod price_history;
+pub mod price_ingestor;
+pub mod mpesa_service;
+pub mod mpesa_token;
+pub mod notification_service;
+pub mod phone_auth_service;
+pub mod ussd_service;
+pub mod deposit_sweeper;
+pub mod loan_service;
+pub mod loan_monitor;
+pub mod withdrawal_policy;
+pub mod contribution_service;
+pub mod contribution_scheduler;
+pub mod employer_service;
+pub mod rebalancing;
+pub mod paper_trading;
+pub mod auto_rebalancer;
+
+#[cfg(any(test, feature = "testutils"))]
+pub mod daraja_simulator;
+#[cfg(any(test, feature = "testutils"))]
+pub mod ussd_simulator;
+#[cfg(any(test, feature = "testutils"))]
+pub mod mock_kyc_provider;
 
 pub use auth_service::AuthService;
 pub use investment_service::InvestmentService;
@@ -12,4 +42,20 @@ pub use transaction_service::TransactionService;
 pub use blockchain_service::BlockchainService;
 pub use stellar::StellarService;
 pub use bpt_manager::BPTManager;
-pub use smile_id::SmileIDClient; 
\ No newline at end of file
+pub use smile_id::SmileIDClient;
+pub use kyc_service::KycService;
+pub use kyc_poller::KycPoller;
+pub use ledger::LedgerService;
+pub use deposit_sweeper::DepositSweeper;
+pub use loan_service::LoanService;
+pub use loan_monitor::LoanMonitor;
+pub use contribution_service::ContributionService;
+pub use contribution_scheduler::ContributionScheduler;
+pub use employer_service::EmployerService;
+pub use rebalancing::RebalancingService;
+pub use paper_trading::PaperTradingVenue;
+pub use auto_rebalancer::AutoRebalancer;
+pub use price_history::PriceHistoryService;
+pub use price_ingestor::PriceIngestor;
+pub use phone_auth_service::PhoneAuthService;
+pub use ussd_service::UssdService;
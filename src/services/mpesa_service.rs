@@ -1,18 +1,76 @@
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
-use chrono::Utc;
-use reqwest::Client;
+use chrono::{NaiveDateTime, Utc};
+use reqwest::{Client, StatusCode, Url};
+use rust_decimal::Decimal;
+use rsa::{pkcs8::DecodePublicKey, Pkcs1v15Encrypt, RsaPublicKey};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use std::env;
+use std::fs;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+use x509_cert::der::{DecodePem, Encode};
+use x509_cert::Certificate;
 
-#[derive(Debug)]
+use crate::error::Error;
+use crate::services::mpesa_token::AccessTokenCache;
+
+pub const DARAJA_SANDBOX_URL: &str = "https://sandbox.safaricom.co.ke";
+
+// Daraja's errorCode for an access token that has expired or been revoked
+const INVALID_TOKEN_ERROR_CODE: &str = "404.001.04";
+
+#[derive(Debug, Clone)]
+pub struct MPesaConfig {
+    pub base_url: String,
+    pub consumer_key: String,
+    pub consumer_secret: String,
+    pub business_shortcode: String,
+    pub passkey: String,
+    pub callback_url: String,
+    pub initiator_name: String,
+    pub security_credential: String,
+    pub b2c_result_url: String,
+    pub b2c_timeout_url: String,
+    pub callback_token: String,
+}
+
+impl MPesaConfig {
+    pub fn from_env() -> Result<Self> {
+        Ok(Self {
+            base_url: env::var("MPESA_BASE_URL").unwrap_or_else(|_| DARAJA_SANDBOX_URL.to_string()),
+            consumer_key: env::var("MPESA_CONSUMER_KEY")?,
+            consumer_secret: env::var("MPESA_CONSUMER_SECRET")?,
+            business_shortcode: env::var("MPESA_BUSINESS_SHORTCODE")?,
+            passkey: env::var("MPESA_PASSKEY")?,
+            callback_url: env::var("MPESA_CALLBACK_URL")?,
+            initiator_name: env::var("MPESA_INITIATOR_NAME")?,
+            security_credential: load_security_credential()?,
+            b2c_result_url: env::var("MPESA_B2C_RESULT_URL")?,
+            b2c_timeout_url: env::var("MPESA_B2C_TIMEOUT_URL")?,
+            callback_token: env::var("MPESA_CALLBACK_TOKEN")?,
+        })
+    }
+}
+
+// Cloning shares the HTTP client and the cached access token
+#[derive(Clone)]
 pub struct MPesaService {
     client: Client,
+    token_cache: Arc<AccessTokenCache>,
+    base_url: String,
     consumer_key: String,
     consumer_secret: String,
     business_shortcode: String,
     passkey: String,
     callback_url: String,
+    initiator_name: String,
+    security_credential: String,
+    b2c_result_url: String,
+    b2c_timeout_url: String,
+    callback_token: String,
 }
 
 #[derive(Serialize)]
@@ -32,23 +90,166 @@ struct STKPushRequest {
 
 #[derive(Deserialize)]
 pub struct STKPushResponse {
+    #[serde(rename = "MerchantRequestID")]
     pub merchant_request_id: String,
+    #[serde(rename = "CheckoutRequestID")]
     pub checkout_request_id: String,
+    #[serde(rename = "ResponseCode")]
     pub response_code: String,
+    #[serde(rename = "ResponseDescription")]
     pub response_description: String,
+    #[serde(rename = "CustomerMessage")]
     pub customer_message: String,
 }
 
+#[derive(Serialize)]
+struct STKQueryRequest {
+    BusinessShortCode: String,
+    Password: String,
+    Timestamp: String,
+    CheckoutRequestID: String,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum STKQueryStatus {
+    Completed,
+    Failed { result_code: String, reason: String },
+    Pending,
+}
+
+// Daraja reports a request that is still being processed as an errorCode
+const STK_QUERY_PROCESSING: &str = "500.001.1001";
+
+impl STKQueryStatus {
+    fn from_response(response: &Value) -> Result<Self> {
+        if let Some(error_code) = response["errorCode"].as_str() {
+            if error_code == STK_QUERY_PROCESSING {
+                return Ok(STKQueryStatus::Pending);
+            }
+
+            return Err(anyhow!(
+                "STK query failed: {} {}",
+                error_code,
+                response["errorMessage"].as_str().unwrap_or_default()
+            ));
+        }
+
+        let result_code = response
+            .get("ResultCode")
+            .map(value_to_string)
+            .ok_or_else(|| anyhow!("STK query response missing ResultCode"))?;
+
+        if result_code == "0" {
+            Ok(STKQueryStatus::Completed)
+        } else {
+            Ok(STKQueryStatus::Failed {
+                result_code,
+                reason: response["ResultDesc"].as_str().unwrap_or_default().to_string(),
+            })
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct B2CRequest {
+    InitiatorName: String,
+    SecurityCredential: String,
+    CommandID: String,
+    Amount: String,
+    PartyA: String,
+    PartyB: String,
+    Remarks: String,
+    QueueTimeOutURL: String,
+    ResultURL: String,
+    Occasion: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct B2CResponse {
+    #[serde(rename = "ConversationID")]
+    pub conversation_id: String,
+    #[serde(rename = "OriginatorConversationID")]
+    pub originator_conversation_id: String,
+    #[serde(rename = "ResponseCode")]
+    pub response_code: String,
+    #[serde(rename = "ResponseDescription")]
+    pub response_description: String,
+}
+
+// Encrypts the initiator password with Safaricom's public certificate
+pub fn generate_security_credential(initiator_password: &str, cert_pem: &str) -> Result<String> {
+    let cert = Certificate::from_pem(cert_pem.as_bytes())?;
+    let public_key_der = cert.tbs_certificate.subject_public_key_info.to_der()?;
+    let public_key = RsaPublicKey::from_public_key_der(&public_key_der)?;
+
+    let encrypted = public_key.encrypt(
+        &mut rsa::rand_core::OsRng,
+        Pkcs1v15Encrypt,
+        initiator_password.as_bytes(),
+    )?;
+
+    Ok(BASE64.encode(encrypted))
+}
+
+fn with_callback_token(url: &str, token: &str) -> String {
+    match Url::parse(url) {
+        Ok(mut url) => {
+            url.query_pairs_mut().append_pair("token", token);
+            url.into()
+        }
+        // Left for Daraja to reject rather than failing at startup
+        Err(_) => format!("{}?token={}", url, token),
+    }
+}
+
+fn is_invalid_token(status: StatusCode, response: &Value) -> bool {
+    status == StatusCode::UNAUTHORIZED
+        || response["errorCode"].as_str() == Some(INVALID_TOKEN_ERROR_CODE)
+}
+
+pub(crate) fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+// A credential pre-generated on the Daraja portal takes precedence
+fn load_security_credential() -> Result<String> {
+    if let Ok(credential) = env::var("MPESA_SECURITY_CREDENTIAL") {
+        return Ok(credential);
+    }
+
+    let cert_pem = fs::read_to_string(env::var("MPESA_CERT_PATH")?)?;
+    generate_security_credential(&env::var("MPESA_INITIATOR_PASSWORD")?, &cert_pem)
+}
+
 impl MPesaService {
     pub fn new() -> Result<Self> {
-        Ok(Self {
+        Ok(Self::from_config(MPesaConfig::from_env()?))
+    }
+
+    // Daraja posts callbacks unsigned, so every callback URL carries the
+    // shared token and the callback routes reject requests without it
+    pub fn from_config(config: MPesaConfig) -> Self {
+        let token = &config.callback_token;
+
+        Self {
             client: Client::new(),
-            consumer_key: env::var("MPESA_CONSUMER_KEY")?,
-            consumer_secret: env::var("MPESA_CONSUMER_SECRET")?,
-            business_shortcode: env::var("MPESA_BUSINESS_SHORTCODE")?,
-            passkey: env::var("MPESA_PASSKEY")?,
-            callback_url: env::var("MPESA_CALLBACK_URL")?,
-        })
+            token_cache: Arc::new(AccessTokenCache::default()),
+            base_url: config.base_url.trim_end_matches('/').to_string(),
+            consumer_key: config.consumer_key,
+            consumer_secret: config.consumer_secret,
+            business_shortcode: config.business_shortcode,
+            passkey: config.passkey,
+            callback_url: with_callback_token(&config.callback_url, token),
+            initiator_name: config.initiator_name,
+            security_credential: config.security_credential,
+            b2c_result_url: with_callback_token(&config.b2c_result_url, token),
+            b2c_timeout_url: with_callback_token(&config.b2c_timeout_url, token),
+            callback_token: config.callback_token,
+        }
+    }
+
+    pub fn verify_callback_token(&self, token: &str) -> bool {
+        constant_time_eq(self.callback_token.as_bytes(), token.as_bytes())
     }
 
     pub async fn initiate_payment(
@@ -57,11 +258,7 @@ impl MPesaService {
         amount: f64,
         account_reference: &str,
     ) -> Result<STKPushResponse> {
-        let timestamp = Utc::now().format("%Y%m%d%H%M%S").to_string();
-        let password = BASE64.encode(format!(
-            "{}{}{}",
-            self.business_shortcode, self.passkey, timestamp
-        ));
+        let (timestamp, password) = self.stk_password();
 
         let request = STKPushRequest {
             BusinessShortCode: self.business_shortcode.clone(),
@@ -77,39 +274,426 @@ impl MPesaService {
             TransactionDesc: "Pension Fund Deposit".to_string(),
         };
 
+        let response = self
+            .post_authorized("/mpesa/stkpush/v1/processrequest", &request)
+            .await?;
+
+        Ok(serde_json::from_value(response)?)
+    }
+
+    pub async fn query_stk_status(&self, checkout_request_id: &str) -> Result<STKQueryStatus> {
+        let (timestamp, password) = self.stk_password();
+
+        let request = STKQueryRequest {
+            BusinessShortCode: self.business_shortcode.clone(),
+            Password: password,
+            Timestamp: timestamp,
+            CheckoutRequestID: checkout_request_id.to_string(),
+        };
+
+        let response = self
+            .post_authorized("/mpesa/stkpushquery/v1/query", &request)
+            .await?;
+
+        STKQueryStatus::from_response(&response)
+    }
+
+    pub async fn initiate_b2c_payment(
+        &self,
+        phone_number: &str,
+        amount: Decimal,
+        remarks: &str,
+    ) -> Result<B2CResponse> {
+        // Payouts are rounded by whoever decides them, never here
+        if amount <= Decimal::ZERO || !amount.fract().is_zero() {
+            return Err(Error::InvalidAmount.into());
+        }
+
+        let request = B2CRequest {
+            InitiatorName: self.initiator_name.clone(),
+            SecurityCredential: self.security_credential.clone(),
+            CommandID: "BusinessPayment".to_string(),
+            Amount: amount.normalize().to_string(),
+            PartyA: self.business_shortcode.clone(),
+            PartyB: phone_number.to_string(),
+            Remarks: remarks.to_string(),
+            QueueTimeOutURL: self.b2c_timeout_url.clone(),
+            ResultURL: self.b2c_result_url.clone(),
+            Occasion: "Pension Withdrawal".to_string(),
+        };
+
+        let response = self
+            .post_authorized("/mpesa/b2c/v1/paymentrequest", &request)
+            .await?;
+
+        if let Some(error_code) = response["errorCode"].as_str() {
+            return Err(anyhow!(
+                "B2C request rejected: {} {}",
+                error_code,
+                response["errorMessage"].as_str().unwrap_or_default()
+            ));
+        }
+
+        Ok(serde_json::from_value(response)?)
+    }
+
+    fn stk_password(&self) -> (String, String) {
+        let timestamp = Utc::now().format("%Y%m%d%H%M%S").to_string();
+        let password = BASE64.encode(format!(
+            "{}{}{}",
+            self.business_shortcode, self.passkey, timestamp
+        ));
+
+        (timestamp, password)
+    }
+
+    // Posts to Daraja with the cached access token. A token Daraja has
+    // already revoked is dropped and the request retried once with a new one.
+    async fn post_authorized<T: Serialize>(&self, path: &str, request: &T) -> Result<Value> {
+        let (status, response) = self.post_with_token(path, request).await?;
+        if !is_invalid_token(status, &response) {
+            return Ok(response);
+        }
+
+        self.token_cache.invalidate().await;
+        let (_, response) = self.post_with_token(path, request).await?;
+        Ok(response)
+    }
+
+    async fn post_with_token<T: Serialize>(
+        &self,
+        path: &str,
+        request: &T,
+    ) -> Result<(StatusCode, Value)> {
         let access_token = self.get_access_token().await?;
 
         let response = self
             .client
-            .post("https://sandbox.safaricom.co.ke/mpesa/stkpush/v1/processrequest")
+            .post(format!("{}{}", self.base_url, path))
             .bearer_auth(access_token)
-            .json(&request)
+            .json(request)
             .send()
-            .await?
-            .json::<STKPushResponse>()
             .await?;
+        let status = response.status();
 
-        Ok(response)
+        Ok((status, response.json().await?))
+    }
+
+    async fn get_access_token(&self) -> crate::error::Result<String> {
+        self.token_cache
+            .get_or_refresh(|| self.fetch_access_token())
+            .await
     }
 
-    async fn get_access_token(&self) -> Result<String> {
+    async fn fetch_access_token(&self) -> crate::error::Result<(String, Duration)> {
         let auth = BASE64.encode(format!(
             "{}:{}",
             self.consumer_key, self.consumer_secret
         ));
 
-        let response: serde_json::Value = self
+        let response = self
             .client
-            .get("https://sandbox.safaricom.co.ke/oauth/v1/generate?grant_type=client_credentials")
+            .get(format!(
+                "{}/oauth/v1/generate?grant_type=client_credentials",
+                self.base_url
+            ))
             .header("Authorization", format!("Basic {}", auth))
             .send()
-            .await?
-            .json()
-            .await?;
+            .await
+            .map_err(|e| Error::MPesa(format!("Token request failed: {}", e)))?;
+
+        let status = response.status();
+        // Daraja answers a bad key/secret pair with an empty 400 body
+        let body: Value = response.json().await.unwrap_or_default();
 
-        Ok(response["access_token"]
+        if !status.is_success() {
+            return Err(Error::MPesa(format!(
+                "Token endpoint rejected credentials ({}): {}",
+                status,
+                body["errorMessage"].as_str().unwrap_or("no error message")
+            )));
+        }
+
+        let access_token = body["access_token"]
             .as_str()
-            .unwrap_or_default()
-            .to_string())
+            .filter(|token| !token.is_empty())
+            .ok_or_else(|| Error::MPesa("Token response missing access_token".to_string()))?;
+
+        // expires_in arrives as a string ("3599"); default to Daraja's one hour
+        let expires_in = body
+            .get("expires_in")
+            .map(value_to_string)
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(3599);
+
+        Ok((access_token.to_string(), Duration::from_secs(expires_in)))
     }
-} 
\ No newline at end of file
+}
+
+// Daraja wraps every STK callback as {"Body": {"stkCallback": {...}}}
+#[derive(Debug, Deserialize)]
+pub struct STKCallbackEnvelope {
+    #[serde(rename = "Body")]
+    pub body: STKCallbackBody,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct STKCallbackBody {
+    #[serde(rename = "stkCallback")]
+    pub stk_callback: STKCallback,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct STKCallback {
+    #[serde(rename = "MerchantRequestID")]
+    pub merchant_request_id: String,
+    #[serde(rename = "CheckoutRequestID")]
+    pub checkout_request_id: String,
+    #[serde(rename = "ResultCode")]
+    pub result_code: i32,
+    #[serde(rename = "ResultDesc")]
+    pub result_desc: String,
+    #[serde(rename = "CallbackMetadata")]
+    pub callback_metadata: Option<CallbackMetadata>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CallbackMetadata {
+    #[serde(rename = "Item")]
+    pub items: Vec<CallbackItem>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CallbackItem {
+    #[serde(rename = "Name")]
+    pub name: String,
+    #[serde(rename = "Value")]
+    pub value: Option<Value>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PaymentDetails {
+    pub amount: Decimal,
+    pub mpesa_receipt_number: String,
+    pub transaction_date: NaiveDateTime,
+    pub phone_number: String,
+}
+
+impl STKCallback {
+    pub fn is_successful(&self) -> bool {
+        self.result_code == 0
+    }
+
+    fn item(&self, name: &str) -> Option<&Value> {
+        self.callback_metadata
+            .as_ref()?
+            .items
+            .iter()
+            .find(|item| item.name == name)?
+            .value
+            .as_ref()
+    }
+
+    // Only successful callbacks carry metadata; failed ones return None
+    pub fn payment_details(&self) -> Result<Option<PaymentDetails>> {
+        if !self.is_successful() {
+            return Ok(None);
+        }
+
+        let amount = self
+            .item("Amount")
+            .map(value_to_string)
+            .ok_or_else(|| anyhow!("Callback missing Amount"))?;
+        let receipt = self
+            .item("MpesaReceiptNumber")
+            .map(value_to_string)
+            .ok_or_else(|| anyhow!("Callback missing MpesaReceiptNumber"))?;
+        let transaction_date = self
+            .item("TransactionDate")
+            .map(value_to_string)
+            .ok_or_else(|| anyhow!("Callback missing TransactionDate"))?;
+        let phone_number = self
+            .item("PhoneNumber")
+            .map(value_to_string)
+            .ok_or_else(|| anyhow!("Callback missing PhoneNumber"))?;
+
+        Ok(Some(PaymentDetails {
+            amount: Decimal::from_str(&amount)?,
+            mpesa_receipt_number: receipt,
+            transaction_date: NaiveDateTime::parse_from_str(&transaction_date, "%Y%m%d%H%M%S")?,
+            phone_number,
+        }))
+    }
+}
+
+// Daraja sends numbers for Amount/PhoneNumber/TransactionDate and strings elsewhere
+fn value_to_string(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+// Daraja posts B2C results and queue timeouts as {"Result": {...}}
+#[derive(Debug, Deserialize)]
+pub struct B2CResultEnvelope {
+    #[serde(rename = "Result")]
+    pub result: B2CResult,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct B2CResult {
+    #[serde(rename = "ResultCode")]
+    pub result_code: i32,
+    #[serde(rename = "ResultDesc")]
+    pub result_desc: String,
+    #[serde(rename = "OriginatorConversationID")]
+    pub originator_conversation_id: String,
+    #[serde(rename = "ConversationID")]
+    pub conversation_id: String,
+    #[serde(rename = "TransactionID")]
+    pub transaction_id: Option<String>,
+}
+
+impl B2CResult {
+    pub fn is_successful(&self) -> bool {
+        self.result_code == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_successful_callback() {
+        let body = r#"{
+            "Body": {
+                "stkCallback": {
+                    "MerchantRequestID": "29115-34620561-1",
+                    "CheckoutRequestID": "ws_CO_191220191020363925",
+                    "ResultCode": 0,
+                    "ResultDesc": "The service request is processed successfully.",
+                    "CallbackMetadata": {
+                        "Item": [
+                            { "Name": "Amount", "Value": 1.00 },
+                            { "Name": "MpesaReceiptNumber", "Value": "NLJ7RT61SV" },
+                            { "Name": "Balance" },
+                            { "Name": "TransactionDate", "Value": 20191219102115 },
+                            { "Name": "PhoneNumber", "Value": 254708374149 }
+                        ]
+                    }
+                }
+            }
+        }"#;
+
+        let envelope: STKCallbackEnvelope = serde_json::from_str(body).unwrap();
+        let callback = envelope.body.stk_callback;
+        let details = callback.payment_details().unwrap().unwrap();
+
+        assert_eq!(callback.checkout_request_id, "ws_CO_191220191020363925");
+        assert_eq!(details.amount, Decimal::ONE);
+        assert_eq!(details.mpesa_receipt_number, "NLJ7RT61SV");
+        assert_eq!(details.phone_number, "254708374149");
+        assert_eq!(
+            details.transaction_date.format("%Y-%m-%d %H:%M:%S").to_string(),
+            "2019-12-19 10:21:15"
+        );
+    }
+
+    #[test]
+    fn test_callback_urls_carry_the_token() {
+        assert_eq!(
+            with_callback_token("https://api.example.com/api/deposit/callback", "s3cret"),
+            "https://api.example.com/api/deposit/callback?token=s3cret"
+        );
+        assert_eq!(
+            with_callback_token("https://api.example.com/cb?env=prod", "a&b"),
+            "https://api.example.com/cb?env=prod&token=a%26b"
+        );
+        assert!(constant_time_eq(b"s3cret", b"s3cret"));
+        assert!(!constant_time_eq(b"s3cret", b"s3cre"));
+        assert!(!constant_time_eq(b"s3cret", b"s3creT"));
+    }
+
+    #[test]
+    fn test_stk_query_status() {
+        let completed = serde_json::json!({
+            "ResponseCode": "0",
+            "CheckoutRequestID": "ws_CO_191220191020363925",
+            "ResultCode": "0",
+            "ResultDesc": "The service request is processed successfully."
+        });
+        let cancelled = serde_json::json!({
+            "ResponseCode": "0",
+            "ResultCode": "1032",
+            "ResultDesc": "Request cancelled by user"
+        });
+        let processing = serde_json::json!({
+            "errorCode": "500.001.1001",
+            "errorMessage": "The transaction is being processed"
+        });
+
+        assert_eq!(
+            STKQueryStatus::from_response(&completed).unwrap(),
+            STKQueryStatus::Completed
+        );
+        assert_eq!(
+            STKQueryStatus::from_response(&cancelled).unwrap(),
+            STKQueryStatus::Failed {
+                result_code: "1032".to_string(),
+                reason: "Request cancelled by user".to_string(),
+            }
+        );
+        assert_eq!(
+            STKQueryStatus::from_response(&processing).unwrap(),
+            STKQueryStatus::Pending
+        );
+    }
+
+    #[test]
+    fn test_parse_b2c_result() {
+        let body = r#"{
+            "Result": {
+                "ResultType": 0,
+                "ResultCode": 0,
+                "ResultDesc": "The service request is processed successfully.",
+                "OriginatorConversationID": "10571-7910404-1",
+                "ConversationID": "AG_20191219_00004e48cf7e3533f581",
+                "TransactionID": "NLJ41HAY6Q",
+                "ResultParameters": {
+                    "ResultParameter": [
+                        { "Key": "TransactionAmount", "Value": 10 },
+                        { "Key": "TransactionReceipt", "Value": "NLJ41HAY6Q" }
+                    ]
+                }
+            }
+        }"#;
+
+        let envelope: B2CResultEnvelope = serde_json::from_str(body).unwrap();
+
+        assert!(envelope.result.is_successful());
+        assert_eq!(envelope.result.originator_conversation_id, "10571-7910404-1");
+        assert_eq!(envelope.result.transaction_id.as_deref(), Some("NLJ41HAY6Q"));
+    }
+
+    #[test]
+    fn test_parse_cancelled_callback() {
+        let body = r#"{
+            "Body": {
+                "stkCallback": {
+                    "MerchantRequestID": "29115-34620561-1",
+                    "CheckoutRequestID": "ws_CO_191220191020363925",
+                    "ResultCode": 1032,
+                    "ResultDesc": "Request cancelled by user."
+                }
+            }
+        }"#;
+
+        let envelope: STKCallbackEnvelope = serde_json::from_str(body).unwrap();
+        let callback = envelope.body.stk_callback;
+
+        assert!(!callback.is_successful());
+        assert!(callback.payment_details().unwrap().is_none());
+    }
+}
@@ -0,0 +1,132 @@
+use std::future::Future;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+use crate::error::Result;
+
+// Refresh this long before Daraja's advertised expiry so a token never
+// lapses between being read from the cache and reaching Safaricom
+const DEFAULT_REFRESH_MARGIN: Duration = Duration::from_secs(60);
+
+struct CachedToken {
+    value: String,
+    expires_at: Instant,
+}
+
+// Holds the current Daraja access token. The lock is held across the
+// refresh, so concurrent callers that find the token stale wait for the
+// one in-flight request instead of each fetching their own.
+pub struct AccessTokenCache {
+    token: Mutex<Option<CachedToken>>,
+    refresh_margin: Duration,
+}
+
+impl Default for AccessTokenCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_REFRESH_MARGIN)
+    }
+}
+
+impl AccessTokenCache {
+    pub fn new(refresh_margin: Duration) -> Self {
+        Self {
+            token: Mutex::new(None),
+            refresh_margin,
+        }
+    }
+
+    pub async fn get_or_refresh<F, Fut>(&self, fetch: F) -> Result<String>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<(String, Duration)>>,
+    {
+        let mut token = self.token.lock().await;
+
+        if let Some(cached) = token.as_ref() {
+            if Instant::now() + self.refresh_margin < cached.expires_at {
+                return Ok(cached.value.clone());
+            }
+        }
+
+        let (value, expires_in) = fetch().await?;
+        *token = Some(CachedToken {
+            value: value.clone(),
+            expires_at: Instant::now() + expires_in,
+        });
+
+        Ok(value)
+    }
+
+    // Drops the cached token, e.g. after Daraja rejects it as invalid
+    pub async fn invalidate(&self) {
+        *self.token.lock().await = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Error;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_concurrent_refreshes_collapse() {
+        let cache = Arc::new(AccessTokenCache::default());
+        let fetches = Arc::new(AtomicUsize::new(0));
+
+        let tasks: Vec<_> = (0..8)
+            .map(|_| {
+                let cache = cache.clone();
+                let fetches = fetches.clone();
+                tokio::spawn(async move {
+                    cache
+                        .get_or_refresh(|| async move {
+                            fetches.fetch_add(1, Ordering::SeqCst);
+                            tokio::time::sleep(Duration::from_millis(20)).await;
+                            Ok(("token".to_string(), Duration::from_secs(3599)))
+                        })
+                        .await
+                })
+            })
+            .collect();
+
+        for task in tasks {
+            assert_eq!(task.await.unwrap().unwrap(), "token");
+        }
+        assert_eq!(fetches.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_refreshes_ahead_of_expiry() {
+        let cache = AccessTokenCache::new(Duration::from_secs(60));
+
+        // Expires inside the refresh margin, so the next call must refetch
+        cache
+            .get_or_refresh(|| async { Ok(("first".to_string(), Duration::from_secs(30))) })
+            .await
+            .unwrap();
+        let token = cache
+            .get_or_refresh(|| async { Ok(("second".to_string(), Duration::from_secs(3599))) })
+            .await
+            .unwrap();
+
+        assert_eq!(token, "second");
+    }
+
+    #[tokio::test]
+    async fn test_failed_refresh_is_not_cached() {
+        let cache = AccessTokenCache::default();
+
+        let result = cache
+            .get_or_refresh(|| async { Err(Error::MPesa("Invalid credentials".to_string())) })
+            .await;
+        assert!(matches!(result, Err(Error::MPesa(_))));
+
+        let token = cache
+            .get_or_refresh(|| async { Ok(("token".to_string(), Duration::from_secs(3599))) })
+            .await
+            .unwrap();
+        assert_eq!(token, "token");
+    }
+}
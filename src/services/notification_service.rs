@@ -3,6 +3,7 @@ use reqwest::Client;
 use serde::Serialize;
 use std::env;
 
+#[derive(Clone)]
 pub struct NotificationService {
     client: Client,
     api_key: String,
@@ -49,6 +50,39 @@ impl NotificationService {
         self.send_sms(phone_number, &message).await
     }
 
+    pub async fn send_otp(&self, phone_number: &str, code: &str, valid_minutes: i64) -> Result<()> {
+        let message = format!(
+            "Your Blupension verification code is {}. It expires in {} minutes. Do not share it with anyone.",
+            code, valid_minutes
+        );
+        self.send_sms(phone_number, &message).await
+    }
+
+    pub async fn send_contribution_reminder(
+        &self,
+        phone_number: &str,
+        amount: f64,
+        at: &str,
+    ) -> Result<()> {
+        let message = format!(
+            "Reminder: we will ask you to save KES {} through M-Pesa at {} today. Keep your phone close to enter your PIN.",
+            amount, at
+        );
+        self.send_sms(phone_number, &message).await
+    }
+
+    pub async fn send_contribution_schedule_paused(
+        &self,
+        phone_number: &str,
+        declined: i32,
+    ) -> Result<()> {
+        let message = format!(
+            "Your automatic savings have been paused after {} declined M-Pesa requests. Resume them in the Blupension app.",
+            declined
+        );
+        self.send_sms(phone_number, &message).await
+    }
+
     async fn send_sms(&self, phone_number: &str, message: &str) -> Result<()> {
         let payload = SMSPayload {
             phone_number: phone_number.to_string(),
@@ -0,0 +1,184 @@
+use async_trait::async_trait;
+use rust_decimal::{Decimal, RoundingStrategy};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use crate::ai::investment_strategy::Asset;
+use crate::config::rebalancing::RebalancingConfig;
+use crate::services::price_history::PriceHistoryService;
+use crate::services::rebalancing::{ExecutionVenue, Fill, TradeOrder, TradeSide};
+
+// Fills every order in full at the quote, moved against the order by the
+// slippage rate, and charges the fee rate on its value. Quotes are the
+// prices set on the venue, falling back to the latest recorded price.
+pub struct PaperTradingVenue {
+    prices: Mutex<HashMap<Asset, Decimal>>,
+    history: Option<Arc<PriceHistoryService>>,
+    fee_rate: Decimal,
+    slippage_rate: Decimal,
+    next_order: AtomicU64,
+    fills: Mutex<Vec<(TradeOrder, Fill)>>,
+}
+
+impl PaperTradingVenue {
+    pub fn new(config: &RebalancingConfig) -> Self {
+        Self {
+            prices: Mutex::new(HashMap::new()),
+            history: None,
+            fee_rate: config.fee_rate,
+            slippage_rate: config.slippage_rate,
+            next_order: AtomicU64::new(1),
+            fills: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn with_price_history(mut self, history: Arc<PriceHistoryService>) -> Self {
+        self.history = Some(history);
+        self
+    }
+
+    pub fn set_price(&self, asset: Asset, price: Decimal) {
+        self.prices.lock().unwrap().insert(asset, price);
+    }
+
+    // Every order filled so far, oldest first
+    pub fn fills(&self) -> Vec<(TradeOrder, Fill)> {
+        self.fills.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl ExecutionVenue for PaperTradingVenue {
+    fn name(&self) -> &'static str {
+        "paper"
+    }
+
+    async fn quote(&self, asset: Asset) -> anyhow::Result<Decimal> {
+        let set_price = self.prices.lock().unwrap().get(&asset).copied();
+        if let Some(price) = set_price {
+            return Ok(price);
+        }
+
+        let history = self
+            .history
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("No price for {}", asset))?;
+        let tick = history
+            .latest_tick(price_symbol(asset))
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("No recorded price for {}", asset))?;
+        Ok(tick.price)
+    }
+
+    async fn execute(&self, order: &TradeOrder) -> anyhow::Result<Fill> {
+        let quote = self.quote(order.asset).await?;
+        let venue_order_id = format!("paper-{}", self.next_order.fetch_add(1, Ordering::Relaxed));
+
+        let fill = match order.side {
+            TradeSide::Sell => {
+                let price = quote * (Decimal::ONE - self.slippage_rate);
+                Fill {
+                    venue_order_id,
+                    quantity: order.quantity,
+                    price,
+                    fee: (order.quantity * price * self.fee_rate).round_dp(2),
+                }
+            }
+            TradeSide::Buy => {
+                let price = quote * (Decimal::ONE + self.slippage_rate);
+                let fee = (order.notional * self.fee_rate).round_dp(2);
+                Fill {
+                    venue_order_id,
+                    quantity: ((order.notional - fee) / price)
+                        .round_dp_with_strategy(8, RoundingStrategy::ToZero),
+                    price,
+                    fee,
+                }
+            }
+        };
+
+        self.fills
+            .lock()
+            .unwrap()
+            .push((order.clone(), fill.clone()));
+        Ok(fill)
+    }
+}
+
+// Price history symbol an asset is valued at. A money-market unit is one
+// shilling, so it follows the KES rate.
+fn price_symbol(asset: Asset) -> &'static str {
+    match asset {
+        Asset::KesMmf => "KES",
+        other => other.as_str(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dec(value: &str) -> Decimal {
+        value.parse().unwrap()
+    }
+
+    fn venue() -> PaperTradingVenue {
+        let venue = PaperTradingVenue::new(&RebalancingConfig {
+            fee_rate: dec("0.001"),
+            slippage_rate: dec("0.002"),
+            ..Default::default()
+        });
+        venue.set_price(Asset::Btc, dec("50000"));
+        venue
+    }
+
+    fn order(side: TradeSide, quantity: &str, notional: &str) -> TradeOrder {
+        TradeOrder {
+            asset: Asset::Btc,
+            side,
+            quantity: dec(quantity),
+            quoted_price: dec("50000"),
+            notional: dec(notional),
+            estimated_cost: Decimal::ZERO,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sell_fills_below_quote_and_pays_fee() {
+        let venue = venue();
+
+        let fill = venue
+            .execute(&order(TradeSide::Sell, "0.01", "500"))
+            .await
+            .unwrap();
+
+        assert_eq!(fill.quantity, dec("0.01"));
+        assert_eq!(fill.price, dec("49900"));
+        assert_eq!(fill.fee, dec("0.50"));
+        assert_eq!(fill.proceeds(), dec("498.50"));
+        assert_eq!(fill.venue_order_id, "paper-1");
+    }
+
+    #[tokio::test]
+    async fn test_buy_spends_notional_above_quote() {
+        let venue = venue();
+
+        let fill = venue
+            .execute(&order(TradeSide::Buy, "0.00997", "500"))
+            .await
+            .unwrap();
+
+        assert_eq!(fill.price, dec("50100"));
+        assert_eq!(fill.fee, dec("0.50"));
+        assert_eq!(fill.quantity, dec("0.00997005"));
+        assert_eq!(venue.fills().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_unpriced_asset_cannot_be_quoted() {
+        let venue = venue();
+
+        assert!(venue.quote(Asset::Tbill).await.is_err());
+    }
+}
@@ -0,0 +1,420 @@
+use argon2::{
+    password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
+    Argon2,
+};
+use chrono::{DateTime, Duration, Utc};
+use rand::Rng;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::config::phone_auth::PhoneAuthConfig;
+use crate::error::{Error, Result};
+use crate::services::notification_service::NotificationService;
+use crate::utils::validation::{is_valid_pin, normalize_msisdn};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OtpPurpose {
+    Registration,
+    PinReset,
+}
+
+impl OtpPurpose {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OtpPurpose::Registration => "REGISTRATION",
+            OtpPurpose::PinReset => "PIN_RESET",
+        }
+    }
+}
+
+// Registration and login by M-Pesa number and PIN for members without a
+// smartphone or email. Ownership of the number is proven by an SMS OTP.
+#[derive(Clone)]
+pub struct PhoneAuthService {
+    pool: PgPool,
+    notification_service: NotificationService,
+    config: PhoneAuthConfig,
+}
+
+impl PhoneAuthService {
+    pub fn new(
+        pool: PgPool,
+        notification_service: NotificationService,
+        config: PhoneAuthConfig,
+    ) -> Self {
+        Self {
+            pool,
+            notification_service,
+            config,
+        }
+    }
+
+    pub async fn request_registration_otp(&self, phone_number: &str) -> Result<()> {
+        let phone_number = msisdn(phone_number)?;
+
+        if self.user_id_by_phone(&phone_number).await?.is_some() {
+            return Err(Error::PhoneAlreadyRegistered);
+        }
+
+        self.issue_otp(&phone_number, OtpPurpose::Registration)
+            .await
+    }
+
+    // Unknown numbers get no SMS but the same response, so the endpoint
+    // cannot be used to discover who is a member.
+    pub async fn request_pin_reset_otp(&self, phone_number: &str) -> Result<()> {
+        let phone_number = msisdn(phone_number)?;
+
+        if self.user_id_by_phone(&phone_number).await?.is_none() {
+            return Ok(());
+        }
+
+        self.issue_otp(&phone_number, OtpPurpose::PinReset).await
+    }
+
+    pub async fn register(
+        &self,
+        phone_number: &str,
+        otp: &str,
+        pin: &str,
+        username: Option<String>,
+    ) -> Result<Uuid> {
+        let phone_number = msisdn(phone_number)?;
+        if !is_valid_pin(pin) {
+            return Err(Error::InvalidPin);
+        }
+
+        self.verify_otp(&phone_number, OtpPurpose::Registration, otp)
+            .await?;
+
+        let user_id = Uuid::new_v4();
+        let inserted = sqlx::query!(
+            r#"
+            INSERT INTO users (id, username, phone_number, pin_hash, phone_verified)
+            VALUES ($1, $2, $3, $4, TRUE)
+            "#,
+            user_id,
+            username.unwrap_or_else(|| phone_number.clone()),
+            phone_number,
+            hash_secret(pin)?,
+        )
+        .execute(&self.pool)
+        .await;
+
+        match inserted {
+            Ok(_) => Ok(user_id),
+            Err(sqlx::Error::Database(e)) if e.is_unique_violation() => {
+                Err(Error::PhoneAlreadyRegistered)
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    // A run of `max_pin_attempts` wrong PINs locks the account for
+    // `lockout_secs`; a correct PIN clears the count.
+    pub async fn login(&self, phone_number: &str, pin: &str) -> Result<Uuid> {
+        let phone_number = msisdn(phone_number).map_err(|_| Error::Unauthorized)?;
+        let now = Utc::now();
+        let mut tx = self.pool.begin().await?;
+
+        let user = sqlx::query!(
+            r#"
+            SELECT id, pin_hash, failed_pin_attempts, locked_until
+            FROM users
+            WHERE phone_number = $1
+            FOR UPDATE
+            "#,
+            phone_number
+        )
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(Error::Unauthorized)?;
+
+        if let Some(until) = user.locked_until.filter(|until| *until > now) {
+            return Err(Error::AccountLocked(until));
+        }
+
+        // Email members who have not set a PIN yet do so through PIN reset
+        let pin_hash = user.pin_hash.ok_or(Error::Unauthorized)?;
+
+        if verify_secret(pin, &pin_hash) {
+            sqlx::query!(
+                r#"
+                UPDATE users
+                SET failed_pin_attempts = 0, locked_until = NULL
+                WHERE id = $1
+                "#,
+                user.id
+            )
+            .execute(&mut *tx)
+            .await?;
+            tx.commit().await?;
+
+            return Ok(user.id);
+        }
+
+        let (failed_attempts, locked_until) =
+            failed_pin_outcome(user.failed_pin_attempts + 1, &self.config, now);
+
+        sqlx::query!(
+            r#"
+            UPDATE users
+            SET failed_pin_attempts = $2, locked_until = $3
+            WHERE id = $1
+            "#,
+            user.id,
+            failed_attempts,
+            locked_until
+        )
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        match locked_until {
+            Some(until) => {
+                tracing::warn!("Locked user {} after repeated wrong PINs", user.id);
+                Err(Error::AccountLocked(until))
+            }
+            None => Err(Error::Unauthorized),
+        }
+    }
+
+    pub async fn reset_pin(&self, phone_number: &str, otp: &str, new_pin: &str) -> Result<()> {
+        let phone_number = msisdn(phone_number)?;
+        if !is_valid_pin(new_pin) {
+            return Err(Error::InvalidPin);
+        }
+
+        self.verify_otp(&phone_number, OtpPurpose::PinReset, otp)
+            .await?;
+
+        // Proving the number also lifts any lockout
+        let updated = sqlx::query!(
+            r#"
+            UPDATE users
+            SET pin_hash = $2,
+                phone_verified = TRUE,
+                failed_pin_attempts = 0,
+                locked_until = NULL,
+                updated_at = NOW()
+            WHERE phone_number = $1
+            "#,
+            phone_number,
+            hash_secret(new_pin)?
+        )
+        .execute(&self.pool)
+        .await?;
+
+        if updated.rows_affected() == 0 {
+            return Err(Error::NotFound("User".to_string()));
+        }
+
+        Ok(())
+    }
+
+    pub async fn user_id_by_phone(&self, phone_number: &str) -> Result<Option<Uuid>> {
+        let user_id =
+            sqlx::query_scalar!("SELECT id FROM users WHERE phone_number = $1", phone_number)
+                .fetch_optional(&self.pool)
+                .await?;
+
+        Ok(user_id)
+    }
+
+    // Replaces any outstanding code for the number and texts the new one
+    async fn issue_otp(&self, phone_number: &str, purpose: OtpPurpose) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        let last_sent = sqlx::query_scalar!(
+            r#"
+            SELECT created_at FROM otp_codes
+            WHERE phone_number = $1 AND purpose = $2
+            ORDER BY created_at DESC
+            LIMIT 1
+            "#,
+            phone_number,
+            purpose.as_str()
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        if let Some(last_sent) = last_sent {
+            if Utc::now() - last_sent < Duration::seconds(self.config.otp_resend_interval_secs) {
+                return Err(Error::OtpRateLimited);
+            }
+        }
+
+        sqlx::query!(
+            r#"
+            UPDATE otp_codes
+            SET consumed_at = NOW()
+            WHERE phone_number = $1 AND purpose = $2 AND consumed_at IS NULL
+            "#,
+            phone_number,
+            purpose.as_str()
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        let code = generate_otp(self.config.otp_length);
+        sqlx::query!(
+            r#"
+            INSERT INTO otp_codes (phone_number, purpose, code_hash, expires_at)
+            VALUES ($1, $2, $3, $4)
+            "#,
+            phone_number,
+            purpose.as_str(),
+            hash_secret(&code)?,
+            Utc::now() + Duration::seconds(self.config.otp_ttl_secs),
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        self.notification_service
+            .send_otp(phone_number, &code, (self.config.otp_ttl_secs / 60).max(1))
+            .await?;
+
+        Ok(())
+    }
+
+    // Consumes the current code on a match. Wrong guesses are counted and
+    // committed even though the call fails, and the code is burned once
+    // `otp_max_attempts` is reached.
+    async fn verify_otp(&self, phone_number: &str, purpose: OtpPurpose, code: &str) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        let otp = sqlx::query!(
+            r#"
+            SELECT id, code_hash, attempts
+            FROM otp_codes
+            WHERE phone_number = $1
+              AND purpose = $2
+              AND consumed_at IS NULL
+              AND expires_at > NOW()
+            ORDER BY created_at DESC
+            LIMIT 1
+            FOR UPDATE
+            "#,
+            phone_number,
+            purpose.as_str()
+        )
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(Error::InvalidOtp)?;
+
+        if verify_secret(code, &otp.code_hash) {
+            sqlx::query!(
+                "UPDATE otp_codes SET consumed_at = NOW() WHERE id = $1",
+                otp.id
+            )
+            .execute(&mut *tx)
+            .await?;
+            tx.commit().await?;
+
+            return Ok(());
+        }
+
+        sqlx::query!(
+            r#"
+            UPDATE otp_codes
+            SET attempts = attempts + 1,
+                consumed_at = CASE WHEN attempts + 1 >= $2 THEN NOW() END
+            WHERE id = $1
+            "#,
+            otp.id,
+            self.config.otp_max_attempts
+        )
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        Err(Error::InvalidOtp)
+    }
+}
+
+fn msisdn(phone_number: &str) -> Result<String> {
+    normalize_msisdn(phone_number).ok_or(Error::InvalidPhoneNumber)
+}
+
+fn generate_otp(length: usize) -> String {
+    let mut rng = rand::rng();
+    (0..length)
+        .map(|_| char::from(b'0' + rng.random_range(0..10u8)))
+        .collect()
+}
+
+// PINs and OTPs are short, so they get the same slow hash as passwords
+fn hash_secret(secret: &str) -> Result<String> {
+    let salt = SaltString::generate(&mut OsRng);
+    let hash = Argon2::default()
+        .hash_password(secret.as_bytes(), &salt)
+        .map_err(|e| anyhow::anyhow!("Failed to hash secret: {}", e))?;
+
+    Ok(hash.to_string())
+}
+
+fn verify_secret(secret: &str, hash: &str) -> bool {
+    PasswordHash::new(hash)
+        .map(|parsed| {
+            Argon2::default()
+                .verify_password(secret.as_bytes(), &parsed)
+                .is_ok()
+        })
+        .unwrap_or(false)
+}
+
+// The failure count and lockout to store after a wrong PIN. Reaching the
+// limit starts a lockout and resets the count for the next window.
+fn failed_pin_outcome(
+    failed_attempts: i32,
+    config: &PhoneAuthConfig,
+    now: DateTime<Utc>,
+) -> (i32, Option<DateTime<Utc>>) {
+    if failed_attempts >= config.max_pin_attempts {
+        (0, Some(now + Duration::seconds(config.lockout_secs)))
+    } else {
+        (failed_attempts, None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_otp() {
+        for length in [4, 6, 8] {
+            let otp = generate_otp(length);
+            assert_eq!(otp.len(), length);
+            assert!(otp.chars().all(|c| c.is_ascii_digit()));
+        }
+    }
+
+    #[test]
+    fn test_hash_and_verify_secret() {
+        let hash = hash_secret("4821").unwrap();
+
+        assert_ne!(hash, "4821");
+        assert!(verify_secret("4821", &hash));
+        assert!(!verify_secret("4822", &hash));
+        assert!(!verify_secret("4821", "not-a-hash"));
+    }
+
+    #[test]
+    fn test_failed_pin_outcome() {
+        let config = PhoneAuthConfig::default();
+        let now = Utc::now();
+
+        assert_eq!(failed_pin_outcome(1, &config, now), (1, None));
+        assert_eq!(
+            failed_pin_outcome(config.max_pin_attempts - 1, &config, now),
+            (config.max_pin_attempts - 1, None)
+        );
+        assert_eq!(
+            failed_pin_outcome(config.max_pin_attempts, &config, now),
+            (0, Some(now + Duration::seconds(config.lockout_secs)))
+        );
+    }
+}
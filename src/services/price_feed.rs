@@ -1,64 +1,706 @@
-use anyhow::Result;
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, TimeZone, Utc};
+use futures::future::join_all;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::env;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
-pub struct PriceFeedService {
-    client: Client,
-    cmc_api_key: String,
-    binance_api_key: String,
-    coingecko_api_key: String,
-}
+use crate::config::price_feed::PriceFeedConfig;
+
+pub const COINGECKO_URL: &str = "https://api.coingecko.com/api/v3";
+pub const COINMARKETCAP_URL: &str = "https://pro-api.coinmarketcap.com";
+pub const BINANCE_URL: &str = "https://api.binance.com";
+pub const EXCHANGE_RATE_URL: &str = "https://open.er-api.com/v6";
+
+// CoinGecko identifies coins by id rather than by ticker
+const COINGECKO_IDS: &[(&str, &str)] = &[
+    ("BTC", "bitcoin"),
+    ("ETH", "ethereum"),
+    ("USDC", "usd-coin"),
+    ("USDT", "tether"),
+    ("XLM", "stellar"),
+];
 
-#[derive(Debug, Serialize, Deserialize)]
+// Binance has no USD books; USDT pairs stand in for the USD price
+const BINANCE_QUOTE_ASSET: &str = "USDT";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PriceData {
     pub price: f64,
     pub volume_24h: f64,
     pub percent_change_24h: f64,
-    pub last_updated: chrono::DateTime<chrono::Utc>,
+    pub last_updated: DateTime<Utc>,
+    pub sources: Vec<String>, // Providers whose quotes make up the price
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum PriceFeedError {
+    #[error("No price source returned a quote for {symbol}: {}", .failures.join("; "))]
+    NoQuotes {
+        symbol: String,
+        failures: Vec<String>,
+    },
+    #[error("The latest {symbol} quote is {age_secs}s old")]
+    Stale { symbol: String, age_secs: i64 },
+    #[error("Only {usable} of the {needed} sources needed agree on the {symbol} price")]
+    InsufficientSources {
+        symbol: String,
+        usable: usize,
+        needed: usize,
+    },
+}
+
+// A market data source quoting prices in USD
+#[async_trait]
+pub trait PriceProvider: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    async fn fetch(&self, symbol: &str) -> Result<PriceData>;
+}
+
+pub struct CoinGeckoProvider {
+    client: Client,
+    base_url: String,
+    api_key: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct CoinGeckoQuote {
+    usd: f64,
+    usd_24h_vol: Option<f64>,
+    usd_24h_change: Option<f64>,
+    last_updated_at: i64,
+}
+
+impl CoinGeckoProvider {
+    pub fn new(client: Client, base_url: impl Into<String>, api_key: Option<String>) -> Self {
+        Self {
+            client,
+            base_url: base_url.into(),
+            api_key,
+        }
+    }
+
+    pub fn from_env(client: Client) -> Self {
+        Self::new(
+            client,
+            env::var("COINGECKO_BASE_URL").unwrap_or_else(|_| COINGECKO_URL.to_string()),
+            env::var("COINGECKO_API_KEY").ok(),
+        )
+    }
+}
+
+#[async_trait]
+impl PriceProvider for CoinGeckoProvider {
+    fn name(&self) -> &'static str {
+        "COINGECKO"
+    }
+
+    async fn fetch(&self, symbol: &str) -> Result<PriceData> {
+        let id = COINGECKO_IDS
+            .iter()
+            .find(|(ticker, _)| *ticker == symbol)
+            .map(|(_, id)| *id)
+            .ok_or_else(|| anyhow!("CoinGecko has no id for {}", symbol))?;
+
+        let mut request = self
+            .client
+            .get(format!("{}/simple/price", self.base_url))
+            .query(&[
+                ("ids", id),
+                ("vs_currencies", "usd"),
+                ("include_24hr_vol", "true"),
+                ("include_24hr_change", "true"),
+                ("include_last_updated_at", "true"),
+            ]);
+        if let Some(api_key) = &self.api_key {
+            request = request.header("x-cg-demo-api-key", api_key);
+        }
+
+        let mut quotes = request
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<HashMap<String, CoinGeckoQuote>>()
+            .await?;
+        let quote = quotes
+            .remove(id)
+            .ok_or_else(|| anyhow!("CoinGecko returned no quote for {}", symbol))?;
+
+        Ok(PriceData {
+            price: quote.usd,
+            volume_24h: quote.usd_24h_vol.unwrap_or_default(),
+            percent_change_24h: quote.usd_24h_change.unwrap_or_default(),
+            last_updated: Utc
+                .timestamp_opt(quote.last_updated_at, 0)
+                .single()
+                .ok_or_else(|| anyhow!("CoinGecko sent an invalid timestamp"))?,
+            sources: vec![self.name().to_string()],
+        })
+    }
+}
+
+pub struct CoinMarketCapProvider {
+    client: Client,
+    base_url: String,
+    api_key: String,
+}
+
+#[derive(Deserialize)]
+struct CmcResponse {
+    data: HashMap<String, CmcListing>,
+}
+
+#[derive(Deserialize)]
+struct CmcListing {
+    quote: HashMap<String, CmcQuote>,
+}
+
+#[derive(Deserialize)]
+struct CmcQuote {
+    price: f64,
+    volume_24h: Option<f64>,
+    percent_change_24h: Option<f64>,
+    last_updated: DateTime<Utc>,
+}
+
+impl CoinMarketCapProvider {
+    pub fn new(client: Client, base_url: impl Into<String>, api_key: impl Into<String>) -> Self {
+        Self {
+            client,
+            base_url: base_url.into(),
+            api_key: api_key.into(),
+        }
+    }
+
+    // CoinMarketCap has no keyless tier, so it is left out without a key
+    pub fn from_env(client: Client) -> Option<Self> {
+        let api_key = env::var("COINMARKETCAP_API_KEY").ok()?;
+
+        Some(Self::new(
+            client,
+            env::var("COINMARKETCAP_BASE_URL").unwrap_or_else(|_| COINMARKETCAP_URL.to_string()),
+            api_key,
+        ))
+    }
+}
+
+#[async_trait]
+impl PriceProvider for CoinMarketCapProvider {
+    fn name(&self) -> &'static str {
+        "COINMARKETCAP"
+    }
+
+    async fn fetch(&self, symbol: &str) -> Result<PriceData> {
+        let mut response = self
+            .client
+            .get(format!("{}/v1/cryptocurrency/quotes/latest", self.base_url))
+            .query(&[("symbol", symbol), ("convert", "USD")])
+            .header("X-CMC_PRO_API_KEY", &self.api_key)
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<CmcResponse>()
+            .await?;
+
+        let quote = response
+            .data
+            .remove(symbol)
+            .and_then(|mut listing| listing.quote.remove("USD"))
+            .ok_or_else(|| anyhow!("CoinMarketCap returned no quote for {}", symbol))?;
+
+        Ok(PriceData {
+            price: quote.price,
+            volume_24h: quote.volume_24h.unwrap_or_default(),
+            percent_change_24h: quote.percent_change_24h.unwrap_or_default(),
+            last_updated: quote.last_updated,
+            sources: vec![self.name().to_string()],
+        })
+    }
+}
+
+pub struct BinanceProvider {
+    client: Client,
+    base_url: String,
+    api_key: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BinanceTicker {
+    last_price: String,
+    quote_volume: String,
+    price_change_percent: String,
+    close_time: i64,
+}
+
+impl BinanceProvider {
+    pub fn new(client: Client, base_url: impl Into<String>, api_key: Option<String>) -> Self {
+        Self {
+            client,
+            base_url: base_url.into(),
+            api_key,
+        }
+    }
+
+    pub fn from_env(client: Client) -> Self {
+        Self::new(
+            client,
+            env::var("BINANCE_BASE_URL").unwrap_or_else(|_| BINANCE_URL.to_string()),
+            env::var("BINANCE_API_KEY").ok(),
+        )
+    }
+}
+
+#[async_trait]
+impl PriceProvider for BinanceProvider {
+    fn name(&self) -> &'static str {
+        "BINANCE"
+    }
+
+    async fn fetch(&self, symbol: &str) -> Result<PriceData> {
+        if symbol == BINANCE_QUOTE_ASSET {
+            return Err(anyhow!("Binance cannot price its own quote asset"));
+        }
+
+        let mut request = self
+            .client
+            .get(format!("{}/api/v3/ticker/24hr", self.base_url))
+            .query(&[("symbol", format!("{}{}", symbol, BINANCE_QUOTE_ASSET))]);
+        if let Some(api_key) = &self.api_key {
+            request = request.header("X-MBX-APIKEY", api_key);
+        }
+
+        let ticker = request
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<BinanceTicker>()
+            .await?;
+
+        Ok(PriceData {
+            price: ticker.last_price.parse()?,
+            volume_24h: ticker.quote_volume.parse()?,
+            percent_change_24h: ticker.price_change_percent.parse()?,
+            last_updated: Utc
+                .timestamp_millis_opt(ticker.close_time)
+                .single()
+                .ok_or_else(|| anyhow!("Binance sent an invalid timestamp"))?,
+            sources: vec![self.name().to_string()],
+        })
+    }
+}
+
+// Fiat currencies, priced as the USD value of one unit
+pub struct ExchangeRateProvider {
+    client: Client,
+    base_url: String,
+}
+
+#[derive(Deserialize)]
+struct ExchangeRateResponse {
+    result: String,
+    time_last_update_unix: i64,
+    rates: HashMap<String, f64>,
+}
+
+impl ExchangeRateProvider {
+    pub fn new(client: Client, base_url: impl Into<String>) -> Self {
+        Self {
+            client,
+            base_url: base_url.into(),
+        }
+    }
+
+    pub fn from_env(client: Client) -> Self {
+        Self::new(
+            client,
+            env::var("EXCHANGE_RATE_BASE_URL").unwrap_or_else(|_| EXCHANGE_RATE_URL.to_string()),
+        )
+    }
+}
+
+#[async_trait]
+impl PriceProvider for ExchangeRateProvider {
+    fn name(&self) -> &'static str {
+        "EXCHANGE_RATE_API"
+    }
+
+    async fn fetch(&self, symbol: &str) -> Result<PriceData> {
+        let response = self
+            .client
+            .get(format!("{}/latest/USD", self.base_url))
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<ExchangeRateResponse>()
+            .await?;
+
+        if response.result != "success" {
+            return Err(anyhow!("Exchange rate lookup failed: {}", response.result));
+        }
+        let units_per_usd = response
+            .rates
+            .get(symbol)
+            .copied()
+            .filter(|rate| *rate > 0.0)
+            .ok_or_else(|| anyhow!("No exchange rate for {}", symbol))?;
+
+        Ok(PriceData {
+            price: 1.0 / units_per_usd,
+            volume_24h: 0.0,
+            percent_change_24h: 0.0,
+            last_updated: Utc
+                .timestamp_opt(response.time_last_update_unix, 0)
+                .single()
+                .ok_or_else(|| anyhow!("Exchange rate API sent an invalid timestamp"))?,
+            sources: vec![self.name().to_string()],
+        })
+    }
+}
+
+struct CachedPrice {
+    data: PriceData,
+    fetched_at: Instant,
+}
+
+// Asks every provider at once and serves the agreed price. Once the cache
+// entry expires a fresh agreement is required: there is no falling back to
+// an old price or to whichever single source happened to answer.
+#[derive(Clone)]
+pub struct PriceFeedService {
+    providers: Vec<Arc<dyn PriceProvider>>,
+    config: PriceFeedConfig,
+    cache: Arc<Mutex<HashMap<String, CachedPrice>>>,
 }
 
 impl PriceFeedService {
-    pub fn new(
-        cmc_api_key: String,
-        binance_api_key: String,
-        coingecko_api_key: String,
-    ) -> Self {
+    pub fn new(providers: Vec<Arc<dyn PriceProvider>>, config: PriceFeedConfig) -> Self {
         Self {
-            client: Client::new(),
-            cmc_api_key,
-            binance_api_key,
-            coingecko_api_key,
+            providers,
+            config,
+            cache: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    pub fn from_env() -> Result<Self> {
+        let config = PriceFeedConfig::from_env();
+        let client = Client::builder()
+            .timeout(Duration::from_secs(config.request_timeout_secs))
+            .build()?;
+
+        let mut providers: Vec<Arc<dyn PriceProvider>> = vec![
+            Arc::new(CoinGeckoProvider::from_env(client.clone())),
+            Arc::new(BinanceProvider::from_env(client.clone())),
+        ];
+        if let Some(cmc) = CoinMarketCapProvider::from_env(client) {
+            providers.push(Arc::new(cmc));
         }
+
+        Ok(Self::new(providers, config))
+    }
+
+    // Fiat rates come from a single reference source that updates about
+    // once a day, so one quote is enough and it may be older
+    pub fn fx_from_env() -> Result<Self> {
+        let config = PriceFeedConfig::from_env();
+        let client = Client::builder()
+            .timeout(Duration::from_secs(config.request_timeout_secs))
+            .build()?;
+
+        Ok(Self::new(
+            vec![Arc::new(ExchangeRateProvider::from_env(client))],
+            PriceFeedConfig {
+                min_sources: 1,
+                max_quote_age_secs: config.fx_max_quote_age_secs,
+                ..config
+            },
+        ))
     }
 
-    pub async fn get_price(&self, symbol: &str) -> Result<PriceData> {
-        // Try CoinGecko first
-        if let Ok(data) = self.get_coingecko_price(symbol).await {
-            return Ok(data);
+    pub async fn get_price(&self, symbol: &str) -> Result<PriceData, PriceFeedError> {
+        let symbol = symbol.trim().to_uppercase();
+        if let Some(price) = self.cached(&symbol) {
+            return Ok(price);
         }
 
-        // Fallback to CoinMarketCap
-        if let Ok(data) = self.get_cmc_price(symbol).await {
-            return Ok(data);
+        let results = join_all(self.providers.iter().map(|p| p.fetch(&symbol))).await;
+
+        let mut quotes = Vec::new();
+        let mut failures = Vec::new();
+        for (provider, result) in self.providers.iter().zip(results) {
+            match result {
+                Ok(quote) => quotes.push(quote),
+                Err(e) => {
+                    tracing::warn!("{} quote for {} failed: {:#}", provider.name(), symbol, e);
+                    failures.push(format!("{}: {}", provider.name(), e));
+                }
+            }
         }
 
-        // Final fallback to Binance
-        self.get_binance_price(symbol).await
+        let price = aggregate(&symbol, quotes, failures, &self.config, Utc::now())?;
+
+        self.cache.lock().unwrap().insert(
+            symbol,
+            CachedPrice {
+                data: price.clone(),
+                fetched_at: Instant::now(),
+            },
+        );
+        Ok(price)
     }
 
-    async fn get_coingecko_price(&self, symbol: &str) -> Result<PriceData> {
-        // Implement CoinGecko API call
-        todo!()
+    fn cached(&self, symbol: &str) -> Option<PriceData> {
+        let ttl = Duration::from_secs(self.config.cache_ttl_secs);
+
+        self.cache
+            .lock()
+            .unwrap()
+            .get(symbol)
+            .filter(|cached| cached.fetched_at.elapsed() < ttl)
+            .map(|cached| cached.data.clone())
     }
+}
 
-    async fn get_cmc_price(&self, symbol: &str) -> Result<PriceData> {
-        // Implement CoinMarketCap API call
-        todo!()
+// Combines provider quotes into one price. Stale quotes are dropped, then
+// any quote too far from the median of the rest, and what remains must
+// still meet the source minimum.
+pub fn aggregate(
+    symbol: &str,
+    quotes: Vec<PriceData>,
+    failures: Vec<String>,
+    config: &PriceFeedConfig,
+    now: DateTime<Utc>,
+) -> Result<PriceData, PriceFeedError> {
+    let newest = match quotes.iter().map(|q| q.last_updated).max() {
+        Some(newest) => newest,
+        None => {
+            return Err(PriceFeedError::NoQuotes {
+                symbol: symbol.to_string(),
+                failures,
+            })
+        }
+    };
+
+    let max_age = chrono::Duration::seconds(config.max_quote_age_secs);
+    let fresh: Vec<PriceData> = quotes
+        .into_iter()
+        .filter(|q| now - q.last_updated <= max_age)
+        .collect();
+    if fresh.is_empty() {
+        return Err(PriceFeedError::Stale {
+            symbol: symbol.to_string(),
+            age_secs: (now - newest).num_seconds(),
+        });
     }
 
-    async fn get_binance_price(&self, symbol: &str) -> Result<PriceData> {
-        // Implement Binance API call
-        todo!()
+    let center = median(fresh.iter().map(|q| q.price));
+    let agreeing: Vec<PriceData> = fresh
+        .into_iter()
+        .filter(|q| center > 0.0 && ((q.price - center) / center).abs() <= config.max_deviation)
+        .collect();
+
+    let needed = config.min_sources.max(1);
+    if agreeing.len() < needed {
+        return Err(PriceFeedError::InsufficientSources {
+            symbol: symbol.to_string(),
+            usable: agreeing.len(),
+            needed,
+        });
     }
-} 
\ No newline at end of file
+
+    Ok(PriceData {
+        price: median(agreeing.iter().map(|q| q.price)),
+        volume_24h: median(agreeing.iter().map(|q| q.volume_24h)),
+        percent_change_24h: median(agreeing.iter().map(|q| q.percent_change_24h)),
+        // Only as fresh as the oldest quote that went into it
+        last_updated: agreeing.iter().map(|q| q.last_updated).min().unwrap_or(now),
+        sources: agreeing.into_iter().flat_map(|q| q.sources).collect(),
+    })
+}
+
+fn median(values: impl Iterator<Item = f64>) -> f64 {
+    let mut values: Vec<f64> = values.collect();
+    if values.is_empty() {
+        return 0.0;
+    }
+    values.sort_by(f64::total_cmp);
+
+    let mid = values.len() / 2;
+    if values.len() % 2 == 0 {
+        (values[mid - 1] + values[mid]) / 2.0
+    } else {
+        values[mid]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{extract::State, routing::get, Json, Router};
+    use serde_json::{json, Value};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn quote(source: &str, price: f64, last_updated: DateTime<Utc>) -> PriceData {
+        PriceData {
+            price,
+            volume_24h: 1_000.0,
+            percent_change_24h: 1.0,
+            last_updated,
+            sources: vec![source.to_string()],
+        }
+    }
+
+    #[test]
+    fn test_median() {
+        assert_eq!(median([3.0, 1.0, 2.0].into_iter()), 2.0);
+        assert_eq!(median([4.0, 1.0, 3.0, 2.0].into_iter()), 2.5);
+    }
+
+    #[test]
+    fn test_aggregate_rejects_outliers() {
+        let now = Utc::now();
+        let quotes = vec![
+            quote("A", 100.0, now),
+            quote("B", 100.5, now),
+            quote("C", 150.0, now),
+        ];
+
+        let price = aggregate("BTC", quotes, vec![], &PriceFeedConfig::default(), now).unwrap();
+
+        assert_eq!(price.price, 100.25);
+        assert_eq!(price.sources, vec!["A", "B"]);
+    }
+
+    #[test]
+    fn test_aggregate_ignores_stale_quotes() {
+        let now = Utc::now();
+        let config = PriceFeedConfig {
+            min_sources: 1,
+            ..PriceFeedConfig::default()
+        };
+
+        let quotes = vec![
+            quote("A", 100.0, now - chrono::Duration::hours(1)),
+            quote("B", 90.0, now),
+        ];
+        let price = aggregate("BTC", quotes, vec![], &config, now).unwrap();
+        assert_eq!(price.price, 90.0);
+
+        let stale = vec![quote("A", 100.0, now - chrono::Duration::hours(1))];
+        assert!(matches!(
+            aggregate("BTC", stale, vec![], &config, now),
+            Err(PriceFeedError::Stale { age_secs: 3600, .. })
+        ));
+    }
+
+    #[test]
+    fn test_aggregate_requires_agreeing_sources() {
+        let now = Utc::now();
+        let config = PriceFeedConfig::default();
+
+        // Two sources too far apart: each is an outlier from their midpoint
+        let quotes = vec![quote("A", 100.0, now), quote("B", 110.0, now)];
+        assert!(matches!(
+            aggregate("BTC", quotes, vec![], &config, now),
+            Err(PriceFeedError::InsufficientSources {
+                usable: 0,
+                needed: 2,
+                ..
+            })
+        ));
+
+        let failures = vec!["A: timed out".to_string()];
+        assert!(matches!(
+            aggregate("BTC", vec![], failures, &config, now),
+            Err(PriceFeedError::NoQuotes { .. })
+        ));
+    }
+
+    async fn coingecko(State(hits): State<Arc<AtomicUsize>>) -> Json<Value> {
+        hits.fetch_add(1, Ordering::SeqCst);
+        Json(json!({
+            "bitcoin": {
+                "usd": 60000.0,
+                "usd_24h_vol": 2.0e10,
+                "usd_24h_change": 1.5,
+                "last_updated_at": Utc::now().timestamp(),
+            }
+        }))
+    }
+
+    async fn coinmarketcap(State(hits): State<Arc<AtomicUsize>>) -> Json<Value> {
+        hits.fetch_add(1, Ordering::SeqCst);
+        Json(json!({
+            "data": {
+                "BTC": {
+                    "quote": {
+                        "USD": {
+                            "price": 60100.0,
+                            "volume_24h": 2.1e10,
+                            "percent_change_24h": 1.6,
+                            "last_updated": Utc::now().to_rfc3339(),
+                        }
+                    }
+                }
+            }
+        }))
+    }
+
+    async fn binance(State(hits): State<Arc<AtomicUsize>>) -> Json<Value> {
+        hits.fetch_add(1, Ordering::SeqCst);
+        Json(json!({
+            "lastPrice": "75000.00",
+            "quoteVolume": "1500000000.00",
+            "priceChangePercent": "9.000",
+            "closeTime": Utc::now().timestamp_millis(),
+        }))
+    }
+
+    // One local server standing in for all three APIs
+    async fn start_stub(hits: Arc<AtomicUsize>) -> String {
+        let app = Router::new()
+            .route("/simple/price", get(coingecko))
+            .route("/v1/cryptocurrency/quotes/latest", get(coinmarketcap))
+            .route("/api/v3/ticker/24hr", get(binance))
+            .with_state(hits);
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.ok();
+        });
+
+        format!("http://{}", addr)
+    }
+
+    #[tokio::test]
+    async fn test_get_price_from_stubbed_providers() {
+        let hits = Arc::new(AtomicUsize::new(0));
+        let base_url = start_stub(hits.clone()).await;
+        let client = Client::new();
+
+        let feed = PriceFeedService::new(
+            vec![
+                Arc::new(CoinGeckoProvider::new(client.clone(), &base_url, None)),
+                Arc::new(CoinMarketCapProvider::new(client.clone(), &base_url, "key")),
+                Arc::new(BinanceProvider::new(client, &base_url, None)),
+            ],
+            PriceFeedConfig::default(),
+        );
+
+        // Binance's quote is an outlier and is left out of the median
+        let price = feed.get_price("btc").await.unwrap();
+        assert_eq!(price.price, 60050.0);
+        assert_eq!(price.sources, vec!["COINGECKO", "COINMARKETCAP"]);
+        assert_eq!(hits.load(Ordering::SeqCst), 3);
+
+        // Served from the cache within the TTL
+        feed.get_price("BTC").await.unwrap();
+        assert_eq!(hits.load(Ordering::SeqCst), 3);
+    }
+}
@@ -0,0 +1,670 @@
+use chrono::{DateTime, NaiveDate, TimeZone, Utc};
+use ndarray::Array2;
+use rust_decimal::prelude::*;
+use serde::{Deserialize, Serialize};
+use sqlx::{PgConnection, PgPool};
+use std::collections::HashMap;
+
+use crate::ai::portfolio_optimizer::MarketHistory;
+use crate::config::price_history::PriceHistoryConfig;
+use crate::error::{Error, Result};
+use crate::services::price_feed::PriceData;
+
+const MAX_SYMBOL_LEN: usize = 10;
+
+// Buckets are aligned to the Unix epoch, so daily candles run midnight to
+// midnight UTC
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum CandleInterval {
+    #[serde(rename = "5m")]
+    FiveMinutes,
+    #[serde(rename = "1h")]
+    OneHour,
+    #[serde(rename = "1d")]
+    OneDay,
+}
+
+impl CandleInterval {
+    pub const ALL: [CandleInterval; 3] = [
+        CandleInterval::FiveMinutes,
+        CandleInterval::OneHour,
+        CandleInterval::OneDay,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CandleInterval::FiveMinutes => "5m",
+            CandleInterval::OneHour => "1h",
+            CandleInterval::OneDay => "1d",
+        }
+    }
+
+    pub fn seconds(&self) -> i64 {
+        match self {
+            CandleInterval::FiveMinutes => 5 * 60,
+            CandleInterval::OneHour => 60 * 60,
+            CandleInterval::OneDay => 24 * 60 * 60,
+        }
+    }
+
+    pub fn bucket_start(&self, at: DateTime<Utc>) -> DateTime<Utc> {
+        let secs = at.timestamp();
+        Utc.timestamp_opt(secs - secs.rem_euclid(self.seconds()), 0)
+            .single()
+            .unwrap_or(at)
+    }
+}
+
+impl FromStr for CandleInterval {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        CandleInterval::ALL
+            .into_iter()
+            .find(|interval| interval.as_str() == s)
+            .ok_or_else(|| {
+                Error::InvalidPriceQuery(format!("Unknown interval {}; use 5m, 1h or 1d", s))
+            })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TickOrigin {
+    Feed,
+    Import,
+}
+
+impl TickOrigin {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TickOrigin::Feed => "FEED",
+            TickOrigin::Import => "IMPORT",
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct PriceTick {
+    pub symbol: String,
+    pub price: Decimal,
+    pub volume_24h: Option<Decimal>,
+    pub sources: Vec<String>,
+    pub origin: String,
+    pub recorded_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Candle {
+    pub symbol: String,
+    pub interval: String,
+    pub bucket_start: DateTime<Utc>,
+    pub open: Decimal,
+    pub high: Decimal,
+    pub low: Decimal,
+    pub close: Decimal,
+    pub tick_count: i32,
+}
+
+// One row of a backfill file: symbol,timestamp,price[,volume_24h]
+#[derive(Debug, Deserialize)]
+struct PriceCsvRow {
+    symbol: String,
+    timestamp: String,
+    price: Decimal,
+    #[serde(default)]
+    volume_24h: Option<Decimal>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportedTick {
+    pub symbol: String,
+    pub price: Decimal,
+    pub volume_24h: Option<Decimal>,
+    pub recorded_at: DateTime<Utc>,
+}
+
+#[derive(Debug)]
+pub struct ParsedPriceRow {
+    pub line_number: u64,
+    pub tick: std::result::Result<ImportedTick, String>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct RejectedPriceRow {
+    pub line_number: u64,
+    pub reason: String,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct PriceImportReport {
+    pub inserted: usize,
+    pub duplicates: usize, // Already recorded for that symbol and time
+    pub rejected: Vec<RejectedPriceRow>,
+}
+
+#[derive(Clone)]
+pub struct PriceHistoryService {
+    pool: PgPool,
+    config: PriceHistoryConfig,
+}
+
+impl PriceHistoryService {
+    pub fn new(pool: PgPool, config: PriceHistoryConfig) -> Self {
+        Self { pool, config }
+    }
+
+    pub fn config(&self) -> &PriceHistoryConfig {
+        &self.config
+    }
+
+    // Records a feed price and rolls it into its candles. False when the
+    // feed served the same quote as last time.
+    pub async fn record_tick(&self, symbol: &str, data: &PriceData) -> Result<bool> {
+        let price = Decimal::from_f64(data.price)
+            .map(|p| p.round_dp(12))
+            .filter(|p| *p > Decimal::ZERO)
+            .ok_or_else(|| {
+                Error::InvalidPriceImport(format!("{} price {} is unusable", symbol, data.price))
+            })?;
+        let volume_24h = Decimal::from_f64(data.volume_24h).map(|v| v.round_dp(2));
+
+        let mut tx = self.pool.begin().await?;
+
+        let inserted = insert_tick(
+            &mut *tx,
+            symbol,
+            price,
+            volume_24h,
+            &data.sources,
+            TickOrigin::Feed,
+            data.last_updated,
+        )
+        .await?;
+        if inserted {
+            for interval in CandleInterval::ALL {
+                refresh_candles(
+                    &mut *tx,
+                    symbol,
+                    interval,
+                    data.last_updated,
+                    data.last_updated,
+                )
+                .await?;
+            }
+        }
+
+        tx.commit().await?;
+        Ok(inserted)
+    }
+
+    // Backfills history from a CSV file. Bad rows are reported and skipped;
+    // the rest are recorded and their candles rebuilt in one transaction.
+    pub async fn import_csv(&self, body: &[u8]) -> Result<PriceImportReport> {
+        let rows = parse_price_csv(body, Utc::now())?;
+
+        let mut report = PriceImportReport::default();
+        let mut ranges: HashMap<String, (DateTime<Utc>, DateTime<Utc>)> = HashMap::new();
+
+        let mut tx = self.pool.begin().await?;
+
+        for row in rows {
+            let tick = match row.tick {
+                Ok(tick) => tick,
+                Err(reason) => {
+                    report.rejected.push(RejectedPriceRow {
+                        line_number: row.line_number,
+                        reason,
+                    });
+                    continue;
+                }
+            };
+
+            let inserted = insert_tick(
+                &mut *tx,
+                &tick.symbol,
+                tick.price,
+                tick.volume_24h,
+                &[],
+                TickOrigin::Import,
+                tick.recorded_at,
+            )
+            .await?;
+            if !inserted {
+                report.duplicates += 1;
+                continue;
+            }
+
+            report.inserted += 1;
+            let range = ranges
+                .entry(tick.symbol)
+                .or_insert((tick.recorded_at, tick.recorded_at));
+            range.0 = range.0.min(tick.recorded_at);
+            range.1 = range.1.max(tick.recorded_at);
+        }
+
+        for (symbol, (from, to)) in &ranges {
+            for interval in CandleInterval::ALL {
+                refresh_candles(&mut *tx, symbol, interval, *from, *to).await?;
+            }
+        }
+
+        tx.commit().await?;
+        Ok(report)
+    }
+
+    pub async fn get_ticks(
+        &self,
+        symbol: &str,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<PriceTick>> {
+        validate_range(from, to)?;
+
+        let ticks = sqlx::query_as!(
+            PriceTick,
+            r#"
+            SELECT symbol, price, volume_24h, sources, origin, recorded_at
+            FROM price_history
+            WHERE symbol = $1 AND recorded_at >= $2 AND recorded_at < $3
+            ORDER BY recorded_at
+            LIMIT $4
+            "#,
+            symbol.to_uppercase(),
+            from,
+            to,
+            self.config.max_points + 1
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        self.check_size(ticks)
+    }
+
+    pub async fn latest_tick(&self, symbol: &str) -> Result<Option<PriceTick>> {
+        let tick = sqlx::query_as!(
+            PriceTick,
+            r#"
+            SELECT symbol, price, volume_24h, sources, origin, recorded_at
+            FROM price_history
+            WHERE symbol = $1
+            ORDER BY recorded_at DESC
+            LIMIT 1
+            "#,
+            symbol.to_uppercase()
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(tick)
+    }
+
+    pub async fn get_candles(
+        &self,
+        symbol: &str,
+        interval: CandleInterval,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<Candle>> {
+        validate_range(from, to)?;
+
+        let candles = sqlx::query_as!(
+            Candle,
+            r#"
+            SELECT symbol, interval, bucket_start, open, high, low, close, tick_count
+            FROM price_candles
+            WHERE symbol = $1 AND interval = $2
+              AND bucket_start >= $3 AND bucket_start < $4
+            ORDER BY bucket_start
+            LIMIT $5
+            "#,
+            symbol.to_uppercase(),
+            interval.as_str(),
+            interval.bucket_start(from),
+            to,
+            self.config.max_points + 1
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        self.check_size(candles)
+    }
+
+    // Daily closes of the given assets for the optimizer, on the days every
+    // one of them has a candle
+    pub async fn market_history(
+        &self,
+        symbols: &[String],
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<MarketHistory> {
+        let mut series = Vec::with_capacity(symbols.len());
+        for symbol in symbols {
+            let candles = self
+                .get_candles(symbol, CandleInterval::OneDay, from, to)
+                .await?;
+            series.push(
+                candles
+                    .into_iter()
+                    .map(|c| (c.bucket_start, c.close))
+                    .collect(),
+            );
+        }
+
+        Ok(align_closes(symbols, &series))
+    }
+
+    // One row past the cap was fetched so an oversized range is refused
+    // rather than silently cut short
+    fn check_size<T>(&self, rows: Vec<T>) -> Result<Vec<T>> {
+        if rows.len() as i64 > self.config.max_points {
+            return Err(Error::InvalidPriceQuery(format!(
+                "More than {} points in range; narrow it or use a longer interval",
+                self.config.max_points
+            )));
+        }
+        Ok(rows)
+    }
+}
+
+async fn insert_tick(
+    conn: &mut PgConnection,
+    symbol: &str,
+    price: Decimal,
+    volume_24h: Option<Decimal>,
+    sources: &[String],
+    origin: TickOrigin,
+    recorded_at: DateTime<Utc>,
+) -> Result<bool> {
+    let inserted = sqlx::query!(
+        r#"
+        INSERT INTO price_history (symbol, price, volume_24h, sources, origin, recorded_at)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        ON CONFLICT (symbol, recorded_at) DO NOTHING
+        "#,
+        symbol,
+        price,
+        volume_24h,
+        sources,
+        origin.as_str(),
+        recorded_at
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(inserted.rows_affected() == 1)
+}
+
+// Rebuilds every candle of one interval touching [from, to] from the ticks,
+// so ticks arriving out of order still leave the right open and close
+async fn refresh_candles(
+    conn: &mut PgConnection,
+    symbol: &str,
+    interval: CandleInterval,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<()> {
+    let start = interval.bucket_start(from);
+    let end = interval.bucket_start(to) + chrono::Duration::seconds(interval.seconds());
+
+    sqlx::query!(
+        r#"
+        INSERT INTO price_candles
+            (symbol, interval, bucket_start, open, high, low, close, tick_count)
+        SELECT $1, $2, bucket,
+               (array_agg(price ORDER BY recorded_at))[1],
+               MAX(price),
+               MIN(price),
+               (array_agg(price ORDER BY recorded_at DESC))[1],
+               COUNT(*)::INTEGER
+        FROM (
+            SELECT price, recorded_at,
+                   to_timestamp(floor(extract(epoch FROM recorded_at)::FLOAT8 / $3) * $3) AS bucket
+            FROM price_history
+            WHERE symbol = $1 AND recorded_at >= $4 AND recorded_at < $5
+        ) ticks
+        GROUP BY bucket
+        ON CONFLICT (symbol, interval, bucket_start) DO UPDATE
+        SET open = EXCLUDED.open,
+            high = EXCLUDED.high,
+            low = EXCLUDED.low,
+            close = EXCLUDED.close,
+            tick_count = EXCLUDED.tick_count,
+            updated_at = NOW()
+        "#,
+        symbol,
+        interval.as_str(),
+        interval.seconds() as f64,
+        start,
+        end
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
+// Lines the series up by day, dropping days missing from any of them
+pub fn align_closes(symbols: &[String], series: &[Vec<(DateTime<Utc>, Decimal)>]) -> MarketHistory {
+    let mut days: Vec<DateTime<Utc>> = series
+        .first()
+        .map(|first| first.iter().map(|(day, _)| *day).collect())
+        .unwrap_or_default();
+    let lookups: Vec<HashMap<DateTime<Utc>, f64>> = series
+        .iter()
+        .map(|closes| {
+            closes
+                .iter()
+                .filter_map(|(day, close)| close.to_f64().map(|close| (*day, close)))
+                .collect()
+        })
+        .collect();
+    days.retain(|day| lookups.iter().all(|closes| closes.contains_key(day)));
+
+    let prices = Array2::from_shape_fn((days.len(), symbols.len()), |(row, col)| {
+        lookups[col][&days[row]]
+    });
+
+    MarketHistory {
+        assets: symbols.to_vec(),
+        prices,
+    }
+}
+
+fn validate_range(from: DateTime<Utc>, to: DateTime<Utc>) -> Result<()> {
+    if from >= to {
+        return Err(Error::InvalidPriceQuery(
+            "The range must start before it ends".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+// A row that cannot be read is rejected on its own; a header we cannot
+// read rejects the whole file
+pub fn parse_price_csv(body: &[u8], now: DateTime<Utc>) -> Result<Vec<ParsedPriceRow>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .flexible(true)
+        .from_reader(body);
+
+    let headers = reader
+        .headers()
+        .map_err(|e| Error::InvalidPriceImport(format!("Unreadable CSV header: {}", e)))?
+        .clone();
+    for column in ["symbol", "timestamp", "price"] {
+        if !headers.iter().any(|h| h == column) {
+            return Err(Error::InvalidPriceImport(format!(
+                "The CSV header must include {}",
+                column
+            )));
+        }
+    }
+
+    let mut rows = Vec::new();
+    for (index, record) in reader.records().enumerate() {
+        // The header is line 1
+        let line_number = record
+            .as_ref()
+            .ok()
+            .and_then(|r| r.position())
+            .map(|p| p.line())
+            .unwrap_or(index as u64 + 2);
+
+        let tick = record
+            .map_err(|e| e.to_string())
+            .and_then(|r| {
+                r.deserialize::<PriceCsvRow>(Some(&headers))
+                    .map_err(|e| e.to_string())
+            })
+            .map_err(|e| format!("Unreadable row: {}", e))
+            .and_then(|row| validate_row(row, now));
+
+        rows.push(ParsedPriceRow { line_number, tick });
+    }
+
+    Ok(rows)
+}
+
+fn validate_row(row: PriceCsvRow, now: DateTime<Utc>) -> std::result::Result<ImportedTick, String> {
+    let symbol = row.symbol.to_uppercase();
+    if symbol.is_empty() || symbol.len() > MAX_SYMBOL_LEN {
+        return Err(format!("Invalid symbol {:?}", row.symbol));
+    }
+    if row.price <= Decimal::ZERO {
+        return Err("Price must be positive".to_string());
+    }
+    if row.volume_24h.is_some_and(|v| v < Decimal::ZERO) {
+        return Err("Volume cannot be negative".to_string());
+    }
+
+    let recorded_at = parse_timestamp(&row.timestamp)
+        .ok_or_else(|| format!("Unreadable timestamp {:?}", row.timestamp))?;
+    if recorded_at > now {
+        return Err("Timestamp is in the future".to_string());
+    }
+
+    Ok(ImportedTick {
+        symbol,
+        price: row.price.round_dp(12),
+        volume_24h: row.volume_24h.map(|v| v.round_dp(2)),
+        recorded_at,
+    })
+}
+
+// RFC 3339, Unix seconds, or a bare date for daily closes (midnight UTC)
+fn parse_timestamp(value: &str) -> Option<DateTime<Utc>> {
+    if let Ok(at) = DateTime::parse_from_rfc3339(value) {
+        return Some(at.with_timezone(&Utc));
+    }
+    if let Ok(secs) = value.parse::<i64>() {
+        return Utc.timestamp_opt(secs, 0).single();
+    }
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .ok()
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .map(|at| at.and_utc())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(value: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(value)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_bucket_start() {
+        let tick = at("2025-03-12T14:37:21Z");
+
+        assert_eq!(
+            CandleInterval::FiveMinutes.bucket_start(tick),
+            at("2025-03-12T14:35:00Z")
+        );
+        assert_eq!(
+            CandleInterval::OneHour.bucket_start(tick),
+            at("2025-03-12T14:00:00Z")
+        );
+        assert_eq!(
+            CandleInterval::OneDay.bucket_start(tick),
+            at("2025-03-12T00:00:00Z")
+        );
+    }
+
+    #[test]
+    fn test_interval_from_str() {
+        assert_eq!(
+            "1h".parse::<CandleInterval>().unwrap(),
+            CandleInterval::OneHour
+        );
+        assert!("1w".parse::<CandleInterval>().is_err());
+    }
+
+    #[test]
+    fn test_parse_timestamp_formats() {
+        let midnight = at("2025-03-01T00:00:00Z");
+
+        assert_eq!(parse_timestamp("2025-03-01T03:00:00+03:00"), Some(midnight));
+        assert_eq!(parse_timestamp("1740787200"), Some(midnight));
+        assert_eq!(parse_timestamp("2025-03-01"), Some(midnight));
+        assert_eq!(parse_timestamp("01/03/2025"), None);
+    }
+
+    #[test]
+    fn test_parse_price_csv() {
+        let body = b"symbol,timestamp,price,volume_24h\n\
+            btc,2025-03-01,84373.01,31000000000\n\
+            KES,2025-03-01T00:00:00Z,0.00773,\n\
+            XLM,2025-03-01,-1,\n\
+            USDC,2999-01-01,1.00,\n\
+            USDC,not a date,1.00,\n";
+
+        let rows = parse_price_csv(body, at("2025-03-12T00:00:00Z")).unwrap();
+        assert_eq!(rows.len(), 5);
+
+        let btc = rows[0].tick.as_ref().unwrap();
+        assert_eq!(btc.symbol, "BTC");
+        assert_eq!(btc.price, Decimal::new(8437301, 2));
+        assert_eq!(btc.volume_24h, Some(Decimal::new(31_000_000_000, 0)));
+
+        let kes = rows[1].tick.as_ref().unwrap();
+        assert_eq!(kes.volume_24h, None);
+
+        assert_eq!(rows[2].line_number, 4);
+        assert_eq!(rows[2].tick, Err("Price must be positive".to_string()));
+        assert_eq!(rows[3].tick, Err("Timestamp is in the future".to_string()));
+        assert!(rows[4].tick.is_err());
+    }
+
+    #[test]
+    fn test_align_closes_drops_incomplete_days() {
+        let day = |d: u32| at(&format!("2025-03-{:02}T00:00:00Z", d));
+        let symbols = vec!["BTC".to_string(), "USDC".to_string()];
+        let series = vec![
+            vec![
+                (day(1), Decimal::new(84000, 0)),
+                (day(2), Decimal::new(86000, 0)),
+                (day(3), Decimal::new(85000, 0)),
+            ],
+            vec![(day(1), Decimal::ONE), (day(3), Decimal::ONE)],
+        ];
+
+        let history = align_closes(&symbols, &series);
+
+        assert_eq!(history.assets, symbols);
+        assert_eq!(
+            history.prices,
+            ndarray::array![[84000.0, 1.0], [85000.0, 1.0]]
+        );
+    }
+
+    #[test]
+    fn test_parse_price_csv_requires_columns() {
+        assert!(matches!(
+            parse_price_csv(b"symbol,date,close\nBTC,2025-03-01,1\n", Utc::now()),
+            Err(Error::InvalidPriceImport(_))
+        ));
+    }
+}
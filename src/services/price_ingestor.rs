@@ -0,0 +1,87 @@
+use anyhow::Result;
+use std::sync::Arc;
+use tokio::task::JoinHandle;
+
+use crate::services::price_feed::PriceFeedService;
+use crate::services::price_history::PriceHistoryService;
+
+#[derive(Debug, Default)]
+pub struct PriceIngestReport {
+    pub recorded: usize,
+    pub unchanged: usize, // The feed served the quote it served last time
+    pub failed: usize,
+}
+
+// Records a tick for every tracked asset and FX rate, keeping
+// price_history and its candles current
+pub struct PriceIngestor {
+    history: Arc<PriceHistoryService>,
+    market_feed: PriceFeedService,
+    fx_feed: PriceFeedService,
+}
+
+impl PriceIngestor {
+    pub fn new(
+        history: Arc<PriceHistoryService>,
+        market_feed: PriceFeedService,
+        fx_feed: PriceFeedService,
+    ) -> Self {
+        Self {
+            history,
+            market_feed,
+            fx_feed,
+        }
+    }
+
+    pub fn spawn(self) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(std::time::Duration::from_secs(
+                self.history.config().ingest_interval_secs,
+            ));
+
+            loop {
+                interval.tick().await;
+
+                match self.run_once().await {
+                    Ok(report) => tracing::info!("Price ingestion finished: {:?}", report),
+                    Err(e) => tracing::error!("Price ingestion failed: {}", e),
+                }
+            }
+        })
+    }
+
+    pub async fn run_once(&self) -> Result<PriceIngestReport> {
+        let config = self.history.config();
+        let symbols = config
+            .market_symbols
+            .iter()
+            .map(|symbol| (symbol, &self.market_feed))
+            .chain(
+                config
+                    .fx_symbols
+                    .iter()
+                    .map(|symbol| (symbol, &self.fx_feed)),
+            );
+
+        let mut report = PriceIngestReport::default();
+        for (symbol, feed) in symbols {
+            // A missing or disputed price leaves a gap rather than a guess
+            let price = match feed.get_price(symbol).await {
+                Ok(price) => price,
+                Err(e) => {
+                    tracing::warn!("No {} price recorded: {}", symbol, e);
+                    report.failed += 1;
+                    continue;
+                }
+            };
+
+            if self.history.record_tick(symbol, &price).await? {
+                report.recorded += 1;
+            } else {
+                report.unchanged += 1;
+            }
+        }
+
+        Ok(report)
+    }
+}
@@ -0,0 +1,1146 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use rust_decimal::{prelude::ToPrimitive, Decimal, RoundingStrategy};
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Postgres, Transaction};
+use std::collections::{BTreeMap, BTreeSet};
+use std::str::FromStr;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::ai::investment_strategy::{Asset, AssetAllocation};
+use crate::config::rebalancing::RebalancingConfig;
+use crate::error::{Error, Result};
+use crate::services::kyc_service::require_approved_kyc;
+
+// Asset quantities are kept to 8 decimals, USD values to cents
+const QUANTITY_DP: u32 = 8;
+const VALUE_DP: u32 = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum TradeSide {
+    Buy,
+    Sell,
+}
+
+impl TradeSide {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TradeSide::Buy => "BUY",
+            TradeSide::Sell => "SELL",
+        }
+    }
+}
+
+impl FromStr for TradeSide {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "BUY" => Ok(TradeSide::Buy),
+            "SELL" => Ok(TradeSide::Sell),
+            other => Err(anyhow::anyhow!("Unknown trade side: {}", other)),
+        }
+    }
+}
+
+// A sell delivers `quantity`; a buy spends `notional`, fees included, and
+// `quantity` is what that is expected to buy at the quoted price
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TradeOrder {
+    pub asset: Asset,
+    pub side: TradeSide,
+    pub quantity: Decimal,
+    pub quoted_price: Decimal,
+    pub notional: Decimal,
+    pub estimated_cost: Decimal,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RebalancePlan {
+    pub portfolio_value: Decimal,
+    pub orders: Vec<TradeOrder>, // Sells first, so their proceeds fund the buys
+    pub estimated_cost: Decimal,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fill {
+    pub venue_order_id: String,
+    pub quantity: Decimal,
+    pub price: Decimal,
+    pub fee: Decimal, // USD
+}
+
+impl Fill {
+    // What a sell leaves the member with once the fee is paid
+    pub fn proceeds(&self) -> Decimal {
+        self.quantity * self.price - self.fee
+    }
+}
+
+// Where rebalancing trades are priced and filled. Prices are USD per unit.
+#[async_trait]
+pub trait ExecutionVenue: Send + Sync {
+    fn name(&self) -> &'static str;
+    async fn quote(&self, asset: Asset) -> anyhow::Result<Decimal>;
+    async fn execute(&self, order: &TradeOrder) -> anyhow::Result<Fill>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecommendationStatus {
+    Pending,
+    Executing,
+    Applied,
+    Failed,
+    Rejected,
+    Superseded,
+}
+
+impl RecommendationStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RecommendationStatus::Pending => "PENDING",
+            RecommendationStatus::Executing => "EXECUTING",
+            RecommendationStatus::Applied => "APPLIED",
+            RecommendationStatus::Failed => "FAILED",
+            RecommendationStatus::Rejected => "REJECTED",
+            RecommendationStatus::Superseded => "SUPERSEDED",
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct RebalanceTrade {
+    pub seq: i16,
+    pub asset: String,
+    pub side: String,
+    pub quantity: Decimal,
+    pub quoted_price: Decimal,
+    pub notional: Decimal,
+    pub estimated_cost: Decimal,
+    pub status: String,
+    pub filled_quantity: Option<Decimal>,
+    pub fill_price: Option<Decimal>,
+    pub fee: Option<Decimal>,
+    pub executed_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Recommendation {
+    pub id: Uuid,
+    pub status: String,
+    pub target: BTreeMap<String, Decimal>, // Percentage per asset
+    pub portfolio_value: Decimal,
+    pub estimated_cost: Decimal,
+    pub trades: Vec<RebalanceTrade>,
+    pub failure_reason: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub applied_at: Option<DateTime<Utc>>,
+}
+
+// Turns target allocations into trades, records them as recommendations and
+// executes accepted ones against the venue
+#[derive(Clone)]
+pub struct RebalancingService {
+    pool: PgPool,
+    venue: Option<Arc<dyn ExecutionVenue>>, // None until a venue is configured
+    config: RebalancingConfig,
+}
+
+impl RebalancingService {
+    pub fn new(
+        pool: PgPool,
+        venue: Option<Arc<dyn ExecutionVenue>>,
+        config: RebalancingConfig,
+    ) -> Self {
+        Self {
+            pool,
+            venue,
+            config,
+        }
+    }
+
+    // Without a venue nothing is priced or traded
+    fn venue(&self) -> Result<&dyn ExecutionVenue> {
+        self.venue.as_deref().ok_or(Error::NoExecutionVenue)
+    }
+
+    pub fn config(&self) -> &RebalancingConfig {
+        &self.config
+    }
+
+    pub async fn get_holdings(&self, user_id: Uuid) -> Result<BTreeMap<Asset, Decimal>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT asset, quantity FROM portfolio_holdings
+            WHERE user_id = $1 AND quantity > 0
+            "#,
+            user_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut holdings = BTreeMap::new();
+        for row in rows {
+            let asset = row
+                .asset
+                .parse::<Asset>()
+                .map_err(|e| Error::Other(e.into()))?;
+            holdings.insert(asset, row.quantity);
+        }
+        Ok(holdings)
+    }
+
+    // What the member holds, as weights at the venue's current quotes
+    pub async fn get_held_allocation(&self, user_id: Uuid) -> Result<Option<AssetAllocation>> {
+        let (holdings, prices) = self.fund_holdings(user_id, &[]).await?;
+        if holdings.is_empty() {
+            return Ok(None);
+        }
+
+        let mut assets = Vec::new();
+        let mut values = Vec::new();
+        for (asset, quantity) in &holdings {
+            assets.push(asset.as_str().to_string());
+            values.push((quantity * prices[asset]).to_f64().unwrap_or_default());
+        }
+
+        AssetAllocation::from_fractions(&assets, &values)
+            .map(Some)
+            .map_err(|e| Error::Other(e.into()))
+    }
+
+    // Plans the trades that move the member onto `target` and records them
+    // for the member to accept, superseding any recommendation still
+    // awaiting an answer
+    pub async fn propose(&self, user_id: Uuid, target: &AssetAllocation) -> Result<Recommendation> {
+        let (_, plan) = self.plan(user_id, target).await?;
+        let recommendation_id = self.record(user_id, target, &plan).await?;
+
+        self.get_recommendation(user_id, recommendation_id).await
+    }
+
+    // Sizes the trades from what the member holds now at the venue's quotes,
+    // returning the holdings they were sized from alongside them
+    async fn plan(
+        &self,
+        user_id: Uuid,
+        target: &AssetAllocation,
+    ) -> Result<(BTreeMap<Asset, Decimal>, RebalancePlan)> {
+        let targeted: Vec<Asset> = target.weights.keys().copied().collect();
+        let (holdings, prices) = self.fund_holdings(user_id, &targeted).await?;
+
+        let plan = plan_trades(&holdings, &prices, target, &self.config)?;
+        Ok((holdings, plan))
+    }
+
+    // The member's holdings sized to their fund balance, with the quotes
+    // used to size them and to price each of `also_quote`
+    async fn fund_holdings(
+        &self,
+        user_id: Uuid,
+        also_quote: &[Asset],
+    ) -> Result<(BTreeMap<Asset, Decimal>, BTreeMap<Asset, Decimal>)> {
+        let venue = self.venue()?;
+        let recorded = self.get_holdings(user_id).await?;
+        let balance = sqlx::query_scalar!(
+            "SELECT balance FROM pension_funds WHERE user_id = $1",
+            user_id
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .unwrap_or(Decimal::ZERO);
+
+        let mut prices = BTreeMap::new();
+        let quoted = recorded.keys().chain(also_quote).chain([&Asset::KesMmf]);
+        for asset in quoted {
+            if !prices.contains_key(asset) {
+                let price = venue.quote(*asset).await.map_err(Error::Other)?;
+                prices.insert(*asset, price);
+            }
+        }
+
+        let holdings = size_to_balance(&recorded, &prices, balance)?;
+        Ok((holdings, prices))
+    }
+
+    pub async fn get_recommendation(
+        &self,
+        user_id: Uuid,
+        recommendation_id: Uuid,
+    ) -> Result<Recommendation> {
+        let row = sqlx::query!(
+            r#"
+            SELECT id, status, portfolio_value, estimated_cost, failure_reason,
+                   created_at, applied_at
+            FROM portfolio_recommendations
+            WHERE id = $1 AND user_id = $2
+            "#,
+            recommendation_id,
+            user_id
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| Error::NotFound("Recommendation".to_string()))?;
+
+        let target = sqlx::query!(
+            r#"
+            SELECT asset, weight FROM portfolio_recommendation_weights
+            WHERE recommendation_id = $1
+            "#,
+            recommendation_id
+        )
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(|w| (w.asset, w.weight))
+        .collect();
+
+        let trades = sqlx::query_as!(
+            RebalanceTrade,
+            r#"
+            SELECT seq, asset, side, quantity, quoted_price, notional, estimated_cost,
+                   status, filled_quantity, fill_price, fee, executed_at
+            FROM rebalance_trades
+            WHERE recommendation_id = $1
+            ORDER BY seq
+            "#,
+            recommendation_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(Recommendation {
+            id: row.id,
+            status: row.status,
+            target,
+            portfolio_value: row.portfolio_value,
+            estimated_cost: row.estimated_cost,
+            trades,
+            failure_reason: row.failure_reason,
+            created_at: row.created_at,
+            applied_at: row.applied_at,
+        })
+    }
+
+    pub async fn get_pending_recommendation(&self, user_id: Uuid) -> Result<Recommendation> {
+        let recommendation_id = sqlx::query_scalar!(
+            r#"
+            SELECT id FROM portfolio_recommendations
+            WHERE user_id = $1 AND status = 'PENDING'
+            ORDER BY created_at DESC
+            LIMIT 1
+            "#,
+            user_id
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| Error::NotFound("Pending recommendation".to_string()))?;
+
+        self.get_recommendation(user_id, recommendation_id).await
+    }
+
+    pub async fn accept(&self, user_id: Uuid, recommendation_id: Uuid) -> Result<Recommendation> {
+        require_approved_kyc(&self.pool, user_id).await?;
+        self.apply(user_id, recommendation_id).await
+    }
+
+    pub async fn reject(&self, user_id: Uuid, recommendation_id: Uuid) -> Result<Recommendation> {
+        let rejected = sqlx::query!(
+            r#"
+            UPDATE portfolio_recommendations SET status = $3
+            WHERE id = $1 AND user_id = $2 AND status = 'PENDING'
+            "#,
+            recommendation_id,
+            user_id,
+            RecommendationStatus::Rejected.as_str()
+        )
+        .execute(&self.pool)
+        .await?;
+
+        if rejected.rows_affected() == 0 {
+            return Err(Error::NotFound("Pending recommendation".to_string()));
+        }
+        self.get_recommendation(user_id, recommendation_id).await
+    }
+
+    pub async fn set_auto_rebalance(&self, user_id: Uuid, enabled: bool) -> Result<()> {
+        let updated = sqlx::query!(
+            "UPDATE user_risk_profiles SET auto_rebalance = $2, updated_at = NOW() WHERE user_id = $1",
+            user_id,
+            enabled
+        )
+        .execute(&self.pool)
+        .await?;
+
+        if updated.rows_affected() == 0 {
+            return Err(Error::NotFound("Risk profile".to_string()));
+        }
+        Ok(())
+    }
+
+    // Opted-in members the scheduled rebalance runs for
+    pub async fn get_auto_rebalance_members(&self) -> Result<Vec<Uuid>> {
+        let members = sqlx::query_scalar!(
+            "SELECT user_id FROM user_risk_profiles WHERE auto_rebalance ORDER BY user_id"
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(members)
+    }
+
+    async fn record(
+        &self,
+        user_id: Uuid,
+        target: &AssetAllocation,
+        plan: &RebalancePlan,
+    ) -> Result<Uuid> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query!(
+            r#"
+            UPDATE portfolio_recommendations SET status = $2
+            WHERE user_id = $1 AND status = 'PENDING'
+            "#,
+            user_id,
+            RecommendationStatus::Superseded.as_str()
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        let recommendation_id = sqlx::query_scalar!(
+            r#"
+            INSERT INTO portfolio_recommendations (user_id, status, portfolio_value, estimated_cost)
+            VALUES ($1, $2, $3, $4)
+            RETURNING id
+            "#,
+            user_id,
+            RecommendationStatus::Pending.as_str(),
+            plan.portfolio_value,
+            plan.estimated_cost
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        for (asset, weight) in &target.weights {
+            sqlx::query!(
+                r#"
+                INSERT INTO portfolio_recommendation_weights (recommendation_id, asset, weight)
+                VALUES ($1, $2, $3)
+                "#,
+                recommendation_id,
+                asset.as_str(),
+                weight
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        insert_trades(&mut tx, recommendation_id, plan).await?;
+
+        tx.commit().await?;
+        Ok(recommendation_id)
+    }
+
+    // Executes a pending recommendation. Prices and holdings have moved
+    // since it was proposed, so its trades are planned again from what the
+    // member holds now before anything is sent to the venue. Once claimed
+    // it always ends APPLIED or FAILED, never left EXECUTING.
+    async fn apply(&self, user_id: Uuid, recommendation_id: Uuid) -> Result<Recommendation> {
+        // Checked first so the recommendation stays pending without a venue
+        self.venue()?;
+
+        let claimed = sqlx::query!(
+            r#"
+            UPDATE portfolio_recommendations SET status = $3
+            WHERE id = $1 AND user_id = $2 AND status = 'PENDING'
+            "#,
+            recommendation_id,
+            user_id,
+            RecommendationStatus::Executing.as_str()
+        )
+        .execute(&self.pool)
+        .await?;
+
+        if claimed.rows_affected() == 0 {
+            return Err(Error::NotFound("Pending recommendation".to_string()));
+        }
+
+        if let Err(e) = self.execute(user_id, recommendation_id).await {
+            tracing::error!(
+                "Rebalance {} for {} failed: {:#}",
+                recommendation_id,
+                user_id,
+                e
+            );
+            self.mark_failed(recommendation_id, &e.to_string()).await?;
+        }
+
+        self.get_recommendation(user_id, recommendation_id).await
+    }
+
+    // Each fill updates the member's holdings as it lands, so a failure part
+    // way leaves them matching what the venue actually did; sale proceeds not
+    // spent on buys are parked in USDC either way
+    async fn execute(&self, user_id: Uuid, recommendation_id: Uuid) -> Result<()> {
+        let target = self.recommended_target(recommendation_id).await?;
+        let (holdings, plan) = self.plan(user_id, &target).await?;
+        let trades = self
+            .replan(user_id, recommendation_id, &holdings, &plan)
+            .await?;
+
+        let mut cash = Decimal::ZERO;
+        let traded = self.execute_trades(user_id, trades, &mut cash).await;
+
+        if cash > Decimal::ZERO {
+            if let Err(e) = self.park_cash(user_id, cash).await {
+                return Err(Error::Other(anyhow::anyhow!(
+                    "{} USD of sale proceeds could not be parked in USDC: {:#}",
+                    cash,
+                    e
+                )));
+            }
+        }
+        traded?;
+
+        self.mark_applied(user_id, recommendation_id).await
+    }
+
+    // Runs the trades in order, stopping at the first one that fails. `cash`
+    // is what the recorded fills leave to spend or park.
+    async fn execute_trades(
+        &self,
+        user_id: Uuid,
+        trades: Vec<(Uuid, TradeOrder)>,
+        cash: &mut Decimal,
+    ) -> Result<()> {
+        let venue = self.venue()?;
+
+        for (trade_id, mut order) in trades {
+            // Buys never spend more than the sells actually raised
+            if order.side == TradeSide::Buy && order.notional > *cash {
+                order.notional = cash.round_dp_with_strategy(VALUE_DP, RoundingStrategy::ToZero);
+                if order.notional <= Decimal::ZERO {
+                    self.mark_trade_failed(trade_id).await?;
+                    continue;
+                }
+            }
+
+            let fill = match venue.execute(&order).await {
+                Ok(fill) => fill,
+                Err(e) => {
+                    self.mark_trade_failed(trade_id).await?;
+                    return Err(Error::Other(anyhow::anyhow!(
+                        "{} {} failed at {}: {:#}",
+                        order.side.as_str(),
+                        order.asset,
+                        venue.name(),
+                        e
+                    )));
+                }
+            };
+
+            let delta = match order.side {
+                TradeSide::Sell => -fill.quantity,
+                TradeSide::Buy => fill.quantity,
+            };
+            // The venue has traded; if the books cannot follow, the fill goes
+            // into the failure reason so it can be reconciled by hand
+            if let Err(e) = self
+                .record_fill(user_id, trade_id, order.asset, delta, &fill)
+                .await
+            {
+                return Err(Error::Other(anyhow::anyhow!(
+                    "{} {} filled at {} as order {} ({} at {}, fee {}) but was not recorded: {:#}",
+                    order.side.as_str(),
+                    order.asset,
+                    venue.name(),
+                    fill.venue_order_id,
+                    fill.quantity,
+                    fill.price,
+                    fill.fee,
+                    e
+                )));
+            }
+
+            match order.side {
+                TradeSide::Sell => *cash += fill.proceeds(),
+                TradeSide::Buy => *cash -= order.notional,
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn recommended_target(&self, recommendation_id: Uuid) -> Result<AssetAllocation> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT asset, weight FROM portfolio_recommendation_weights
+            WHERE recommendation_id = $1
+            "#,
+            recommendation_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut weights = BTreeMap::new();
+        for row in rows {
+            let asset = row
+                .asset
+                .parse::<Asset>()
+                .map_err(|e| Error::Other(e.into()))?;
+            weights.insert(asset, row.weight);
+        }
+
+        AssetAllocation::new(weights).map_err(|e| Error::Other(e.into()))
+    }
+
+    // Swaps the trades proposed earlier for `plan`, returning them in order.
+    // The holdings it was sized from are recorded first, so the fills adjust
+    // positions that match the fund balance.
+    async fn replan(
+        &self,
+        user_id: Uuid,
+        recommendation_id: Uuid,
+        holdings: &BTreeMap<Asset, Decimal>,
+        plan: &RebalancePlan,
+    ) -> Result<Vec<(Uuid, TradeOrder)>> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query!("DELETE FROM portfolio_holdings WHERE user_id = $1", user_id)
+            .execute(&mut *tx)
+            .await?;
+        for (asset, quantity) in holdings {
+            adjust_holding(&mut tx, user_id, *asset, *quantity).await?;
+        }
+
+        sqlx::query!(
+            "DELETE FROM rebalance_trades WHERE recommendation_id = $1",
+            recommendation_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            UPDATE portfolio_recommendations SET portfolio_value = $2, estimated_cost = $3
+            WHERE id = $1
+            "#,
+            recommendation_id,
+            plan.portfolio_value,
+            plan.estimated_cost
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        let trade_ids = insert_trades(&mut tx, recommendation_id, plan).await?;
+
+        tx.commit().await?;
+        Ok(trade_ids.into_iter().zip(plan.orders.clone()).collect())
+    }
+
+    // The target becomes the member's allocation
+    async fn mark_applied(&self, user_id: Uuid, recommendation_id: Uuid) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        let allocation_id = sqlx::query_scalar!(
+            "INSERT INTO portfolio_allocations (user_id) VALUES ($1) RETURNING id",
+            user_id
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        sqlx::query!(
+            r#"
+            INSERT INTO portfolio_allocation_weights (allocation_id, asset, weight)
+            SELECT $1, asset, weight FROM portfolio_recommendation_weights
+            WHERE recommendation_id = $2
+            "#,
+            allocation_id,
+            recommendation_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            UPDATE portfolio_recommendations SET status = $2, applied_at = NOW()
+            WHERE id = $1
+            "#,
+            recommendation_id,
+            RecommendationStatus::Applied.as_str()
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn mark_failed(&self, recommendation_id: Uuid, reason: &str) -> Result<()> {
+        sqlx::query!(
+            r#"
+            UPDATE portfolio_recommendations SET status = $2, failure_reason = $3
+            WHERE id = $1 AND status = 'EXECUTING'
+            "#,
+            recommendation_id,
+            RecommendationStatus::Failed.as_str(),
+            reason
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn record_fill(
+        &self,
+        user_id: Uuid,
+        trade_id: Uuid,
+        asset: Asset,
+        delta: Decimal,
+        fill: &Fill,
+    ) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query!(
+            r#"
+            UPDATE rebalance_trades SET
+                status = 'FILLED',
+                venue = $2,
+                venue_order_id = $3,
+                filled_quantity = $4,
+                fill_price = $5,
+                fee = $6,
+                executed_at = NOW()
+            WHERE id = $1
+            "#,
+            trade_id,
+            self.venue()?.name(),
+            fill.venue_order_id,
+            fill.quantity,
+            fill.price,
+            fill.fee
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        adjust_holding(&mut tx, user_id, asset, delta).await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn mark_trade_failed(&self, trade_id: Uuid) -> Result<()> {
+        sqlx::query!(
+            "UPDATE rebalance_trades SET status = 'FAILED', venue = $2, executed_at = NOW() WHERE id = $1",
+            trade_id,
+            self.venue()?.name()
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn park_cash(&self, user_id: Uuid, cash: Decimal) -> Result<()> {
+        let price = self
+            .venue()?
+            .quote(Asset::Usdc)
+            .await
+            .map_err(Error::Other)?;
+        let quantity = (cash / price).round_dp_with_strategy(QUANTITY_DP, RoundingStrategy::ToZero);
+
+        let mut tx = self.pool.begin().await?;
+        adjust_holding(&mut tx, user_id, Asset::Usdc, quantity).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+}
+
+async fn insert_trades(
+    tx: &mut Transaction<'_, Postgres>,
+    recommendation_id: Uuid,
+    plan: &RebalancePlan,
+) -> Result<Vec<Uuid>> {
+    let mut trade_ids = Vec::with_capacity(plan.orders.len());
+
+    for (seq, order) in plan.orders.iter().enumerate() {
+        let trade_id = sqlx::query_scalar!(
+            r#"
+            INSERT INTO rebalance_trades
+                (recommendation_id, seq, asset, side, quantity, quoted_price, notional, estimated_cost)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            RETURNING id
+            "#,
+            recommendation_id,
+            seq as i16,
+            order.asset.as_str(),
+            order.side.as_str(),
+            order.quantity,
+            order.quoted_price,
+            order.notional,
+            order.estimated_cost
+        )
+        .fetch_one(&mut **tx)
+        .await?;
+        trade_ids.push(trade_id);
+    }
+
+    Ok(trade_ids)
+}
+
+async fn adjust_holding(
+    tx: &mut Transaction<'_, Postgres>,
+    user_id: Uuid,
+    asset: Asset,
+    delta: Decimal,
+) -> Result<()> {
+    sqlx::query!(
+        r#"
+        INSERT INTO portfolio_holdings (user_id, asset, quantity)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (user_id, asset) DO UPDATE SET
+            quantity = portfolio_holdings.quantity + EXCLUDED.quantity,
+            updated_at = NOW()
+        "#,
+        user_id,
+        asset.as_str(),
+        delta
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+// Contributions and withdrawals move the fund balance in shillings, not the
+// recorded holdings, so the holdings are scaled to be worth the balance at
+// the quoted shilling rate. A money-market unit is one shilling, so a fund
+// with nothing recorded yet holds its balance in KES_MMF.
+pub fn size_to_balance(
+    recorded: &BTreeMap<Asset, Decimal>,
+    prices: &BTreeMap<Asset, Decimal>,
+    balance: Decimal,
+) -> Result<BTreeMap<Asset, Decimal>> {
+    let mut holdings = BTreeMap::new();
+    if balance <= Decimal::ZERO {
+        return Ok(holdings);
+    }
+
+    let price = |asset: &Asset| quoted(prices, asset);
+
+    let mut recorded_value = Decimal::ZERO;
+    for (asset, quantity) in recorded {
+        recorded_value += *quantity * price(asset)?;
+    }
+    if recorded_value <= Decimal::ZERO {
+        holdings.insert(Asset::KesMmf, balance);
+        return Ok(holdings);
+    }
+
+    let scale = balance * price(&Asset::KesMmf)? / recorded_value;
+    for (asset, quantity) in recorded {
+        let sized =
+            (*quantity * scale).round_dp_with_strategy(QUANTITY_DP, RoundingStrategy::ToZero);
+        if sized > Decimal::ZERO {
+            holdings.insert(*asset, sized);
+        }
+    }
+    Ok(holdings)
+}
+
+fn quoted(prices: &BTreeMap<Asset, Decimal>, asset: &Asset) -> Result<Decimal> {
+    prices
+        .get(asset)
+        .copied()
+        .filter(|p| *p > Decimal::ZERO)
+        .ok_or_else(|| Error::Other(anyhow::anyhow!("No price for {}", asset)))
+}
+
+// Diffs what the member holds against the target and sizes the orders that
+// close the gap. Sells are sized first; buys then share out what the sells
+// raise after costs, so the plan never spends money it does not have.
+// Orders below the minimum trade value are dropped.
+pub fn plan_trades(
+    holdings: &BTreeMap<Asset, Decimal>,
+    prices: &BTreeMap<Asset, Decimal>,
+    target: &AssetAllocation,
+    config: &RebalancingConfig,
+) -> Result<RebalancePlan> {
+    let price = |asset: &Asset| quoted(prices, asset);
+
+    let mut values = BTreeMap::new();
+    for (asset, quantity) in holdings {
+        values.insert(*asset, *quantity * price(asset)?);
+    }
+    let portfolio_value: Decimal = values.values().sum();
+
+    let mut plan = RebalancePlan {
+        portfolio_value: portfolio_value.round_dp(VALUE_DP),
+        orders: Vec::new(),
+        estimated_cost: Decimal::ZERO,
+    };
+    if portfolio_value <= Decimal::ZERO {
+        return Ok(plan);
+    }
+
+    let assets: BTreeSet<Asset> = holdings
+        .keys()
+        .chain(target.weights.keys())
+        .copied()
+        .collect();
+    let cost_rate = config.cost_rate();
+    let mut raised = Decimal::ZERO;
+    let mut wanted = Vec::new();
+
+    for asset in assets {
+        let current = values.get(&asset).copied().unwrap_or(Decimal::ZERO);
+        let goal = portfolio_value * target.weight(asset) / Decimal::ONE_HUNDRED;
+        let gap = goal - current;
+
+        if gap.abs() < config.min_trade_value {
+            continue;
+        }
+        if gap > Decimal::ZERO {
+            wanted.push((asset, gap));
+            continue;
+        }
+
+        let held = holdings[&asset];
+        let quoted_price = price(&asset)?;
+        // Assets leaving the allocation are sold outright rather than to dust
+        let quantity = if target.weight(asset).is_zero() {
+            held
+        } else {
+            (-gap / quoted_price)
+                .round_dp_with_strategy(QUANTITY_DP, RoundingStrategy::ToZero)
+                .min(held)
+        };
+        let notional = (quantity * quoted_price).round_dp(VALUE_DP);
+        let estimated_cost = (notional * cost_rate).round_dp(VALUE_DP);
+
+        raised += notional - estimated_cost;
+        plan.orders.push(TradeOrder {
+            asset,
+            side: TradeSide::Sell,
+            quantity,
+            quoted_price,
+            notional,
+            estimated_cost,
+        });
+    }
+
+    let total_wanted: Decimal = wanted.iter().map(|(_, gap)| *gap).sum();
+    let scale = if total_wanted > raised {
+        raised / total_wanted
+    } else {
+        Decimal::ONE
+    };
+
+    for (asset, gap) in wanted {
+        let notional = (gap * scale).round_dp_with_strategy(VALUE_DP, RoundingStrategy::ToZero);
+        if notional < config.min_trade_value {
+            continue;
+        }
+
+        let quoted_price = price(&asset)?;
+        let estimated_cost = (notional * cost_rate).round_dp(VALUE_DP);
+        plan.orders.push(TradeOrder {
+            asset,
+            side: TradeSide::Buy,
+            quantity: ((notional - estimated_cost) / quoted_price)
+                .round_dp_with_strategy(QUANTITY_DP, RoundingStrategy::ToZero),
+            quoted_price,
+            notional,
+            estimated_cost,
+        });
+    }
+
+    plan.estimated_cost = plan.orders.iter().map(|o| o.estimated_cost).sum();
+    Ok(plan)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::investment_strategy::RiskTolerance;
+    use crate::config::{
+        transaction_limits::TransactionLimits, withdrawal_policy::WithdrawalPolicyConfig,
+    };
+    use crate::services::{fund_service::FundService, paper_trading::PaperTradingVenue};
+
+    fn dec(value: &str) -> Decimal {
+        value.parse().unwrap()
+    }
+
+    fn config() -> RebalancingConfig {
+        RebalancingConfig {
+            min_trade_value: dec("10"),
+            fee_rate: dec("0.001"),
+            slippage_rate: dec("0.002"),
+            ..Default::default()
+        }
+    }
+
+    fn prices() -> BTreeMap<Asset, Decimal> {
+        BTreeMap::from([
+            (Asset::Usdc, dec("1")),
+            (Asset::Btc, dec("50000")),
+            (Asset::Xlm, dec("0.10")),
+            (Asset::Tbill, dec("1")),
+            (Asset::KesMmf, dec("0.0077")),
+        ])
+    }
+
+    fn allocation(weights: &[(Asset, &str)]) -> AssetAllocation {
+        AssetAllocation::new(weights.iter().map(|(a, w)| (*a, dec(w))).collect()).unwrap()
+    }
+
+    #[test]
+    fn test_plan_sells_overweight_and_funds_buys_from_proceeds() {
+        // $1,000 all in BTC, moving to 50% BTC / 50% USDC
+        let holdings = BTreeMap::from([(Asset::Btc, dec("0.02"))]);
+        let target = allocation(&[(Asset::Btc, "50"), (Asset::Usdc, "50")]);
+
+        let plan = plan_trades(&holdings, &prices(), &target, &config()).unwrap();
+
+        assert_eq!(plan.portfolio_value, dec("1000"));
+        assert_eq!(plan.orders.len(), 2);
+
+        let sell = &plan.orders[0];
+        assert_eq!(sell.side, TradeSide::Sell);
+        assert_eq!(sell.asset, Asset::Btc);
+        assert_eq!(sell.quantity, dec("0.01"));
+        assert_eq!(sell.notional, dec("500"));
+        assert_eq!(sell.estimated_cost, dec("1.50"));
+
+        // Only what the sale raised after costs is spent
+        let buy = &plan.orders[1];
+        assert_eq!(buy.side, TradeSide::Buy);
+        assert_eq!(buy.asset, Asset::Usdc);
+        assert_eq!(buy.notional, dec("498.50"));
+        assert_eq!(buy.estimated_cost, dec("1.50"));
+        assert_eq!(buy.quantity, dec("497"));
+        assert_eq!(plan.estimated_cost, dec("3.00"));
+    }
+
+    #[test]
+    fn test_plan_skips_trades_below_minimum_size() {
+        // $1,000 at 50.4% USDC / 49.6% BTC against a 50/50 target
+        let holdings = BTreeMap::from([(Asset::Usdc, dec("504")), (Asset::Btc, dec("0.00992"))]);
+        let target = allocation(&[(Asset::Btc, "50"), (Asset::Usdc, "50")]);
+
+        let plan = plan_trades(&holdings, &prices(), &target, &config()).unwrap();
+
+        assert!(plan.orders.is_empty());
+        assert_eq!(plan.estimated_cost, Decimal::ZERO);
+    }
+
+    #[test]
+    fn test_plan_sells_assets_leaving_the_allocation_outright() {
+        let holdings = BTreeMap::from([
+            (Asset::Usdc, dec("876.543211")),
+            (Asset::Xlm, dec("1234.56789")),
+        ]);
+        let target = allocation(&[(Asset::Usdc, "100")]);
+
+        let plan = plan_trades(&holdings, &prices(), &target, &config()).unwrap();
+
+        let sell = &plan.orders[0];
+        assert_eq!(sell.side, TradeSide::Sell);
+        assert_eq!(sell.asset, Asset::Xlm);
+        assert_eq!(sell.quantity, dec("1234.56789"));
+        assert_eq!(plan.orders[1].asset, Asset::Usdc);
+    }
+
+    #[test]
+    fn test_plan_requires_a_price_for_every_asset() {
+        let holdings = BTreeMap::from([(Asset::Tbill, dec("100"))]);
+        let target = allocation(&[(Asset::Usdc, "100")]);
+        let mut plan_prices = prices();
+        plan_prices.remove(&Asset::Tbill);
+
+        assert!(plan_trades(&holdings, &plan_prices, &target, &config()).is_err());
+    }
+
+    #[test]
+    fn test_empty_portfolio_needs_no_trades() {
+        let target = AssetAllocation::from_risk_tolerance(&RiskTolerance::Moderate);
+        let plan = plan_trades(&BTreeMap::new(), &prices(), &target, &config()).unwrap();
+
+        assert_eq!(plan.portfolio_value, Decimal::ZERO);
+        assert!(plan.orders.is_empty());
+    }
+
+    #[test]
+    fn test_uninvested_balance_is_held_in_the_money_market() {
+        let holdings = size_to_balance(&BTreeMap::new(), &prices(), dec("130000")).unwrap();
+
+        assert_eq!(holdings, BTreeMap::from([(Asset::KesMmf, dec("130000"))]));
+    }
+
+    #[test]
+    fn test_recorded_holdings_are_scaled_to_the_balance() {
+        // $1,001 recorded, half BTC and half USDC, against a balance that a
+        // withdrawal has halved to KES 65,000 ($500.50)
+        let recorded = BTreeMap::from([(Asset::Btc, dec("0.01001")), (Asset::Usdc, dec("500.5"))]);
+
+        let holdings = size_to_balance(&recorded, &prices(), dec("65000")).unwrap();
+
+        assert_eq!(holdings[&Asset::Btc], dec("0.005005"));
+        assert_eq!(holdings[&Asset::Usdc], dec("250.25"));
+    }
+
+    #[sqlx::test(migrator = "crate::db::MIGRATOR")]
+    async fn test_deposit_is_planned_into_the_target(pool: PgPool) {
+        let user_id = sqlx::query_scalar!(
+            "INSERT INTO users (username, phone_number) VALUES ('saver', '254708374149') RETURNING id"
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        sqlx::query!(
+            "INSERT INTO pension_funds (user_id, investment_plan) VALUES ($1, 'MODERATE')",
+            user_id
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let funds = FundService::new(
+            pool.clone(),
+            TransactionLimits::default(),
+            WithdrawalPolicyConfig::default(),
+        );
+        funds
+            .record_pending_deposit(user_id, 130_000.0, "ws_CO_001", None)
+            .await
+            .unwrap();
+        funds
+            .complete_deposit_from_query("ws_CO_001")
+            .await
+            .unwrap();
+
+        let venue = PaperTradingVenue::new(&config());
+        for (asset, price) in prices() {
+            venue.set_price(asset, price);
+        }
+        let service = RebalancingService::new(pool, Some(Arc::new(venue)), config());
+        let target = allocation(&[(Asset::Btc, "50"), (Asset::Usdc, "50")]);
+
+        let recommendation = service.propose(user_id, &target).await.unwrap();
+
+        assert_eq!(recommendation.portfolio_value, dec("1001"));
+        let trades: Vec<(&str, &str)> = recommendation
+            .trades
+            .iter()
+            .map(|t| (t.side.as_str(), t.asset.as_str()))
+            .collect();
+        assert_eq!(
+            trades,
+            vec![("SELL", "KES_MMF"), ("BUY", "BTC"), ("BUY", "USDC")]
+        );
+    }
+}
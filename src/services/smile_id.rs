@@ -1,11 +1,403 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use chrono::{SecondsFormat, Utc};
+use hmac::{Hmac, Mac};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::env;
+use uuid::Uuid;
+
+use crate::services::kyc_service::{KycCallback, KycProvider, KycResult, KycStatus, KycSubmission};
+
+pub const SMILE_ID_SANDBOX_URL: &str = "https://testapi.smileidentity.com/v1";
+
+// Enhanced KYC: the ID number is checked against the issuing authority
+const ENHANCED_KYC_JOB_TYPE: u8 = 5;
+const SOURCE_SDK: &str = "rest_api";
+const SOURCE_SDK_VERSION: &str = "1.0.0";
+
+#[derive(Debug, Clone)]
+pub struct SmileIDConfig {
+    pub base_url: String,
+    pub partner_id: String,
+    pub api_key: String,
+    pub callback_url: String,
+}
+
+impl SmileIDConfig {
+    pub fn from_env() -> Result<Self> {
+        Ok(Self {
+            base_url: env::var("SMILE_ID_BASE_URL")
+                .unwrap_or_else(|_| SMILE_ID_SANDBOX_URL.to_string()),
+            partner_id: env::var("SMILE_ID_PARTNER_ID")?,
+            api_key: env::var("SMILE_ID_API_KEY")?,
+            callback_url: env::var("SMILE_ID_CALLBACK_URL")?,
+        })
+    }
+}
+
 #[derive(Clone)]
 pub struct SmileIDClient {
-    api_key: String,
+    client: Client,
+    base_url: String,
     partner_id: String,
+    api_key: String,
+    callback_url: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct PartnerParams {
+    job_id: String,
+    user_id: String,
+    job_type: u8,
+}
+
+#[derive(Serialize)]
+struct EnhancedKycRequest<'a> {
+    partner_id: &'a str,
+    timestamp: String,
+    signature: String,
+    callback_url: &'a str,
+    source_sdk: &'static str,
+    source_sdk_version: &'static str,
+    partner_params: PartnerParams,
+    country: &'a str,
+    id_type: &'a str,
+    id_number: &'a str,
+    first_name: &'a str,
+    last_name: &'a str,
+    dob: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    phone_number: Option<&'a str>,
+}
+
+#[derive(Deserialize)]
+struct AsyncSubmitResponse {
+    success: bool,
+}
+
+#[derive(Serialize)]
+struct JobStatusRequest<'a> {
+    partner_id: &'a str,
+    timestamp: String,
+    signature: String,
+    user_id: String,
+    job_id: String,
+    image_links: bool,
+    history: bool,
+}
+
+#[derive(Deserialize)]
+struct JobStatusResponse {
+    job_complete: bool,
+    // An object once the job is complete, a status string before that
+    result: Value,
+}
+
+// The result document Smile ID sends to the callback URL and returns from
+// job_status
+#[derive(Debug, Deserialize)]
+pub struct SmileIDResult {
+    #[serde(rename = "ResultCode")]
+    pub result_code: String,
+    #[serde(rename = "ResultText")]
+    pub result_text: String,
+    #[serde(rename = "SmileJobID")]
+    pub smile_job_id: Option<String>,
+    #[serde(rename = "PartnerParams")]
+    partner_params: PartnerParams,
+    #[serde(rename = "Actions", default)]
+    pub actions: HashMap<String, String>,
+    pub signature: Option<String>,
+    pub timestamp: Option<String>,
+}
+
+impl SmileIDResult {
+    pub fn job_id(&self) -> Result<Uuid> {
+        Ok(Uuid::parse_str(&self.partner_params.job_id)?)
+    }
+
+    pub fn to_kyc_result(&self) -> KycResult {
+        KycResult {
+            status: decide(&self.result_code, &self.actions),
+            result_code: self.result_code.clone(),
+            result_text: self.result_text.clone(),
+            provider_reference: self.smile_job_id.clone(),
+        }
+    }
+}
+
+// 1012 means the authority holds a record for the ID number; the name
+// check decides whether it belongs to this member. 1013 means no such ID.
+// Anything else (authority down, unsupported ID type) needs a person.
+pub fn decide(result_code: &str, actions: &HashMap<String, String>) -> KycStatus {
+    match result_code {
+        "1012" => match actions.get("Names").map(String::as_str) {
+            None | Some("Exact Match") => KycStatus::Approved,
+            Some(_) => KycStatus::NeedsReview,
+        },
+        "1013" => KycStatus::Rejected,
+        _ => KycStatus::NeedsReview,
+    }
+}
+
+// base64(HMAC-SHA256(api_key, timestamp + partner_id + "sid_request"))
+pub fn generate_signature(api_key: &str, partner_id: &str, timestamp: &str) -> String {
+    BASE64.encode(
+        signature_mac(api_key, partner_id, timestamp)
+            .finalize()
+            .into_bytes(),
+    )
+}
+
+pub fn verify_signature(api_key: &str, partner_id: &str, timestamp: &str, signature: &str) -> bool {
+    BASE64
+        .decode(signature)
+        .map(|expected| {
+            signature_mac(api_key, partner_id, timestamp)
+                .verify_slice(&expected)
+                .is_ok()
+        })
+        .unwrap_or(false)
+}
+
+fn signature_mac(api_key: &str, partner_id: &str, timestamp: &str) -> Hmac<Sha256> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(api_key.as_bytes())
+        .expect("HMAC accepts keys of any length");
+    mac.update(timestamp.as_bytes());
+    mac.update(partner_id.as_bytes());
+    mac.update(b"sid_request");
+    mac
 }
 
 impl SmileIDClient {
-    pub fn new(api_key: String, partner_id: String) -> Self {
-        Self { api_key, partner_id }
+    pub fn new() -> Result<Self> {
+        Ok(Self::from_config(SmileIDConfig::from_env()?))
     }
-} 
\ No newline at end of file
+
+    pub fn from_config(config: SmileIDConfig) -> Self {
+        Self {
+            client: Client::new(),
+            base_url: config.base_url.trim_end_matches('/').to_string(),
+            partner_id: config.partner_id,
+            api_key: config.api_key,
+            callback_url: config.callback_url,
+        }
+    }
+
+    fn sign(&self) -> (String, String) {
+        let timestamp = Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true);
+        let signature = generate_signature(&self.api_key, &self.partner_id, &timestamp);
+        (timestamp, signature)
+    }
+}
+
+#[async_trait]
+impl KycProvider for SmileIDClient {
+    fn name(&self) -> &'static str {
+        "SMILE_ID"
+    }
+
+    async fn submit(&self, job_id: Uuid, user_id: Uuid, submission: &KycSubmission) -> Result<()> {
+        let (timestamp, signature) = self.sign();
+
+        let request = EnhancedKycRequest {
+            partner_id: &self.partner_id,
+            timestamp,
+            signature,
+            callback_url: &self.callback_url,
+            source_sdk: SOURCE_SDK,
+            source_sdk_version: SOURCE_SDK_VERSION,
+            partner_params: PartnerParams {
+                job_id: job_id.to_string(),
+                user_id: user_id.to_string(),
+                job_type: ENHANCED_KYC_JOB_TYPE,
+            },
+            country: &submission.country,
+            id_type: &submission.id_type,
+            id_number: &submission.id_number,
+            first_name: &submission.first_name,
+            last_name: &submission.last_name,
+            dob: submission.dob.format("%Y-%m-%d").to_string(),
+            phone_number: submission.phone_number.as_deref(),
+        };
+
+        let response = self
+            .client
+            .post(format!("{}/async_id_verification", self.base_url))
+            .json(&request)
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<AsyncSubmitResponse>()
+            .await?;
+
+        if !response.success {
+            return Err(anyhow!("Smile ID did not accept job {}", job_id));
+        }
+        Ok(())
+    }
+
+    async fn job_status(&self, job_id: Uuid, user_id: Uuid) -> Result<Option<KycResult>> {
+        let (timestamp, signature) = self.sign();
+
+        let request = JobStatusRequest {
+            partner_id: &self.partner_id,
+            timestamp,
+            signature,
+            user_id: user_id.to_string(),
+            job_id: job_id.to_string(),
+            image_links: false,
+            history: false,
+        };
+
+        let response = self
+            .client
+            .post(format!("{}/job_status", self.base_url))
+            .json(&request)
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<JobStatusResponse>()
+            .await?;
+
+        if !response.job_complete {
+            return Ok(None);
+        }
+
+        let result: SmileIDResult = serde_json::from_value(response.result)?;
+        Ok(Some(result.to_kyc_result()))
+    }
+
+    fn parse_callback(&self, body: &[u8]) -> Result<KycCallback> {
+        let result: SmileIDResult = serde_json::from_slice(body)?;
+
+        let (Some(timestamp), Some(signature)) = (&result.timestamp, &result.signature) else {
+            return Err(anyhow!("Callback is not signed"));
+        };
+        if !verify_signature(&self.api_key, &self.partner_id, timestamp, signature) {
+            return Err(anyhow!("Callback signature mismatch"));
+        }
+
+        Ok(KycCallback {
+            job_id: result.job_id()?,
+            result: result.to_kyc_result(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn client() -> SmileIDClient {
+        SmileIDClient::from_config(SmileIDConfig {
+            base_url: SMILE_ID_SANDBOX_URL.to_string(),
+            partner_id: "1234".to_string(),
+            api_key: "test-api-key".to_string(),
+            callback_url: "https://example.com/api/kyc/callback".to_string(),
+        })
+    }
+
+    fn callback_body(job_id: Uuid, signature: &str, names: &str) -> Vec<u8> {
+        serde_json::to_vec(&json!({
+            "ResultCode": "1012",
+            "ResultText": "Valid ID Number",
+            "SmileJobID": "0000001096",
+            "PartnerParams": {
+                "job_id": job_id.to_string(),
+                "user_id": Uuid::nil().to_string(),
+                "job_type": 5
+            },
+            "Actions": {
+                "Verify_ID_Number": "Verified",
+                "Names": names,
+                "DOB": "Exact Match"
+            },
+            "timestamp": "2025-03-08T09:00:00.000Z",
+            "signature": signature
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_signature_round_trip() {
+        let timestamp = "2025-03-08T09:00:00.000Z";
+        let signature = generate_signature("test-api-key", "1234", timestamp);
+
+        assert!(verify_signature(
+            "test-api-key",
+            "1234",
+            timestamp,
+            &signature
+        ));
+        assert!(!verify_signature(
+            "other-key",
+            "1234",
+            timestamp,
+            &signature
+        ));
+        assert!(!verify_signature(
+            "test-api-key",
+            "1234",
+            "2025-03-08T09:00:01.000Z",
+            &signature
+        ));
+        assert!(!verify_signature(
+            "test-api-key",
+            "1234",
+            timestamp,
+            "not base64!"
+        ));
+    }
+
+    #[test]
+    fn test_decide() {
+        let names = |value: &str| HashMap::from([("Names".to_string(), value.to_string())]);
+
+        assert_eq!(decide("1012", &names("Exact Match")), KycStatus::Approved);
+        assert_eq!(decide("1012", &HashMap::new()), KycStatus::Approved);
+        assert_eq!(
+            decide("1012", &names("Partial Match")),
+            KycStatus::NeedsReview
+        );
+        assert_eq!(
+            decide("1012", &names("Not Verified")),
+            KycStatus::NeedsReview
+        );
+        assert_eq!(decide("1013", &HashMap::new()), KycStatus::Rejected);
+        assert_eq!(decide("1015", &HashMap::new()), KycStatus::NeedsReview);
+    }
+
+    #[test]
+    fn test_parse_signed_callback() {
+        let job_id = Uuid::new_v4();
+        let signature = generate_signature("test-api-key", "1234", "2025-03-08T09:00:00.000Z");
+
+        let callback = client()
+            .parse_callback(&callback_body(job_id, &signature, "Exact Match"))
+            .unwrap();
+
+        assert_eq!(callback.job_id, job_id);
+        assert_eq!(callback.result.status, KycStatus::Approved);
+        assert_eq!(callback.result.result_code, "1012");
+        assert_eq!(
+            callback.result.provider_reference.as_deref(),
+            Some("0000001096")
+        );
+    }
+
+    #[test]
+    fn test_rejects_forged_callback() {
+        let signature = generate_signature("wrong-key", "1234", "2025-03-08T09:00:00.000Z");
+
+        assert!(client()
+            .parse_callback(&callback_body(Uuid::new_v4(), &signature, "Exact Match"))
+            .is_err());
+    }
+}
@@ -1,8 +1,17 @@
 use stellar_sdk::{
     Client, Network, Keypair,
     types::Transaction,
+    xdr::ScVal,
 };
-use anyhow::Result;
+use anyhow::{anyhow, Result};
+
+// Argument to a Soroban contract call made by the platform account
+#[derive(Debug, Clone)]
+pub enum ContractArg {
+    Address(String),
+    I128(i128),
+    U32(u32),
+}
 
 pub struct StellarService {
     client: Client,
@@ -47,4 +56,58 @@ impl StellarService {
 
         Ok(balance)
     }
-} 
\ No newline at end of file
+
+    // Signs and submits a contract invocation from the platform account,
+    // returning the transaction hash once the network has applied it
+    pub async fn invoke_contract(
+        &self,
+        contract_id: &str,
+        function: &str,
+        args: Vec<ContractArg>,
+    ) -> Result<String> {
+        let account = self.client.get_account(&self.keypair.public_key()).await?;
+        let tx = self
+            .client
+            .build_invoke_contract(&account, contract_id, function, to_sc_vals(args))
+            .await?;
+        let response = self.client.submit_transaction(&tx.sign(&self.keypair)).await?;
+
+        if !response.successful {
+            return Err(anyhow!("Contract call {} failed: {}", function, response.result_xdr));
+        }
+
+        Ok(response.hash)
+    }
+
+    // Simulates a read-only call whose result is an i128 (balances, supply)
+    pub async fn read_contract_i128(
+        &self,
+        contract_id: &str,
+        function: &str,
+        args: Vec<ContractArg>,
+    ) -> Result<i128> {
+        let simulation = self
+            .client
+            .simulate_invoke_contract(contract_id, function, to_sc_vals(args))
+            .await?;
+
+        match simulation.result {
+            ScVal::I128(value) => Ok(value.into()),
+            other => Err(anyhow!("Unexpected result from {}: {:?}", function, other)),
+        }
+    }
+
+    pub fn public_key(&self) -> String {
+        self.keypair.public_key()
+    }
+}
+
+fn to_sc_vals(args: Vec<ContractArg>) -> Vec<ScVal> {
+    args.into_iter()
+        .map(|arg| match arg {
+            ContractArg::Address(address) => ScVal::address(&address),
+            ContractArg::I128(value) => ScVal::I128(value.into()),
+            ContractArg::U32(value) => ScVal::U32(value),
+        })
+        .collect()
+}
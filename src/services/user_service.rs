@@ -6,6 +6,7 @@ use argon2::{
 use sqlx::PgPool;
 use uuid::Uuid;
 
+#[derive(Clone)]
 pub struct UserService {
     pool: PgPool,
 }
@@ -14,9 +15,11 @@ pub struct UserService {
 pub struct User {
     pub id: Uuid,
     pub username: String,
-    pub email: String,
-    pub wallet_address: String,
-    pub password_hash: String,
+    // Phone members may have no email, wallet or password
+    pub email: Option<String>,
+    pub phone_number: Option<String>,
+    pub wallet_address: Option<String>,
+    pub password_hash: Option<String>,
     pub created_at: chrono::DateTime<chrono::Utc>,
     pub updated_at: chrono::DateTime<chrono::Utc>,
 }
@@ -63,7 +66,9 @@ impl UserService {
         let user = sqlx::query_as!(
             User,
             r#"
-            SELECT * FROM users WHERE id = $1
+            SELECT id, username, email, phone_number, wallet_address, password_hash,
+                   created_at, updated_at
+            FROM users WHERE id = $1
             "#,
             user_id
         )
@@ -77,20 +82,22 @@ impl UserService {
         let user = sqlx::query_as!(
             User,
             r#"
-            SELECT * FROM users WHERE email = $1
+            SELECT id, username, email, phone_number, wallet_address, password_hash,
+                   created_at, updated_at
+            FROM users WHERE email = $1
             "#,
             email
         )
         .fetch_optional(&self.pool)
         .await?;
 
-        if let Some(user) = user {
-            let parsed_hash = PasswordHash::new(&user.password_hash)?;
+        if let Some(password_hash) = user.as_ref().and_then(|u| u.password_hash.as_deref()) {
+            let parsed_hash = PasswordHash::new(password_hash)?;
             if Argon2::default()
                 .verify_password(password.as_bytes(), &parsed_hash)
                 .is_ok()
             {
-                return Ok(Some(user));
+                return Ok(user);
             }
         }
 
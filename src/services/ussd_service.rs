@@ -0,0 +1,461 @@
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use std::str::FromStr;
+use uuid::Uuid;
+
+use crate::error::{Error, Result};
+use crate::services::fund_service::{FundService, TransactionSummary};
+use crate::services::mpesa_service::{constant_time_eq, MPesaService};
+use crate::services::phone_auth_service::PhoneAuthService;
+use crate::services::withdrawal_policy::WithdrawalKind;
+use crate::utils::validation::normalize_msisdn;
+
+const MAIN_MENU: &str =
+    "Welcome to Blupension\n1. Check balance\n2. Save\n3. Withdraw\n4. Last transactions";
+const STATEMENT_LENGTH: i64 = 4;
+
+// What the gateway shows the member: `Continue` keeps the session open for
+// another input, `End` closes it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UssdReply {
+    Continue(String),
+    End(String),
+}
+
+impl UssdReply {
+    // Africa's Talking expects plain text prefixed with CON or END
+    pub fn into_body(self) -> String {
+        match self {
+            UssdReply::Continue(text) => format!("CON {}", text),
+            UssdReply::End(text) => format!("END {}", text),
+        }
+    }
+
+    pub fn from_body(body: &str) -> Option<Self> {
+        if let Some(text) = body.strip_prefix("CON ") {
+            Some(UssdReply::Continue(text.to_string()))
+        } else {
+            body.strip_prefix("END ")
+                .map(|text| UssdReply::End(text.to_string()))
+        }
+    }
+}
+
+// Where a session's inputs lead: either another screen, or an operation
+// that needs the database or M-Pesa.
+#[derive(Debug, PartialEq)]
+pub enum UssdAction {
+    Reply(UssdReply),
+    Balance { pin: String },
+    Deposit { amount: Decimal, pin: String },
+    Withdraw { amount: Decimal, pin: String },
+    Statement { pin: String },
+}
+
+// The gateway resends every input of the session joined by `*`, so the
+// menu needs no server-side session state: the path is the state.
+pub fn navigate(text: &str) -> UssdAction {
+    let inputs: Vec<&str> = if text.is_empty() {
+        Vec::new()
+    } else {
+        text.split('*').map(str::trim).collect()
+    };
+
+    match inputs.as_slice() {
+        [] => prompt(MAIN_MENU),
+        ["1"] | ["4"] => prompt("Enter your PIN"),
+        ["1", pin] => UssdAction::Balance {
+            pin: pin.to_string(),
+        },
+        ["2"] => prompt("Enter amount to save (KES)"),
+        ["2", amount] => with_amount(amount, |amount| {
+            prompt(&format!(
+                "Save KES {} from M-Pesa?\n1. Confirm\n2. Cancel",
+                amount
+            ))
+        }),
+        ["2", amount, "1"] => with_amount(amount, |amount| {
+            prompt(&format!("Enter PIN to save KES {}", amount))
+        }),
+        ["2", amount, "1", pin] => with_amount(amount, |amount| UssdAction::Deposit {
+            amount,
+            pin: pin.to_string(),
+        }),
+        ["2", _, "2"] => end("Cancelled"),
+        ["3"] => prompt("Enter amount to withdraw (KES)"),
+        ["3", amount] => with_amount(amount, |amount| {
+            prompt(&format!("Enter PIN to withdraw KES {}", amount))
+        }),
+        ["3", amount, pin] => with_amount(amount, |amount| UssdAction::Withdraw {
+            amount,
+            pin: pin.to_string(),
+        }),
+        ["4", pin] => UssdAction::Statement {
+            pin: pin.to_string(),
+        },
+        _ => end("Invalid choice"),
+    }
+}
+
+fn prompt(text: &str) -> UssdAction {
+    UssdAction::Reply(UssdReply::Continue(text.to_string()))
+}
+
+fn end(text: &str) -> UssdAction {
+    UssdAction::Reply(UssdReply::End(text.to_string()))
+}
+
+// M-Pesa only moves whole shillings
+fn with_amount(input: &str, next: impl FnOnce(Decimal) -> UssdAction) -> UssdAction {
+    match Decimal::from_str(input) {
+        Ok(amount) if amount > Decimal::ZERO && amount.fract().is_zero() => next(amount.trunc()),
+        _ => end("Invalid amount"),
+    }
+}
+
+// Feature-phone access to a member's fund over USSD, authenticated by the
+// calling number and the PIN set at phone registration. The gateway itself
+// is trusted only with the shared token in the callback URL we register.
+#[derive(Clone)]
+pub struct UssdService {
+    phone_auth_service: PhoneAuthService,
+    fund_service: FundService,
+    mpesa_service: MPesaService,
+    gateway_token: String,
+}
+
+impl UssdService {
+    pub fn new(
+        phone_auth_service: PhoneAuthService,
+        fund_service: FundService,
+        mpesa_service: MPesaService,
+        gateway_token: String,
+    ) -> Self {
+        Self {
+            phone_auth_service,
+            fund_service,
+            mpesa_service,
+            gateway_token,
+        }
+    }
+
+    pub fn verify_gateway_token(&self, token: &str) -> bool {
+        constant_time_eq(self.gateway_token.as_bytes(), token.as_bytes())
+    }
+
+    // Never fails: the gateway needs a screen even when something broke
+    pub async fn handle(&self, phone_number: &str, text: &str) -> UssdReply {
+        match self.try_handle(phone_number, text).await {
+            Ok(reply) => reply,
+            Err(e) => error_reply(e),
+        }
+    }
+
+    async fn try_handle(&self, phone_number: &str, text: &str) -> Result<UssdReply> {
+        let phone_number = normalize_msisdn(phone_number).ok_or(Error::InvalidPhoneNumber)?;
+
+        let user_id = match self
+            .phone_auth_service
+            .user_id_by_phone(&phone_number)
+            .await?
+        {
+            Some(user_id) => user_id,
+            None => {
+                return Ok(UssdReply::End(
+                    "This number is not registered with Blupension".to_string(),
+                ))
+            }
+        };
+
+        match navigate(text) {
+            UssdAction::Reply(reply) => Ok(reply),
+            UssdAction::Balance { pin } => {
+                self.phone_auth_service.login(&phone_number, &pin).await?;
+                let balance = self.fund_service.get_user_balance(user_id).await?;
+
+                Ok(UssdReply::End(format!(
+                    "Your pension balance is KES {:.2}",
+                    balance
+                )))
+            }
+            UssdAction::Deposit { amount, pin } => {
+                self.phone_auth_service.login(&phone_number, &pin).await?;
+                self.deposit(user_id, &phone_number, amount).await?;
+
+                Ok(UssdReply::End(format!(
+                    "Enter your M-Pesa PIN on the prompt to save KES {}",
+                    amount
+                )))
+            }
+            UssdAction::Withdraw { amount, pin } => {
+                self.phone_auth_service.login(&phone_number, &pin).await?;
+                let payout = self.withdraw(user_id, &phone_number, amount).await?;
+
+                Ok(UssdReply::End(format!(
+                    "Withdrawal of KES {} initiated. You will receive KES {:.2} on M-Pesa shortly",
+                    amount, payout
+                )))
+            }
+            UssdAction::Statement { pin } => {
+                self.phone_auth_service.login(&phone_number, &pin).await?;
+                let transactions = self
+                    .fund_service
+                    .get_recent_transactions(user_id, STATEMENT_LENGTH)
+                    .await?;
+
+                Ok(UssdReply::End(statement(&transactions)))
+            }
+        }
+    }
+
+    async fn deposit(&self, user_id: Uuid, phone_number: &str, amount: Decimal) -> Result<()> {
+        self.fund_service.require_fund(user_id).await?;
+        self.fund_service.validate_deposit(user_id, amount).await?;
+
+        let amount = amount.to_f64().ok_or(Error::InvalidAmount)?;
+
+        let stk_response = self
+            .mpesa_service
+            .initiate_payment(phone_number, amount, &format!("PEN{}", user_id))
+            .await
+            .map_err(|e| match e.downcast::<Error>() {
+                Ok(err) => err,
+                Err(e) => Error::MPesa(e.to_string()),
+            })?;
+
+        self.fund_service
+            .record_pending_deposit(user_id, amount, &stk_response.checkout_request_id, None)
+            .await?;
+
+        Ok(())
+    }
+
+    // Same flow as the withdrawal endpoint: hold the amount, subject to
+    // the withdrawal limits and early-access rules, then ask M-Pesa to pay
+    // out what is left after any penalty. Hardship claims need a reason, so
+    // they go through the app.
+    async fn withdraw(
+        &self,
+        user_id: Uuid,
+        phone_number: &str,
+        amount: Decimal,
+    ) -> Result<Decimal> {
+        let transaction = self
+            .fund_service
+            .process_withdrawal(
+                user_id,
+                amount,
+                phone_number,
+                WithdrawalKind::EarlyAccess,
+                None,
+            )
+            .await?;
+
+        match self
+            .mpesa_service
+            .initiate_b2c_payment(
+                phone_number,
+                transaction.payout,
+                &format!("PEN{}", transaction.id),
+            )
+            .await
+        {
+            Ok(b2c_response) => {
+                self.fund_service
+                    .record_payout_request(transaction.id, &b2c_response.originator_conversation_id)
+                    .await?;
+            }
+            Err(e) => {
+                self.fund_service
+                    .fail_withdrawal(transaction.id, "M-Pesa payout request failed")
+                    .await?;
+                return Err(Error::MPesa(e.to_string()));
+            }
+        }
+
+        Ok(transaction.payout)
+    }
+}
+
+fn statement(transactions: &[TransactionSummary]) -> String {
+    if transactions.is_empty() {
+        return "No transactions yet".to_string();
+    }
+
+    transactions
+        .iter()
+        .map(|t| {
+            format!(
+                "{} {} {:.2} {}",
+                t.created_at.format("%d/%m"),
+                t.transaction_type,
+                t.amount,
+                t.status
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+// Rule violations are shown to the member; anything else is logged and
+// reported as an outage.
+fn error_reply(err: Error) -> UssdReply {
+    let message = match err {
+        Error::Unauthorized => "Wrong PIN".to_string(),
+        Error::AccountLocked(until) => format!(
+            "Too many wrong PINs. Try again after {}",
+            until.format("%H:%M")
+        ),
+        Error::InvalidPhoneNumber
+        | Error::InvalidAmount
+        | Error::InsufficientFunds
+        | Error::WithdrawalLimitExceeded
+        | Error::WithdrawalTooSmall(_)
+        | Error::WithdrawalTooFrequent
+        | Error::DailyWithdrawalLimitExceeded
+        | Error::MonthlyWithdrawalLimitExceeded
+        | Error::WithdrawalNotPermitted(_)
+        | Error::DepositTooSmall(_)
+        | Error::DailyDepositLimitExceeded
+        | Error::MonthlyDepositLimitExceeded
+        | Error::NotFound(_)
+        | Error::KycNotApproved => err.to_string(),
+        err => {
+            tracing::error!("USSD request failed: {}", err);
+            "Service unavailable, please try again later".to_string()
+        }
+    };
+
+    UssdReply::End(message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    fn screen(text: &str) -> UssdReply {
+        match navigate(text) {
+            UssdAction::Reply(reply) => reply,
+            action => panic!("expected a screen, got {:?}", action),
+        }
+    }
+
+    #[test]
+    fn test_main_menu() {
+        assert_eq!(screen(""), UssdReply::Continue(MAIN_MENU.to_string()));
+        assert_eq!(screen("9"), UssdReply::End("Invalid choice".to_string()));
+        assert_eq!(
+            screen("1*1234*5"),
+            UssdReply::End("Invalid choice".to_string())
+        );
+    }
+
+    #[test]
+    fn test_balance_and_statement_need_pin() {
+        assert_eq!(
+            screen("1"),
+            UssdReply::Continue("Enter your PIN".to_string())
+        );
+        assert_eq!(
+            navigate("1*1234"),
+            UssdAction::Balance {
+                pin: "1234".to_string()
+            }
+        );
+        assert_eq!(
+            navigate("4*1234"),
+            UssdAction::Statement {
+                pin: "1234".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_deposit_flow() {
+        assert_eq!(
+            screen("2*500"),
+            UssdReply::Continue("Save KES 500 from M-Pesa?\n1. Confirm\n2. Cancel".to_string())
+        );
+        assert_eq!(
+            screen("2*500*1"),
+            UssdReply::Continue("Enter PIN to save KES 500".to_string())
+        );
+        assert_eq!(
+            navigate("2*500*1*1234"),
+            UssdAction::Deposit {
+                amount: Decimal::new(500, 0),
+                pin: "1234".to_string()
+            }
+        );
+        assert_eq!(screen("2*500*2"), UssdReply::End("Cancelled".to_string()));
+    }
+
+    #[test]
+    fn test_withdraw_flow() {
+        assert_eq!(
+            screen("3*1000"),
+            UssdReply::Continue("Enter PIN to withdraw KES 1000".to_string())
+        );
+        assert_eq!(
+            navigate("3*1000*1234"),
+            UssdAction::Withdraw {
+                amount: Decimal::new(1000, 0),
+                pin: "1234".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_invalid_amounts() {
+        for text in ["2*abc", "2*0", "2*-5", "2*10.50", "3*abc*1234"] {
+            assert_eq!(navigate(text), end("Invalid amount"), "{}", text);
+        }
+    }
+
+    #[test]
+    fn test_reply_body_round_trip() {
+        for reply in [
+            UssdReply::Continue("Enter your PIN".to_string()),
+            UssdReply::End("Cancelled".to_string()),
+        ] {
+            let body = reply.clone().into_body();
+            assert_eq!(UssdReply::from_body(&body), Some(reply));
+        }
+        assert_eq!(UssdReply::Continue("x".to_string()).into_body(), "CON x");
+        assert_eq!(UssdReply::from_body("garbage"), None);
+    }
+
+    #[test]
+    fn test_statement() {
+        assert_eq!(statement(&[]), "No transactions yet");
+
+        let transactions = vec![TransactionSummary {
+            transaction_type: "DEPOSIT".to_string(),
+            amount: Decimal::new(500, 0),
+            status: "COMPLETED".to_string(),
+            created_at: Utc.with_ymd_and_hms(2025, 3, 7, 9, 30, 0).unwrap(),
+        }];
+        assert_eq!(statement(&transactions), "07/03 DEPOSIT 500.00 COMPLETED");
+    }
+
+    #[test]
+    fn test_error_reply() {
+        assert_eq!(
+            error_reply(Error::Unauthorized),
+            UssdReply::End("Wrong PIN".to_string())
+        );
+        assert_eq!(
+            error_reply(Error::DailyWithdrawalLimitExceeded),
+            UssdReply::End("Daily withdrawal limit exceeded".to_string())
+        );
+        assert_eq!(
+            error_reply(Error::NotFound("Pension fund".to_string())),
+            UssdReply::End("Pension fund not found".to_string())
+        );
+        assert_eq!(
+            error_reply(Error::Blockchain("rpc down".to_string())),
+            UssdReply::End("Service unavailable, please try again later".to_string())
+        );
+    }
+}
@@ -0,0 +1,157 @@
+use anyhow::{anyhow, Result};
+use reqwest::Client;
+use uuid::Uuid;
+
+use crate::services::ussd_service::UssdReply;
+
+// Plays the part of a handset dialling through Africa's Talking: it keeps
+// the session's inputs and posts each step to the gateway callback the way
+// the aggregator does, so USSD menus can be driven end to end in tests.
+pub struct UssdSimulator {
+    client: Client,
+    callback_url: String,
+    service_code: String,
+    phone_number: String,
+    session_id: String,
+    inputs: Vec<String>,
+    open: bool,
+}
+
+impl UssdSimulator {
+    pub fn new(callback_url: &str, service_code: &str, phone_number: &str) -> Self {
+        Self {
+            client: Client::new(),
+            callback_url: callback_url.to_string(),
+            service_code: service_code.to_string(),
+            phone_number: phone_number.to_string(),
+            session_id: String::new(),
+            inputs: Vec::new(),
+            open: false,
+        }
+    }
+
+    // Starts a fresh session, as dialling the service code does
+    pub async fn dial(&mut self) -> Result<UssdReply> {
+        self.session_id = format!("ATUid_{}", Uuid::new_v4().simple());
+        self.inputs.clear();
+        self.open = true;
+        self.send().await
+    }
+
+    pub async fn reply(&mut self, input: &str) -> Result<UssdReply> {
+        if !self.open {
+            return Err(anyhow!("No open USSD session"));
+        }
+        self.inputs.push(input.to_string());
+        self.send().await
+    }
+
+    pub fn session_id(&self) -> &str {
+        &self.session_id
+    }
+
+    pub fn text(&self) -> String {
+        self.inputs.join("*")
+    }
+
+    async fn send(&mut self) -> Result<UssdReply> {
+        let text = self.text();
+        let body = self
+            .client
+            .post(&self.callback_url)
+            .form(&[
+                ("sessionId", self.session_id.as_str()),
+                ("serviceCode", self.service_code.as_str()),
+                ("phoneNumber", self.phone_number.as_str()),
+                ("networkCode", "63902"),
+                ("text", text.as_str()),
+            ])
+            .send()
+            .await?
+            .error_for_status()?
+            .text()
+            .await?;
+
+        let reply = UssdReply::from_body(&body)
+            .ok_or_else(|| anyhow!("Gateway answered without CON/END: {}", body))?;
+
+        if let UssdReply::End(_) = reply {
+            self.open = false;
+        }
+
+        Ok(reply)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::ussd_service::{navigate, UssdAction};
+    use axum::{routing::post, Form, Router};
+    use std::collections::HashMap;
+
+    // A gateway that only renders menu screens, which is enough to check
+    // the session protocol without a database behind it
+    async fn start_gateway() -> String {
+        async fn callback(Form(form): Form<HashMap<String, String>>) -> String {
+            match navigate(&form["text"]) {
+                UssdAction::Reply(reply) => reply.into_body(),
+                action => UssdReply::End(format!("{:?}", action)).into_body(),
+            }
+        }
+
+        let app = Router::new().route("/api/ussd", post(callback));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.ok();
+        });
+
+        format!("http://{}/api/ussd", addr)
+    }
+
+    #[tokio::test]
+    async fn test_session_accumulates_inputs() {
+        let url = start_gateway().await;
+        let mut phone = UssdSimulator::new(&url, "*384*1#", "+254708374149");
+
+        assert!(matches!(
+            phone.dial().await.unwrap(),
+            UssdReply::Continue(_)
+        ));
+        phone.reply("2").await.unwrap();
+        let confirm = phone.reply("500").await.unwrap();
+
+        assert_eq!(phone.text(), "2*500");
+        assert_eq!(
+            confirm,
+            UssdReply::Continue("Save KES 500 from M-Pesa?\n1. Confirm\n2. Cancel".to_string())
+        );
+        assert_eq!(
+            phone.reply("2").await.unwrap(),
+            UssdReply::End("Cancelled".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_session_closes_on_end() {
+        let url = start_gateway().await;
+        let mut phone = UssdSimulator::new(&url, "*384*1#", "+254708374149");
+
+        phone.dial().await.unwrap();
+        let first_session = phone.session_id().to_string();
+        assert_eq!(
+            phone.reply("9").await.unwrap(),
+            UssdReply::End("Invalid choice".to_string())
+        );
+        assert!(phone.reply("1").await.is_err());
+
+        // Dialling again starts over with a new session
+        assert!(matches!(
+            phone.dial().await.unwrap(),
+            UssdReply::Continue(_)
+        ));
+        assert_ne!(phone.session_id(), first_session);
+        assert_eq!(phone.text(), "");
+    }
+}
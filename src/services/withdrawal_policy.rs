@@ -0,0 +1,376 @@
+use chrono::{DateTime, Duration, Utc};
+use rust_decimal::{Decimal, RoundingStrategy};
+use serde::{Deserialize, Serialize};
+
+use crate::config::withdrawal_policy::WithdrawalPolicyConfig;
+
+// Why the member wants their savings. Anything asked for after retirement
+// age is treated as a retirement withdrawal. Hardship claims are only paid
+// once an administrator has reviewed them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum WithdrawalKind {
+    Retirement,
+    Hardship,
+    #[default]
+    EarlyAccess,
+}
+
+impl WithdrawalKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WithdrawalKind::Retirement => "RETIREMENT",
+            WithdrawalKind::Hardship => "HARDSHIP",
+            WithdrawalKind::EarlyAccess => "EARLY_ACCESS",
+        }
+    }
+}
+
+// The rule that decided a request; stored with every decision
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum PolicyRule {
+    RetirementAgeReached,
+    HardshipAllowance,
+    EarlyAccessAllowance,
+    RetirementAgeNotReached,
+    AgeUnknown,
+    HardshipReasonMissing,
+    MembershipTooShort,
+    ExceedsAccessibleAmount,
+}
+
+impl PolicyRule {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PolicyRule::RetirementAgeReached => "RETIREMENT_AGE_REACHED",
+            PolicyRule::HardshipAllowance => "HARDSHIP_ALLOWANCE",
+            PolicyRule::EarlyAccessAllowance => "EARLY_ACCESS_ALLOWANCE",
+            PolicyRule::RetirementAgeNotReached => "RETIREMENT_AGE_NOT_REACHED",
+            PolicyRule::AgeUnknown => "AGE_UNKNOWN",
+            PolicyRule::HardshipReasonMissing => "HARDSHIP_REASON_MISSING",
+            PolicyRule::MembershipTooShort => "MEMBERSHIP_TOO_SHORT",
+            PolicyRule::ExceedsAccessibleAmount => "EXCEEDS_ACCESSIBLE_AMOUNT",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            PolicyRule::RetirementAgeReached => "Retirement age reached",
+            PolicyRule::HardshipAllowance => "Within the hardship allowance",
+            PolicyRule::EarlyAccessAllowance => "Within the early access allowance",
+            PolicyRule::RetirementAgeNotReached => "Retirement age not reached",
+            PolicyRule::AgeUnknown => "Verify your identity to confirm your age",
+            PolicyRule::HardshipReasonMissing => "Hardship withdrawals need a reason",
+            PolicyRule::MembershipTooShort => "Savings are too recent for early access",
+            PolicyRule::ExceedsAccessibleAmount => "Amount exceeds what can be withdrawn",
+        }
+    }
+}
+
+// What we know about the member when they ask
+#[derive(Debug, Clone)]
+pub struct MemberFacts {
+    pub age: Option<u8>,
+    pub available_balance: Decimal, // Balance not backing BPT
+    pub unvested: Decimal,          // Contributions still inside the vesting period
+    pub first_contribution_at: Option<DateTime<Utc>>,
+    pub early_withdrawn: Decimal, // Taken out before retirement so far
+}
+
+impl MemberFacts {
+    pub fn vested(&self) -> Decimal {
+        (self.available_balance - self.unvested).max(Decimal::ZERO)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PolicyDecision {
+    pub kind: WithdrawalKind, // As applied, which may differ from what was asked
+    pub rule: PolicyRule,
+    pub allowed: bool,
+    pub requested: Decimal,
+    pub accessible: Decimal,
+    pub locked: Decimal,
+    pub penalty: Decimal,
+    pub payout: Decimal, // Whole shillings: M-Pesa pays out no cents
+}
+
+impl PolicyDecision {
+    // What leaves the member's fund. Cents that cannot be paid out stay in
+    // the fund rather than being charged.
+    pub fn debited(&self) -> Decimal {
+        self.payout + self.penalty
+    }
+
+    pub fn denial_message(&self) -> String {
+        if self.accessible > Decimal::ZERO {
+            format!(
+                "{}. You can withdraw up to {}",
+                self.rule.description(),
+                self.accessible.round_dp(2)
+            )
+        } else {
+            self.rule.description().to_string()
+        }
+    }
+}
+
+pub fn evaluate(
+    config: &WithdrawalPolicyConfig,
+    requested_kind: WithdrawalKind,
+    reason: Option<&str>,
+    facts: &MemberFacts,
+    amount: Decimal,
+    now: DateTime<Utc>,
+) -> PolicyDecision {
+    let vested = facts.vested();
+    let retired = facts.age.is_some_and(|age| age >= config.retirement_age);
+
+    let kind = if retired {
+        WithdrawalKind::Retirement
+    } else {
+        requested_kind
+    };
+
+    let outcome = match kind {
+        WithdrawalKind::Retirement if retired => {
+            Ok((PolicyRule::RetirementAgeReached, vested, Decimal::ZERO))
+        }
+        WithdrawalKind::Retirement if facts.age.is_none() => Err(PolicyRule::AgeUnknown),
+        WithdrawalKind::Retirement => Err(PolicyRule::RetirementAgeNotReached),
+        WithdrawalKind::Hardship => {
+            if reason.map_or(true, |r| r.trim().is_empty()) {
+                Err(PolicyRule::HardshipReasonMissing)
+            } else {
+                Ok((
+                    PolicyRule::HardshipAllowance,
+                    early_allowance(config.hardship_max_portion, facts),
+                    config.hardship_penalty_rate,
+                ))
+            }
+        }
+        WithdrawalKind::EarlyAccess => {
+            let member_since = facts.first_contribution_at.unwrap_or(now);
+            if now - member_since < Duration::days(config.early_access_min_membership_days) {
+                Err(PolicyRule::MembershipTooShort)
+            } else {
+                Ok((
+                    PolicyRule::EarlyAccessAllowance,
+                    early_allowance(config.early_access_max_portion, facts),
+                    config.early_access_penalty_rate,
+                ))
+            }
+        }
+    };
+
+    let (rule, accessible, penalty_rate) = match outcome {
+        Ok((_, accessible, _)) if amount > accessible => {
+            (PolicyRule::ExceedsAccessibleAmount, accessible, None)
+        }
+        Ok((rule, accessible, penalty_rate)) => (rule, accessible, Some(penalty_rate)),
+        Err(rule) => (rule, Decimal::ZERO, None),
+    };
+
+    let penalty = penalty_rate
+        .map(|rate| (amount * rate).round_dp(2))
+        .unwrap_or_default();
+
+    PolicyDecision {
+        kind,
+        rule,
+        allowed: penalty_rate.is_some(),
+        requested: amount,
+        accessible,
+        locked: (facts.available_balance - accessible).max(Decimal::ZERO),
+        penalty,
+        payout: if penalty_rate.is_some() {
+            (amount - penalty).round_dp_with_strategy(0, RoundingStrategy::ToZero)
+        } else {
+            Decimal::ZERO
+        },
+    }
+}
+
+// The allowance is a share of everything that has vested, including what
+// was already taken early, so repeated requests cannot drain the fund
+fn early_allowance(max_portion: Decimal, facts: &MemberFacts) -> Decimal {
+    let vested = facts.vested();
+    let allowance = (vested + facts.early_withdrawn) * max_portion - facts.early_withdrawn;
+    allowance.max(Decimal::ZERO).min(vested).round_dp(2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn now() -> DateTime<Utc> {
+        "2025-03-09T09:00:00Z".parse().unwrap()
+    }
+
+    fn facts(age: Option<u8>) -> MemberFacts {
+        MemberFacts {
+            age,
+            available_balance: Decimal::new(120_000, 0),
+            unvested: Decimal::new(20_000, 0),
+            first_contribution_at: Some(now() - Duration::days(3 * 365)),
+            early_withdrawn: Decimal::ZERO,
+        }
+    }
+
+    fn decide(
+        kind: WithdrawalKind,
+        reason: Option<&str>,
+        facts: &MemberFacts,
+        amount: i64,
+    ) -> PolicyDecision {
+        evaluate(
+            &WithdrawalPolicyConfig::default(),
+            kind,
+            reason,
+            facts,
+            Decimal::new(amount, 0),
+            now(),
+        )
+    }
+
+    #[test]
+    fn test_retirement_unlocks_vested_savings_without_penalty() {
+        let decision = decide(WithdrawalKind::Retirement, None, &facts(Some(61)), 100_000);
+
+        assert!(decision.allowed);
+        assert_eq!(decision.rule, PolicyRule::RetirementAgeReached);
+        assert_eq!(decision.accessible, Decimal::new(100_000, 0));
+        assert_eq!(decision.locked, Decimal::new(20_000, 0));
+        assert_eq!(decision.penalty, Decimal::ZERO);
+        assert_eq!(decision.payout, Decimal::new(100_000, 0));
+    }
+
+    #[test]
+    fn test_retired_members_are_never_penalised() {
+        let decision = decide(WithdrawalKind::EarlyAccess, None, &facts(Some(60)), 10_000);
+
+        assert!(decision.allowed);
+        assert_eq!(decision.kind, WithdrawalKind::Retirement);
+        assert_eq!(decision.penalty, Decimal::ZERO);
+    }
+
+    #[test]
+    fn test_retirement_before_retirement_age_is_denied() {
+        let decision = decide(WithdrawalKind::Retirement, None, &facts(Some(45)), 1_000);
+        assert!(!decision.allowed);
+        assert_eq!(decision.rule, PolicyRule::RetirementAgeNotReached);
+
+        let decision = decide(WithdrawalKind::Retirement, None, &facts(None), 1_000);
+        assert!(!decision.allowed);
+        assert_eq!(decision.rule, PolicyRule::AgeUnknown);
+    }
+
+    #[test]
+    fn test_early_access_is_capped_and_penalised() {
+        // Half of the 100,000 vested is accessible, less 10% penalty
+        let decision = decide(WithdrawalKind::EarlyAccess, None, &facts(Some(35)), 50_000);
+
+        assert!(decision.allowed);
+        assert_eq!(decision.rule, PolicyRule::EarlyAccessAllowance);
+        assert_eq!(decision.accessible, Decimal::new(50_000, 0));
+        assert_eq!(decision.locked, Decimal::new(70_000, 0));
+        assert_eq!(decision.penalty, Decimal::new(5_000, 0));
+        assert_eq!(decision.payout, Decimal::new(45_000, 0));
+
+        let decision = decide(WithdrawalKind::EarlyAccess, None, &facts(Some(35)), 50_001);
+        assert!(!decision.allowed);
+        assert_eq!(decision.rule, PolicyRule::ExceedsAccessibleAmount);
+        assert_eq!(decision.payout, Decimal::ZERO);
+        assert_eq!(
+            decision.denial_message(),
+            "Amount exceeds what can be withdrawn. You can withdraw up to 50000.00"
+        );
+    }
+
+    #[test]
+    fn test_payout_is_whole_shillings() {
+        let decision = evaluate(
+            &WithdrawalPolicyConfig::default(),
+            WithdrawalKind::EarlyAccess,
+            None,
+            &facts(Some(35)),
+            Decimal::new(1_005_55, 2),
+            now(),
+        );
+
+        // 10% of 1,005.55 is 100.56, leaving 904.99 of which 904 is paid
+        assert_eq!(decision.penalty, Decimal::new(100_56, 2));
+        assert_eq!(decision.payout, Decimal::new(904, 0));
+        assert_eq!(decision.debited(), Decimal::new(1_004_56, 2));
+    }
+
+    #[test]
+    fn test_early_access_allowance_counts_previous_withdrawals() {
+        // 60,000 vested now after 40,000 was taken early: half of 100,000
+        // was the allowance, so 10,000 is left
+        let facts = MemberFacts {
+            available_balance: Decimal::new(80_000, 0),
+            early_withdrawn: Decimal::new(40_000, 0),
+            ..facts(Some(35))
+        };
+
+        let decision = decide(WithdrawalKind::EarlyAccess, None, &facts, 10_000);
+        assert!(decision.allowed);
+        assert_eq!(decision.accessible, Decimal::new(10_000, 0));
+    }
+
+    #[test]
+    fn test_early_access_needs_membership_period() {
+        let facts = MemberFacts {
+            first_contribution_at: Some(now() - Duration::days(100)),
+            ..facts(Some(35))
+        };
+
+        let decision = decide(WithdrawalKind::EarlyAccess, None, &facts, 1_000);
+        assert!(!decision.allowed);
+        assert_eq!(decision.rule, PolicyRule::MembershipTooShort);
+
+        let never_contributed = MemberFacts {
+            first_contribution_at: None,
+            ..facts
+        };
+        assert!(!decide(WithdrawalKind::EarlyAccess, None, &never_contributed, 1).allowed);
+    }
+
+    #[test]
+    fn test_hardship_needs_a_reason_but_no_membership_period() {
+        let facts = MemberFacts {
+            first_contribution_at: Some(now() - Duration::days(100)),
+            ..facts(Some(35))
+        };
+
+        let decision = decide(WithdrawalKind::Hardship, Some("  "), &facts, 1_000);
+        assert!(!decision.allowed);
+        assert_eq!(decision.rule, PolicyRule::HardshipReasonMissing);
+
+        let decision = decide(
+            WithdrawalKind::Hardship,
+            Some("Medical bills"),
+            &facts,
+            75_000,
+        );
+        assert!(decision.allowed);
+        assert_eq!(decision.rule, PolicyRule::HardshipAllowance);
+        assert_eq!(decision.accessible, Decimal::new(75_000, 0));
+        assert_eq!(decision.penalty, Decimal::ZERO);
+    }
+
+    #[test]
+    fn test_unvested_contributions_stay_locked() {
+        let facts = MemberFacts {
+            unvested: Decimal::new(150_000, 0),
+            ..facts(Some(65))
+        };
+
+        let decision = decide(WithdrawalKind::Retirement, None, &facts, 1);
+        assert!(!decision.allowed);
+        assert_eq!(decision.accessible, Decimal::ZERO);
+        assert_eq!(decision.locked, Decimal::new(120_000, 0));
+    }
+}
@@ -1 +1,78 @@
-// Implement your validation utilities here
+pub const PIN_MIN_LENGTH: usize = 4;
+pub const PIN_MAX_LENGTH: usize = 6;
+
+// Brings a Kenyan mobile number into the 2547XXXXXXXX / 2541XXXXXXXX form
+// M-Pesa uses, accepting the local 07.../01... and +254 spellings.
+pub fn normalize_msisdn(phone_number: &str) -> Option<String> {
+    let digits: String = phone_number
+        .trim()
+        .trim_start_matches('+')
+        .chars()
+        .filter(|c| !matches!(c, ' ' | '-'))
+        .collect();
+
+    if !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+
+    let subscriber = if let Some(rest) = digits.strip_prefix("254") {
+        rest
+    } else if let Some(rest) = digits.strip_prefix('0') {
+        rest
+    } else {
+        return None;
+    };
+
+    match subscriber.as_bytes() {
+        [b'7' | b'1', ..] if subscriber.len() == 9 => Some(format!("254{}", subscriber)),
+        _ => None,
+    }
+}
+
+pub fn is_valid_pin(pin: &str) -> bool {
+    (PIN_MIN_LENGTH..=PIN_MAX_LENGTH).contains(&pin.len())
+        && pin.chars().all(|c| c.is_ascii_digit())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_msisdn() {
+        for input in [
+            "0708374149",
+            "+254708374149",
+            "254708374149",
+            "0708 374 149",
+            "0708-374-149",
+        ] {
+            assert_eq!(
+                normalize_msisdn(input).as_deref(),
+                Some("254708374149"),
+                "{}",
+                input
+            );
+        }
+        assert_eq!(
+            normalize_msisdn("0110123456").as_deref(),
+            Some("254110123456")
+        );
+
+        assert_eq!(normalize_msisdn("070837414"), None);
+        assert_eq!(normalize_msisdn("0208374149"), None);
+        assert_eq!(normalize_msisdn("255708374149"), None);
+        assert_eq!(normalize_msisdn("07o8374149"), None);
+        assert_eq!(normalize_msisdn(""), None);
+    }
+
+    #[test]
+    fn test_is_valid_pin() {
+        assert!(is_valid_pin("1234"));
+        assert!(is_valid_pin("123456"));
+        assert!(!is_valid_pin("123"));
+        assert!(!is_valid_pin("1234567"));
+        assert!(!is_valid_pin("12a4"));
+        assert!(!is_valid_pin("12 4"));
+    }
+}